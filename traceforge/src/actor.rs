@@ -0,0 +1,119 @@
+//! A minimal actor framework on top of message passing.
+//!
+//! An [`Actor`] owns its state and handles the messages sent to its [`Addr`], one at a time, in
+//! a thread of its own. The actor's mailbox delivers the messages in causal order: a message
+//! sent after another one, in the happens-before sense, is handled after it. The other orders
+//! are explored by TraceForge like those of any other messages.
+//!
+//! ```no_run
+//! use traceforge::actor::{self, Actor};
+//! use traceforge::Config;
+//!
+//! struct Counter(u32);
+//!
+//! impl Actor for Counter {
+//!     type Msg = u32;
+//!     fn handle(&mut self, n: u32) {
+//!         self.0 += n;
+//!     }
+//! }
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     let counter = actor::spawn(Counter(0));
+//!     counter.addr().send(1);
+//!     counter.addr().send(2);
+//!     assert_eq!(counter.stop().0, 3);
+//! });
+//! ```
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use crate::channel::{Builder, Sender};
+use crate::thread::{self, JoinHandle, ThreadId};
+use crate::CommunicationModel::CausalOrder;
+
+/// The behavior of an actor
+pub trait Actor: Send + 'static {
+    /// The type of the messages that the actor handles
+    type Msg: Clone + Debug + PartialEq + Send + 'static;
+
+    /// Handles a message sent to the actor. The handler can send messages to other actors, or
+    /// use any other TraceForge function.
+    fn handle(&mut self, msg: Self::Msg);
+}
+
+// What an actor receives in its mailbox
+#[derive(Clone, Debug, PartialEq)]
+enum Envelope<M> {
+    Msg(M),
+    Stop,
+}
+
+/// The address of an actor, to send it messages
+#[derive(Clone, Debug, PartialEq)]
+pub struct Addr<M> {
+    sender: Sender<Envelope<M>>,
+    thread: ThreadId,
+}
+
+impl<M: Clone + Debug + PartialEq + Send + 'static> Addr<M> {
+    /// Sends a message to the actor
+    pub fn send(&self, msg: M) {
+        self.sender.send_msg(Envelope::Msg(msg));
+    }
+
+    /// The thread that runs the actor
+    pub fn thread(&self) -> ThreadId {
+        self.thread
+    }
+}
+
+/// An owned handle to a running actor, see [`spawn`]
+pub struct ActorHandle<A: Actor> {
+    addr: Addr<A::Msg>,
+    thread: JoinHandle<()>,
+    // The actor is handed back here when it stops
+    stopped: Arc<Mutex<Option<A>>>,
+}
+
+impl<A: Actor> ActorHandle<A> {
+    /// The address of the actor
+    pub fn addr(&self) -> Addr<A::Msg> {
+        self.addr.clone()
+    }
+
+    /// Stops the actor once it has handled the messages sent to it before the call, and
+    /// returns it, with its final state.
+    pub fn stop(self) -> A {
+        self.addr.sender.send_msg(Envelope::Stop);
+        self.thread.join().unwrap();
+        let actor = self.stopped.lock().unwrap().take();
+        actor.expect("the actor was not handed back")
+    }
+}
+
+/// Spawns an actor and returns a handle to it. The actor runs as a daemon thread: an actor that
+/// is never stopped need not finish for the execution to complete.
+pub fn spawn<A: Actor>(mut actor: A) -> ActorHandle<A> {
+    let (sender, receiver) = Builder::<Envelope<A::Msg>>::new()
+        .with_comm(CausalOrder)
+        .build();
+    let stopped = Arc::new(Mutex::new(None));
+    let slot = stopped.clone();
+    let thread = thread::spawn_daemon(move || {
+        while let Envelope::Msg(msg) = receiver.recv_msg_block() {
+            actor.handle(msg);
+        }
+        *slot.lock().unwrap() = Some(actor);
+    });
+    let addr = Addr {
+        sender,
+        thread: thread.thread().id(),
+    };
+    ActorHandle {
+        addr,
+        thread,
+        stopped,
+    }
+}
@@ -0,0 +1,72 @@
+//! Allocation accounting, for
+//! [`ConfigBuilder::with_memory_budget`](crate::ConfigBuilder::with_memory_budget).
+//!
+//! TraceForge cannot see the memory its explorations use unless the program counts its
+//! allocations, by installing the [`CountingAllocator`] as its global allocator, e.g. in the test
+//! crate that runs the model:
+//!
+//! ```no_run
+//! #[global_allocator]
+//! static ALLOCATOR: traceforge::alloc::CountingAllocator = traceforge::alloc::CountingAllocator;
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// A global allocator that allocates from the [`System`] allocator, and counts the bytes that are
+/// currently allocated
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            count(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            count(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        count(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            count(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+fn count(bytes: isize) {
+    if bytes >= 0 {
+        ALLOCATED.fetch_add(bytes as usize, Ordering::Relaxed);
+    } else {
+        ALLOCATED.fetch_sub(bytes.unsigned_abs(), Ordering::Relaxed);
+    }
+    INSTALLED.store(true, Ordering::Relaxed);
+}
+
+/// The number of bytes currently allocated, or `None` if the [`CountingAllocator`] is not the
+/// global allocator of the program
+pub fn allocated() -> Option<usize> {
+    if INSTALLED.load(Ordering::Relaxed) {
+        Some(ALLOCATED.load(Ordering::Relaxed))
+    } else {
+        None
+    }
+}
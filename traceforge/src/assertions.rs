@@ -0,0 +1,120 @@
+//! Assertion macros that report their failures to the model checker.
+//!
+//! [`assert!`] and [`assert_eq!`] behave like the system macros of the same name, and panic with
+//! the same messages. When they fail inside a TraceForge test, the counterexample additionally
+//! records the failure as a user assertion (rather than as a runtime error), together with the
+//! task and the event at which it failed, and the source location of the assertion.
+//!
+//! Import them explicitly so that they take precedence over the system macros:
+//!
+//! ```
+//! use traceforge::assertions::{assert, assert_eq};
+//!
+//! assert!(1 + 1 == 2);
+//! assert_eq!(2, 1 + 1, "arithmetic is broken");
+//! ```
+//!
+//! A glob import (`use traceforge::assertions::*`) makes the names ambiguous with the system
+//! macros, so it should be avoided.
+
+use std::panic::Location;
+
+use crate::runtime::execution::ExecutionState;
+use crate::runtime::failure::{record_failure, FailureCategory};
+
+#[doc(inline)]
+pub use crate::__traceforge_assert as assert;
+#[doc(inline)]
+pub use crate::__traceforge_assert_eq as assert_eq;
+
+/// Asserts that a boolean expression is `true`, like [`std::assert!`].
+///
+/// See the [module documentation](crate::assertions) for how failures are reported.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __traceforge_assert {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            $crate::assertions::assert_failed_impl(::std::concat!(
+                "assertion failed: ",
+                ::std::stringify!($cond)
+            ));
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::assertions::assert_failed_fmt_impl(::std::format_args!($($arg)+));
+        }
+    };
+}
+
+/// Asserts that two expressions are equal to each other (using [`PartialEq`]), like
+/// [`std::assert_eq!`].
+///
+/// See the [module documentation](crate::assertions) for how failures are reported.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __traceforge_assert_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    $crate::assertions::assert_failed_fmt_impl(::std::format_args!(
+                        "assertion `left == right` failed\n  left: {:?}\n right: {:?}",
+                        &*left_val,
+                        &*right_val
+                    ));
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    $crate::assertions::assert_failed_fmt_impl(::std::format_args!(
+                        "assertion `left == right` failed: {}\n  left: {:?}\n right: {:?}",
+                        ::std::format_args!($($arg)+),
+                        &*left_val,
+                        &*right_val
+                    ));
+                }
+            }
+        }
+    };
+}
+
+// Used by the assertion macros. Not intended to be invoked directly.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_failed_impl(msg: &'static str) -> ! {
+    record_assertion_failure(msg.to_string(), Location::caller());
+    std::panic::panic_any(msg)
+}
+
+// Used by the assertion macros. Not intended to be invoked directly.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_failed_fmt_impl(msg: std::fmt::Arguments<'_>) -> ! {
+    // Keep the same payload type as the system macros: `&'static str` for plain literals,
+    // `String` otherwise
+    match msg.as_str() {
+        Some(msg) => assert_failed_impl(msg),
+        None => {
+            let msg = msg.to_string();
+            record_assertion_failure(msg.clone(), Location::caller());
+            std::panic::panic_any(msg)
+        }
+    }
+}
+
+fn record_assertion_failure(message: String, location: &Location<'_>) {
+    // Outside of a test there is no counterexample to annotate
+    if ExecutionState::try_with(|_| ()).is_some() {
+        record_failure(
+            FailureCategory::Assertion,
+            message,
+            Some(location.to_string()),
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Cancellation safety of async functions.
+//!
+//! [`check_cancel_safe`] drops the future of an async function at each point where it is
+//! suspended, i.e. where one of its `.await`s is pending, and checks that an invariant of the
+//! state that the function changes still holds. TraceForge explores the cancellation at every
+//! suspension as a choice, together with the schedules of the other tasks.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::{future, verify, Config, Stats};
+
+/// Verifies that the future of an async function is cancellation safe, and returns the
+/// statistics of the exploration. In every execution, `f` builds the future to check, and the
+/// invariant of the state that it changes. Wherever the future is suspended, TraceForge explores
+/// both dropping it there and letting it go on. The invariant must hold when the future is
+/// dropped, and when it completes.
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use traceforge::{channel, thread, Config};
+///
+/// traceforge::check_cancel_safe(Config::builder().build(), || {
+///     let balance = Arc::new(Mutex::new(0));
+///     let (tx, rx) = channel::Builder::new().build();
+///     let _ = thread::spawn(move || tx.send_msg(10));
+///     let b = balance.clone();
+///     let deposit = async move {
+///         let amount = rx.async_recv_msg().await;
+///         *b.lock().unwrap() += amount;
+///     };
+///     (deposit, move || *balance.lock().unwrap() >= 0)
+/// });
+/// ```
+///
+/// The future only ever runs on the main thread of the model, with
+/// [`future::block_on`]. An `.await` that is ready at once, or that blocks the task instead of
+/// being pending, does not suspend the future, so the future is not dropped there.
+///
+/// # Panics
+///
+/// Panics at the first execution in which the invariant does not hold, as a failure of the
+/// model, with the counterexample of that execution.
+pub fn check_cancel_safe<F, Fut, I>(config: Config, f: F) -> Stats
+where
+    F: Fn() -> (Fut, I) + Send + Sync + 'static,
+    Fut: Future,
+    I: FnOnce() -> bool,
+{
+    verify(config, move || {
+        let (fut, invariant) = f();
+        let cancellable = Cancellable {
+            fut: Some(Box::pin(fut)),
+            suspensions: 0,
+        };
+        match future::block_on(cancellable) {
+            Ok(()) => assert!(invariant(), "the invariant does not hold after completion"),
+            Err(suspension) => assert!(
+                invariant(),
+                "not cancellation safe: the invariant does not hold after dropping the future \
+                 at its suspension {}",
+                suspension
+            ),
+        }
+    })
+}
+
+// Drops the future at the suspension chosen by the model checker, and returns its number then
+struct Cancellable<F: Future> {
+    fut: Option<Pin<Box<F>>>,
+    suspensions: usize,
+}
+
+impl<F: Future> Future for Cancellable<F> {
+    type Output = Result<(), usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = self.fut.as_mut().expect("polled after completion");
+        if fut.as_mut().poll(cx).is_ready() {
+            self.fut = None;
+            return Poll::Ready(Ok(()));
+        }
+        self.suspensions += 1;
+        if crate::nondet() {
+            self.fut = None;
+            return Poll::Ready(Err(self.suspensions));
+        }
+        Poll::Pending
+    }
+}
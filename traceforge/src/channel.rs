@@ -18,6 +18,41 @@ use std::sync::Arc;
 
 type Result<T> = std::result::Result<T, Error>;
 
+/// The messages of a channel over the complete executions of an exploration, reported in
+/// [`Stats::message_counts`](crate::Stats::message_counts) if
+/// [`ConfigBuilder::with_message_counts`](crate::ConfigBuilder::with_message_counts) is set.
+///
+/// Every message is either received, lost, or still in flight at the end of its execution, so
+/// `sent == received + lost + in_flight`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCounts {
+    /// Number of messages sent on the channel
+    pub sent: usize,
+    /// Number of messages received from the channel
+    pub received: usize,
+    /// Number of lossy messages that were lost
+    pub lost: usize,
+    /// Number of messages that were never received nor lost
+    pub in_flight: usize,
+    /// Number of executions that ended with messages in flight on the channel
+    pub executions_with_in_flight: usize,
+}
+
+impl MessageCounts {
+    /// Whether every message that was sent was received
+    pub fn all_received(&self) -> bool {
+        self.sent == self.received
+    }
+
+    pub(crate) fn merge(&mut self, other: &MessageCounts) {
+        self.sent += other.sent;
+        self.received += other.received;
+        self.lost += other.lost;
+        self.in_flight += other.in_flight;
+        self.executions_with_in_flight += other.executions_with_in_flight;
+    }
+}
+
 #[allow(deprecated)]
 pub(crate) fn cons_to_model(c: ConsType) -> CommunicationModel {
     match c {
@@ -31,6 +66,7 @@ pub(crate) fn cons_to_model(c: ConsType) -> CommunicationModel {
 pub struct Builder<T> {
     named_id: Option<Loc>,
     comm: Option<CommunicationModel>,
+    internal: bool,
     _t: std::marker::PhantomData<T>,
 }
 
@@ -39,6 +75,7 @@ impl<T: Message + Clone + 'static> Builder<T> {
         Builder {
             named_id: None,
             comm: None,
+            internal: false,
             _t: std::marker::PhantomData,
         }
     }
@@ -53,6 +90,13 @@ impl<T: Message + Clone + 'static> Builder<T> {
         self
     }
 
+    // Marks the channel as one of the protocol of a TraceForge primitive: its messages do not
+    // count towards `ConfigBuilder::with_max_in_flight`
+    pub(crate) fn internal(mut self) -> Self {
+        self.internal = true;
+        self
+    }
+
     pub fn build(self) -> Channel<T> {
         let (inner, comm) = if let Some(name) = self.named_id {
             let comm = self.comm.unwrap_or_else(|| get_execution_state_info().1);
@@ -67,7 +111,10 @@ impl<T: Message + Clone + 'static> Builder<T> {
                 (s.must.borrow_mut().handle_unique(channel), comm)
             })
         };
-        (Sender::new(inner.clone(), comm), Receiver::new(inner, comm))
+        (
+            Sender::new(inner.clone(), comm, self.internal),
+            Receiver::new(inner, comm),
+        )
     }
 }
 
@@ -79,8 +126,9 @@ impl<T: Message + Clone + 'static> Default for Builder<T> {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Sender<T> {
-    inner: Loc,
+    pub(crate) inner: Loc,
     comm: CommunicationModel,
+    internal: bool,
     _t: std::marker::PhantomData<T>,
 }
 
@@ -89,38 +137,44 @@ unsafe impl<T: Send> Send for Sender<T> {}
 unsafe impl<T: Sync> Sync for Sender<T> {}
 
 impl<T: Message + 'static> Sender<T> {
-    fn new(inner: Loc, comm: CommunicationModel) -> Self {
+    fn new(inner: Loc, comm: CommunicationModel, internal: bool) -> Self {
         Sender {
             inner,
             comm,
+            internal,
             _t: std::marker::PhantomData,
         }
     }
 
     pub fn send_tagged_msg(&self, tag: u32, v: T) {
-        crate::send_msg_with_tag(v, Some(tag), &self.inner, self.comm, false)
+        crate::send_msg_with_tag(v, Some(tag), &self.inner, self.comm, false, self.internal)
     }
 
     pub fn send_tagged_lossy_msg(&self, tag: u32, v: T) {
-        crate::send_msg_with_tag(v, Some(tag), &self.inner, self.comm, true)
+        crate::send_msg_with_tag(v, Some(tag), &self.inner, self.comm, true, self.internal)
     }
 
     pub fn send_vec_tagged_msg(&self, tag: Vec<u32>, v: T) {
         let tag = if tag.is_empty() { None } else { Some(tag) };
-        crate::send_msg_with_vec_tag(v, tag, &self.inner, self.comm, false)
+        crate::send_msg_with_vec_tag(v, tag, &self.inner, self.comm, false, self.internal)
     }
 
     pub fn send_vec_tagged_lossy_msg(&self, tag: Vec<u32>, v: T) {
         let tag = if tag.is_empty() { None } else { Some(tag) };
-        crate::send_msg_with_vec_tag(v, tag, &self.inner, self.comm, true)
+        crate::send_msg_with_vec_tag(v, tag, &self.inner, self.comm, true, self.internal)
     }
 
     pub fn send_msg(&self, v: T) {
-        crate::send_msg_with_tag(v, None, &self.inner, self.comm, false);
+        crate::send_msg_with_tag(v, None, &self.inner, self.comm, false, self.internal);
     }
 
     pub fn send_lossy_msg(&self, v: T) {
-        crate::send_msg_with_tag(v, None, &self.inner, self.comm, true);
+        crate::send_msg_with_tag(v, None, &self.inner, self.comm, true, self.internal);
+    }
+
+    /// Sends all messages in `msgs` as one contiguous block, see [`crate::send_all`]
+    pub fn send_all(&self, msgs: impl IntoIterator<Item = T>) {
+        crate::send_batch(msgs, &self.inner, self.comm, self.internal);
     }
 }
 
@@ -214,7 +268,7 @@ pub type Channel<T> = (Sender<T>, Receiver<T>);
 // Hacky way for async_recv's cancel
 #[allow(dead_code)]
 pub(crate) fn from_receiver<T: Message + 'static>(recv: Receiver<T>) -> Sender<T> {
-    Sender::new(recv.inner, recv.comm)
+    Sender::new(recv.inner, recv.comm, false)
 }
 
 // A synonym of ThreadId that is hidden from the user,
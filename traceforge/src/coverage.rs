@@ -1,4 +1,5 @@
 use log::debug;
+use serde::{Deserialize, Serialize};
 
 use crate::{monitor_types::EndCondition, telemetry::Coverage};
 
@@ -34,7 +35,7 @@ use crate::{monitor_types::EndCondition, telemetry::Coverage};
 /// }
 /// ```
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct CoverageInfo {
     // the field is currently public to implement merge easily
     // TODO: how can I do this without making the field public?
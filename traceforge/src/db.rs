@@ -0,0 +1,214 @@
+//! A minimal transactional key-value store, to model code that relies on the isolation of
+//! database transactions.
+//!
+//! A [`Transaction`] reads from the store and buffers its writes until [`Transaction::commit`],
+//! which installs them atomically. Beginning a transaction, each of its reads and its commit are
+//! scheduling points: the transactions of different tasks interleave, and TraceForge explores
+//! their orders. What a transaction sees of the others, and which commits are rejected, depends
+//! on the [`Isolation`] of the store:
+//!
+//! * [`Isolation::ReadCommitted`]: each read sees the latest committed value, and every commit
+//!   succeeds. Two reads of the same key can differ, and concurrent updates can be lost.
+//! * [`Isolation::Snapshot`]: the reads see the committed values as of the beginning of the
+//!   transaction, and a commit fails if another transaction committed a write to one of the
+//!   same keys since then (first committer wins). Transactions that read keys that the others
+//!   write, and write disjoint keys, can still commit together: their outcome can be a *write
+//!   skew* that no serial order of the transactions gives.
+//! * [`Isolation::Serializable`]: as with snapshots, but a commit also fails if one of the keys
+//!   that the transaction read was written since it began, so the committed transactions
+//!   behave as if they ran one at a time, in the order of their commits.
+//!
+//! The anomalies of the weaker levels show up as the violations of the invariants that a model
+//! checks, like any other bug:
+//!
+//! ```no_run
+//! use traceforge::db::{Db, Isolation};
+//! use traceforge::thread;
+//!
+//! traceforge::verify(traceforge::Config::default(), || {
+//!     let db = Db::new(Isolation::Snapshot);
+//!     let mut setup = db.begin();
+//!     setup.write("x", 0);
+//!     setup.commit().unwrap();
+//!     let increment = |db: Db<i32>| {
+//!         move || {
+//!             let mut tx = db.begin();
+//!             let x = tx.read("x").unwrap();
+//!             tx.write("x", x + 1);
+//!             tx.commit().is_ok()
+//!         }
+//!     };
+//!     let a = thread::spawn(increment(db.clone()));
+//!     let b = thread::spawn(increment(db.clone()));
+//!     let committed = [a.join().unwrap(), b.join().unwrap()];
+//!     // No update is lost: a transaction that read a stale value fails to commit
+//!     let commits = committed.iter().filter(|c| **c).count() as i32;
+//!     assert_eq!(db.get("x"), Some(commits));
+//! });
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use crate::sync::Mutex;
+
+/// The isolation of the transactions of a [`Db`], see [the module](self)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Isolation {
+    ReadCommitted,
+    Snapshot,
+    Serializable,
+}
+
+/// Error returned by [`Transaction::commit`] when the transaction conflicts with another one
+/// that committed since it began, in which case none of its writes are installed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// A key that the other transaction wrote
+    pub key: String,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transaction conflicts on key {}", self.key)
+    }
+}
+
+impl Error for Conflict {}
+
+#[derive(Debug)]
+struct State<V> {
+    // The committed values of each key, with the number of the commit that wrote them, oldest
+    // first
+    versions: HashMap<String, Vec<(u64, V)>>,
+    // The number of commits so far
+    commits: u64,
+}
+
+impl<V: Clone> State<V> {
+    // The value of `key` after the first `commits` commits
+    fn value_at(&self, key: &str, commits: u64) -> Option<V> {
+        let versions = self.versions.get(key)?;
+        let version = versions.iter().rev().find(|(c, _)| *c <= commits);
+        version.map(|(_, v)| v.clone())
+    }
+
+    // Whether a commit after the first `commits` ones wrote `key`
+    fn written_since(&self, key: &str, commits: u64) -> bool {
+        let last = self.versions.get(key).and_then(|v| v.last());
+        last.is_some_and(|(c, _)| *c > commits)
+    }
+}
+
+/// An in-memory transactional key-value store. Clones refer to the same store. It can only be
+/// used in the execution that created it.
+#[derive(Debug)]
+pub struct Db<V> {
+    state: Arc<Mutex<State<V>>>,
+    isolation: Isolation,
+}
+
+impl<V: Clone> Db<V> {
+    /// Creates an empty store, whose transactions have the isolation `isolation`
+    pub fn new(isolation: Isolation) -> Self {
+        let state = State {
+            versions: HashMap::new(),
+            commits: 0,
+        };
+        Db {
+            state: Arc::new(Mutex::new(state)),
+            isolation,
+        }
+    }
+
+    /// The isolation of the transactions of the store
+    pub fn isolation(&self) -> Isolation {
+        self.isolation
+    }
+
+    /// Begins a transaction
+    pub fn begin(&self) -> Transaction<V> {
+        let start = self.state.blocking_lock().commits;
+        Transaction {
+            db: self.clone(),
+            start,
+            reads: BTreeSet::new(),
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the latest committed value of `key`, outside of any transaction
+    pub fn get(&self, key: &str) -> Option<V> {
+        let state = self.state.blocking_lock();
+        state.value_at(key, state.commits)
+    }
+}
+
+impl<V> Clone for Db<V> {
+    fn clone(&self) -> Self {
+        Db {
+            state: self.state.clone(),
+            isolation: self.isolation,
+        }
+    }
+}
+
+/// A transaction of a [`Db`]. Dropping it without committing it aborts it.
+#[derive(Debug)]
+pub struct Transaction<V> {
+    db: Db<V>,
+    // The number of commits when the transaction began, which it sees with snapshots
+    start: u64,
+    reads: BTreeSet<String>,
+    writes: BTreeMap<String, V>,
+}
+
+impl<V: Clone> Transaction<V> {
+    /// Returns the value of `key`, which is the last write of the transaction to it, if any.
+    /// Otherwise, it is the committed value that the isolation of the store lets the
+    /// transaction see.
+    pub fn read(&mut self, key: &str) -> Option<V> {
+        if let Some(v) = self.writes.get(key) {
+            return Some(v.clone());
+        }
+        let state = self.db.state.blocking_lock();
+        let commits = match self.db.isolation {
+            Isolation::ReadCommitted => state.commits,
+            Isolation::Snapshot | Isolation::Serializable => self.start,
+        };
+        self.reads.insert(key.to_owned());
+        state.value_at(key, commits)
+    }
+
+    /// Writes `value` to `key`. The write is only seen by the other transactions once this one
+    /// commits.
+    pub fn write(&mut self, key: &str, value: V) {
+        self.writes.insert(key.to_owned(), value);
+    }
+
+    /// Commits the transaction, which installs all its writes at once. Returns [`Conflict`],
+    /// and installs none of them, if the isolation of the store rejects the transaction
+    /// because of a transaction that committed since it began.
+    pub fn commit(self) -> Result<(), Conflict> {
+        let mut state = self.db.state.blocking_lock();
+        let checked: Vec<&String> = match self.db.isolation {
+            Isolation::ReadCommitted => Vec::new(),
+            Isolation::Snapshot => self.writes.keys().collect(),
+            Isolation::Serializable => self.writes.keys().chain(&self.reads).collect(),
+        };
+        if let Some(key) = checked
+            .into_iter()
+            .find(|key| state.written_since(key, self.start))
+        {
+            return Err(Conflict { key: key.clone() });
+        }
+        state.commits += 1;
+        let commit = state.commits;
+        for (key, value) in self.writes {
+            state.versions.entry(key).or_default().push((commit, value));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+//! Signatures of the deadlocks found during an exploration, for
+//! [`ConfigBuilder::with_collect_deadlocks`](crate::ConfigBuilder::with_collect_deadlocks),
+//! and the reasons why tasks wait, for [`blocked_tasks`](crate::blocked_tasks) and
+//! [`task_graph`](crate::task_graph).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event_label::{BlockType, LabelEnum};
+use crate::exec_graph::ExecutionGraph;
+use crate::runtime::execution::ExecutionState;
+use crate::thread::ThreadId;
+
+/// Why a blocked task cannot make progress, see [`blocked_tasks`](crate::blocked_tasks)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WaitReason {
+    /// Waiting for a message, e.g. in [`recv_msg_block`](crate::recv_msg_block) or on a channel
+    Receive,
+    /// Waiting for the given thread to finish
+    Join(ThreadId),
+    /// Waiting to acquire a [`Mutex`](crate::sync::Mutex) or an [`RwLock`](crate::sync::RwLock)
+    Lock,
+    /// Waiting for a notification of a [`Notify`](crate::sync::Notify)
+    Notify,
+    /// Waiting for a pending future to be woken, for another reason
+    Future,
+}
+
+impl fmt::Display for WaitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitReason::Receive => write!(f, "receive"),
+            WaitReason::Join(t) => write!(f, "join {}", t),
+            WaitReason::Lock => write!(f, "lock"),
+            WaitReason::Notify => write!(f, "notify"),
+            WaitReason::Future => write!(f, "future"),
+        }
+    }
+}
+
+impl WaitReason {
+    // What thread `t` waits for if it is blocked, as far as the graph tells. Daemon threads
+    // that wait for a message, and user blockings, are not waiting for another task.
+    pub(crate) fn of_block(g: &ExecutionGraph, t: ThreadId) -> Option<Self> {
+        let Some(LabelEnum::Block(b)) = g.thread_last(t) else {
+            return None;
+        };
+        match b.btype() {
+            BlockType::Value(..) if g.is_thread_daemon(t) => None,
+            BlockType::Value(..) => Some(WaitReason::Receive),
+            BlockType::Join(target) => Some(WaitReason::Join(*target)),
+            BlockType::Assume | BlockType::Assert => None,
+        }
+    }
+}
+
+/// Whether a task can run, see [`task_graph`](crate::task_graph)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskState {
+    /// The task can be scheduled next
+    Runnable,
+    /// The task cannot run until another task acts
+    Blocked,
+    /// The task returned
+    Finished,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskState::Runnable => write!(f, "runnable"),
+            TaskState::Blocked => write!(f, "blocked"),
+            TaskState::Finished => write!(f, "finished"),
+        }
+    }
+}
+
+/// A task of the current execution, as returned by [`task_graph`](crate::task_graph)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskSnapshot {
+    /// The thread of the task
+    pub id: ThreadId,
+    /// The name of the task, if it has one
+    pub name: Option<String>,
+    /// Whether the task can run
+    pub state: TaskState,
+    /// What the task waits for, if it is blocked on another task. Blocked daemon threads that
+    /// wait for a message, and tasks blocked by an assumption, have none.
+    pub wait_reason: Option<WaitReason>,
+    /// Whether the execution can complete without the task finishing, e.g. for a daemon thread
+    pub detached: bool,
+}
+
+// Records that the current task waits for `reason` while it runs `f`, which blocks, so that a
+// primitive built on messages is reported by what it waits for rather than as a receive
+pub(crate) fn waiting_for<T>(reason: WaitReason, f: impl FnOnce() -> T) -> T {
+    let outer = ExecutionState::with(|s| s.current_mut().wait_reason.replace(reason));
+    let result = f();
+    ExecutionState::with(|s| s.current_mut().wait_reason = outer);
+    result
+}
+
+// Records that the future polled by the current task is pending on `reason`, until its task is
+// woken, see `waiting_for_wake`
+pub(crate) fn pending_on(reason: WaitReason) {
+    ExecutionState::with(|s| s.current_mut().wait_reason = Some(reason));
+}
+
+// Waits in `f` for the future polled by the current task to be woken
+pub(crate) fn waiting_for_wake<T>(f: impl FnOnce() -> T) -> T {
+    ExecutionState::with(|s| {
+        let task = s.current_mut();
+        task.wait_reason.get_or_insert(WaitReason::Future);
+    });
+    let result = f();
+    ExecutionState::with(|s| s.current_mut().wait_reason = None);
+    result
+}
+
+/// A task that can never make progress, and what it waits for
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BlockedTask {
+    /// The name of the task, or its thread id if it has no name
+    pub name: String,
+    /// What the task waits for, as reported by [`blocked_tasks`](crate::blocked_tasks)
+    pub reason: WaitReason,
+}
+
+/// The tasks that are blocked when an execution deadlocks. Two executions with the same
+/// signature are reported as a single deadlock.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Deadlock {
+    /// The blocked tasks, ordered by name
+    pub blocked: Vec<BlockedTask>,
+}
+
+impl Deadlock {
+    /// The signature of the deadlock of a blocked execution graph, given what its tasks waited
+    /// for in the primitives they blocked in. Like `ExecutionGraph::check_blocked`, daemon
+    /// threads that wait for a message are not blocked.
+    pub(crate) fn of(g: &ExecutionGraph, wait_reasons: &HashMap<ThreadId, WaitReason>) -> Self {
+        let mut blocked = Vec::new();
+        for t in g.thread_ids() {
+            let Some(reason) = WaitReason::of_block(g, t) else {
+                continue;
+            };
+            let name = match g.get_thread_tclab(t).name() {
+                Some(name) => name.clone(),
+                None => format!("thread {}", t),
+            };
+            blocked.push(BlockedTask {
+                name,
+                reason: wait_reasons.get(&t).cloned().unwrap_or(reason),
+            });
+        }
+        blocked.sort();
+        Deadlock { blocked }
+    }
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tasks = self
+            .blocked
+            .iter()
+            .map(|t| format!("{} ({})", t.name, t.reason))
+            .collect::<Vec<_>>();
+        write!(f, "[{}]", tasks.join(", "))
+    }
+}
@@ -0,0 +1,93 @@
+//! Differential testing of two implementations of the same model.
+//!
+//! [`differential`] explores every schedule of a reference implementation, and drives the
+//! implementation under test, e.g. a refactor of the reference, through each of them. Both
+//! have to produce the same output under every schedule, and the first schedule under which
+//! they differ fails the exploration, with its counterexample.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::must::Must;
+use crate::runtime::execution::ExecutionState;
+use crate::script::Action;
+use crate::{explore, Config, Stats};
+
+/// Verifies that `test` produces the same observable outputs as `reference` under the same
+/// sequences of scheduling decisions, and returns the statistics of the exploration of
+/// `reference`. The output of an implementation is the value that its main task returns.
+///
+/// The executions of `reference` are explored under `config`, recording the scheduling
+/// decisions of each one up to the return of its main task. `test` then runs once on each of
+/// these schedules, as a script of [`run_script`](crate::run_script), and its output is
+/// compared with the output of `reference` under the same schedule. Since a refactor may
+/// change the events of the implementation, the decisions that pick a task which cannot run in
+/// `test` are skipped, and once the schedule is over, `test` goes on with the schedule policy
+/// of `config`.
+///
+/// # Panics
+///
+/// Panics at the first schedule under which `test` returns another output than `reference`,
+/// as a failure of the model, with the counterexample of the execution of `test`. The message
+/// spells out the schedule that each implementation ran on, so that either one can be rerun
+/// on its own with [`run_script`](crate::run_script).
+pub fn differential<T, R, S>(config: Config, reference: R, test: S) -> Stats
+where
+    T: fmt::Debug + PartialEq + Send + Sync + 'static,
+    R: Fn() -> T + Send + Sync + 'static,
+    S: Fn() -> T + Send + Sync + 'static,
+{
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let must = Rc::new(RefCell::new(Must::new(config.clone(), false)));
+    must.borrow_mut().record_schedule();
+    let recorded = runs.clone();
+    explore(
+        &must,
+        &Arc::new(move || {
+            let output = reference();
+            let schedule = ExecutionState::with(|s| s.must.borrow().schedule());
+            recorded.lock().unwrap().push((schedule, output));
+        }),
+    );
+    let stats = must.borrow().stats();
+
+    let test = Arc::new(test);
+    let runs = std::mem::take(&mut *runs.lock().unwrap());
+    let mut followed = HashSet::new();
+    for (schedule, expected) in runs {
+        // Executions that only differ after the return of the main task have the same schedule
+        if !followed.insert(schedule.clone()) {
+            continue;
+        }
+        follow(&config, schedule, expected, test.clone());
+    }
+    stats
+}
+
+// Runs `test` on `schedule`, under which the reference returns `expected`
+fn follow<T, S>(config: &Config, schedule: Vec<Action>, expected: T, test: Arc<S>)
+where
+    T: fmt::Debug + PartialEq + Send + Sync + 'static,
+    S: Fn() -> T + Send + Sync + 'static,
+{
+    let must = Rc::new(RefCell::new(Must::new(config.clone(), false)));
+    must.borrow_mut().follow_script(&schedule);
+    must.borrow_mut().record_schedule();
+    explore(
+        &must,
+        &Arc::new(move || {
+            let output = test();
+            if output != expected {
+                let followed = ExecutionState::with(|s| s.must.borrow().schedule());
+                panic!(
+                    "the reference returned {:?} under the schedule {:?}, but the implementation \
+                     under test returned {:?} under the schedule {:?} that follows it",
+                    expected, schedule, output, followed
+                );
+            }
+        }),
+    );
+}
@@ -0,0 +1,161 @@
+//! Clusters of modeled nodes, for distributed protocols such as leader elections or quorums.
+//!
+//! [`spawn`] starts `n` nodes, each in a thread of its own, numbered from 0 to `n - 1`. A
+//! [`Node`] knows the size of its cluster and can send a message to any node, or to all of them,
+//! and receive the messages sent to it together with their sender. TraceForge explores the
+//! orders in which the messages reach each node, under the communication model of the
+//! configuration. The returned [`Cluster`] is the driver of the nodes: it waits for them and
+//! collects their results.
+//!
+//! ```no_run
+//! use traceforge::{dist, Config};
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     // Each node learns the largest id of the cluster
+//!     let results = dist::spawn(3, |node| {
+//!         node.broadcast(node.id());
+//!         let ids = (1..node.size()).map(|_| node.recv().1);
+//!         ids.fold(node.id(), usize::max)
+//!     })
+//!     .join();
+//!     assert_eq!(results, vec![2, 2, 2]);
+//! });
+//! ```
+//!
+//! Nodes whose behavior does not depend on their id can be spawned with [`spawn_symmetric`]
+//! instead, which explores their interactions only up to renaming of the nodes.
+
+use std::fmt::Debug;
+
+use crate::channel::{Builder, Receiver, Sender};
+use crate::msg::Message;
+use crate::thread::JoinHandle;
+
+/// A node of a [`Cluster`], handed to the function it runs
+#[derive(Debug)]
+pub struct Node<M> {
+    id: usize,
+    peers: Vec<Sender<(usize, M)>>,
+    inbox: Receiver<(usize, M)>,
+}
+
+impl<M: Clone + Debug + PartialEq + Send + 'static> Node<M> {
+    /// The id of the node, between 0 and `size() - 1`
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// The number of nodes in the cluster
+    pub fn size(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// The number of nodes that make a majority of the cluster
+    pub fn quorum(&self) -> usize {
+        self.size() / 2 + 1
+    }
+
+    /// Sends `msg` to the node `to`, which may be this node
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no node `to` in the cluster.
+    pub fn send(&self, to: usize, msg: M) {
+        self.peers[to].send_msg((self.id, msg));
+    }
+
+    /// Sends `msg` to every other node of the cluster
+    pub fn broadcast(&self, msg: M) {
+        (0..self.size())
+            .filter(|&to| to != self.id)
+            .for_each(|to| self.send(to, msg.clone()));
+    }
+
+    /// Receives a message sent to this node, waiting for one, and returns it with the id of
+    /// its sender
+    pub fn recv(&self) -> (usize, M) {
+        self.inbox.recv_msg_block()
+    }
+
+    /// Receives a message sent to this node if there is one, or returns `None`, which models a
+    /// timeout
+    pub fn try_recv(&self) -> Option<(usize, M)> {
+        self.inbox.recv_msg()
+    }
+}
+
+/// The nodes started by [`spawn`] or [`spawn_symmetric`]
+#[derive(Debug)]
+pub struct Cluster<R> {
+    nodes: Vec<JoinHandle<R>>,
+}
+
+impl<R: Message + 'static> Cluster<R> {
+    /// The number of nodes in the cluster
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Waits for all the nodes to finish, and returns their results, by id
+    pub fn join(self) -> Vec<R> {
+        self.nodes.into_iter().map(|n| n.join().unwrap()).collect()
+    }
+}
+
+// The nodes of a cluster of `n`, before they are spawned
+fn nodes<M>(n: usize) -> Vec<Node<M>>
+where
+    M: Clone + Debug + PartialEq + Send + 'static,
+{
+    let (peers, inboxes): (Vec<_>, Vec<_>) =
+        (0..n).map(|_| Builder::<(usize, M)>::new().build()).unzip();
+    inboxes
+        .into_iter()
+        .enumerate()
+        .map(|(id, inbox)| Node {
+            id,
+            peers: peers.clone(),
+            inbox,
+        })
+        .collect()
+}
+
+/// Starts a cluster of `n` nodes, each running `f` in a thread of its own, and exchanging
+/// messages of type `M`
+pub fn spawn<M, R, F>(n: usize, f: F) -> Cluster<R>
+where
+    M: Clone + Debug + PartialEq + Send + 'static,
+    R: Message + 'static,
+    F: Fn(Node<M>) -> R + Clone + Send + 'static,
+{
+    let nodes = nodes(n)
+        .into_iter()
+        .map(|node| {
+            let f = f.clone();
+            crate::thread::spawn(move || f(node))
+        })
+        .collect();
+    Cluster { nodes }
+}
+
+/// Starts a cluster like [`spawn`], whose nodes are symmetric: nothing in the execution may
+/// depend on which node did what, in particular not on their ids. TraceForge then explores the
+/// interactions of the nodes only up to their renaming, see
+/// [`spawn_symmetric_group`](crate::spawn_symmetric_group).
+pub fn spawn_symmetric<M, R, F>(n: usize, f: F) -> Cluster<R>
+where
+    M: Clone + Debug + PartialEq + Send + 'static,
+    R: Message + 'static,
+    F: Fn(Node<M>) -> R + Clone + Send + 'static,
+{
+    let mut handles: Vec<JoinHandle<R>> = Vec::with_capacity(n);
+    for node in nodes(n) {
+        let f = f.clone();
+        let h = match handles.last() {
+            None => crate::thread::spawn(move || f(node)),
+            Some(prev) => crate::spawn_symmetric(move || f(node), prev.thread().id()),
+        };
+        handles.push(h);
+    }
+    Cluster { nodes: handles }
+}
@@ -3,6 +3,10 @@ use crate::thread::{main_thread_id, ThreadId};
 use serde::{Deserialize, Serialize};
 
 /// Models a single event in an execution graph.
+///
+/// An event is identified by the thread that executed it and by its position within the
+/// thread, i.e., the number of instructions the thread executed before it. Events are ordered
+/// by thread, then by position, so the events of one thread are ordered in program order.
 #[derive(PartialEq, Copy, Clone, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Event {
     pub(crate) thread: ThreadId,
@@ -10,7 +14,18 @@ pub struct Event {
 }
 
 impl Event {
-    pub(crate) fn new(t: ThreadId, i: u32) -> Self {
+    /// The thread that executed the event
+    pub fn thread(&self) -> ThreadId {
+        self.thread
+    }
+
+    /// The position of the event within its thread
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The event at position `i` of thread `t`, e.g., to look up an event of a counterexample
+    pub fn new(t: ThreadId, i: u32) -> Self {
         Self {
             thread: t,
             index: i,
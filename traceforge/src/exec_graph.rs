@@ -7,6 +7,7 @@ use crate::channel::Thread;
 use crate::event::Event;
 use crate::indexed_map::IndexedMap;
 use crate::loc::{Loc, RecvLoc};
+use crate::msg::MsgId;
 use crate::revisit::{Revisit, RevisitPlacement};
 use crate::runtime::task::TaskId;
 use crate::thread::{construct_thread_id, main_thread_id};
@@ -201,8 +202,12 @@ impl ExecutionGraph {
             .map(move |v| {
                 v.iter()
                     .map(move |&pos| self.send_label(pos).unwrap())
-                    // Filter dropped sends and tags
-                    .filter(move |&slab| !slab.is_dropped() && recv_loc.matches_tag(slab))
+                    // Filter dropped sends, tags and values
+                    .filter(move |&slab| {
+                        !slab.is_dropped()
+                            && recv_loc.matches_tag(slab)
+                            && recv_loc.matches_filter(slab)
+                    })
             })
             .fold(
                 Box::new(std::iter::empty()) as Box<dyn Iterator<Item = &SendMsg>>,
@@ -305,6 +310,10 @@ impl ExecutionGraph {
         }
     }
 
+    pub(crate) fn try_to_thread_id(&self, task_id: TaskId) -> Option<ThreadId> {
+        self.task_id_map.get(&task_id).copied()
+    }
+
     pub(crate) fn get_thread_tclab(&self, tid: ThreadId) -> TCreate {
         self.get_thr(&tid).tclab.clone()
     }
@@ -644,6 +653,36 @@ impl ExecutionGraph {
         }
     }
 
+    /// Returns the send event identified by `id`, if the sender has executed it
+    pub(crate) fn find_send(&self, id: &MsgId) -> Option<Event> {
+        let thread = self.threads.iter().find(|t| match t.tclab.name() {
+            Some(name) => *name == id.sender,
+            None => t.tid.to_string() == id.sender,
+        })?;
+        thread
+            .labels
+            .iter()
+            .filter_map(|lab| match lab {
+                LabelEnum::SendMsg(s) => Some(s.pos()),
+                _ => None,
+            })
+            .nth(id.index)
+    }
+
+    /// Returns the identifier of the send event `e`
+    pub(crate) fn msg_id(&self, e: Event) -> MsgId {
+        let thread = self.get_thr(&e.thread);
+        let sender = match thread.tclab.name() {
+            Some(name) => name.clone(),
+            None => e.thread.to_string(),
+        };
+        let index = thread.labels[..e.index as usize]
+            .iter()
+            .filter(|lab| matches!(lab, LabelEnum::SendMsg(_)))
+            .count();
+        MsgId::new(sender, index)
+    }
+
     pub(crate) fn send_label_mut(&mut self, e: Event) -> Option<&mut SendMsg> {
         if let LabelEnum::SendMsg(l) = self.label_mut(e) {
             Some(l)
@@ -252,31 +252,34 @@ fn worker_loop<F>(
 
             let this_func = exec_func.clone();
             let execution = Execution::new(must_wrap.clone());
-            Must::begin_execution(&must_wrap);
-
-            // Unless we're in debug mode, don't pay the cost for collecting
-            // and outputting runtimes.
-            //
-            if cfg!(debug_assertions) {
-                trace!("[{}] is executing.", thread_idx);
-                let start_time = Instant::now();
-                execution.run(move || this_func());
-                let end_time = Instant::now();
-                trace!(
-                    "[{}] is done executing, ran from {:?} to {:?} for {:?}",
-                    thread_idx,
-                    start_time,
-                    end_time,
-                    end_time.duration_since(start_time)
-                );
-            } else {
-                execution.run(move || this_func());
-            }
+            let done = crate::run_with_hooks(&must_wrap, || {
+                Must::begin_execution(&must_wrap);
+
+                // Unless we're in debug mode, don't pay the cost for collecting
+                // and outputting runtimes.
+                //
+                if cfg!(debug_assertions) {
+                    trace!("[{}] is executing.", thread_idx);
+                    let start_time = Instant::now();
+                    execution.run(move || this_func());
+                    let end_time = Instant::now();
+                    trace!(
+                        "[{}] is done executing, ran from {:?} to {:?} for {:?}",
+                        thread_idx,
+                        start_time,
+                        end_time,
+                        end_time.duration_since(start_time)
+                    );
+                } else {
+                    execution.run(move || this_func());
+                }
 
-            *pool_exec_can_drain
-                .lock()
-                .expect("expect_pool_can_drain mutex") = true;
-            if Must::complete_execution(&must_wrap) {
+                *pool_exec_can_drain
+                    .lock()
+                    .expect("expect_pool_can_drain mutex") = true;
+                Must::complete_execution(&must_wrap)
+            });
+            if done {
                 break;
             }
         }); // loop until graph processing complete.
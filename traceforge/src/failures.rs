@@ -0,0 +1,110 @@
+//! The distinct failures found during an exploration, for
+//! [`ConfigBuilder::with_collect_failures`](crate::ConfigBuilder::with_collect_failures).
+//!
+//! Instead of stopping at the first failing execution, the exploration records why each
+//! execution failed and goes on. Executions that fail the same way, i.e. with the same kind of
+//! failure and message, in the same task and at the same source location, are reported as a
+//! single [`CollectedFailure`], which counts them and keeps the schedule of the first one.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::runtime::failure::{FailureCategory, FailureReport};
+
+/// A failure found by the exploration, and how many executions failed the same way
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectedFailure {
+    /// The kind of failure
+    pub category: FailureCategory,
+    /// The message of the failure, e.g. of the panic
+    pub message: String,
+    /// The name of the task that failed, if the failure was raised by a task
+    pub task: Option<String>,
+    /// The source location of the failing assertion, if it is known
+    pub location: Option<String>,
+    /// Number of executions that failed this way
+    pub count: usize,
+    /// The number of the first execution that failed this way, counting from 1, as in the
+    /// reports of [`ConfigBuilder::with_fast_failure`](crate::ConfigBuilder::with_fast_failure)
+    pub execution: usize,
+    /// The events of the first execution that failed this way, in an order in which they can
+    /// run, as printed in the execution graphs, e.g. `(t1, 2): RECV(...)`
+    pub schedule: Vec<String>,
+    /// The file of the counterexample of the first execution that failed this way, if
+    /// [`ConfigBuilder::with_error_trace`](crate::ConfigBuilder::with_error_trace) is set: the
+    /// error trace file, numbered after the failure, e.g. `trace-2.json` for the second one
+    pub trace: Option<String>,
+}
+
+impl CollectedFailure {
+    pub(crate) fn new(report: &FailureReport, execution: usize, schedule: Vec<String>) -> Self {
+        CollectedFailure {
+            category: report.category,
+            message: report.message.clone(),
+            task: report.task.clone(),
+            location: report.location.clone(),
+            count: 1,
+            execution,
+            schedule,
+            trace: None,
+        }
+    }
+
+    /// Whether `other` is the same failure, found in another execution
+    pub fn same_as(&self, other: &CollectedFailure) -> bool {
+        self.category == other.category
+            && self.message == other.message
+            && self.task == other.task
+            && self.location == other.location
+    }
+}
+
+impl fmt::Display for CollectedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.category.name(), self.message)?;
+        if let Some(task) = &self.task {
+            write!(f, " in task {}", task)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        write!(
+            f,
+            " ({} executions, first in execution {})",
+            self.count, self.execution
+        )
+    }
+}
+
+// Adds `failure` to the distinct failures `failures`, in the order in which they were found.
+// Returns whether it is a new one.
+pub(crate) fn merge(failures: &mut Vec<CollectedFailure>, failure: CollectedFailure) -> bool {
+    match failures.iter_mut().find(|f| f.same_as(&failure)) {
+        Some(f) => {
+            f.count += failure.count;
+            false
+        }
+        None => {
+            failures.push(failure);
+            true
+        }
+    }
+}
+
+// The trace file of the `n`th distinct failure, counting from 1, next to the error trace file
+// `base`: `trace.json` becomes `trace-n.json`
+pub(crate) fn trace_file(base: &str, n: usize) -> String {
+    let path = Path::new(base);
+    let stem = path.file_stem().map_or_else(
+        || base.to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, n),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
@@ -0,0 +1,70 @@
+//! The final states reached by the complete executions of an exploration, for
+//! [`ConfigBuilder::with_reachable_states`](crate::ConfigBuilder::with_reachable_states).
+
+use std::fmt;
+
+use crate::event_label::LabelEnum;
+use crate::exec_graph::ExecutionGraph;
+use crate::thread::main_thread_id;
+use crate::Val;
+
+/// A task at the end of a complete execution
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinalTask {
+    /// The name of the task, or its thread id if it has no name
+    pub name: String,
+    /// The value the task returned, or `None` if it did not finish, like a daemon task waiting
+    /// for a message
+    pub result: Option<Val>,
+}
+
+/// The final configuration of a complete execution: which tasks finished and what they
+/// returned, and the shared state as described by the hook of
+/// [`ConfigBuilder::with_reachable_states`](crate::ConfigBuilder::with_reachable_states).
+/// Two executions with the same configuration are reported as a single state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinalState {
+    /// The tasks of the execution, ordered by name
+    pub tasks: Vec<FinalTask>,
+    /// The description of the shared state
+    pub shared: String,
+}
+
+impl FinalState {
+    /// The final state of a complete execution graph, with the given shared state
+    pub(crate) fn of(g: &ExecutionGraph, shared: String) -> Self {
+        let mut tasks: Vec<FinalTask> = g
+            .thread_ids()
+            .into_iter()
+            .map(|t| FinalTask {
+                name: match g.get_thread_tclab(t).name() {
+                    Some(name) => name.clone(),
+                    None => format!("thread {}", t),
+                },
+                result: match g.thread_last(t) {
+                    Some(LabelEnum::End(end)) => Some(end.result().clone()),
+                    // The main thread has no end event, and returns `()` once it finishes
+                    Some(LabelEnum::Block(_)) => None,
+                    _ if t == main_thread_id() => Some(Val::new(())),
+                    _ => None,
+                },
+            })
+            .collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        FinalState { tasks, shared }
+    }
+}
+
+impl fmt::Display for FinalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tasks = self
+            .tasks
+            .iter()
+            .map(|t| match &t.result {
+                Some(_) => format!("{} (finished)", t.name),
+                None => format!("{} (unfinished)", t.name),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "[{}] {}", tasks.join(", "), self.shared)
+    }
+}
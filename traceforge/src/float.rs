@@ -0,0 +1,106 @@
+//! Order-sensitive floating-point computations.
+//!
+//! IEEE addition is not associative: summing the same values in a different order can round
+//! to a different result. A parallel reduction, whose partial results are combined in the
+//! order in which they arrive, can thus compute different sums in different interleavings.
+//! [`sum_in_any_order`] explores every order of a sequential reduction, and [`observe`]
+//! records the values a computation takes across the explored executions, which
+//! [`Stats::float_ranges`](crate::Stats::float_ranges) reports.
+//!
+//! ```no_run
+//! use traceforge::{float, Config};
+//!
+//! let stats = traceforge::verify(Config::default(), || {
+//!     let sum = float::sum_in_any_order(&[1e16, 1.0, -1e16]);
+//!     float::observe("sum", sum);
+//! });
+//! let range = &stats.float_ranges["sum"];
+//! assert!(range.spread() <= 2.0, "the sum varies by {}", range.spread());
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::execution::ExecutionState;
+use crate::Nondet;
+
+/// The smallest and largest values recorded by [`observe`] under one name
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FloatRange {
+    /// The smallest value observed
+    pub min: f64,
+    /// The largest value observed
+    pub max: f64,
+    /// Number of observations
+    pub count: usize,
+}
+
+impl FloatRange {
+    pub(crate) fn new(value: f64) -> Self {
+        FloatRange {
+            min: value,
+            max: value,
+            count: 1,
+        }
+    }
+
+    /// The difference between the largest and the smallest value observed
+    pub fn spread(&self) -> f64 {
+        self.max - self.min
+    }
+
+    /// Whether different values were observed
+    pub fn varies(&self) -> bool {
+        self.min != self.max
+    }
+
+    pub(crate) fn add(&mut self, value: f64) {
+        self.merge(&FloatRange::new(value));
+    }
+
+    pub(crate) fn merge(&mut self, other: &FloatRange) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+    }
+}
+
+impl fmt::Display for FloatRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:e}, {:e}] (spread {:e}, {} observations)",
+            self.min,
+            self.max,
+            self.spread(),
+            self.count
+        )
+    }
+}
+
+/// Sums `values` in an order chosen by the model checker, which explores every order, as a
+/// parallel reduction could. Use it to mark a reduction whose rounded result depends on the
+/// order of its operations. There are `n!` orders of `n` values, so this is meant for a
+/// handful of values.
+pub fn sum_in_any_order(values: &[f64]) -> f64 {
+    let mut remaining = values.to_vec();
+    let mut sum = 0.0;
+    while !remaining.is_empty() {
+        let next = if remaining.len() == 1 {
+            0
+        } else {
+            (0..remaining.len()).nondet()
+        };
+        sum += remaining.remove(next);
+    }
+    sum
+}
+
+/// Records that the float `name` takes the value `value` in the current execution. The
+/// smallest and largest values of each name across the explored executions are reported in
+/// [`Stats::float_ranges`](crate::Stats::float_ranges), which shows how much an
+/// order-sensitive result varies with the interleavings. The observation creates no event.
+pub fn observe(name: &str, value: f64) {
+    ExecutionState::with(|s| s.must.borrow_mut().observe_float(name, value));
+}
@@ -0,0 +1,314 @@
+//! A minimal in-memory filesystem, to model concurrent file accesses and crash consistency.
+//!
+//! Every operation is a scheduling point: the operations of different tasks interleave, and
+//! TraceForge explores their orders. Each operation is atomic, e.g. a write is never torn.
+//!
+//! Changes are volatile until they are made durable, like on a POSIX filesystem: the content of
+//! a file by [`File::fsync`], and the creations and renames by [`Fs::fsync_dir`].
+//! [`Fs::after_crash`] models a power failure: it returns the filesystem without everything
+//! that is not durable, so a model can check what its files look like after restarting.
+//!
+//! A real disk may also have written some of the volatile changes when the power fails.
+//! [`Fs::recover`] explores all the filesystems that a crash can leave, and
+//! [`Fs::inject_crash`] crashes at any point of the execution and runs recovery code on each
+//! of them.
+//!
+//! ```no_run
+//! use traceforge::fs::Fs;
+//!
+//! traceforge::verify(traceforge::Config::default(), || {
+//!     let fs = Fs::new();
+//!     let mut tmp = fs.create("config.tmp");
+//!     tmp.write(b"new").unwrap();
+//!     tmp.fsync();
+//!     fs.rename("config.tmp", "config").unwrap();
+//!     // The rename was not made durable
+//!     assert!(!fs.after_crash().exists("config"));
+//! });
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::sync::Mutex;
+use crate::thread::{self, JoinHandle};
+use crate::Nondet;
+
+// A change to the content of a file that is not durable yet
+#[derive(Clone, Debug)]
+enum Write {
+    Truncate,
+    At(usize, Vec<u8>),
+}
+
+impl Write {
+    fn apply(&self, data: &mut Vec<u8>) {
+        match self {
+            Write::Truncate => data.clear(),
+            Write::At(pos, buf) => {
+                let end = pos + buf.len();
+                if data.len() < end {
+                    data.resize(end, 0);
+                }
+                data[*pos..end].copy_from_slice(buf);
+            }
+        }
+    }
+}
+
+// A change to the names of the files that is not durable yet
+#[derive(Clone, Debug)]
+enum Link {
+    Create(String, usize),
+    Rename(String, String),
+}
+
+impl Link {
+    fn apply(&self, names: &mut HashMap<String, usize>) {
+        match self {
+            Link::Create(name, inode) => {
+                names.insert(name.clone(), *inode);
+            }
+            Link::Rename(from, to) => {
+                if let Some(inode) = names.remove(from) {
+                    names.insert(to.clone(), inode);
+                }
+            }
+        }
+    }
+}
+
+// The content of a file, which outlives its names
+#[derive(Debug, Default)]
+struct Inode {
+    data: Vec<u8>,
+    durable: Vec<u8>,
+    // The writes since the last fsync, in order
+    volatile: Vec<Write>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    inodes: Vec<Inode>,
+    // The names of the files, and the names that survive a crash
+    names: HashMap<String, usize>,
+    durable_names: HashMap<String, usize>,
+    // The creations and renames since the last fsync_dir, in order
+    volatile_links: Vec<Link>,
+}
+
+impl State {
+    // The state after a crash in which `kept(n)` of the `n` volatile changes of each file, and
+    // of the names, reached the disk. The changes reach the disk in order.
+    fn crashed(&self, mut kept: impl FnMut(usize) -> usize) -> State {
+        let inodes = self.inodes.iter().map(|inode| {
+            let mut data = inode.durable.clone();
+            let n = kept(inode.volatile.len());
+            inode.volatile[..n].iter().for_each(|w| w.apply(&mut data));
+            Inode {
+                durable: data.clone(),
+                data,
+                volatile: Vec::new(),
+            }
+        });
+        let inodes = inodes.collect();
+        let mut names = self.durable_names.clone();
+        let n = kept(self.volatile_links.len());
+        self.volatile_links[..n]
+            .iter()
+            .for_each(|l| l.apply(&mut names));
+        State {
+            inodes,
+            durable_names: names.clone(),
+            names,
+            volatile_links: Vec::new(),
+        }
+    }
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{}: no such file", path))
+}
+
+/// An in-memory filesystem. Clones refer to the same filesystem. It can only be used in the
+/// execution that created it.
+#[derive(Clone, Debug)]
+pub struct Fs {
+    state: Arc<Mutex<State>>,
+}
+
+impl Fs {
+    /// Creates an empty filesystem
+    pub fn new() -> Self {
+        Fs {
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Opens the file `path`, creating it if it does not exist, and truncating it otherwise.
+    /// The new name is volatile until [`Fs::fsync_dir`].
+    pub fn create(&self, path: &str) -> File {
+        let mut state = self.state.blocking_lock();
+        let inode = match state.names.get(path) {
+            Some(&inode) => {
+                let inode_state = &mut state.inodes[inode];
+                inode_state.data.clear();
+                inode_state.volatile.push(Write::Truncate);
+                inode
+            }
+            None => {
+                state.inodes.push(Inode::default());
+                let inode = state.inodes.len() - 1;
+                state.names.insert(path.to_owned(), inode);
+                state
+                    .volatile_links
+                    .push(Link::Create(path.to_owned(), inode));
+                inode
+            }
+        };
+        File::new(self, inode)
+    }
+
+    /// Opens the existing file `path`, positioned at its beginning
+    pub fn open(&self, path: &str) -> io::Result<File> {
+        let state = self.state.blocking_lock();
+        let inode = *state.names.get(path).ok_or_else(|| not_found(path))?;
+        Ok(File::new(self, inode))
+    }
+
+    /// Renames the file `from` to `to`, atomically replacing `to` if it exists. The rename is
+    /// volatile until [`Fs::fsync_dir`].
+    pub fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let mut state = self.state.blocking_lock();
+        let inode = state.names.remove(from).ok_or_else(|| not_found(from))?;
+        state.names.insert(to.to_owned(), inode);
+        state
+            .volatile_links
+            .push(Link::Rename(from.to_owned(), to.to_owned()));
+        Ok(())
+    }
+
+    /// Returns whether the file `path` exists
+    pub fn exists(&self, path: &str) -> bool {
+        self.state.blocking_lock().names.contains_key(path)
+    }
+
+    /// Returns the whole content of the file `path`
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let state = self.state.blocking_lock();
+        let inode = *state.names.get(path).ok_or_else(|| not_found(path))?;
+        Ok(state.inodes[inode].data.clone())
+    }
+
+    /// Makes the current names of the files durable, i.e. the creations and renames so far,
+    /// like syncing the directory
+    pub fn fsync_dir(&self) {
+        let mut state = self.state.blocking_lock();
+        state.durable_names = state.names.clone();
+        state.volatile_links.clear();
+    }
+
+    /// Returns the filesystem that a crash at this point would leave: a new filesystem with
+    /// the names of the last [`Fs::fsync_dir`], and the content of the last [`File::fsync`] of
+    /// each file. This filesystem is not affected, so the tasks using it can go on.
+    pub fn after_crash(&self) -> Fs {
+        let crashed = self.state.blocking_lock().crashed(|_| 0);
+        Fs {
+            state: Arc::new(Mutex::new(crashed)),
+        }
+    }
+
+    /// Returns a filesystem that a crash at this point can leave, which the model checker
+    /// chooses among all of them. What is durable survives, as in [`Fs::after_crash`], and so
+    /// may any part of the volatile changes: the disk writes the changes of each file, and
+    /// the creations and renames, in the order in which they were made, and the crash can
+    /// stop it anywhere. Losing all the volatile changes is one of the explored outcomes.
+    pub fn recover(&self) -> Fs {
+        let crashed = self
+            .state
+            .blocking_lock()
+            .crashed(|volatile| match volatile {
+                0 => 0,
+                _ => (0..=volatile).nondet(),
+            });
+        Fs {
+            state: Arc::new(Mutex::new(crashed)),
+        }
+    }
+
+    /// Crashes the filesystem at a point of the execution chosen by the model checker, and
+    /// runs `recovery` on each filesystem that the crash can leave, see [`Fs::recover`].
+    /// TraceForge explores the crash before and after every operation of the other tasks
+    /// on the filesystem, so `recovery` can check that the durable state is consistent
+    /// wherever the crash happens.
+    ///
+    /// The crash runs in its own thread, whose handle is returned, and does not affect this
+    /// filesystem: the tasks using it go on as if the crash happened in another copy.
+    pub fn inject_crash<F>(&self, recovery: F) -> JoinHandle<()>
+    where
+        F: FnOnce(Fs) + Send + 'static,
+    {
+        let fs = self.clone();
+        thread::spawn(move || recovery(fs.recover()))
+    }
+}
+
+impl Default for Fs {
+    fn default() -> Self {
+        Fs::new()
+    }
+}
+
+/// An open file of an [`Fs`], with its own position
+#[derive(Debug)]
+pub struct File {
+    fs: Fs,
+    inode: usize,
+    pos: usize,
+}
+
+impl File {
+    fn new(fs: &Fs, inode: usize) -> Self {
+        File {
+            fs: fs.clone(),
+            inode,
+            pos: 0,
+        }
+    }
+
+    /// Writes `buf` at the position of the file, overwriting and extending its content, and
+    /// advances the position. Returns the number of bytes written, which is `buf.len()`.
+    pub fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.fs.state.blocking_lock();
+        let inode = &mut state.inodes[self.inode];
+        let write = Write::At(self.pos, buf.to_vec());
+        write.apply(&mut inode.data);
+        inode.volatile.push(write);
+        self.pos += buf.len();
+        Ok(buf.len())
+    }
+
+    /// Reads the content of the file from its position to its end, and advances the position
+    pub fn read_to_end(&mut self) -> io::Result<Vec<u8>> {
+        let state = self.fs.state.blocking_lock();
+        let data = &state.inodes[self.inode].data;
+        let read = data.get(self.pos..).unwrap_or_default().to_vec();
+        self.pos += read.len();
+        Ok(read)
+    }
+
+    /// Moves the position of the file to `pos` bytes from its beginning
+    pub fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Makes the content of the file durable: it survives a later crash, see
+    /// [`Fs::after_crash`]. The names of the file are not, see [`Fs::fsync_dir`].
+    pub fn fsync(&self) {
+        let mut state = self.fs.state.blocking_lock();
+        let inode = &mut state.inodes[self.inode];
+        inode.durable = inode.data.clone();
+        inode.volatile.clear();
+    }
+}
@@ -24,22 +24,64 @@ use std::fmt::{Display, Formatter};
 use std::future::Future;
 use std::pin::Pin;
 use std::result::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
 
+mod timeout;
+pub use timeout::{timeout, timeout_at, Elapsed};
+
 // Not really unsafe, we're not doing any concurrency.
 // This is needed for `Waker::from`
 // unsafe impl Sync for Sender<()> {}
 
-// The value is irrelevant, we're using a Channel<()> as a waker.
-impl std::task::Wake for Sender<WakeMsg> {
-    fn wake(self: std::sync::Arc<Self>) {
-        self.send_msg(WakeMsg);
+// The waker of the task that polls a future. The other tasks wake it up with a message, whose
+// value is irrelevant. A future that wakes its own task while it is polled, as in an internal
+// polling loop, only has it polled again, once the other tasks have had a chance to run.
+struct TaskWaker {
+    sender: Sender<WakeMsg>,
+    task: TaskId,
+    woken_by_itself: AtomicBool,
+}
+
+impl TaskWaker {
+    fn new(sender: Sender<WakeMsg>) -> Arc<Self> {
+        let task = ExecutionState::with(|s| s.current().id());
+        Arc::new(Self {
+            sender,
+            task,
+            woken_by_itself: AtomicBool::new(false),
+        })
+    }
+
+    // Whether the task woke itself up while polling; if so, yields to the other tasks first
+    fn yield_if_woken_by_itself(&self) -> bool {
+        let woken = self.woken_by_itself.swap(false, Ordering::SeqCst);
+        if woken {
+            crate::thread::yield_now();
+        }
+        woken
+    }
+}
+
+impl std::task::Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let current = ExecutionState::try_with(|s| s.try_current().map(|t| t.id())).flatten();
+        if current == Some(self.task) {
+            self.woken_by_itself.store(true, Ordering::SeqCst);
+        } else {
+            self.sender.send_msg(WakeMsg);
+        }
     }
 }
 
 fn get_bidir_handles() -> (TwoWayCom, TwoWayCom) {
-    let (sender1, receiver1) = Builder::new().with_comm(LocalOrder).build();
-    let (sender2, receiver2) = Builder::new().with_comm(LocalOrder).build();
+    let (sender1, receiver1) = Builder::new().with_comm(LocalOrder).internal().build();
+    let (sender2, receiver2) = Builder::new().with_comm(LocalOrder).internal().build();
     // *flip* them
     (
         TwoWayCom {
@@ -75,13 +117,14 @@ where
 
     let task_id = ExecutionState::spawn_thread(
         move || {
-            let (sender, fut_recv) = Builder::<WakeMsg>::new().build();
-            let fut_waker = Waker::from(std::sync::Arc::new(sender.clone()));
+            let (sender, fut_recv) = Builder::<WakeMsg>::new().internal().build();
+            let task_waker = TaskWaker::new(sender);
+            let fut_waker = Waker::from(task_waker.clone());
 
             // Poll once in advance:
             // tokio's spawn semantics: the future will start running immediately.
             let mut fut = Box::pin(fut);
-            let mut res = fut.as_mut().poll(&mut Context::from_waker(&fut_waker));
+            let mut res = poll_in_task(fut.as_mut(), &mut Context::from_waker(&fut_waker));
             let mut join_waker: Option<Waker> = None;
             let res = loop {
                 match res {
@@ -92,11 +135,17 @@ where
                         }
                         break Some(res);
                     }
+                    Poll::Pending if task_waker.yield_if_woken_by_itself() => {
+                        res = poll_in_task(fut.as_mut(), &mut Context::from_waker(&fut_waker));
+                        continue;
+                    }
                     Poll::Pending => { /* keep going */ }
                 }
 
                 // Wait for either the joiner or the future, to poll or inform us, respectively
-                let (msg, ind) = crate::select_val_block(&fut_handles.receiver, &fut_recv);
+                let (msg, ind) = crate::deadlock::waiting_for_wake(|| {
+                    crate::select_val_block(&fut_handles.receiver, &fut_recv)
+                });
 
                 // Joiner polled us, inform them it's pending
                 if ind == 0 {
@@ -116,7 +165,7 @@ where
                     // Futured informed us to poll again
                     assert!(ind == 1);
                     assert!(msg.as_any().downcast::<WakeMsg>().is_ok());
-                    res = fut.as_mut().poll(&mut Context::from_waker(&fut_waker));
+                    res = poll_in_task(fut.as_mut(), &mut Context::from_waker(&fut_waker));
                 }
             };
 
@@ -483,22 +532,56 @@ impl<T: Message + 'static> Future for JoinHandle<T> {
     }
 }
 
+// Polls a future on behalf of the current task, which cannot `block_on` another future until
+// the poll returns
+fn poll_in_task<F: Future + ?Sized>(fut: Pin<&mut F>, cx: &mut Context) -> Poll<F::Output> {
+    let was_polling = ExecutionState::with(|s| {
+        let task = s.current_mut();
+        // The future records again what it is pending on, see `deadlock::pending_on`
+        task.wait_reason = None;
+        std::mem::replace(&mut task.polling, true)
+    });
+    let res = fut.poll(cx);
+    ExecutionState::with(|s| s.current_mut().polling = was_polling);
+    res
+}
+
 /// Run a future to completion on the current thread.
+///
+/// # Panics
+///
+/// Panics if called from a future that is being polled, i.e. from an async task or from the
+/// future of another `block_on`: that future has to `.await` instead.
 pub fn block_on<F: Future>(future: F) -> F::Output {
+    if ExecutionState::with(|s| s.current().polling) {
+        panic!(
+            "nested block_on is not supported here: the task is already running a future, \
+             which has to .await instead"
+        );
+    }
+    block_on_unchecked(future)
+}
+
+// Like `block_on`, for the blocking operations of the synchronization primitives, which do not
+// wait on the future being polled, and so can run from within it
+pub(crate) fn block_on_unchecked<F: Future>(future: F) -> F::Output {
     let mut future = Box::pin(future);
-    let (sender, receiver) = Builder::<WakeMsg>::new().build();
-    let waker = Waker::from(std::sync::Arc::new(sender.clone()));
+    let (sender, receiver) = Builder::<WakeMsg>::new().internal().build();
+    let task_waker = TaskWaker::new(sender);
+    let waker = Waker::from(task_waker.clone());
     let cx = &mut Context::from_waker(&waker);
 
     thread::switch();
 
     loop {
-        match future.as_mut().poll(cx) {
+        match poll_in_task(future.as_mut(), cx) {
             Poll::Ready(result) => {
                 break result;
             }
             Poll::Pending => {
-                receiver.recv_msg_block();
+                if !task_waker.yield_if_woken_by_itself() {
+                    crate::deadlock::waiting_for_wake(|| receiver.recv_msg_block());
+                }
             }
         }
 
@@ -506,6 +589,62 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
     }
 }
 
+/// A guard that runs an async cleanup when it is dropped, modeling `AsyncDrop`.
+///
+/// Created by [on_drop]. Store it in the task or future that owns the resource: when the owner
+/// is dropped (including when a future is cancelled), the cleanup is spawned as a new task of the
+/// execution, so its interleavings with the rest of the model, and with the other cleanups, are
+/// explored like those of any other task. The execution only ends once every cleanup finishes.
+///
+/// The cleanups have to run while the execution is still scheduling tasks. Guards that are still
+/// alive when the execution ends, for example held by a blocked daemon thread, are dropped as the
+/// runtime tears the execution down, and their cleanup does not run.
+#[must_use = "the cleanup runs when the guard is dropped"]
+pub struct AsyncDropGuard {
+    cleanup: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+/// Registers an async cleanup, that runs when the returned guard is dropped.
+pub fn on_drop<F>(cleanup: F) -> AsyncDropGuard
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    AsyncDropGuard {
+        cleanup: Some(Box::pin(cleanup)),
+    }
+}
+
+impl AsyncDropGuard {
+    /// Drops the guard without running the cleanup.
+    pub fn disarm(mut self) {
+        self.cleanup = None;
+    }
+}
+
+impl std::fmt::Debug for AsyncDropGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncDropGuard")
+            .field("armed", &self.cleanup.is_some())
+            .finish()
+    }
+}
+
+impl Drop for AsyncDropGuard {
+    fn drop(&mut self) {
+        // As for JoinHandle, skip during panic unwinding, and once the execution has stopped
+        // scheduling tasks: spawning would panic
+        if std::thread::panicking() || !ExecutionState::with(|state| state.is_running()) {
+            return;
+        }
+        if let Some(cleanup) = self.cleanup.take() {
+            crate::thread::Builder::new()
+                .name("<async-drop>".to_string())
+                .spawn(move || block_on(cleanup))
+                .unwrap();
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{recv_msg_block, send_msg, thread, verify, Config};
@@ -0,0 +1,92 @@
+//! Futures that race a deadline of simulated time, see [`timeout_at`].
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::channel;
+use crate::runtime::execution::ExecutionState;
+use crate::{thread, time};
+
+/// Error returned by [`timeout_at`] and [`timeout`] when the deadline elapsed before the future
+/// completed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// Requires `fut` to complete before the logical instant `deadline`, as returned by
+/// [`time::now`](crate::time::now). Returns the output of `fut`, or [`Elapsed`] if the deadline
+/// elapsed first, in which case `fut` is dropped and the clock of the calling task advances to
+/// the deadline.
+///
+/// The deadline is a timer of its own, which fires like a [`time::sleep`](crate::time::sleep)
+/// until the deadline: TraceForge explores both the executions in which `fut` completes first,
+/// and those in which the deadline elapses while `fut` is pending. Under
+/// [`ConfigBuilder::with_clock_drift`](crate::ConfigBuilder::with_clock_drift), it fires in
+/// order with the other timers, up to the drift bound. A future that is ready when it is first
+/// polled completes, even if the deadline is already past, and a future that blocks its task
+/// instead of being pending cannot be interrupted.
+pub async fn timeout_at<F: Future>(deadline: Duration, fut: F) -> Result<F::Output, Elapsed> {
+    // The timer is a message from a thread that sleeps until the deadline, so that it races
+    // the progress of `fut`
+    let (tx, rx) = channel::Builder::<()>::new().internal().build();
+    thread::Builder::new()
+        .name("traceforge_runtime::timeout".to_string())
+        .spawn_daemon(move || {
+            let now = time::now();
+            if deadline > now {
+                time::sleep(deadline - now);
+            }
+            tx.send_msg(());
+        })
+        .unwrap();
+    let timer = Box::pin(rx.async_recv_msg());
+    let elapsed = Timeout {
+        fut: Box::pin(fut),
+        timer,
+    }
+    .await;
+    if elapsed.is_err() {
+        ExecutionState::with(|s| {
+            let tid = s.must.borrow().to_thread_id(s.current().id());
+            let mut must = s.must.borrow_mut();
+            let now = must.task_clock(tid).max(deadline);
+            must.set_task_clock(tid, now);
+        });
+    }
+    elapsed
+}
+
+/// Requires `fut` to complete within `dur` of logical time, see [`timeout_at`]
+pub async fn timeout<F: Future>(dur: Duration, fut: F) -> Result<F::Output, Elapsed> {
+    timeout_at(time::now() + dur, fut).await
+}
+
+struct Timeout<F: Future, T> {
+    fut: Pin<Box<F>>,
+    timer: Pin<Box<T>>,
+}
+
+impl<F: Future, T: Future<Output = ()>> Future for Timeout<F, T> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(v) = self.fut.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+        match self.timer.as_mut().poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(Elapsed(()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Execution-global state, shared by all the tasks of an execution and reset between executions.
+//!
+//! A `static` that a model mutates, like a `lazy_static` behind a `Mutex` or a `static mut`,
+//! keeps its value from one execution to the next, so each execution starts from whatever the
+//! previous schedule left behind and the results depend on the order in which TraceForge
+//! explores the schedules. An [`ExecutionGlobal`], declared with
+//! [`execution_global!`](crate::execution_global), is initialized in each execution the first
+//! time it is used, and dropped when the execution ends.
+//!
+//! ```no_run
+//! use std::cell::Cell;
+//! use traceforge::thread;
+//!
+//! traceforge::execution_global! {
+//!     static REQUESTS: Cell<u32> = Cell::new(0);
+//! }
+//!
+//! traceforge::verify(traceforge::Config::default(), || {
+//!     let worker = thread::spawn(|| REQUESTS.with(|r| r.set(r.get() + 1)));
+//!     worker.join().unwrap();
+//!     assert_eq!(REQUESTS.with(Cell::get), 1);
+//! });
+//! ```
+//!
+//! The tasks of an execution run one at a time, so the value can use `Cell` or `RefCell` for
+//! its mutations. Its accesses are not events: TraceForge does not explore their orders, which
+//! only change at the scheduling points of the tasks. Use the synchronization primitives of
+//! [`sync`](crate::sync) for state whose accesses race.
+
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::runtime::execution::ExecutionState;
+
+/// A value shared by the tasks of an execution, that is reset in every execution. Declare it with
+/// [`execution_global!`](crate::execution_global).
+#[derive(Debug)]
+pub struct ExecutionGlobal<T: 'static> {
+    init: fn() -> T,
+}
+
+impl<T: 'static> ExecutionGlobal<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        ExecutionGlobal { init }
+    }
+
+    /// Calls `f` with the value of the current execution, which is initialized if this is the
+    /// first use in the execution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of an execution.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        // Each static has its own address, which identifies its value in the execution
+        let key = self as *const Self as usize;
+        let value = match ExecutionState::with(|s| s.global(key)) {
+            Some(value) => value,
+            None => {
+                // The initializer may use the execution state too
+                let value: Rc<dyn Any> = Rc::new((self.init)());
+                ExecutionState::with(|s| s.set_global(key, value.clone()));
+                value
+            }
+        };
+        let value = value
+            .downcast_ref::<T>()
+            .expect("a global has a single type");
+        f(value)
+    }
+}
+
+/// Declares [`ExecutionGlobal`] statics, with the syntax of `std::thread_local!`. The value is
+/// initialized in each execution the first time it is used, and the tasks of the execution share
+/// it, see [the module](crate::global).
+///
+/// ```no_run
+/// use std::cell::RefCell;
+///
+/// traceforge::execution_global! {
+///     static LOG: RefCell<Vec<String>> = RefCell::new(Vec::new());
+///     pub static NEXT_ID: std::cell::Cell<u64> = std::cell::Cell::new(1);
+/// }
+/// ```
+#[macro_export]
+macro_rules! execution_global {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::global::ExecutionGlobal<$t> =
+            $crate::global::ExecutionGlobal::new(|| $init);
+        $crate::execution_global!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::global::ExecutionGlobal<$t> =
+            $crate::global::ExecutionGlobal::new(|| $init);
+    };
+}
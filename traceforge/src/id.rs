@@ -0,0 +1,24 @@
+//! Deterministic ids for models.
+//!
+//! Code that generates UUIDs or other random ids behaves differently on every run, so its
+//! counterexamples cannot be replayed. [`next_id`] is a drop-in replacement during tests: the ids
+//! it returns only depend on the calling thread and on the number of ids that thread already
+//! generated in the current execution. Every execution, and every replay of a counterexample,
+//! therefore generates the same ids.
+
+use crate::runtime::execution::ExecutionState;
+
+/// Returns a new id, unique within the current execution.
+///
+/// The ids generated by a thread are increasing. The ids of different threads never collide,
+/// but they are not ordered: the high 32 bits of an id identify the thread, and the low 32 bits
+/// count the ids the thread generated, starting from 1.
+pub fn next_id() -> u64 {
+    ExecutionState::with(|s| {
+        let me = s.current().id();
+        let mut must = s.must.borrow_mut();
+        let tid = must.to_thread_id(me);
+        let n = must.next_generated_id(tid);
+        ((tid.to_number() as u64) << 32) | n as u64
+    })
+}
@@ -0,0 +1,86 @@
+//! The global bound on the messages in flight, see `ConfigBuilder::with_max_in_flight`.
+//!
+//! A daemon holds the credits, one per message that may be in flight: a counted send first
+//! waits for the daemon to grant it a credit, and the receive of a counted message returns the
+//! credit to the daemon. Since the credits are messages themselves, TraceForge explores the
+//! orders in which the senders get them like any other interleaving.
+
+use std::collections::VecDeque;
+
+use crate::channel::{self, Receiver, Sender};
+use crate::loc::CommunicationModel;
+use crate::runtime::execution::ExecutionState;
+use crate::thread;
+use crate::Event;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Request {
+    // A sender waiting for a credit, with the channel to grant it on
+    Acquire(Sender<()>),
+    // A counted message was received
+    Release,
+}
+
+// Starts the thread that holds the `k` credits of the execution. Called by the main thread,
+// before the model runs, if the messages in flight are bounded.
+pub(crate) fn start_credit_service(k: usize) {
+    let (tx, rx) = channel::Builder::<Request>::new()
+        .with_comm(CommunicationModel::CausalOrder)
+        .internal()
+        .build();
+    thread::Builder::new()
+        .name("traceforge_runtime::in_flight".to_string())
+        .spawn_daemon(move || serve(rx, k))
+        .unwrap();
+    ExecutionState::with(|s| s.must.borrow_mut().credit_service = Some(tx));
+}
+
+fn serve(requests: Receiver<Request>, mut credits: usize) {
+    let mut waiting: VecDeque<Sender<()>> = VecDeque::new();
+    loop {
+        match requests.recv_msg_block() {
+            Request::Acquire(grant) => waiting.push_back(grant),
+            Request::Release => credits += 1,
+        }
+        while credits > 0 && !waiting.is_empty() {
+            credits -= 1;
+            waiting.pop_front().unwrap().send_msg(());
+        }
+    }
+}
+
+/// Called before a send: if the send is `counted` towards the bound, waits for a credit and
+/// returns true
+pub(crate) fn acquire(counted: bool) -> bool {
+    if !counted {
+        return false;
+    }
+    let Some(service) = ExecutionState::with(|s| s.must.borrow().credit_service.clone()) else {
+        return false;
+    };
+    let (tx, rx) = channel::Builder::<()>::new().internal().build();
+    service.send_msg(Request::Acquire(tx));
+    rx.recv_msg_block();
+    true
+}
+
+/// Records that the counted send `send` holds its credit until it is received
+pub(crate) fn record(send: Event) {
+    ExecutionState::with(|s| s.must.borrow_mut().in_flight_sends.insert(send));
+}
+
+/// Called after the current task received: returns the credits of the counted sends it read
+pub(crate) fn release() {
+    let Some(service) = ExecutionState::with(|s| s.must.borrow().credit_service.clone()) else {
+        return;
+    };
+    let returned = ExecutionState::with(|s| {
+        let recv = s.curr_pos();
+        let mut must = s.must.borrow_mut();
+        let read = must.read_sends(recv);
+        read.iter()
+            .filter(|send| must.in_flight_sends.remove(send))
+            .count()
+    });
+    (0..returned).for_each(|_| service.send_msg(Request::Release));
+}
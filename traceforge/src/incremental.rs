@@ -0,0 +1,162 @@
+//! Incremental verification, which skips re-exploring a model whose code did not change.
+//!
+//! The code under test is split into named code paths, e.g. a function or a module, whose
+//! versions are registered with [`ConfigBuilder::with_code_version`]: a hash of their source, a
+//! version number, the commit that last changed them... The model declares which code paths
+//! it runs with [`depends_on`]. With [`ConfigBuilder::with_incremental`], the statistics of the
+//! exploration are saved to a file, together with the versions of the code paths that the
+//! executions ran. The next exploration returns the saved statistics without exploring
+//! anything, marked with [`Stats::cached`], if none of those code paths changed since; changing
+//! a code path that the model does not run does not invalidate them.
+//!
+//! ```no_run
+//! use traceforge::{incremental, thread, Config};
+//!
+//! fn transfer() { incremental::depends_on("transfer"); /* ... */ }
+//!
+//! let config = Config::builder()
+//!     .with_incremental("target/traceforge/transfer.json")
+//!     // e.g. the hash of the source of `transfer`, computed by the build
+//!     .with_code_version("transfer", "3f2a9c")
+//!     .build();
+//! let stats = traceforge::verify(config, || {
+//!     let t = thread::spawn(transfer);
+//!     transfer();
+//!     t.join().unwrap();
+//! });
+//! ```
+//!
+//! This is only sound if the model declares every code path whose changes can affect the
+//! schedules, and if the file is only used with one model and one configuration: neither the
+//! model closure nor the other options are part of the comparison. Explorations that found a
+//! failure, or whose model declared no code path, are not saved, so they run again.
+//!
+//! [`ConfigBuilder::with_code_version`]: crate::ConfigBuilder::with_code_version
+//! [`ConfigBuilder::with_incremental`]: crate::ConfigBuilder::with_incremental
+//! [`Stats::cached`]: crate::Stats::cached
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::BufReader;
+use std::path::Path;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::execution::ExecutionState;
+use crate::{Config, Stats};
+
+// What the file of `Config::with_incremental` holds
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    // The versions of the code paths that the executions ran
+    code: BTreeMap<String, u64>,
+    stats: Stats,
+}
+
+// The 64-bit FNV-1a hash of the versions of the code paths. Unlike `DefaultHasher`, its
+// results do not change between the Rust releases, so the saved versions stay comparable.
+pub(crate) struct VersionHasher(u64);
+
+impl VersionHasher {
+    pub(crate) fn new() -> Self {
+        VersionHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for VersionHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Declares that the current execution runs the code path `name`, whose version is registered
+/// with [`ConfigBuilder::with_code_version`](crate::ConfigBuilder::with_code_version). The
+/// declaration creates no event.
+///
+/// # Panics
+///
+/// Panics under [`ConfigBuilder::with_incremental`](crate::ConfigBuilder::with_incremental) if
+/// no version is registered for `name`, since its changes would go unnoticed.
+pub fn depends_on(name: &str) {
+    ExecutionState::with(|s| {
+        let mut must = s.must.borrow_mut();
+        let config = must.config();
+        assert!(
+            config.incremental.is_none() || config.code_versions.contains_key(name),
+            "no version is registered for the code path {}",
+            name
+        );
+        must.code_paths.insert(name.to_string());
+    });
+}
+
+/// The saved statistics of the file of `config`, if the code paths they depend on did not
+/// change. Statistics that depend on no code path are never reused.
+pub(crate) fn load(config: &Config) -> Option<Stats> {
+    let file = File::open(config.incremental.as_ref()?).ok()?;
+    let saved: Saved = match serde_json::from_reader(BufReader::new(file)) {
+        Ok(saved) => saved,
+        Err(e) => {
+            warn!(
+                "Ignoring the unreadable incremental verification file: {}",
+                e
+            );
+            return None;
+        }
+    };
+    let unchanged = !saved.code.is_empty()
+        && saved
+            .code
+            .iter()
+            .all(|(name, version)| config.code_versions.get(name) == Some(version));
+    unchanged.then_some(Stats {
+        cached: true,
+        ..saved.stats
+    })
+}
+
+/// Saves `stats` to the file of `config`, unless the exploration found a failure or the model
+/// declared no code path, since nothing would tell when they are out of date
+pub(crate) fn save(config: &Config, stats: &Stats) {
+    let Some(path) = &config.incremental else {
+        return;
+    };
+    if stats.failures > 0 {
+        return;
+    }
+    let code = stats
+        .code_paths
+        .iter()
+        .filter_map(|name| Some((name.clone(), *config.code_versions.get(name)?)))
+        .collect::<BTreeMap<_, _>>();
+    if code.is_empty() {
+        warn!(
+            "Not saving the incremental verification file {}: the model declared no code path",
+            path
+        );
+        return;
+    }
+    let saved = Saved {
+        code,
+        stats: stats.clone(),
+    };
+    let written = Path::new(path)
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| File::create(path))
+        .and_then(|file| serde_json::to_writer(file, &saved).map_err(Into::into));
+    if let Err(e) = written {
+        warn!(
+            "Could not save the incremental verification file {}: {}",
+            path, e
+        );
+    }
+}
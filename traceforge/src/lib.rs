@@ -1,30 +1,71 @@
 // #![doc = include_str!("../../README.md")]
+pub mod actor;
+pub mod alloc;
+pub mod assertions;
+mod cancel;
+pub use cancel::check_cancel_safe;
 pub mod channel;
 mod cons;
 pub mod coverage;
 pub use coverage::{CoverageInfo, ExecutionId};
+pub mod db;
+pub mod deadlock;
+mod differential;
+pub use differential::differential;
+pub mod dist;
+pub mod final_state;
+pub mod float;
+pub use deadlock::{Deadlock, TaskSnapshot, TaskState, WaitReason};
+pub use final_state::FinalState;
+use channel::MessageCounts;
+use float::FloatRange;
 pub mod parallel_verify;
 pub use parallel_verify::verify_partitioned_rayon;
 
 mod event;
+pub use event::Event;
 mod event_label;
 mod exec_graph;
 mod exec_pool;
+pub mod failures;
+pub use failures::CollectedFailure;
+pub use runtime::failure::FailureCategory;
+pub mod fs;
 pub mod future;
+pub mod global;
 // pub mod turmoil; // working on tcp support
 // mod experimental_runtimes;
+pub mod id;
 mod identifier;
+pub mod incremental;
+mod in_flight;
 mod indexed_map;
 pub mod loc;
+pub mod mailbox;
+mod minimize;
+pub use minimize::minimize_messages;
 pub mod monitor_types;
 pub mod msg;
 mod must;
 mod predicate;
+pub mod profile;
 mod replay;
 mod revisit;
+pub mod rpc;
+pub mod script;
+pub use script::run_script;
+pub mod rng;
+pub use rng::DeterministicRng;
 mod runtime;
+pub mod shims;
+pub mod shutdown;
+mod step;
+pub use step::Step;
+mod sweep;
+pub use sweep::{verify_for_each, InputStats, SweepReport};
 pub mod sync;
 mod telemetry;
+pub mod testing;
 mod testmode;
 use future::spawn_receive;
 pub use testmode::{parallel_test, test};
@@ -32,21 +73,24 @@ pub use testmode::{parallel_test, test};
 #[cfg(feature = "symbolic")]
 pub mod symbolic;
 pub mod thread;
+pub mod time;
 mod vector_clock;
+pub use vector_clock::VectorClock;
 
-pub use crate::msg::Val;
+pub use crate::msg::{MsgId, Val};
 // `Val` is used by monitors.
 
 use channel::{cons_to_model, self_loc_comm, thread_loc_comm, Receiver};
 use coverage::ExecutionObserver;
 use event_label::{Block, BlockType, CToss, Choice, RecvMsg, SendMsg};
 use loc::{CommunicationModel, Loc, RecvLoc, SendLoc};
-use msg::Message;
+use msg::{Batch, Message};
 
 use rand::{distr::Distribution, Rng};
 use replay::ReplayInformation;
 use runtime::execution::{Execution, ExecutionState};
-use runtime::failure::persist_task_failure;
+use runtime::failure::{persist_task_failure, FailureReport};
+use runtime::task::DEFAULT_INLINE_TASKS;
 use runtime::thread::continuation::{ContinuationPool, CONTINUATION_POOL};
 use runtime::thread::switch;
 
@@ -54,8 +98,10 @@ use log::{debug, info, trace};
 use serde::{Deserialize, Serialize};
 use smallvec::alloc::sync::Arc;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::iter;
 use std::rc::Rc;
 use std::time::Instant;
@@ -65,7 +111,7 @@ use std::io::Write;
 use crate::event_label::*;
 use crate::exec_pool::ExecutionPool;
 use crate::must::{MonitorInfo, Must};
-use crate::predicate::{normalize_vec_tag, PredicateType};
+use crate::predicate::{normalize_vec_tag, PredicateType, ValuePredicate};
 
 use std::any::type_name;
 
@@ -78,7 +124,11 @@ fn type_of<T>(_: &T) -> &'static str {
 pub const FILTERED_THREAD_NAME_PATTERN: &str = "traceforge";
 
 /// TraceForge exploration statistics.
-#[derive(Default, Clone, Debug)]
+///
+/// They serialize with serde, e.g. to JSON with [`Stats::to_json`] for tools that track them.
+/// [`Stats::reachable_states`] is not serialized, since the values that the tasks return need
+/// not be serializable.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Stats {
     /// Number of complete executions explored
     pub execs: usize,
@@ -88,6 +138,65 @@ pub struct Stats {
     pub coverage: CoverageInfo,
     /// Maximum number of events across all execution graphs (complete or blocked)
     pub max_graph_events: usize,
+    /// Maximum number of scheduling decisions across all executions (complete or blocked)
+    pub max_schedule_depth: usize,
+    // Sum of the scheduling decisions of all executions, used to compute the average
+    pub(crate) total_schedule_depth: usize,
+    /// The distinct deadlocks found, if [`ConfigBuilder::with_collect_deadlocks`] is set
+    pub deadlocks: Vec<Deadlock>,
+    /// The distinct failures found, in the order in which they were found, if
+    /// [`ConfigBuilder::with_collect_failures`] is set
+    pub collected_failures: Vec<CollectedFailure>,
+    /// Number of executions that spawned more tasks than
+    /// [`ConfigBuilder::with_task_capacity`] reserved room for
+    pub task_spills: usize,
+    /// Number of steps in which a task executed more instructions than
+    /// [`ConfigBuilder::with_long_step_warning`] without yielding (debug builds only)
+    pub long_steps: usize,
+    /// The fraction of the branches that were explored, if [`ConfigBuilder::with_sampling`] is
+    /// set. The exploration is then not exhaustive.
+    pub sampling_rate: Option<f64>,
+    /// Number of branches skipped by [`ConfigBuilder::with_sampling`]
+    pub sampled_out: usize,
+    /// The distinct fingerprints of the final states of the complete executions, if
+    /// [`ConfigBuilder::with_fingerprint`] is set
+    pub final_states: BTreeSet<u64>,
+    /// The distinct final states of the complete executions, in the order in which they were
+    /// reached, if [`ConfigBuilder::with_reachable_states`] is set
+    #[serde(skip)]
+    pub reachable_states: Vec<FinalState>,
+    /// Whether the exploration stopped because it exceeded
+    /// [`ConfigBuilder::with_memory_budget`], in which case it is not exhaustive
+    pub out_of_memory: bool,
+    /// Whether the exploration stopped at [`ConfigBuilder::with_max_iterations`], in which case
+    /// it may not be exhaustive
+    pub max_iterations_reached: bool,
+    /// Number of executions that failed, which only goes beyond 1 under
+    /// [`ConfigBuilder::with_keep_going_after_error`]
+    pub failures: usize,
+    /// Time spent exploring
+    pub elapsed: std::time::Duration,
+    /// Number of data races on [`sync::Shared`] values that were not reported, since
+    /// [`ConfigBuilder::with_benign_race`] declared them benign
+    pub suppressed_races: usize,
+    /// Number of executions that did not reach the goal of
+    /// [`ConfigBuilder::with_coverage_target`], which are not counted in [`Stats::execs`] or
+    /// [`Stats::block`]
+    pub off_target: usize,
+    /// The code paths that the executions declared with [`incremental::depends_on`]
+    pub code_paths: BTreeSet<String>,
+    /// Whether these are the statistics of an earlier exploration, which
+    /// [`ConfigBuilder::with_incremental`] reused since its code did not change: nothing was
+    /// explored
+    pub cached: bool,
+    /// The range of the values recorded under each name by [`float::observe`]
+    pub float_ranges: BTreeMap<String, FloatRange>,
+    /// The messages of each channel, by its name, over the complete executions, if
+    /// [`ConfigBuilder::with_message_counts`] is set
+    pub message_counts: BTreeMap<String, MessageCounts>,
+    /// The scheduling steps and the logical time of each task, if
+    /// [`ConfigBuilder::with_profile`] is set
+    pub profile: profile::Profile,
 }
 
 impl Stats {
@@ -98,6 +207,67 @@ impl Stats {
         if rhs.max_graph_events > self.max_graph_events {
             self.max_graph_events = rhs.max_graph_events;
         }
+        if rhs.max_schedule_depth > self.max_schedule_depth {
+            self.max_schedule_depth = rhs.max_schedule_depth;
+        }
+        self.total_schedule_depth += rhs.total_schedule_depth;
+        self.task_spills += rhs.task_spills;
+        self.long_steps += rhs.long_steps;
+        self.sampling_rate = self.sampling_rate.or(rhs.sampling_rate);
+        self.sampled_out += rhs.sampled_out;
+        self.out_of_memory |= rhs.out_of_memory;
+        self.max_iterations_reached |= rhs.max_iterations_reached;
+        self.failures += rhs.failures;
+        self.suppressed_races += rhs.suppressed_races;
+        self.off_target += rhs.off_target;
+        // Explorations that are merged ran side by side
+        self.elapsed = self.elapsed.max(rhs.elapsed);
+        self.code_paths.extend(rhs.code_paths.iter().cloned());
+        self.final_states.extend(&rhs.final_states);
+        for state in &rhs.reachable_states {
+            if !self.reachable_states.contains(state) {
+                self.reachable_states.push(state.clone());
+            }
+        }
+        for (name, range) in &rhs.float_ranges {
+            match self.float_ranges.get_mut(name) {
+                Some(r) => r.merge(range),
+                None => {
+                    self.float_ranges.insert(name.clone(), *range);
+                }
+            }
+        }
+        for (channel, counts) in &rhs.message_counts {
+            self.message_counts
+                .entry(channel.clone())
+                .or_default()
+                .merge(counts);
+        }
+        self.profile.merge(&rhs.profile);
+        for f in &rhs.collected_failures {
+            failures::merge(&mut self.collected_failures, f.clone());
+        }
+        for d in &rhs.deadlocks {
+            if let Err(i) = self.deadlocks.binary_search(d) {
+                self.deadlocks.insert(i, d.clone());
+            }
+        }
+    }
+
+    /// Average number of scheduling decisions per execution (complete or blocked)
+    pub fn avg_schedule_depth(&self) -> f64 {
+        let n = self.execs + self.block;
+        if n == 0 {
+            0.0
+        } else {
+            self.total_schedule_depth as f64 / n as f64
+        }
+    }
+
+    /// The statistics as a JSON object, with a field per statistic. [`Stats::elapsed`] is an
+    /// object with the `secs` and `nanos` of the duration.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("the statistics serialize to JSON")
     }
 }
 
@@ -105,6 +275,10 @@ impl Stats {
 ///
 /// These have no outcome on the number of executions
 /// explored by TraceForge; they are mostly useful for debugging.
+///
+/// For the same reason, there is no bound on preemptions, global or per task: bounding how
+/// often the scheduler switches tasks would not change the explored executions. To narrow an
+/// exploration, use [`ConfigBuilder::with_sampling`] or [`ConfigBuilder::with_max_iterations`].
 #[derive(PartialEq, Eq, Default, Clone, Copy, Serialize, Deserialize, Debug)]
 pub enum SchedulePolicy {
     /// left-to-right (default)
@@ -114,6 +288,22 @@ pub enum SchedulePolicy {
     Arbitrary,
 }
 
+/// How the [`SchedulePolicy::LTR`] scheduler picks among the tasks that can run next.
+///
+/// Like the scheduling policy, the tie-break has no outcome on the executions explored by
+/// TraceForge, only on the order in which they are explored: it can make an exploration find
+/// a bug sooner.
+#[derive(PartialEq, Eq, Default, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum TieBreak {
+    /// The task with the lowest id (default)
+    #[default]
+    LowestId,
+    /// The task that follows, in id order, the task that ran last
+    RoundRobin,
+    /// The task that ran last, as long as it can run, then the task with the lowest id
+    LastRunFirst,
+}
+
 /// Branching strategy for parallel verification.
 ///
 /// Controls how work is partitioned when spawning parallel exploration tasks.
@@ -210,6 +400,22 @@ impl<'de> Deserialize<'de> for ConsType {
     }
 }
 
+// Scores an execution for guided search, see `ConfigBuilder::with_guide`
+pub(crate) type Guide = Arc<Mutex<Box<dyn Fn(&monitor_types::ExecutionEnd) -> i64 + Send>>>;
+
+// Called with the index of an execution, see `ConfigBuilder::with_before_each`
+pub(crate) type ExecutionHook = Arc<Mutex<Box<dyn Fn(usize) + Send>>>;
+
+// Evaluated after every step, see `ConfigBuilder::with_invariant` and `with_eventually`
+pub(crate) type StatePredicate = Arc<Mutex<Box<dyn Fn() -> bool + Send>>>;
+
+// Summarizes the final state of a complete execution, see `ConfigBuilder::with_fingerprint`
+pub(crate) type Fingerprint = Arc<Mutex<Box<dyn Fn() -> u64 + Send>>>;
+
+// Describes the shared state at the end of a complete execution, see
+// `ConfigBuilder::with_reachable_states`
+pub(crate) type StateDescription = Arc<Mutex<Box<dyn Fn() -> String + Send>>>;
+
 /// TraceForge configuration options.
 ///
 /// Use the [`ConfigBuilder`] class to construct a `Config` struct.
@@ -223,15 +429,58 @@ pub struct Config {
     pub(crate) mode: ExplorationMode,
     pub(crate) cons_type: ConsType,
     pub(crate) schedule_policy: SchedulePolicy,
+    #[serde(default)]
+    pub(crate) tie_break: TieBreak,
     pub(crate) max_iterations: Option<u64>,
     pub(crate) verbose: usize,
     pub(crate) seed: u64,
     pub(crate) symmetry: bool,
     pub(crate) vr: bool,
     pub(crate) lossy_budget: usize,
+    #[serde(default)]
+    pub(crate) exactly_once: bool,
     pub(crate) dot_file: Option<String>,
     pub(crate) trace_file: Option<String>,
     pub(crate) error_trace_file: Option<String>,
+    #[serde(default)]
+    pub(crate) capture_output: bool,
+    #[serde(default = "default_install_panic_hook")]
+    pub(crate) install_panic_hook: bool,
+    #[serde(default)]
+    pub(crate) fast_failure: bool,
+    #[serde(default)]
+    pub(crate) ignore_drop_panics: bool,
+    #[serde(default)]
+    pub(crate) memory_budget: Option<usize>,
+    #[serde(default)]
+    pub(crate) collect_deadlocks: bool,
+    #[serde(default)]
+    pub(crate) collect_failures: bool,
+    #[serde(default)]
+    pub(crate) message_counts: bool,
+    #[serde(default)]
+    pub(crate) max_in_flight: Option<usize>,
+    #[serde(default)]
+    pub(crate) incremental: Option<String>,
+    #[serde(default)]
+    pub(crate) code_versions: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub(crate) benign_races: BTreeSet<String>,
+    #[serde(default)]
+    pub(crate) coverage_target: Option<String>,
+    #[serde(default)]
+    pub(crate) profile: bool,
+    #[serde(default)]
+    pub(crate) state_dedup: bool,
+    #[serde(default)]
+    pub(crate) clock_drift: Option<std::time::Duration>,
+    #[serde(default)]
+    pub(crate) spurious_wakeups: bool,
+    #[serde(default)]
+    pub(crate) delivery_order: Vec<msg::MsgId>,
+    // Messages that are never delivered, see `minimize_messages`
+    #[serde(default)]
+    pub(crate) excluded_messages: Vec<msg::MsgId>,
     pub(crate) turmoil_trace_file: Option<String>,
     pub(crate) parallel: bool,
     pub(crate) parallel_workers: Option<usize>,
@@ -241,12 +490,36 @@ pub struct Config {
     pub(crate) warmup: usize,
     pub(crate) iterations_until_split: usize,
     pub(crate) state_batch_size: usize,
+    #[serde(default = "default_task_capacity")]
+    pub(crate) task_capacity: usize,
+    #[serde(default = "default_long_step_warning")]
+    pub(crate) long_step_warning: usize,
+    #[serde(default)]
+    pub(crate) sampling: Option<(f64, u64)>,
     pub(crate) keep_per_execution_coverage: bool,
     pub(crate) predetermined_choices: HashMap<String, Vec<Vec<bool>>>,
     pub(crate) predetermined_global_choices: HashMap<String, bool>,
     pub(crate) pretty_graph_printing: bool,
+    #[serde(default)]
+    pub(crate) independent_tags: Vec<(u32, u32)>,
+    #[serde(default)]
+    pub(crate) delays: Vec<(String, String)>,
     #[serde(skip)]
     pub(crate) callbacks: Arc<Mutex<Vec<Box<dyn ExecutionObserver + Send>>>>,
+    #[serde(skip)]
+    pub(crate) guide: Option<Guide>,
+    #[serde(skip)]
+    pub(crate) before_each: Option<ExecutionHook>,
+    #[serde(skip)]
+    pub(crate) after_each: Option<ExecutionHook>,
+    #[serde(skip)]
+    pub(crate) invariants: Vec<StatePredicate>,
+    #[serde(skip)]
+    pub(crate) eventually: Vec<StatePredicate>,
+    #[serde(skip)]
+    pub(crate) fingerprint: Option<Fingerprint>,
+    #[serde(skip)]
+    pub(crate) reachable_states: Option<StateDescription>,
 
     #[cfg(feature = "symbolic")]
     pub(crate) symbolic: bool,
@@ -270,6 +543,19 @@ impl Config {
     }
 }
 
+// Traces written before the panic hook could be disabled always installed it
+fn default_install_panic_hook() -> bool {
+    true
+}
+
+fn default_task_capacity() -> usize {
+    DEFAULT_INLINE_TASKS
+}
+
+fn default_long_step_warning() -> usize {
+    10_000
+}
+
 impl Default for Config {
     fn default() -> Self {
         ConfigBuilder::new().build()
@@ -296,15 +582,36 @@ impl ConfigBuilder {
             mode: ExplorationMode::Verification,
             cons_type: ConsType::FIFO,
             schedule_policy: SchedulePolicy::LTR,
+            tie_break: TieBreak::LowestId,
             max_iterations: None,
             verbose: 0,
             seed: rand::rng().next_u64(),
             symmetry: false,
             vr: false,
             lossy_budget: 0,
+            exactly_once: false,
             dot_file: None,
             trace_file: None,
             error_trace_file: None,
+            capture_output: false,
+            install_panic_hook: true,
+            fast_failure: false,
+            ignore_drop_panics: false,
+            memory_budget: None,
+            collect_deadlocks: false,
+            collect_failures: false,
+            message_counts: false,
+            max_in_flight: None,
+            incremental: None,
+            code_versions: BTreeMap::new(),
+            benign_races: BTreeSet::new(),
+            coverage_target: None,
+            profile: false,
+            state_dedup: false,
+            clock_drift: None,
+            spurious_wakeups: false,
+            delivery_order: Vec::new(),
+            excluded_messages: Vec::new(),
             turmoil_trace_file: None,
             parallel: false,
             parallel_workers: None,
@@ -314,11 +621,23 @@ impl ConfigBuilder {
             warmup: 100,
             iterations_until_split: 100,
             state_batch_size: 1,
+            task_capacity: DEFAULT_INLINE_TASKS,
+            long_step_warning: default_long_step_warning(),
+            sampling: None,
             keep_per_execution_coverage: false,
 	        predetermined_choices: HashMap::new(),
             predetermined_global_choices: HashMap::new(),
             pretty_graph_printing: false,
+            independent_tags: Vec::new(),
+            delays: Vec::new(),
             callbacks: Arc::new(Mutex::new(Vec::new())),
+            guide: None,
+            before_each: None,
+            after_each: None,
+            invariants: Vec::new(),
+            eventually: Vec::new(),
+            fingerprint: None,
+            reachable_states: None,
             #[cfg(feature = "symbolic")]
             symbolic: false,
         })
@@ -342,6 +661,11 @@ impl ConfigBuilder {
             eprintln!("Cannot use both parallel and partitioned_parallelization modes!");
             std::process::exit(exitcode::CONFIG);
         }
+        if !self.0.delivery_order.is_empty()
+            && (self.0.parallel || self.0.partitioned_parallelization)
+        {
+            panic!("A pinned delivery order is not supported with parallel exploration");
+        }
         self
     }
 
@@ -396,6 +720,146 @@ impl ConfigBuilder {
         self
     }
 
+    /// Records the signature of every deadlocked execution: the blocked tasks and what each of
+    /// them waits for. At the end of the exploration, the distinct signatures are printed and
+    /// returned in [`Stats::deadlocks`], so that several deadlocks can be fixed in one pass.
+    ///
+    /// Deadlocked executions are counted as blocked and do not stop the exploration; combine
+    /// this with [`ConfigBuilder::with_keep_going_after_error`] to also get past failed
+    /// assertions.
+    pub fn with_collect_deadlocks(mut self, b: bool) -> Self {
+        self.0.collect_deadlocks = b;
+        self
+    }
+
+    /// Goes on exploring after a failing execution, whatever the failure: a panic, a failed
+    /// `assert!`, a failed [`assert()`] with or without
+    /// [`ConfigBuilder::with_keep_going_after_error`]... The failing execution is counted as
+    /// blocked, and the failures are returned in [`Stats::collected_failures`] instead of
+    /// panicking: each distinct failure once, with the number of executions that failed this
+    /// way and the schedule of the first one, so that one run reports all the bugs it finds.
+    /// With [`ConfigBuilder::with_error_trace`], the counterexample of each distinct failure
+    /// is saved to a file of its own, see [`CollectedFailure::trace`].
+    ///
+    /// This has no effect with [`ConfigBuilder::with_fast_failure`], which stops at the first
+    /// failure.
+    pub fn with_collect_failures(mut self, b: bool) -> Self {
+        self.0.collect_failures = b;
+        self
+    }
+
+    /// Profiles the work of the tasks of the model: the scheduling steps of each task, and the
+    /// logical time that it spent, summed over the explored executions, are reported in
+    /// [`Stats::profile`], which can be rendered as a flamegraph, see [`profile`].
+    pub fn with_profile(mut self, b: bool) -> Self {
+        self.0.profile = b;
+        self
+    }
+
+    /// Counts the messages sent, received, lost and left in flight on each channel at the end
+    /// of every complete execution, and reports their sums in [`Stats::message_counts`], e.g.
+    /// to check that a protocol receives every message it sends. Channels are named by their
+    /// [`Builder::with_name`](channel::Builder::with_name) identifier, the mailboxes of the
+    /// threads by their thread, and the other channels by the event that created them.
+    pub fn with_message_counts(mut self, b: bool) -> Self {
+        self.0.message_counts = b;
+        self
+    }
+
+    /// Bounds the number of messages in flight, i.e. sent but not received yet, across all the
+    /// channels and mailboxes: a send waits while `k` messages are in flight, until a receive
+    /// makes room. The model checker then explores the interleavings in which senders wait for
+    /// the receivers, even on channels that are otherwise unbounded, which reveals deadlocks
+    /// that unbounded buffering hides.
+    ///
+    /// Lossy messages, and the messages that TraceForge's primitives exchange to implement
+    /// them, such as the ones of its mutexes, futures or timers, are not counted and never wait.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is 0.
+    pub fn with_max_in_flight(mut self, k: usize) -> Self {
+        assert!(k > 0, "at least 1 message must be allowed in flight");
+        self.0.max_in_flight = Some(k);
+        self
+    }
+
+    /// Saves the statistics of the exploration to `file`, and reuses them instead of exploring
+    /// again as long as the code paths that the model declared with
+    /// [`incremental::depends_on`] keep the versions registered with
+    /// [`ConfigBuilder::with_code_version`]. See [`incremental`] for when this is sound.
+    pub fn with_incremental(mut self, file: &str) -> Self {
+        self.0.incremental = Some(file.to_string());
+        self
+    }
+
+    /// Registers the version of the code path `name` for [`ConfigBuilder::with_incremental`],
+    /// e.g. a hash of its source: the saved statistics of the models that run it are reused
+    /// as long as its version does not change. The version is saved as its FNV-1a hash, which
+    /// is the same across Rust releases.
+    pub fn with_code_version<V: Hash>(mut self, name: &str, version: V) -> Self {
+        let mut hasher = incremental::VersionHasher::new();
+        version.hash(&mut hasher);
+        self.0.code_versions.insert(name.to_string(), hasher.finish());
+        self
+    }
+
+    /// Declares a data race on a [`sync::Shared`] value as benign, e.g. on a racy performance
+    /// counter: the race is counted in [`Stats::suppressed_races`] instead of failing the
+    /// execution. `race` is either the tag of the value, see [`sync::Shared::with_tag`], or the
+    /// source location of one of the racing accesses, as `file:line` or `file:line:column`.
+    /// Can be called several times to suppress several races.
+    pub fn with_benign_race(mut self, race: &str) -> Self {
+        self.0.benign_races.insert(race.to_string());
+        self
+    }
+
+    /// Focuses the exploration on the executions that reach the coverage goal `goal`, see
+    /// [`cover!`]. The executions that do not reach it are still run, since the schedules that
+    /// branch off them may, but they are not reported: they are counted in
+    /// [`Stats::off_target`] instead of [`Stats::execs`] or [`Stats::block`], and their
+    /// deadlocks, final states, message counts and profiles are not collected, nor are they
+    /// passed to the observers of the exploration. Unless [`ConfigBuilder::with_guide`] is set,
+    /// TraceForge also continues from the executions that reached the goal first, as a guided
+    /// search would.
+    ///
+    /// The failures of all the executions are reported, whether they reach the goal or not,
+    /// including the ones that the monitors and the eventually properties find.
+    pub fn with_coverage_target(mut self, goal: &str) -> Self {
+        self.0.coverage_target = Some(goal.to_string());
+        self
+    }
+
+    /// Prunes the exploration when a [`checkpoint`] is reached in a state that an earlier
+    /// execution already reached at a checkpoint: the same task positions, the same unread
+    /// messages and the same user-provided state. The pruned executions are counted as blocked.
+    ///
+    /// This is only sound if everything the rest of the execution depends on is covered by the
+    /// state passed to `checkpoint`, the task positions and the in-flight messages. In
+    /// particular, the local variables of the tasks are not part of the comparison.
+    pub fn with_state_dedup(mut self, b: bool) -> Self {
+        self.0.state_dedup = b;
+        self
+    }
+
+    /// Models time for the timers of [`time::sleep`]: they fire in the order of their deadlines,
+    /// except that the logical clocks of different tasks may drift apart by up to `bound`.
+    /// Without this, time is not modeled and timers fire in any order.
+    pub fn with_clock_drift(mut self, bound: std::time::Duration) -> Self {
+        self.0.clock_drift = Some(bound);
+        self
+    }
+
+    /// Lets a wait on a [`sync::Notify`] return without a notification, as real condition
+    /// variables may, to check that the waiters check their condition again after waking up
+    /// (see [`sync::wait_while`]). A wait that finds no stored permit becomes a choice between
+    /// waiting and waking up spuriously, which grows the explored executions, so this is off by
+    /// default. At most one wait per `Notify` wakes up spuriously in an execution.
+    pub fn with_spurious_wakeups(mut self, b: bool) -> Self {
+        self.0.spurious_wakeups = b;
+        self
+    }
+
     /// Specifies the consistency model for TraceForge
     pub fn with_cons_type(mut self, t: ConsType) -> Self {
         self.0.cons_type = t;
@@ -408,12 +872,33 @@ impl ConfigBuilder {
         self
     }
 
+    /// Specifies how the [`SchedulePolicy::LTR`] scheduler picks among the tasks that can run
+    /// next, see [`TieBreak`]
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.0.tie_break = tie_break;
+        self
+    }
+
     /// Specifies an upper bound on the number of iterations
     pub fn with_max_iterations(mut self, n: u64) -> Self {
         self.0.max_iterations = Some(n);
         self
     }
 
+    /// Stops the exploration once the memory in use has grown by more than `bytes` since it
+    /// started, instead of letting it grow until the OS kills the process. The exploration then
+    /// returns the statistics of the executions explored so far, with [`Stats::out_of_memory`]
+    /// set.
+    ///
+    /// The budget is checked at the end of every execution, and only if the program counts its
+    /// allocations with the [`alloc::CountingAllocator`]; it is ignored otherwise. The count is
+    /// of the net live bytes of the whole process: frees are subtracted, and the allocations of
+    /// all threads are counted, including those of other tests running in parallel.
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.0.memory_budget = Some(bytes);
+        self
+    }
+
     /// Controls how much input is printed in `stdout`
     /// 0 = default, sparse information
     /// 1 = more information, and print the execution graph every time it's blocked.
@@ -429,6 +914,9 @@ impl ConfigBuilder {
     /// Seeds TraceForge's random number gneerator.
     /// Has no effect without `[SchedulePolicy::Random]`
     /// being the selected scheduling policy.
+    ///
+    /// Under a random schedule, the seed determines every scheduling choice, and a failure
+    /// reports the seed of the exploration that produced it, so that it can be rerun on its own.
     pub fn with_seed(mut self, s: u64) -> Self {
         self.0.seed = s;
         self
@@ -451,6 +939,14 @@ impl ConfigBuilder {
         self
     }
 
+    /// Models an idealized transport that delivers every message exactly once: lossy sends are
+    /// never dropped, whatever the budget given to [`ConfigBuilder::with_lossy`]. (Messages are
+    /// never duplicated in any mode.) This is useful as a baseline to compare a protocol against.
+    pub fn with_exactly_once(mut self, b: bool) -> Self {
+        self.0.exactly_once = b;
+        self
+    }
+
     /// Whenever the execution graph is printed, the same
     /// information will be written to this file in DOT format.
     ///
@@ -489,6 +985,57 @@ impl ConfigBuilder {
         self
     }
 
+    /// Buffers everything the model writes to stdout and stderr during each execution, and
+    /// only shows the output of a failing execution, right after its counterexample. The
+    /// output of passing executions is discarded. The captured output is also stored in the
+    /// error trace file, if one is configured.
+    ///
+    /// This redirects the process-wide stdout and stderr, so it is only supported on Unix and
+    /// should not be combined with parallel exploration. Under the Rust test harness, `print!`
+    /// and `eprint!` and their variants write to the harness's own buffer instead of stdout and
+    /// stderr, unless the test runs with `--nocapture`: TraceForge cannot capture what they
+    /// print, and the harness shows it for the whole test instead. Writes to
+    /// [`std::io::stdout`] and [`std::io::stderr`] are captured in any case.
+    pub fn with_capture_output(mut self, b: bool) -> Self {
+        self.0.capture_output = b;
+        self
+    }
+
+    /// Whether to install TraceForge's panic hook while a model runs (the default). The hook
+    /// records where a panic was raised and persists the counterexample before the panic
+    /// unwinds.
+    ///
+    /// Disable it when the test harness or another tool relies on its own panic hook.
+    /// Failures are then reported from the result of catching the panic instead, which still
+    /// records the failing task and event, but not the source location of the panic.
+    pub fn with_install_panic_hook(mut self, b: bool) -> Self {
+        self.0.install_panic_hook = b;
+        self
+    }
+
+    /// Reports failures as cheaply as possible, for CI runs that only need to know whether a
+    /// model has a bug. The exploration stops at the first failure, even with
+    /// [`ConfigBuilder::with_keep_going_after_error`], and the panic only carries the number of
+    /// the failing execution and a compact id of its schedule: no counterexample is stored or
+    /// printed, and the panic hook neither prints the panic nor captures a backtrace.
+    ///
+    /// Rerun without this option to get the details of the failure.
+    pub fn with_fast_failure(mut self, b: bool) -> Self {
+        self.0.fast_failure = b;
+        self
+    }
+
+    /// Ignores the panics of Drop handlers that run when an execution ends, e.g. for the values
+    /// captured by a task that never ran. By default, such a panic fails the execution as a
+    /// panic of the task that owned the value. Ignored panics are only logged.
+    ///
+    /// The values on the stack of an unfinished task are dropped while its stack unwinds, where
+    /// Rust aborts on a panic, whatever this option.
+    pub fn with_ignore_drop_panics(mut self, b: bool) -> Self {
+        self.0.ignore_drop_panics = b;
+        self
+    }
+
     /// Enables parallel processing of model. By default the number of system
     /// cores is chosen as for the max worker count unless .with_parallel_workers()
     /// explicitly sets a value or env var MUST_PARALLEL_WORKERS is set.
@@ -545,6 +1092,40 @@ impl ConfigBuilder {
         self
     }
 
+    /// Number of tasks to reserve room for at the start of every execution. There is always
+    /// room for 16 tasks, which are stored inline; a model that spawns more tasks than that
+    /// (including the daemon threads behind the [`sync`] primitives) has to grow the task list
+    /// while it runs. Raising this for models with many tasks avoids that cost, see
+    /// [`Stats::task_spills`].
+    pub fn with_task_capacity(mut self, n: usize) -> Self {
+        self.0.task_capacity = n;
+        self
+    }
+
+    /// In debug builds, TraceForge warns when a task executes more than `threshold`
+    /// instructions (10000 by default) in a single step, i.e., without yielding to another
+    /// task, e.g. in a long loop. The interleavings inside such a step may be hidden; calling
+    /// [`thread::yield_now`] in the loop lets the other tasks run. Such steps are counted in
+    /// [`Stats::long_steps`].
+    pub fn with_long_step_warning(mut self, threshold: usize) -> Self {
+        self.0.long_step_warning = threshold;
+        self
+    }
+
+    /// Explores only a random sample of the schedules: each branch of the exploration is
+    /// explored with probability `rate`, and skipped (along with the schedules below it)
+    /// otherwise. The skipped branches only depend on `seed`, so the same seed explores the same
+    /// sample. The rate and the number of skipped branches are reported in
+    /// [`Stats::sampling_rate`] and [`Stats::sampled_out`].
+    pub fn with_sampling(mut self, rate: f64, seed: u64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "the sampling rate must be between 0 and 1"
+        );
+        self.0.sampling = Some((rate, seed));
+        self
+    }
+
     /// Number of (graph, rqueue) pairs per spawned task in `RevisitQueueRayon`.
     /// Default is 1. Higher values mean coarser tasks (less spawning overhead,
     /// but coarser load balancing).
@@ -554,6 +1135,117 @@ impl ConfigBuilder {
         self
     }
 
+    /// Guides the exploration toward a target. At the end of every execution, `guide` scores
+    /// how close the execution got to the target, usually from the values the model
+    /// [`publish`]ed, and TraceForge continues from the highest-scoring explored execution
+    /// that still has unexplored alternatives (best-first, instead of depth-first).
+    ///
+    /// This only changes the order of the exploration: every execution is still explored
+    /// eventually, so a guide that runs to completion explores the same executions as without
+    /// it. It is useful to find a targeted bug faster in a large state space.
+    pub fn with_guide<G>(mut self, guide: G) -> Self
+    where
+        G: Fn(&monitor_types::ExecutionEnd) -> i64 + Send + 'static,
+    {
+        self.0.guide = Some(Arc::new(Mutex::new(Box::new(guide))));
+        self
+    }
+
+    /// Registers a hook that is called with the index of each execution before it starts,
+    /// e.g., to set up fresh external fixtures. The hook runs outside the model, so it must not
+    /// call TraceForge functions.
+    pub fn with_before_each<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(usize) + Send + 'static,
+    {
+        self.0.before_each = Some(Arc::new(Mutex::new(Box::new(hook))));
+        self
+    }
+
+    /// Registers a hook that is called with the index of each execution after it ends, even if
+    /// it failed, e.g., to tear down the fixtures set up by
+    /// [`ConfigBuilder::with_before_each`]. The hook runs outside the model, so it must not call
+    /// TraceForge functions.
+    pub fn with_after_each<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(usize) + Send + 'static,
+    {
+        self.0.after_each = Some(Arc::new(Mutex::new(Box::new(hook))));
+        self
+    }
+
+    /// Registers a global invariant, checked after every scheduling step of every execution.
+    /// The invariant reads the state shared by the tasks, e.g. captured by `Arc`, and must not
+    /// call TraceForge functions. As soon as it returns false, the execution fails, reporting the
+    /// event, and its task, after which the invariant first broke. Several invariants can be
+    /// registered; they are numbered in registration order, starting at 0.
+    pub fn with_invariant<I>(mut self, invariant: I) -> Self
+    where
+        I: Fn() -> bool + Send + 'static,
+    {
+        self.0.invariants.push(Arc::new(Mutex::new(Box::new(invariant))));
+        self
+    }
+
+    /// Registers a liveness property: the predicate must eventually hold in every complete
+    /// execution, e.g. "the request is eventually acknowledged". Like an invariant, it reads the
+    /// shared state and is evaluated after every scheduling step. An execution in which all the
+    /// threads completed without the predicate ever holding fails. Executions blocked by an
+    /// assumption are not complete, and are not checked.
+    ///
+    /// TraceForge executions are finite, so every complete execution is fair: no task is
+    /// starved forever. Several properties can be registered; they are numbered in registration
+    /// order, starting at 0.
+    pub fn with_eventually<P>(mut self, property: P) -> Self
+    where
+        P: Fn() -> bool + Send + 'static,
+    {
+        self.0.eventually.push(Arc::new(Mutex::new(Box::new(property))));
+        self
+    }
+
+    /// Registers a fingerprint of the final state, computed at the end of every complete
+    /// execution, e.g. a hash of the shared state of the model. The distinct fingerprints are
+    /// reported in [`Stats::final_states`]: a deterministic algorithm reaches a single final
+    /// state whatever the interleaving. Blocked executions have no final state, and are not
+    /// fingerprinted. Like the hooks of [`ConfigBuilder::with_after_each`], the fingerprint runs
+    /// outside the model, so it must not call TraceForge functions.
+    pub fn with_fingerprint<P>(mut self, fingerprint: P) -> Self
+    where
+        P: Fn() -> u64 + Send + 'static,
+    {
+        self.0.fingerprint = Some(Arc::new(Mutex::new(Box::new(fingerprint))));
+        self
+    }
+
+    /// Enumerates the distinct final states reachable by the model, e.g. to document all the
+    /// outcomes of a small algorithm. At the end of every complete execution, TraceForge
+    /// records which tasks finished and the values they returned, along with the shared state
+    /// as given by `describe`, and reports each distinct combination once in
+    /// [`Stats::reachable_states`]. Like the fingerprint of
+    /// [`ConfigBuilder::with_fingerprint`], `describe` runs outside the model, so it must not
+    /// call TraceForge functions.
+    pub fn with_reachable_states<D>(mut self, describe: D) -> Self
+    where
+        D: Fn() -> String + Send + 'static,
+    {
+        self.0.reachable_states = Some(Arc::new(Mutex::new(Box::new(describe))));
+        self
+    }
+
+    /// Pins the order in which the given messages are delivered, e.g. to turn a
+    /// message-ordering bug found by a full exploration into a fast regression test. Only the
+    /// executions in which the messages are received in this order, each after the previous
+    /// one, are explored; the other messages are delivered in any order.
+    ///
+    /// The verification panics if no execution delivers all of these messages in this order,
+    /// which indicates that the pinned order is no longer feasible, e.g. because the model
+    /// changed. Messages read by an `inbox` are not tracked.
+    pub fn with_delivery_order(mut self, order: &[msg::MsgId]) -> Self {
+        self.0.delivery_order = order.to_vec();
+        self
+    }
+
     /// Registers a callback that is called at the end of an execution by the model checker
     ///
     pub fn with_callback(self, cb: Box<dyn ExecutionObserver + Send>) -> Self {
@@ -626,6 +1318,34 @@ impl ConfigBuilder {
         self
     }
 
+    /// Declares that messages tagged with `a` and messages tagged with `b` commute: a receiver
+    /// behaves the same no matter in which order it receives them. TraceForge then does not
+    /// explore the alternative orders in which a receive can read such messages, which can
+    /// shrink the state space considerably. The declaration is symmetric, and `a` may be
+    /// equal to `b`. Only messages sent with a single tag (e.g., with [`send_tagged_msg`])
+    /// are affected.
+    ///
+    /// This is unsound if the declaration is wrong: when the order does matter, the skipped
+    /// executions are never checked and bugs in them go unreported.
+    pub fn with_independent_tags(mut self, a: u32, b: u32) -> Self {
+        self.0.independent_tags.push((a, b));
+        self
+    }
+
+    /// Injects a delay between the program points `first` and `second` (see [`point`]) of
+    /// every task that reaches both, in this order: when the task reaches `second`, it lets the
+    /// other tasks run first, until none of them can run, as if it had been delayed in between.
+    ///
+    /// This concentrates the exploration on a suspected race window: the delayed schedules are
+    /// explored first, which matters for bounded explorations (e.g., with
+    /// [`ConfigBuilder::with_max_iterations`]), and `second` becomes a scheduling point, so
+    /// that the other tasks can run inside the window even if the task reaches no TraceForge
+    /// primitive in it. Several windows can be declared.
+    pub fn with_delay_between(mut self, first: &str, second: &str) -> Self {
+        self.0.delays.push((first.to_owned(), second.to_owned()));
+        self
+    }
+
     /// Consumes the builder and produces the [`Config`]
     pub fn build(self) -> Config {
         self.check_valid().0
@@ -644,16 +1364,91 @@ where
         panic!("Cannot use both .with_parallel(true) and .with_partitioned_parallelization(true)");
     }
 
+    if let Some(stats) = incremental::load(&conf) {
+        return stats;
+    }
     let f = Arc::new(f);
-    if conf.partitioned_parallelization {
-        parallel_verify::verify_partitioned_rayon(conf, move || f())
+    let stats = if conf.partitioned_parallelization {
+        parallel_verify::verify_partitioned_rayon(conf.clone(), move || f())
     } else if conf.parallel {
         ExecutionPool::new(&conf).explore(&f)
     } else {
-        let must = Rc::new(RefCell::new(Must::new(conf, false)));
+        let must = Rc::new(RefCell::new(Must::new(conf.clone(), false)));
         explore(&must, &f);
         let stats = must.borrow().stats();
         stats
+    };
+    incremental::save(&conf, &stats);
+    stats
+}
+
+/// Model Checker API
+///
+/// Verifies `f` like [`verify`], and also returns the value that `f` returned in the last
+/// complete execution, i.e., the last explored execution in which all the threads finished.
+/// The values of blocked executions are discarded; the value is `None` if no execution
+/// completed.
+///
+/// The order in which the executions are explored is unspecified. The value is meant for
+/// summaries that are the same in every complete execution; when the executions can return
+/// different values, check them in the model instead, e.g. with an assertion or a monitor.
+pub fn verify_value<F, R>(mut conf: Config, f: F) -> (Stats, Option<R>)
+where
+    F: Fn() -> R + Send + Sync + 'static,
+    R: Send + 'static,
+{
+    let current = Arc::new(Mutex::new(None));
+    let last = Arc::new(Mutex::new(None));
+    // Wrap the observers of the caller rather than adding to them: they are shared by the
+    // clones of `conf`
+    let observer = ValueObserver {
+        current: current.clone(),
+        last: last.clone(),
+        inner: conf.callbacks.clone(),
+    };
+    conf.callbacks = Arc::new(Mutex::new(vec![Box::new(observer)]));
+    let stats = verify(conf, move || {
+        let value = f();
+        *current.lock().unwrap() = Some(value);
+    });
+    let value = last.lock().unwrap().take();
+    (stats, value)
+}
+
+// Keeps the value of the last complete execution for `verify_value`, and forwards the
+// notifications to the observers of the configuration
+struct ValueObserver<R> {
+    current: Arc<Mutex<Option<R>>>,
+    last: Arc<Mutex<Option<R>>>,
+    inner: Arc<Mutex<Vec<Box<dyn ExecutionObserver + Send>>>>,
+}
+
+impl<R> ExecutionObserver for ValueObserver<R> {
+    fn before(&mut self, eid: ExecutionId) {
+        for cb in self.inner.lock().unwrap().iter_mut() {
+            cb.before(eid);
+        }
+    }
+
+    fn after(
+        &mut self,
+        eid: ExecutionId,
+        end_condition: &monitor_types::EndCondition,
+        c: CoverageInfo,
+    ) {
+        let value = self.current.lock().unwrap().take();
+        if *end_condition == monitor_types::EndCondition::AllThreadsCompleted && value.is_some() {
+            *self.last.lock().unwrap() = value;
+        }
+        for cb in self.inner.lock().unwrap().iter_mut() {
+            cb.after(eid, end_condition, c.clone());
+        }
+    }
+
+    fn at_end_of_exploration(&mut self) {
+        for cb in self.inner.lock().unwrap().iter_mut() {
+            cb.at_end_of_exploration();
+        }
     }
 }
 
@@ -661,6 +1456,28 @@ where
 ///
 /// Replays `f` using `replay_info`.
 pub fn replay<F>(f: F, error_file: &str)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    replay_with(f, error_file, None)
+}
+
+/// Model Checker API
+///
+/// Replays the counterexample saved in `error_file` like [`replay`], stepping through its
+/// schedule: `step` is called at every scheduling decision, with the state of the
+/// execution at that point, e.g. to log the interleaving or to assert on it.
+///
+/// `step` runs in the scheduler, outside the model, so it must not call TraceForge functions.
+pub fn replay_steps<F, S>(f: F, error_file: &str, step: S)
+where
+    F: Fn() + Send + Sync + 'static,
+    S: FnMut(&Step) + 'static,
+{
+    replay_with(f, error_file, Some(Box::new(step)))
+}
+
+fn replay_with<F>(f: F, error_file: &str, step_hook: Option<step::StepHook>)
 where
     F: Fn() + Send + Sync + 'static,
 {
@@ -680,6 +1497,9 @@ where
 
     // Add the error graph to this new instance of TraceForge
     must.borrow_mut().load_replay_information(replay_info);
+    if let Some(hook) = step_hook {
+        must.borrow_mut().set_step_hook(hook);
+    }
 
     explore(&must, &f);
 }
@@ -734,6 +1554,33 @@ where
     estimate_sum / (num_samples as f64)
 }
 
+// Runs an execution with `run`, which returns whether the exploration is complete, between the
+// `before_each` and `after_each` hooks of the configuration. `after_each` also runs when the
+// execution fails.
+pub(crate) fn run_with_hooks(must: &Rc<RefCell<Must>>, run: impl FnOnce() -> bool) -> bool {
+    let (index, before, after) = {
+        let must = must.borrow();
+        let config = must.config();
+        (
+            must.execution_index(),
+            config.before_each.clone(),
+            config.after_each.clone(),
+        )
+    };
+    if let Some(hook) = &before {
+        (hook.lock().expect("Could not lock before_each hook"))(index);
+    }
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(run));
+    must.borrow_mut().next_execution_index();
+    if let Some(hook) = &after {
+        (hook.lock().expect("Could not lock after_each hook"))(index);
+    }
+    match result {
+        Ok(done) => done,
+        Err(e) => std::panic::resume_unwind(e),
+    }
+}
+
 fn explore<F>(must: &Rc<RefCell<must::Must>>, f: &Arc<F>)
 where
     F: Fn() + Send + Sync + 'static,
@@ -743,15 +1590,19 @@ where
     CONTINUATION_POOL.set(&ContinuationPool::new(), || loop {
         let f = Arc::clone(f);
         let execution = Execution::new(Rc::clone(must));
-        Must::begin_execution(must);
-        execution.run(move || f());
-        if Must::complete_execution(must) {
+        let done = run_with_hooks(must, || {
+            Must::begin_execution(must);
+            execution.run(move || f());
+            Must::complete_execution(must)
+        });
+        if done {
             // `done` internally calls `run_metrics_after`
             break;
         }
     });
     // end of model checking
     must.borrow_mut().run_metrics_at_end();
+    must.borrow().check_delivery_order_feasible();
 }
 
 /// This allows the caller to reuse a single pool across many explorations, avoiding
@@ -765,13 +1616,17 @@ where
     loop {
         let f = Arc::clone(f);
         let execution = Execution::new(Rc::clone(must));
-        Must::begin_execution(must);
-        execution.run(move || f());
-        if Must::complete_execution(must) {
+        let done = run_with_hooks(must, || {
+            Must::begin_execution(must);
+            execution.run(move || f());
+            Must::complete_execution(must)
+        });
+        if done {
             break;
         }
     }
     must.borrow_mut().run_metrics_at_end();
+    must.borrow().check_delivery_order_feasible();
 }
 
 ///
@@ -878,7 +1733,7 @@ pub(crate) fn select_val_block<'a, T: Message + 'static, U: Message + 'static>(
     let locs = iter::once(&primary.inner).chain(iter::once(&secondary.inner));
     // *Only* use main recv's communication model
     let comm = primary.comm;
-    recv_val_block_with_tag(locs, comm, None)
+    recv_val_block_with_tag(locs, comm, None, None)
 }
 
 ///
@@ -990,37 +1845,87 @@ where
 /// Sends to `t` the message `v`
 pub fn send_msg<T: Message + 'static>(t: ThreadId, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_tag(v, None, &loc, comm, false)
+    send_msg_with_tag(v, None, &loc, comm, false, false)
+}
+
+/// Sends to `t` all messages in `msgs` as one contiguous block: the receiver gets them in order,
+/// one per receive, and no other message is received in between (as with a transactional
+/// outbox). The position of the block relative to the messages of other senders is still
+/// explored.
+///
+/// Only the block as a whole is an event of the execution graph, received by the first receive.
+/// The messages following the first one skip the graph: they are handed to the next receives of
+/// the receiving thread on the same location, without a scheduling point, so they are not
+/// explored as separate receives, and these receives ignore their tag filters. Monitors do not
+/// observe the batched messages, and batches cannot be received with [`inbox`].
+pub fn send_all<T: Message + 'static>(t: ThreadId, msgs: impl IntoIterator<Item = T>) {
+    let (loc, comm) = thread_loc_comm(t);
+    send_batch(msgs, &loc, comm, false)
+}
+
+/// Helper for [`send_all`]
+pub(crate) fn send_batch<T: Message + 'static>(
+    msgs: impl IntoIterator<Item = T>,
+    loc: &Loc,
+    comm: CommunicationModel,
+    internal: bool,
+) {
+    let mut msgs = msgs.into_iter().collect::<Vec<_>>();
+    match msgs.len() {
+        0 => {}
+        1 => send_msg_with_tag(msgs.pop().unwrap(), None, loc, comm, false, internal),
+        _ => send_msg_with_tag(
+            Batch(msgs.into_iter().map(Val::new).collect()),
+            None,
+            loc,
+            comm,
+            false,
+            internal,
+        ),
+    }
 }
 
 /// Sends to `t` the message `v`, which can be lost
 pub fn send_lossy_msg<T: Message + 'static>(t: ThreadId, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_tag(v, None, &loc, comm, true)
+    send_msg_with_tag(v, None, &loc, comm, true, false)
 }
 
 /// Sends to `t` the message `v` tagged with 'tag
 pub fn send_tagged_msg<T: Message + 'static>(t: ThreadId, tag: u32, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_tag(v, Some(tag), &loc, comm, false)
+    send_msg_with_tag(v, Some(tag), &loc, comm, false, false)
 }
 
 /// Sends to `t` the message `v`, which can be lost, tagged with 'tag
 pub fn send_tagged_lossy_msg<T: Message + 'static>(t: ThreadId, tag: u32, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_tag(v, Some(tag), &loc, comm, true)
+    send_msg_with_tag(v, Some(tag), &loc, comm, true, false)
+}
+
+// Sends to `t` the message `v` of the protocol of a TraceForge primitive, which does not count
+// towards `ConfigBuilder::with_max_in_flight`
+pub(crate) fn send_internal_msg<T: Message + 'static>(t: ThreadId, v: T) {
+    let (loc, comm) = thread_loc_comm(t);
+    send_msg_with_tag(v, None, &loc, comm, false, true)
+}
+
+// Like `send_internal_msg`, tagged with `tag`
+pub(crate) fn send_internal_tagged_msg<T: Message + 'static>(t: ThreadId, tag: u32, v: T) {
+    let (loc, comm) = thread_loc_comm(t);
+    send_msg_with_tag(v, Some(tag), &loc, comm, false, true)
 }
 
 /// Sends to `t` the message `v` tagged with a vector 'tag
 pub fn send_vec_tagged_msg<T: Message + 'static>(t: ThreadId, tag: Vec<u32>, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_vec_tag(v, Some(tag), &loc, comm, false)
+    send_msg_with_vec_tag(v, Some(tag), &loc, comm, false, false)
 }
 
 /// Sends to `t` the message `v`, which can be lost, tagged with 'tag
 pub fn send_vec_tagged_lossy_msg<T: Message + 'static>(t: ThreadId, tag: Vec<u32>, v: T) {
     let (loc, comm) = thread_loc_comm(t);
-    send_msg_with_vec_tag(v, Some(tag), &loc, comm, true)
+    send_msg_with_vec_tag(v, Some(tag), &loc, comm, true, false)
 }
 
 /// Helper for [`send_msg`] and [`send_tagged_msg`]
@@ -1030,8 +1935,9 @@ fn send_msg_with_tag<T: Message + 'static>(
     loc: &Loc,
     comm: CommunicationModel,
     lossy: bool,
+    internal: bool,
 ) {
-    send_msg_with_vec_tag(v, tag.map(|t| vec![t]), loc, comm, lossy);
+    send_msg_with_vec_tag(v, tag.map(|t| vec![t]), loc, comm, lossy, internal);
 }
 
 /// Helper for vector tagged message sending
@@ -1041,10 +1947,12 @@ fn send_msg_with_vec_tag<T: Message + 'static>(
     loc: &Loc,
     comm: CommunicationModel,
     lossy: bool,
+    internal: bool,
 ) {
     let tag = normalize_vec_tag(tag);
+    let counted = in_flight::acquire(!lossy && !internal);
     switch();
-    ExecutionState::with(|s| {
+    let pos = ExecutionState::with(|s| {
         // creating the send label for the system send
         let pos = s.next_pos();
         let sender_tid = pos.thread;
@@ -1096,7 +2004,11 @@ fn send_msg_with_vec_tag<T: Message + 'static>(
                 task.unstuck();
             }
         });
+        pos
     });
+    if counted {
+        in_flight::record(pos);
+    }
 }
 
 /// Returns a message from the thread queue or times out
@@ -1134,29 +2046,65 @@ where
     .map(|x| x.0)
 }
 
+/// Returns the next message from the thread queue for which `p` holds, or times out. The
+/// messages for which `p` doesn't hold stay in the queue, for later receives; the checker
+/// explores each of the messages for which `p` holds that the receive can read.
+pub fn recv_msg_where<T, P>(p: P) -> Option<T>
+where
+    T: Message + 'static,
+    P: Fn(&T) -> bool + 'static + Send + Sync,
+{
+    let (loc, comm) = self_loc_comm();
+    recv_val_with_tag(iter::once(&loc), comm, None, Some(value_predicate(p)))
+        .map(|(val, _)| expect_msg(val))
+}
+
+// Lifts a predicate on messages of type `T` to values; the values of other types don't match
+fn value_predicate<T, P>(p: P) -> ValuePredicate
+where
+    T: Message + 'static,
+    P: Fn(&T) -> bool + 'static + Send + Sync,
+{
+    ValuePredicate(Arc::new(move |val: &Val| {
+        val.as_any_ref().downcast_ref::<T>().is_some_and(&p)
+    }))
+}
+
 fn recv_msg_with_tag<'a, T: Message + 'static>(
     locs: impl Iterator<Item = &'a Loc>,
     comm: CommunicationModel,
     tag: Option<PredicateType>,
 ) -> Option<(T, usize)> {
-    recv_val_with_tag(locs, comm, tag).map(|(val, ind)| (expect_msg(val), ind))
+    recv_val_with_tag(locs, comm, tag, None).map(|(val, ind)| (expect_msg(val), ind))
 }
 
 fn recv_val_with_tag<'a>(
     locs: impl Iterator<Item = &'a Loc>,
     comm: CommunicationModel,
     tag: Option<PredicateType>,
+    filter: Option<ValuePredicate>,
 ) -> Option<(Val, usize)> {
     let locs = locs.collect::<Vec<_>>();
     validate_locs(&locs);
+    if let Some(batched) = ExecutionState::with(|s| s.current_mut().take_batched(&locs)) {
+        return Some(batched);
+    }
     loop {
         switch();
-        let locs = locs.clone();
         let tag = tag.clone();
         let (val, ind) = ExecutionState::with(|s| {
             let pos = s.next_pos();
+            if s.current().atomic_depth > 0 {
+                s.must.borrow_mut().set_atomic_recv(pos);
+            }
             s.must.borrow_mut().handle_recv(
-                RecvMsg::new(pos, RecvLoc::new(locs, tag), comm, None, true),
+                RecvMsg::new(
+                    pos,
+                    RecvLoc::new(locs.clone(), tag).with_filter(filter.clone()),
+                    comm,
+                    None,
+                    true,
+                ),
                 false,
             )
         });
@@ -1168,11 +2116,60 @@ fn recv_val_with_tag<'a>(
                 s.prev_pos();
             });
         } else {
-            return val.map(|v| (v, ind.unwrap()));
+            if let Some(val) = &val {
+                ExecutionState::with(|s| {
+                    let pos = s.curr_pos();
+                    s.must.borrow_mut().receive_clock(pos);
+                });
+                assume_delivery_order();
+                assume_filter(&filter, val);
+                in_flight::release();
+            }
+            return val.map(|v| {
+                let ind = ind.unwrap();
+                (unbatch(v, locs[ind]), ind)
+            });
         }
     }
 }
 
+// Ends the execution if the receive that the current task just executed breaks the delivery
+// order pinned with `ConfigBuilder::with_delivery_order`, or the edges added with
+// `add_sync_edge`
+fn assume_delivery_order() {
+    let in_order = ExecutionState::with(|s| {
+        let must = s.must.borrow();
+        must.in_delivery_order(s.curr_pos()) && must.sync_edges_hold()
+    });
+    if !in_order {
+        crate::assume!(false);
+    }
+}
+
+// Ends the execution if the receive read a message, not known when the receive was added, for
+// which its value predicate doesn't hold
+fn assume_filter(filter: &Option<ValuePredicate>, val: &Val) {
+    if filter.as_ref().is_some_and(|f| !f.0(val)) {
+        crate::assume!(false);
+    }
+}
+
+/// If `val` is a batch received from `loc`, returns its first message
+/// and keeps the others for the next receives of the current task
+fn unbatch(val: Val, loc: &Loc) -> Val {
+    if !val.as_any_ref().is::<Batch>() {
+        return val;
+    }
+    let mut msgs = expect_msg::<Batch>(val).0.into_iter();
+    let first = msgs.next().unwrap();
+    ExecutionState::with(|s| {
+        s.current_mut()
+            .batched
+            .extend(msgs.map(|msg| (loc.clone(), msg)))
+    });
+    first
+}
+
 /// Returns a message from the queue.
 pub fn recv_msg_block<T: Message + 'static>() -> T {
     let (loc, comm) = self_loc_comm();
@@ -1208,13 +2205,24 @@ where
     .0
 }
 
+/// Returns the next message from the queue for which `p` holds, see [`recv_msg_where`]
+pub fn recv_msg_block_where<T, P>(p: P) -> T
+where
+    T: Message + 'static,
+    P: Fn(&T) -> bool + 'static + Send + Sync,
+{
+    let (loc, comm) = self_loc_comm();
+    let (val, _) = recv_val_block_with_tag(iter::once(&loc), comm, None, Some(value_predicate(p)));
+    expect_msg(val)
+}
+
 /// Helper function for [`recv_msg_block`] and [`recv_tagged_msg_block`]
 fn recv_msg_block_with_tag<'a, T: Message + 'static>(
     locs: impl Iterator<Item = &'a Loc>,
     comm: CommunicationModel,
     tag: Option<PredicateType>,
 ) -> (T, usize) {
-    let (val, ind) = recv_val_block_with_tag(locs, comm, tag);
+    let (val, ind) = recv_val_block_with_tag(locs, comm, tag, None);
     (expect_msg(val), ind)
 }
 
@@ -1222,16 +2230,28 @@ fn recv_val_block_with_tag<'a>(
     locs: impl Iterator<Item = &'a Loc>,
     comm: CommunicationModel,
     tag: Option<PredicateType>,
+    filter: Option<ValuePredicate>,
 ) -> (Val, usize) {
     let locs = locs.collect::<Vec<_>>();
     validate_locs(&locs);
+    if let Some(batched) = ExecutionState::with(|s| s.current_mut().take_batched(&locs)) {
+        return batched;
+    }
     loop {
         switch();
-        let locs = locs.clone();
         let (val, ind) = ExecutionState::with(|s| {
             let pos = s.next_pos();
+            if s.current().atomic_depth > 0 {
+                s.must.borrow_mut().set_atomic_recv(pos);
+            }
             s.must.borrow_mut().handle_recv(
-                RecvMsg::new(pos, RecvLoc::new(locs, tag.clone()), comm, None, false),
+                RecvMsg::new(
+                    pos,
+                    RecvLoc::new(locs.clone(), tag.clone()).with_filter(filter.clone()),
+                    comm,
+                    None,
+                    false,
+                ),
                 true,
             )
         });
@@ -1242,7 +2262,15 @@ fn recv_val_block_with_tag<'a>(
                 // Block this thread and let the other thread finish.
                 ExecutionState::with(|s| s.current_mut().stuck());
             } else {
-                return (box_msg, ind.unwrap());
+                ExecutionState::with(|s| {
+                    let pos = s.curr_pos();
+                    s.must.borrow_mut().receive_clock(pos);
+                });
+                assume_delivery_order();
+                assume_filter(&filter, &box_msg);
+                in_flight::release();
+                let ind = ind.unwrap();
+                return (unbatch(box_msg, locs[ind]), ind);
             }
         };
 
@@ -1336,6 +2364,7 @@ fn inbox_internal(tag: Option<PredicateType>, min: usize, max: Option<usize>) ->
                 s.prev_pos();
             });
         } else {
+            in_flight::release();
             return vals;
         }
     }
@@ -1351,6 +2380,36 @@ pub fn nondet() -> bool {
         s.must.borrow_mut().handle_ctoss(CToss::new(pos, toss))
     })
 }
+
+/// Models a task reading an input from its environment, e.g. a sensor or a user, which
+/// TraceForge does not control: the task waits for the input, which can be any value of
+/// `domain`. Every value of the domain is explored, like a nondeterministic choice, and the
+/// input can arrive at any point of the schedule of the other tasks.
+///
+/// # Panics
+///
+/// Panics if `domain` is empty.
+pub fn external_input<T: Clone>(domain: &[T]) -> T {
+    if domain.is_empty() {
+        panic!("The domain of an external input is empty");
+    }
+    // Waiting for the environment lets the other tasks run
+    crate::thread::yield_now();
+    domain[(0..domain.len()).nondet()].clone()
+}
+
+/// Returns a random number generator whose values are determined by the configured seed.
+///
+/// See the [`rng`](mod@crate::rng) module for details.
+pub fn rng() -> DeterministicRng {
+    ExecutionState::with(|s| {
+        let me = s.current().id();
+        let mut must = s.must.borrow_mut();
+        let tid = must.to_thread_id(me);
+        must.new_rng(tid)
+    })
+}
+
 #[deprecated(
     since = "0.2.0",
     note = "please use `nondet()` or `<bool>::nondet()` instead"
@@ -1722,6 +2781,126 @@ pub fn assume_impl(cond: bool, macro_info: Option<(&str, &str, u32)>) {
     }
 }
 
+/// Marks a point where the state of the execution is summarized by `state`.
+///
+/// With [`ConfigBuilder::with_state_dedup`], an execution that reaches a checkpoint in a state
+/// that was already explored from is not explored any further. Two states are the same when they
+/// have equal hashes of `state`, all tasks are at the same positions, and the same messages are
+/// waiting to be received. Without `with_state_dedup`, this does nothing.
+///
+/// ```no_run
+/// use traceforge::{thread, Config};
+///
+/// traceforge::verify(Config::builder().with_state_dedup(true).build(), || {
+///     let me = thread::current().id();
+///     for i in 0..3 {
+///         thread::spawn(move || traceforge::send_msg(me, i));
+///     }
+///     let sum: i32 = (0..3).map(|_| traceforge::recv_msg_block::<i32>()).sum();
+///     // The order of the receives does not matter for the rest of the execution
+///     traceforge::checkpoint(&sum);
+///     // ...
+/// });
+/// ```
+pub fn checkpoint<S: Hash>(state: &S) {
+    let dedup = ExecutionState::with(|s| s.must.borrow().config.state_dedup);
+    if !dedup {
+        return;
+    }
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    let hash = hasher.finish();
+    let seen = ExecutionState::with(|s| {
+        let tasks = {
+            let must = s.must.borrow();
+            s.tasks
+                .iter()
+                .map(|t| (must.to_thread_id(t.id()), t.instructions))
+                .collect::<Vec<_>>()
+        };
+        s.must.borrow_mut().visit_state(hash, &tasks)
+    });
+    if seen {
+        log::info!("This execution is ending because its state was already explored");
+        ExecutionState::with(|s| {
+            let pos = s.next_pos();
+            s.must
+                .borrow_mut()
+                .handle_block(Block::new(pos, BlockType::Assume))
+        });
+        switch();
+    }
+}
+
+/// Runs `f` as an atomic step for the scheduler: the calling task is not preempted inside the
+/// region, unless it blocks, and its receives inside the region read the first available message
+/// only. The interleavings and message orders that the region excludes are not explored.
+///
+/// This trades soundness for speed, to exclude critical sections that are known to be
+/// irrelevant: **bugs that only show up in the excluded schedules are not found**. A warning
+/// is logged the first time a region is entered.
+///
+/// ```no_run
+/// use traceforge::{thread, Config};
+///
+/// traceforge::verify(Config::builder().build(), || {
+///     let me = thread::current().id();
+///     for i in 0..2 {
+///         thread::spawn(move || traceforge::send_msg(me, i));
+///     }
+///     // Only one of the two orders of the receives is explored
+///     let (a, b): (i32, i32) = traceforge::atomic_region(|| {
+///         (traceforge::recv_msg_block(), traceforge::recv_msg_block())
+///     });
+/// });
+/// ```
+pub fn atomic_region<T>(f: impl FnOnce() -> T) -> T {
+    static WARNING: std::sync::Once = std::sync::Once::new();
+    WARNING.call_once(|| {
+        log::warn!(
+            "atomic_region excludes interleavings from the exploration: bugs inside it may not be found"
+        )
+    });
+    unpreempted(f)
+}
+
+/// Runs `f` without scheduling points: the calling task keeps running until `f` returns, unless
+/// it blocks, so the code inside is not interleaved with other tasks. This is meant for setup
+/// code, e.g. building a large input of a model, that only uses TraceForge primitives
+/// incidentally.
+///
+/// It shares its implementation with [`atomic_region`]: receives inside `f` read the first
+/// available message only. Unlike `atomic_region`, it logs no warning, since it is meant for
+/// code whose interleavings are irrelevant by construction. Primitives served by other threads,
+/// such as those of [`sync::atomic`], are still explored from the side of those threads.
+///
+/// ```no_run
+/// use traceforge::{thread, Config};
+///
+/// traceforge::verify(Config::builder().build(), || {
+///     let me = thread::current().id();
+///     for i in 0..3 {
+///         thread::spawn(move || traceforge::send_msg(me, i));
+///     }
+///     // The order in which the inputs arrive is irrelevant, only one is explored
+///     let mut inputs: Vec<i32> = traceforge::without_scheduling(|| {
+///         (0..3).map(|_| traceforge::recv_msg_block()).collect()
+///     });
+///     inputs.sort();
+/// });
+/// ```
+pub fn without_scheduling<T>(f: impl FnOnce() -> T) -> T {
+    unpreempted(f)
+}
+
+// Runs `f` without preempting the calling task, see `atomic_region`
+fn unpreempted<T>(f: impl FnOnce() -> T) -> T {
+    ExecutionState::with(|s| s.current_mut().atomic_depth += 1);
+    let result = f();
+    ExecutionState::with(|s| s.current_mut().atomic_depth -= 1);
+    result
+}
+
 /// TraceForge's wrapper for an assertion. It behaves similarly to the system's `assert!`
 /// but allows the underlying model checker to continue exploration even if an assertion
 /// violation has been found.
@@ -1729,16 +2908,17 @@ pub fn assume_impl(cond: bool, macro_info: Option<(&str, &str, u32)>) {
 /// You can have both the system `assert!` and TraceForge's `assert` in a model. The system `assert!`
 /// panics on failure, but TraceForge's assert can carry on with the search if the `keep_going_after_error`
 /// flag is set in the configuration.
+#[track_caller]
 pub fn assert(cond: bool) {
     if !cond {
+        let location = std::panic::Location::caller().to_string();
         ExecutionState::with(|s| {
             let pos = s.next_pos();
 
             let mut must = s.must.borrow_mut();
-            if must.config().keep_going_after_error {
+            if must.config().keep_going_after_error && !must.config().fast_failure {
                 let name = if let Some(task) = s.try_current() {
-                    task.name()
-                        .unwrap_or_else(|| format!("task-{:?}", task.id().0))
+                    task.display_name()
                 } else {
                     "<unknown>".into()
                 };
@@ -1747,6 +2927,14 @@ pub fn assert(cond: bool) {
                 // the assertion violation is reported only if the execution graph is consistent
                 // needed for semantics like Mailbox which generate executions under causal delivery and which need to be filtered to satisfy the stronger mailbox semantics
                 if must.is_consistent() {
+                    must.record_failure(FailureReport {
+                        category: FailureCategory::Assertion,
+                        message: "assertion failed".to_string(),
+                        task: Some(name.clone()),
+                        pos: Some(pos),
+                        location: Some(location),
+                    });
+                    drop(must);
                     let message = persist_task_failure(name, Some(pos));
                     info!("Persisted failure {message}");
                 }
@@ -1757,18 +2945,258 @@ pub fn assert(cond: bool) {
                 // as above, we report the assertion violation only if the execution graph is consistent
                 if must.is_consistent() {
                     info!("Error Detected!");
+                    must.record_failure(FailureReport {
+                        category: FailureCategory::Assertion,
+                        message: "assertion failed".to_string(),
+                        task: s.try_current().map(|task| task.display_name()),
+                        pos: Some(pos),
+                        location: Some(location),
+                    });
                     println!("{}", must.print_graph(None));
                     // The graph is completely generated, now build the linearization
                     must.store_replay_information(Some(pos));
                     std::io::stderr().flush().unwrap();
                     // Report the failure
-                    assert!(cond);
+                    std::assert!(cond);
                 }
             }
         });
     }
 }
 
+/// Hints the scheduler to prefer the thread of `handle` over runnable threads with a lower
+/// priority. Threads have priority 0 unless set otherwise, and the priority lasts until the end
+/// of the current execution.
+///
+/// Priorities only change the order in which executions are explored (e.g., which one is found
+/// first), not the set of explored executions.
+pub fn set_task_priority<T: 'static>(handle: &JoinHandle<T>, priority: i32) {
+    let tid = handle.thread().id();
+    ExecutionState::with(|s| s.must.borrow_mut().set_task_priority(tid, priority));
+}
+
+/// Checks that the thread of `first` finishes before the thread of `second` in the current
+/// execution: the execution fails as soon as `second` finishes unless `first` finished before
+/// it, like for a violated invariant. Unlike [`happens_before`], which relates two given
+/// events, this constrains the termination of the two threads, whatever they run until then.
+///
+/// TraceForge does not tell apart the executions that only differ in the order of independent
+/// events, so `first` finishes before `second` when the end of `first` happens before the end
+/// of `second`, e.g. because `second` waits for a message sent after joining `first`. Two
+/// threads that do not synchronize like that can finish in either order, which fails the
+/// check.
+///
+/// The check lasts until the end of the current execution. It fails at once if `second` has
+/// already finished, but not before `first`.
+pub fn assert_finishes_before<T: 'static, U: 'static>(
+    first: &JoinHandle<T>,
+    second: &JoinHandle<U>,
+) {
+    let (first, second) = (first.thread().id(), second.thread().id());
+    ExecutionState::with(|s| s.must.borrow_mut().add_finish_order(first, second));
+    // The order is only checked when `second` finishes, which might have happened already
+    let violated = ExecutionState::with(|s| {
+        let must = s.must.borrow();
+        must.has_finished(second) && !must.finished_before(first, second) && must.is_consistent()
+    });
+    if violated {
+        ExecutionState::fail_finish_order(second, first);
+    }
+}
+
+/// Sets the relative speed of the thread of `handle`, to model a slow node among fast ones:
+/// the scheduler runs a thread of speed 0.1 about ten times less often than a thread of the
+/// default speed 1. With [`SchedulePolicy::LTR`], the scheduler picks the runnable thread that
+/// is the most behind in virtual time, where every step of a thread takes `1 / speed`; with
+/// [`SchedulePolicy::Arbitrary`], it picks threads at random, in proportion to their speed.
+/// The speed lasts until the end of the current execution, and priorities (see
+/// [`set_task_priority`]) take precedence over speeds.
+///
+/// Like priorities, speeds only change the order in which executions are explored, e.g. to find
+/// a timing-sensitive bug sooner, not the set of explored executions.
+pub fn set_task_speed<T: 'static>(handle: &JoinHandle<T>, speed: f64) {
+    assert!(
+        speed > 0.0 && speed.is_finite(),
+        "the speed of a task must be positive"
+    );
+    let tid = handle.thread().id();
+    ExecutionState::with(|s| s.must.borrow_mut().set_task_speed(tid, speed));
+}
+
+/// Marks a named program point of the current task, which delimits the windows of
+/// [`ConfigBuilder::with_delay_between`]. The point creates no event, and is not a scheduling
+/// point unless it ends such a window.
+pub fn point(name: &str) {
+    let delay = ExecutionState::with(|s| {
+        let pos = s.curr_pos();
+        let me = s.current().id();
+        let mut must = s.must.borrow_mut();
+        let delay = must.reach_point(pos, name);
+        if delay {
+            must.delay_task(me);
+        }
+        delay
+    });
+    if delay {
+        switch();
+    }
+}
+
+/// Returns the index of the current execution in the exploration, counting from 0. This is
+/// the index passed to the hooks of [`ConfigBuilder::with_before_each`] and
+/// [`ConfigBuilder::with_after_each`].
+pub fn execution_index() -> usize {
+    ExecutionState::with(|s| s.must.borrow().execution_index())
+}
+
+/// Returns whether the caller runs under TraceForge, i.e. inside a model explored by [`verify`]
+/// or one of its variants, so that library code can use the TraceForge primitives under test,
+/// and the standard ones otherwise. The check is cheap, and creates no event.
+///
+/// ```no_run
+/// fn spawn_worker(work: impl FnOnce() + Send + 'static) {
+///     if traceforge::is_under_test() {
+///         traceforge::thread::spawn(work);
+///     } else {
+///         std::thread::spawn(work);
+///     }
+/// }
+/// ```
+pub fn is_under_test() -> bool {
+    ExecutionState::is_set()
+}
+
+/// Returns the number of threads (including the calling one) that could be scheduled next,
+/// i.e., that are neither finished nor blocked.
+pub fn runnable_count() -> usize {
+    ExecutionState::with(|s| s.runnable_count())
+}
+
+/// Returns the tasks of the current execution that are blocked, i.e. that cannot run until
+/// another task acts, with what they wait for. The daemon threads that wait for a message,
+/// such as the ones backing the synchronization primitives, are left out, like in the
+/// deadlock reports of [`ConfigBuilder::with_collect_deadlocks`].
+///
+/// A task only counts as blocked once it has tried to proceed: a task that was just notified,
+/// or that did not reach its blocking operation yet, is runnable. A task can also be blocked
+/// for a moment only, e.g. until the daemon backing a lock grants it. Liveness checks, such as
+/// a watchdog task that reports the stuck tasks, are thus best made once the checking task is
+/// the only one left that can run, see [`runnable_count`].
+///
+/// ```no_run
+/// use traceforge::{thread, WaitReason};
+///
+/// traceforge::verify(traceforge::Config::default(), || {
+///     let worker = thread::spawn(|| {
+///         let _: u32 = traceforge::recv_msg_block();
+///     });
+///     while traceforge::runnable_count() > 1 {
+///         thread::yield_now();
+///     }
+///     let blocked = traceforge::blocked_tasks();
+///     assert_eq!(blocked, vec![(worker.thread().id(), WaitReason::Receive)]);
+///     traceforge::send_msg(worker.thread().id(), 1u32);
+/// });
+/// ```
+pub fn blocked_tasks() -> Vec<(ThreadId, WaitReason)> {
+    ExecutionState::with(|s| s.blocked_tasks())
+}
+
+/// Returns a snapshot of all the tasks of the current execution, finished ones included, in
+/// the order in which they were spawned: their thread, name and whether they can run, with
+/// what they wait for if they are blocked, e.g. for a debugging UI that renders them. Unlike
+/// [`blocked_tasks`], the daemon threads are included. The same caveats apply to when a task
+/// counts as blocked.
+///
+/// ```no_run
+/// use traceforge::{thread, TaskState};
+///
+/// traceforge::verify(traceforge::Config::default(), || {
+///     let tasks = traceforge::task_graph();
+///     assert_eq!(tasks[0].id, thread::current().id());
+///     assert_eq!(tasks[0].state, TaskState::Runnable);
+/// });
+/// ```
+pub fn task_graph() -> Vec<TaskSnapshot> {
+    ExecutionState::with(|s| s.task_graph())
+}
+
+/// Returns the event most recently executed by the calling thread.
+///
+/// Record it right after an action of interest (e.g., a send or a receive) to later refer to
+/// that action in [`happens_before`].
+///
+/// Its [`Event::index`] is the number of events that the thread has executed so far in the
+/// current execution. The count grows by one with every event of the thread, e.g. a send, a
+/// receive, a spawn or a nondeterministic choice, and not in between, so it ties what the thread
+/// does to its logical position, e.g. in custom coverage metrics. Yielding does not create an
+/// event.
+pub fn current_event() -> Event {
+    ExecutionState::with(|s| s.curr_pos())
+}
+
+/// Returns whether event `a` happens before event `b` in the current execution, that is,
+/// whether `a` precedes `b` in program order or is causally before it through messages
+/// and thread spawns/joins.
+///
+/// Returns `false` if `a == b`, or if either event is not part of the current execution.
+///
+/// ```no_run
+/// use traceforge::thread;
+///
+/// traceforge::verify(traceforge::Config::default(), || {
+///     let child = thread::spawn(traceforge::current_event);
+///     let spawned = child.join().unwrap();
+///     assert!(traceforge::happens_before(spawned, traceforge::current_event()));
+/// });
+/// ```
+pub fn happens_before(a: Event, b: Event) -> bool {
+    ExecutionState::with(|s| s.must.borrow().happens_before(a, b))
+}
+
+/// Declares that event `before` happens before event `after` in the current execution, to model
+/// a synchronization that TraceForge cannot see, e.g. a real-world ordering guarantee of the
+/// environment. Both events must already be part of the current execution (see
+/// [`current_event`]).
+///
+/// The edge is added to [`happens_before`], and constrains the exploration: the execution ends
+/// right away, as with `assume!(false)`, if it contradicts its edges, i.e. if `after` already
+/// happens before `before`, or if the messages of a
+/// [`CausalOrder`](loc::CommunicationModel::CausalOrder) channel are received out of the order
+/// of their sends. Later receives that contradict the edges end the execution too.
+///
+/// **An edge that the environment does not actually guarantee over-constrains the
+/// exploration, and may hide bugs.**
+pub fn add_sync_edge(before: Event, after: Event) {
+    let holds = ExecutionState::with(|s| s.must.borrow_mut().add_sync_edge(before, after));
+    if !holds {
+        crate::assume!(false);
+    }
+}
+
+/// Returns the number of messages in flight to thread `receiver`, as observed by the calling
+/// thread: the messages sent to it that happen before the call, and whose receipt does not
+/// happen before the call (see [`happens_before`]).
+///
+/// Counting only causally observed messages makes the result independent of how the threads
+/// happen to be scheduled. The query creates no event, so it does not affect the exploration.
+pub fn inflight_count(receiver: ThreadId) -> usize {
+    let (loc, _) = thread_loc_comm(receiver);
+    ExecutionState::with(|s| {
+        let pos = s.curr_pos();
+        s.must.borrow().inflight_count(pos, &loc)
+    })
+}
+
+/// Returns the vector clock of the calling thread, i.e., the causal view of its latest event.
+///
+/// The clock advances past an event of another thread once the calling thread synchronizes with
+/// it, by receiving a message sent after it, or by being spawned or joining a thread after it.
+/// Clocks start afresh in every execution.
+pub fn current_clock() -> VectorClock {
+    ExecutionState::with(|s| s.update_clock())
+}
+
 /// Spawns a new thread symmetric to `tid`
 pub fn spawn_symmetric<F, T>(f: F, tid: crate::thread::ThreadId) -> crate::thread::JoinHandle<T>
 where
@@ -1781,6 +3209,29 @@ where
     jh
 }
 
+/// Spawns `n` threads running `f`, each symmetric to the previous one.
+///
+/// The threads must be interchangeable: they run the same code, and nothing that happens in the
+/// execution depends on which of them did what. TraceForge then explores the orders in which
+/// they interact with the rest of the program only up to renaming of the threads, which can cut
+/// the number of executions by up to `n!`.
+pub fn spawn_symmetric_group<F, T>(n: usize, f: F) -> Vec<crate::thread::JoinHandle<T>>
+where
+    F: Fn() -> T + Clone,
+    F: Send + 'static,
+    T: Message + 'static,
+{
+    let mut handles: Vec<crate::thread::JoinHandle<T>> = Vec::with_capacity(n);
+    for _ in 0..n {
+        let h = match handles.last() {
+            None => crate::thread::spawn(f.clone()),
+            Some(prev) => spawn_symmetric(f.clone(), prev.thread().id()),
+        };
+        handles.push(h);
+    }
+    handles
+}
+
 // This function is public so that it can be invoked from within the expansion of the
 // Monitor macro; it should not be directly invoked from customer models.
 #[doc(hidden)]
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    event_label::SendMsg, identifier::Identifier, predicate::PredicateType, thread::ThreadId,
+    event_label::SendMsg,
+    identifier::Identifier,
+    predicate::{PredicateType, ValuePredicate},
+    thread::ThreadId,
 };
 
 use std::fmt::{Debug, Display};
@@ -30,6 +33,19 @@ impl Loc {
     pub(crate) fn new<T: crate::identifier::Identifier>(id: T) -> Self {
         Loc(Box::new(id))
     }
+
+    /// The identifier of the location, for reports: the `Debug` of the identifier, without the
+    /// quotes of a string and the wrapping of a named channel
+    pub(crate) fn name(&self) -> String {
+        let mut name = self.to_string();
+        while let Some(inner) = name.strip_prefix("Loc(").and_then(|n| n.strip_suffix(')')) {
+            name = inner.to_string();
+        }
+        match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+            Some(unquoted) => unquoted.to_string(),
+            None => name,
+        }
+    }
 }
 
 impl Display for Loc {
@@ -48,6 +64,9 @@ pub(crate) struct RecvLoc {
     locs: Option<Vec<Loc>>,
     // Option for the fast case where the predicate would be always true
     tag: Option<PredicateType>,
+    // The predicate on the message values, restored like the locations
+    #[serde(skip)]
+    filter: Option<ValuePredicate>,
 }
 
 impl RecvLoc {
@@ -55,9 +74,15 @@ impl RecvLoc {
         RecvLoc {
             locs: Some(locs.into_iter().cloned().collect()),
             tag,
+            filter: None,
         }
     }
 
+    pub(crate) fn with_filter(mut self, filter: Option<ValuePredicate>) -> Self {
+        self.filter = filter;
+        self
+    }
+
     pub(crate) fn locs(&self) -> &Vec<Loc> {
         self.locs.as_ref().unwrap()
     }
@@ -68,9 +93,20 @@ impl RecvLoc {
         self.tag.is_none() || self.tag.as_ref().unwrap().0(send_loc.sender_tid, send_loc.tag.clone())
     }
 
+    /// Returns whether the receive's value predicate holds for the send's message.
+    /// A message that is not known yet (revisit replay) matches: the receive checks
+    /// the predicate again once it gets the message.
+    pub(crate) fn matches_filter(&self, send: &SendMsg) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|f| send.val().is_pending() || f.0(send.val()))
+    }
+
     /// Return whether the receive's tag and any of it's locations matches the send
     pub(crate) fn matches(&self, send: &SendMsg) -> bool {
-        self.matches_tag(send) && self.locs().iter().any(|rc| rc == send.loc())
+        self.matches_tag(send)
+            && self.locs().iter().any(|rc| rc == send.loc())
+            && self.matches_filter(send)
     }
 
     /// Given a matching SendLoc, it returns the index of the unique matching location
@@ -0,0 +1,159 @@
+//! Bounded actor mailboxes with a policy for when they are full.
+//!
+//! [`bounded`] creates a mailbox that holds at most `capacity` messages, together with a cloneable
+//! [`MailboxSender`] to fill it. When a message arrives at a full mailbox, the
+//! [`OverflowPolicy`] decides whether the sender blocks until there is room, or which message is
+//! dropped. TraceForge explores the orders in which the messages of different senders
+//! reach the mailbox, and hence which of them are dropped.
+//!
+//! Messages reach the mailbox in causal order: a message that is sent after (in happens-before
+//! order) another one is never put in the mailbox before it. In particular, the messages of a
+//! single sender are kept in order.
+//!
+//! ```no_run
+//! use traceforge::mailbox::{self, OverflowPolicy};
+//! use traceforge::{thread, Config};
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     let (tx, mailbox) = mailbox::bounded(1, OverflowPolicy::DropOldest);
+//!     let consumer = thread::spawn(move || mailbox.recv());
+//!     tx.send(1);
+//!     tx.send(2);
+//!     let v: i32 = consumer.join().unwrap();
+//!     assert!(v == 1 || v == 2);
+//! });
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use crate::channel::{self, Receiver, Sender};
+use crate::loc::CommunicationModel;
+use crate::msg::Message;
+use crate::thread;
+
+/// What happens to a message sent to a full mailbox
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The sender waits until the mailbox has room for its message
+    Block,
+    /// The incoming message is dropped
+    DropNewest,
+    /// The oldest message in the mailbox is dropped to make room for the incoming one
+    DropOldest,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Request<T> {
+    // A message, with the channel to acknowledge it on under `OverflowPolicy::Block`
+    Put(T, Option<Sender<()>>),
+    // A receive, with the channel to deliver the message on
+    Take(Sender<T>),
+}
+
+/// Creates a mailbox that holds at most `capacity` messages, returning its sending and
+/// receiving ends.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn bounded<T>(capacity: usize, policy: OverflowPolicy) -> (MailboxSender<T>, Mailbox<T>)
+where
+    T: Clone + Debug + PartialEq + Message + 'static,
+{
+    assert!(capacity > 0, "a mailbox needs a capacity of at least 1");
+    let (tx, rx) = channel::Builder::<Request<T>>::new()
+        .with_comm(CommunicationModel::CausalOrder)
+        .internal()
+        .build();
+    thread::Builder::new()
+        .name("traceforge_runtime::mailbox".to_string())
+        .spawn_daemon(move || serve(rx, capacity, policy))
+        .unwrap();
+    (
+        MailboxSender {
+            requests: tx.clone(),
+            policy,
+        },
+        Mailbox { requests: tx },
+    )
+}
+
+fn serve<T>(requests: Receiver<Request<T>>, capacity: usize, policy: OverflowPolicy)
+where
+    T: Clone + Debug + PartialEq + Message + 'static,
+{
+    let mut queue = VecDeque::with_capacity(capacity);
+    // Senders waiting for room, under `OverflowPolicy::Block`
+    let mut blocked: VecDeque<(T, Sender<()>)> = VecDeque::new();
+    // Receivers waiting for a message
+    let mut takers: VecDeque<Sender<T>> = VecDeque::new();
+    loop {
+        match requests.recv_msg_block() {
+            Request::Put(v, ack) => {
+                if queue.len() < capacity {
+                    queue.push_back(v);
+                    if let Some(ack) = ack {
+                        ack.send_msg(());
+                    }
+                } else {
+                    match policy {
+                        OverflowPolicy::Block => blocked.push_back((v, ack.unwrap())),
+                        OverflowPolicy::DropNewest => {}
+                        OverflowPolicy::DropOldest => {
+                            queue.pop_front();
+                            queue.push_back(v);
+                        }
+                    }
+                }
+            }
+            Request::Take(reply) => takers.push_back(reply),
+        }
+        while !takers.is_empty() && !queue.is_empty() {
+            takers
+                .pop_front()
+                .unwrap()
+                .send_msg(queue.pop_front().unwrap());
+            if let Some((v, ack)) = blocked.pop_front() {
+                queue.push_back(v);
+                ack.send_msg(());
+            }
+        }
+    }
+}
+
+/// The sending end of a mailbox created by [`bounded`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MailboxSender<T> {
+    requests: Sender<Request<T>>,
+    policy: OverflowPolicy,
+}
+
+impl<T: Clone + Debug + PartialEq + Message + 'static> MailboxSender<T> {
+    /// Sends `v` to the mailbox. Under [`OverflowPolicy::Block`], waits until the mailbox
+    /// has accepted it.
+    pub fn send(&self, v: T) {
+        if self.policy == OverflowPolicy::Block {
+            let (ack_tx, ack_rx) = channel::Builder::<()>::new().internal().build();
+            self.requests.send_msg(Request::Put(v, Some(ack_tx)));
+            ack_rx.recv_msg_block();
+        } else {
+            self.requests.send_msg(Request::Put(v, None));
+        }
+    }
+}
+
+/// The receiving end of a mailbox created by [`bounded`]
+#[derive(Debug)]
+pub struct Mailbox<T> {
+    requests: Sender<Request<T>>,
+}
+
+impl<T: Clone + Debug + PartialEq + Message + 'static> Mailbox<T> {
+    /// Takes the oldest message out of the mailbox, waiting for one if it is empty
+    pub fn recv(&self) -> T {
+        let (tx, rx) = channel::Builder::<T>::new().internal().build();
+        self.requests.send_msg(Request::Take(tx));
+        rx.recv_msg_block()
+    }
+}
@@ -0,0 +1,103 @@
+//! Minimization of the messages that a failure depends on.
+//!
+//! A failing execution of a message-passing model usually receives many messages that have
+//! nothing to do with the failure. [`minimize_messages`] shrinks them, delta-debugging style:
+//! it explores the model again with subsets of the messages of the failing execution, the
+//! other messages never being delivered, until no message can be removed without the failure
+//! disappearing.
+
+use std::cell::RefCell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::msg::MsgId;
+use crate::must::Must;
+use crate::runtime::failure::FailureReport;
+use crate::Config;
+
+/// Looks for a failing execution of `f`, and returns a minimal list of the messages it needs to
+/// fail, in the order in which they were received. Returns `None` if no execution fails.
+///
+/// Each candidate list is checked by an exploration of `f` in which these messages are
+/// delivered in this order (see [`ConfigBuilder::with_delivery_order`]), and the other messages
+/// of the failing execution are never delivered. The list is minimal in the sense of delta
+/// debugging: removing any one of its messages makes the failure disappear. Failures are told
+/// apart by their category and source location.
+///
+/// This runs one exploration per candidate, so it is meant for small models, or for models
+/// narrowed down to a failure, e.g. with [`ConfigBuilder::with_delivery_order`].
+///
+/// [`ConfigBuilder::with_delivery_order`]: crate::ConfigBuilder::with_delivery_order
+pub fn minimize_messages<F>(mut conf: Config, f: F) -> Option<Vec<MsgId>>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    conf.keep_going_after_error = false;
+    conf.parallel = false;
+    conf.partitioned_parallelization = false;
+    let f = Arc::new(f);
+    let (failure, delivered) = find_failure(&conf, &f)?;
+    let fails = |kept: &[MsgId]| {
+        let mut conf = conf.clone();
+        conf.delivery_order = kept.to_vec();
+        conf.excluded_messages = delivered
+            .iter()
+            .filter(|m| !kept.contains(m))
+            .cloned()
+            .collect();
+        find_failure(&conf, &f).is_some_and(|(other, _)| {
+            other.category == failure.category && other.location == failure.location
+        })
+    };
+    Some(ddmin(delivered.clone(), fails))
+}
+
+// Explores `f` until an execution fails, and returns the failure with the messages received by
+// the failing execution
+fn find_failure<F>(conf: &Config, f: &Arc<F>) -> Option<(FailureReport, Vec<MsgId>)>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let must = Rc::new(RefCell::new(Must::new(conf.clone(), false)));
+    let explored = catch_unwind(AssertUnwindSafe(|| crate::explore(&must, f)));
+    if explored.is_ok() {
+        return None;
+    }
+    // The graph of the failing execution is still the current one
+    let must = must.borrow();
+    let failure = must.failure()?.clone();
+    Some((failure, must.delivered_messages()))
+}
+
+// Shrinks `items` to a subset for which `fails` holds, and from which no item can be removed,
+// see Zeller and Hildebrandt, "Simplifying and Isolating Failure-Inducing Input"
+fn ddmin<T: Clone>(mut items: Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    if fails(&[]) {
+        return Vec::new();
+    }
+    let mut n = 2;
+    while items.len() >= 2 {
+        let size = items.len().div_ceil(n);
+        let chunks: Vec<Vec<T>> = items.chunks(size).map(|c| c.to_vec()).collect();
+        if let Some(chunk) = chunks.iter().find(|c| fails(c)) {
+            items = chunk.clone();
+            n = 2;
+            continue;
+        }
+        let complement = |i: usize| -> Vec<T> {
+            let other = chunks.iter().enumerate().filter(|(j, _)| *j != i);
+            other.flat_map(|(_, c)| c.iter().cloned()).collect()
+        };
+        if let Some(rest) = (0..chunks.len()).map(complement).find(|c| fails(c)) {
+            items = rest;
+            n = (n - 1).max(2);
+            continue;
+        }
+        if n >= items.len() {
+            break;
+        }
+        n = (2 * n).min(items.len());
+    }
+    items
+}
@@ -1,6 +1,7 @@
 //! Must's requirements for types passed as messages
 
 use dyn_clone::DynClone;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 
 /// This type is used to signify the type of messages
@@ -19,6 +20,32 @@ pub struct Val {
     pub type_name: String,
 }
 
+/// Identifies a message by its sender and the position of the send among the sends of the
+/// sender, counting from 0. The sender is the name of the thread, or its id as printed in the
+/// execution graphs (e.g., `t2`) if it has no name.
+///
+/// See [`ConfigBuilder::with_delivery_order`](crate::ConfigBuilder::with_delivery_order).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MsgId {
+    pub(crate) sender: String,
+    pub(crate) index: usize,
+}
+
+impl MsgId {
+    pub fn new(sender: impl Into<String>, index: usize) -> Self {
+        Self {
+            sender: sender.into(),
+            index,
+        }
+    }
+}
+
+impl std::fmt::Display for MsgId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.sender, self.index)
+    }
+}
+
 impl PartialEq for Val {
     fn eq(&self, other: &Self) -> bool {
         self.val.msg_equals(other)
@@ -73,6 +100,17 @@ impl std::fmt::Display for Unit {
     }
 }
 
+/// Several messages sent as one contiguous block by `send_all`.
+/// The receiver unpacks it and hands out one message per receive.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Batch(pub(crate) Vec<Val>);
+
+impl std::fmt::Display for Batch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "batch of {}", self.0.len())
+    }
+}
+
 macro_rules! sign_msg_core {
     () => {
         // Prefix these types with 'msg' so that their use must be
@@ -1,17 +1,30 @@
 use crate::cons::Consistency;
 use crate::event::Event;
+use crate::deadlock::{Deadlock, WaitReason};
+use crate::failures::{self, CollectedFailure};
+use crate::final_state::FinalState;
+use crate::float::FloatRange;
+use crate::profile::Profile;
+use crate::script::Action;
+use crate::step::{Step, StepHook};
 use crate::exec_graph::{ExecutionGraph, RecvLike};
 use crate::exec_pool::ExecutionPool;
 use crate::revisit::{Revisit, RevisitEnum, RevisitPlacement};
+use crate::rng::DeterministicRng;
 use crate::future::PollerMsg;
-use crate::loc::{Loc, WakeMsg};
-use crate::runtime::failure::init_panic_hook;
-use crate::runtime::task::TaskId;
+use crate::loc::{CommunicationModel, Loc, WakeMsg};
+use crate::runtime::failure::{
+    init_panic_hook, persist_task_failure, FailureCategory, FailureReport,
+};
+use crate::runtime::task::{TaskId, DEFAULT_INLINE_TASKS};
 use crate::telemetry::{Recorder, Telemetry};
+use crate::channel::{MessageCounts, Sender};
+use crate::in_flight;
+use crate::time::Fire;
 use crate::vector_clock::VectorClock;
 use crate::{event_label::*, ExecutionState, MonitorAcceptorFn, MonitorCreateFn};
 use crate::{replay as REPLAY, Val};
-use crate::{Config, ExplorationMode, SchedulePolicy, Stats};
+use crate::{Config, ExplorationMode, SchedulePolicy, Stats, TieBreak};
 use log::{debug, info, trace, warn};
 use rand::distr::Distribution;
 use rand::seq::IndexedRandom;
@@ -24,9 +37,9 @@ use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::rc::Rc;
 use std::sync::{Arc, Condvar, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use crate::msg::Message;
+use crate::msg::{Message, MsgId};
 use crate::thread::{main_thread_id, ThreadId};
 
 #[cfg(feature = "symbolic")]
@@ -34,7 +47,9 @@ use crate::symbolic::SymbolicSolver;
 
 use crate::monitor_types::{EndCondition, ExecutionEnd, Monitor, MonitorResult};
 use std::any::TypeId;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::fs::File;
 use std::io::Write;
 
@@ -60,6 +75,9 @@ type StateStack = Vec<MustState>;
 pub struct MustState {
     graph: ExecutionGraph,
     rqueue: RQueue,
+    // how close the execution of `graph` got to the target of a guided search
+    #[serde(default)]
+    score: i64,
 }
 
 impl MustState {
@@ -67,6 +85,7 @@ impl MustState {
         Self {
             graph: ExecutionGraph::new(),
             rqueue: RQueue::new(),
+            score: 0,
         }
     }
 }
@@ -110,6 +129,13 @@ pub(crate) struct MonitorInfo {
     pub monitor_struct: Arc<Mutex<dyn Monitor>>,
 }
 
+// An async mutex with priority inheritance, see `Mutex::with_priority_inheritance`
+#[derive(Default)]
+struct InheritingLock {
+    holder: Option<ThreadId>,
+    waiters: Vec<ThreadId>,
+}
+
 type ExecutionGraphEnqueuePair = (Arc<Mutex<VecDeque<Option<ExecutionGraph>>>>, Arc<Condvar>);
 
 // No getters so that the borrow checker does not get confused
@@ -148,6 +174,117 @@ pub(crate) struct Must {
     pub(crate) global_named_choices: HashMap<String, bool>,
     // Maximum number of events across all complete (non-blocked) execution graphs
     max_graph_events: usize,
+    // Number of scheduling decisions taken in the current execution
+    schedule_depth: usize,
+    // Maximum and sum of the schedule depths across all explored executions
+    max_schedule_depth: usize,
+    total_schedule_depth: usize,
+    // Why the current execution failed, if it did
+    failure: Option<FailureReport>,
+    // Number of deterministic RNGs created by each thread in the current execution
+    rng_streams: HashMap<ThreadId, u32>,
+    // Number of `rpc::call`s made by each thread in the current execution
+    call_ids: HashMap<ThreadId, u32>,
+    // Number of ids generated by each thread in the current execution, see `id::next_id`
+    generated_ids: HashMap<ThreadId, u32>,
+    // The task that yielded at the last scheduling point, which only runs next if no other task
+    // can
+    yielded: Option<TaskId>,
+    // The windows of `Config::with_delay_between` that a thread of the current execution is
+    // in, with the event at which it entered them
+    open_delays: HashMap<(ThreadId, usize), Event>,
+    // The tasks delayed there, which only run next if no other task can
+    delayed: HashSet<TaskId>,
+    // The task picked at the last scheduling point, see `Config::with_tie_break`
+    last_run: Option<TaskId>,
+    // Called at every scheduling decision of a replay, see `replay_steps`
+    step_hook: Option<StepHook>,
+    // The scheduling decisions left in the script of `run_script`
+    script: Option<VecDeque<Action>>,
+    // Whether the actions of the script that pick a task which cannot run are skipped rather
+    // than failing the execution, see `differential`
+    skip_infeasible: bool,
+    // The scheduling decisions of the current execution, if they are recorded for `differential`
+    schedule: Option<Vec<Action>>,
+    // Scheduling priorities set by the current execution; threads without one have priority 0
+    task_priorities: HashMap<ThreadId, i32>,
+    // The mutexes with priority inheritance of the current execution, by the thread id of
+    // their synchronizer
+    inheriting_locks: HashMap<ThreadId, InheritingLock>,
+    // Pairs of threads of the current execution where the first has to finish before the
+    // second, see `assert_finishes_before`
+    finish_orders: Vec<(ThreadId, ThreadId)>,
+    // Relative speeds set by the current execution, and the virtual time of the tasks that ran
+    // at these speeds; threads without a speed have speed 1
+    task_speeds: HashMap<ThreadId, f64>,
+    task_progress: HashMap<ThreadId, f64>,
+    // What the tasks of the current execution waited for when it stopped, as recorded by the
+    // primitives they blocked in, see `Deadlock::of`
+    wait_reasons: HashMap<ThreadId, WaitReason>,
+    // Distinct deadlocks found so far, see `Config::with_collect_deadlocks`
+    deadlocks: BTreeSet<Deadlock>,
+    // Distinct failures found so far, see `Config::with_collect_failures`
+    collected_failures: Vec<CollectedFailure>,
+    // Executions that spawned more tasks than `Config::with_task_capacity`
+    task_spills: usize,
+    // Steps in which a task executed too many instructions without yielding, see
+    // `Config::with_long_step_warning`
+    long_steps: usize,
+    // Draws the branches to skip, and the number skipped, see `Config::with_sampling`
+    sampling_rng: Option<Pcg64Mcg>,
+    sampled_out: usize,
+    // Bytes allocated when the exploration started, and whether it then allocated more than
+    // `Config::with_memory_budget`
+    allocated_at_start: usize,
+    out_of_memory: bool,
+    // Whether the exploration stopped at `Config::with_max_iterations`
+    max_iterations_reached: bool,
+    // Number of executions that recorded a failure, under `Config::with_keep_going_after_error`
+    failures: usize,
+    // Data races declared benign by `Config::with_benign_race`
+    pub(crate) suppressed_races: usize,
+    // Executions that did not reach the goal of `Config::with_coverage_target`
+    off_target: usize,
+    // The work of the tasks so far, see `Config::with_profile`, and the tasks of the current
+    // execution that it covers, with their names and their clocks at their first steps
+    profile: Profile,
+    profiled: HashMap<ThreadId, (String, Duration)>,
+    // The code paths declared with `incremental::depends_on` so far
+    pub(crate) code_paths: BTreeSet<String>,
+    // Fingerprints of the final states reached so far, see `Config::with_fingerprint`
+    final_states: BTreeSet<u64>,
+    // Final states reached so far, see `Config::with_reachable_states`
+    reachable_states: Vec<FinalState>,
+    // The values recorded by `float::observe` so far
+    float_ranges: BTreeMap<String, FloatRange>,
+    // The messages of each channel in the complete executions so far, see
+    // `Config::with_message_counts`
+    message_counts: BTreeMap<String, MessageCounts>,
+    // States reached at a `checkpoint` so far, and those reached in the current execution,
+    // see `Config::with_state_dedup`
+    visited_states: HashSet<u64>,
+    current_states: HashSet<u64>,
+    // Logical clocks of the tasks that have one, see `time::now`, and the clocks of the senders
+    // at their sends, which the receivers catch up with
+    task_clocks: HashMap<ThreadId, Duration>,
+    send_clocks: HashMap<Event, Duration>,
+    // Where timers are sent to fire, and the task that fires them, if the clock drift is bounded
+    pub(crate) timer_service: Option<(Sender<Fire>, ThreadId)>,
+    // Where credits are requested and returned, if the messages in flight are bounded, and the
+    // counted sends of the current execution that were not received yet
+    pub(crate) credit_service: Option<Sender<in_flight::Request>>,
+    pub(crate) in_flight_sends: HashSet<Event>,
+    // Index of the current execution of the exploration, counting from 0
+    execution_index: usize,
+    // Eventually properties that held at some point of the current execution,
+    // see `Config::with_eventually`
+    eventually_held: HashSet<usize>,
+    // Whether an execution delivered the messages pinned with `Config::with_delivery_order`
+    delivery_order_seen: bool,
+    // Receives of the current execution run inside an `atomic_region`, which are not revisited
+    atomic_recvs: HashSet<Event>,
+    // Happens-before edges added by the current execution, see `add_sync_edge`
+    sync_edges: Vec<(Event, Event)>,
 }
 
 impl Must {
@@ -158,6 +295,10 @@ impl Must {
         {
             info!("Random schedule seed: {:?}", seed);
         }
+        if let Some((rate, seed)) = conf.sampling {
+            info!("Sampling {} of the branches with seed {}", rate, seed);
+        }
+        let sampling_rng = conf.sampling.map(|(_, seed)| Pcg64Mcg::seed_from_u64(seed));
         let telemetry = Telemetry::new(conf.keep_per_execution_coverage);
         let _ = telemetry.register_counter(&EXECS.to_owned());
         let _ = telemetry.register_counter(&BLOCKED.to_owned());
@@ -185,6 +326,58 @@ impl Must {
             symbolic_solver: SymbolicSolver::new(),
             global_named_choices: HashMap::new(),
             max_graph_events: 0,
+            schedule_depth: 0,
+            max_schedule_depth: 0,
+            total_schedule_depth: 0,
+            failure: None,
+            rng_streams: HashMap::new(),
+            call_ids: HashMap::new(),
+            generated_ids: HashMap::new(),
+            yielded: None,
+            open_delays: HashMap::new(),
+            delayed: HashSet::new(),
+            last_run: None,
+            step_hook: None,
+            script: None,
+            skip_infeasible: false,
+            schedule: None,
+            task_priorities: HashMap::new(),
+            inheriting_locks: HashMap::new(),
+            finish_orders: Vec::new(),
+            task_speeds: HashMap::new(),
+            task_progress: HashMap::new(),
+            wait_reasons: HashMap::new(),
+            deadlocks: BTreeSet::new(),
+            collected_failures: Vec::new(),
+            task_spills: 0,
+            long_steps: 0,
+            sampling_rng,
+            sampled_out: 0,
+            allocated_at_start: crate::alloc::allocated().unwrap_or(0),
+            out_of_memory: false,
+            max_iterations_reached: false,
+            failures: 0,
+            suppressed_races: 0,
+            off_target: 0,
+            profile: Profile::default(),
+            profiled: HashMap::new(),
+            code_paths: BTreeSet::new(),
+            final_states: BTreeSet::new(),
+            reachable_states: Vec::new(),
+            float_ranges: BTreeMap::new(),
+            message_counts: BTreeMap::new(),
+            visited_states: HashSet::new(),
+            current_states: HashSet::new(),
+            task_clocks: HashMap::new(),
+            send_clocks: HashMap::new(),
+            timer_service: None,
+            credit_service: None,
+            in_flight_sends: HashSet::new(),
+            execution_index: 0,
+            eventually_held: HashSet::new(),
+            delivery_order_seen: false,
+            atomic_recvs: HashSet::new(),
+            sync_edges: Vec::new(),
         }
     }
 
@@ -212,6 +405,53 @@ impl Must {
         #[cfg(feature = "symbolic")]
         self.symbolic_solver.reset();
         self.global_named_choices.clear();
+        self.rng_streams.clear();
+        self.call_ids.clear();
+        self.generated_ids.clear();
+        self.yielded = None;
+        self.open_delays.clear();
+        self.delayed.clear();
+        self.last_run = None;
+        self.task_priorities.clear();
+        self.inheriting_locks.clear();
+        self.finish_orders.clear();
+        self.task_speeds.clear();
+        self.task_progress.clear();
+        self.wait_reasons.clear();
+        self.deadlocks.clear();
+        self.collected_failures.clear();
+        self.task_spills = 0;
+        self.long_steps = 0;
+        self.sampling_rng = self
+            .config
+            .sampling
+            .map(|(_, seed)| Pcg64Mcg::seed_from_u64(seed));
+        self.sampled_out = 0;
+        self.allocated_at_start = crate::alloc::allocated().unwrap_or(0);
+        self.out_of_memory = false;
+        self.max_iterations_reached = false;
+        self.failures = 0;
+        self.suppressed_races = 0;
+        self.off_target = 0;
+        self.profile = Profile::default();
+        self.profiled.clear();
+        self.code_paths.clear();
+        self.final_states.clear();
+        self.reachable_states.clear();
+        self.float_ranges.clear();
+        self.message_counts.clear();
+        self.visited_states.clear();
+        self.current_states.clear();
+        self.task_clocks.clear();
+        self.send_clocks.clear();
+        self.timer_service = None;
+        self.credit_service = None;
+        self.in_flight_sends.clear();
+        self.execution_index = 0;
+        self.eventually_held.clear();
+        self.delivery_order_seen = false;
+        self.atomic_recvs.clear();
+        self.sync_edges.clear();
     }
 
     pub(crate) fn gen_bool(&mut self) -> bool {
@@ -259,6 +499,33 @@ impl Must {
 
         must.choice_occurrence_counters.clear();
         must.global_named_choices.clear();
+        must.failure = None;
+        must.current_states.clear();
+        must.schedule_depth = 0;
+        if let Some(schedule) = must.schedule.as_mut() {
+            schedule.clear();
+        }
+        must.rng_streams.clear();
+        must.call_ids.clear();
+        must.generated_ids.clear();
+        must.yielded = None;
+        must.open_delays.clear();
+        must.delayed.clear();
+        must.last_run = None;
+        must.task_priorities.clear();
+        must.inheriting_locks.clear();
+        must.finish_orders.clear();
+        must.task_speeds.clear();
+        must.task_progress.clear();
+        must.wait_reasons.clear();
+        must.task_clocks.clear();
+        must.send_clocks.clear();
+        must.timer_service = None;
+        must.credit_service = None;
+        must.in_flight_sends.clear();
+        must.eventually_held.clear();
+        must.atomic_recvs.clear();
+        must.sync_edges.clear();
 
         // TODO: when must is borrowed, the panic handler cannot capture
         // a counterexample. run_metrics_before() invokes must model code
@@ -267,6 +534,159 @@ impl Must {
         must.run_metrics_before();
     }
 
+    /// Create the next deterministic RNG of thread `tid`
+    pub(crate) fn new_rng(&mut self, tid: ThreadId) -> DeterministicRng {
+        let stream = self.rng_streams.entry(tid).or_insert(0);
+        let rng = DeterministicRng::new(self.config.seed, tid.to_number(), *stream);
+        *stream += 1;
+        rng
+    }
+
+    /// Allocate the correlation id of the next call made by thread `tid`
+    pub(crate) fn next_call_id(&mut self, tid: ThreadId) -> u32 {
+        let id = self.call_ids.entry(tid).or_insert(0);
+        *id += 1;
+        *id
+    }
+
+    /// The number of ids thread `tid` generated in the current execution, including a new one
+    pub(crate) fn next_generated_id(&mut self, tid: ThreadId) -> u32 {
+        let id = self.generated_ids.entry(tid).or_insert(0);
+        *id += 1;
+        *id
+    }
+
+    /// Lets the other runnable tasks run before task `t`, at the next scheduling point
+    pub(crate) fn yield_task(&mut self, t: TaskId) {
+        self.yielded = Some(t);
+    }
+
+    /// Records that the thread at `pos` reached the program point `name`, and returns whether it
+    /// has to be delayed there, see `Config::with_delay_between`
+    pub(crate) fn reach_point(&mut self, pos: Event, name: &str) -> bool {
+        let mut delay = false;
+        for (i, (first, second)) in self.config.delays.iter().enumerate() {
+            if second == name {
+                if let Some(entered) = self.open_delays.remove(&(pos.thread, i)) {
+                    debug!(
+                        "Delaying {} between {} ({}) and {}",
+                        pos.thread, first, entered, name
+                    );
+                    delay = true;
+                }
+            }
+            if first == name {
+                self.open_delays.insert((pos.thread, i), pos);
+            }
+        }
+        delay
+    }
+
+    /// Lets the other runnable tasks run before task `t`, until none of them can
+    pub(crate) fn delay_task(&mut self, t: TaskId) {
+        self.delayed.insert(t);
+    }
+
+    /// Records what the tasks waited for when the current execution stopped
+    pub(crate) fn set_wait_reasons(&mut self, wait_reasons: HashMap<ThreadId, WaitReason>) {
+        self.wait_reasons = wait_reasons;
+    }
+
+    /// Whether thread `t` is a daemon, which the execution can complete without
+    pub(crate) fn is_thread_daemon(&self, t: ThreadId) -> bool {
+        self.current.graph.is_thread_daemon(t)
+    }
+
+    /// What thread `t` waits for, if the graph shows that it is blocked
+    pub(crate) fn block_reason(&self, t: ThreadId) -> Option<WaitReason> {
+        WaitReason::of_block(&self.current.graph, t)
+    }
+
+    /// Records a value of the float `name`, see `float::observe`
+    pub(crate) fn observe_float(&mut self, name: &str, value: f64) {
+        match self.float_ranges.get_mut(name) {
+            Some(range) => range.add(value),
+            None => {
+                self.float_ranges
+                    .insert(name.to_owned(), FloatRange::new(value));
+            }
+        }
+    }
+
+    /// Why the current execution failed, if it did
+    pub(crate) fn failure(&self) -> Option<&FailureReport> {
+        self.failure.as_ref()
+    }
+
+    /// The messages received in the current execution, in the order of their receives
+    pub(crate) fn delivered_messages(&self) -> Vec<MsgId> {
+        let g = &self.current.graph;
+        let mut received: Vec<(usize, Event)> = g
+            .all_store_iter()
+            .filter_map(|s| s.reader().map(|r| (g.label(r).stamp(), s.pos())))
+            .collect();
+        received.sort();
+        received.into_iter().map(|(_, s)| g.msg_id(s)).collect()
+    }
+
+    /// Remember why the current execution failed; only the first failure is kept
+    pub(crate) fn record_failure(&mut self, failure: FailureReport) {
+        if self.failure.is_none() {
+            self.failures += 1;
+            self.failure = Some(failure);
+        }
+    }
+
+    /// Whether failing executions are collected rather than stopping the exploration, see
+    /// `Config::with_collect_failures`
+    pub(crate) fn collects_failures(&self) -> bool {
+        self.config.collect_failures && !self.config.fast_failure
+    }
+
+    /// Adds the failure of the current execution, which ended, to the collected failures
+    pub(crate) fn collect_failure(&mut self) {
+        if !self.collects_failures() {
+            return;
+        }
+        let Some(failure) = &self.failure else {
+            return;
+        };
+        let schedule = self
+            .current
+            .graph
+            .top_sort(None)
+            .labels()
+            .map(|label| label.to_string())
+            .collect();
+        let mut collected = CollectedFailure::new(failure, self.execution_number(), schedule);
+        // Each distinct failure gets a counterexample of its own
+        let trace = self
+            .config
+            .error_trace_file
+            .as_ref()
+            .map(|base| failures::trace_file(base, self.collected_failures.len() + 1));
+        if self
+            .collected_failures
+            .iter()
+            .all(|f| !f.same_as(&collected))
+        {
+            if let Some(trace) = &trace {
+                // The replay stops at the failure instead of collecting it
+                let mut config = self.config.clone();
+                config.collect_failures = false;
+                let replay_info = REPLAY::ReplayInformation::create(
+                    self.current.graph.top_sort(failure.pos),
+                    self.current.clone(),
+                    config,
+                    Some(failure.clone()),
+                );
+                write_trace(trace, &replay_info);
+            }
+            collected.trace = trace;
+        }
+        failures::merge(&mut self.collected_failures, collected);
+    }
+
     pub(crate) fn publish<T: Message + 'static>(&mut self, thread_id: ThreadId, val: T) {
         self.published_values
             .insert((thread_id, TypeId::of::<T>()), Val::new(val));
@@ -297,6 +717,18 @@ impl Must {
     }
 
     pub(crate) fn run_metrics_at_end(&mut self) {
+        if self.config.collect_deadlocks {
+            println!("Distinct deadlocks found: {}", self.deadlocks.len());
+            for d in &self.deadlocks {
+                println!("  {}", d);
+            }
+        }
+        if self.collects_failures() {
+            println!("Distinct failures found: {}", self.collected_failures.len());
+            for f in &self.collected_failures {
+                println!("  {}", f);
+            }
+        }
         for cb in &mut self
             .config
             .callbacks
@@ -312,6 +744,10 @@ impl Must {
         self.current.graph.to_thread_id(task_id)
     }
 
+    pub(crate) fn try_to_thread_id(&self, task_id: TaskId) -> Option<ThreadId> {
+        self.current.graph.try_to_thread_id(task_id)
+    }
+
     pub(crate) fn to_task_id(&self, tid: ThreadId) -> Option<TaskId> {
         self.current.graph.to_task_id(tid)
     }
@@ -341,6 +777,8 @@ impl Must {
         self.choice_occurrence_counters.clear();
         self.global_named_choices.clear();
         self.max_graph_events = 0;
+        self.max_schedule_depth = 0;
+        self.total_schedule_depth = 0;
         // Reset telemetry so stats() starts from zero for this task.
         self.telemetry = Telemetry::new(self.config.keep_per_execution_coverage);
         let _ = self.telemetry.register_counter(&EXECS.to_owned());
@@ -381,7 +819,11 @@ impl Must {
 
         // Remaining entries become saved states (moved, not cloned)
         for (graph, rqueue) in stack {
-            self.states.push(MustState { graph, rqueue });
+            self.states.push(MustState {
+                graph,
+                rqueue,
+                score: 0,
+            });
         }
     }
 
@@ -392,40 +834,255 @@ impl Must {
         self.config = self.replay_info.config();
     }
 
+    pub(crate) fn set_step_hook(&mut self, hook: StepHook) {
+        self.step_hook = Some(hook);
+    }
+
+    /// Runs a single execution, whose schedule starts with `script`, see `run_script`
+    pub(crate) fn set_script(&mut self, script: &[Action]) {
+        self.script = Some(script.iter().copied().collect());
+    }
+
+    /// Like `set_script`, but skips the actions that pick a task which cannot run
+    pub(crate) fn follow_script(&mut self, script: &[Action]) {
+        self.set_script(script);
+        self.skip_infeasible = true;
+    }
+
+    /// Records the scheduling decisions of each execution, see `schedule`
+    pub(crate) fn record_schedule(&mut self) {
+        self.schedule = Some(Vec::new());
+    }
+
+    /// The scheduling decisions of the current execution so far, as a script
+    pub(crate) fn schedule(&self) -> Vec<Action> {
+        self.schedule
+            .clone()
+            .expect("the schedules are not recorded")
+    }
+
+    // Schedules the task of the next action of the script, which has to be able to run unless
+    // the infeasible actions are skipped
+    fn next_scripted(&mut self, runnable: &[(TaskId, usize)]) -> Option<TaskId> {
+        let (tid, t, i) = loop {
+            let action = self.script.as_mut()?.pop_front()?;
+            let tid = action.thread_id();
+            let next = runnable
+                .iter()
+                .find(|(t, i)| self.to_thread_id(*t) == tid && self.is_task_enabled(*t, *i));
+            match next {
+                Some(&(t, i)) => break (tid, t, i),
+                // The implementation under test of `differential` may not be able to follow
+                // every decision of the reference
+                None if self.skip_infeasible => {}
+                None => {
+                    let enabled = runnable
+                        .iter()
+                        .filter(|(t, i)| self.is_task_enabled(*t, *i))
+                        .map(|(t, _)| self.to_thread_id(*t).to_string())
+                        .collect::<Vec<_>>();
+                    panic!(
+                        "Infeasible script: {} at step {} cannot run, the tasks that can are [{}]",
+                        action,
+                        self.schedule_depth,
+                        enabled.join(", ")
+                    );
+                }
+            }
+        };
+        // A blocked task that can go on resumes, as in `unblock_ready`
+        if !self.is_thread_runnable(&t, &i) {
+            self.current.graph.remove_last(tid);
+        }
+        self.yielded = None;
+        self.schedule_depth += 1;
+        self.last_run = Some(t);
+        Some(t)
+    }
+
+    // Reports the scheduling decision of a replay that picked `next` to the step hook
+    fn report_step(&mut self, runnable: &[(TaskId, usize)], current: Option<TaskId>, next: TaskId) {
+        let Some(mut hook) = self.step_hook.take() else {
+            return;
+        };
+        let label = self
+            .replay_info
+            .current_event()
+            .expect("the replayed event of the scheduled task");
+        let step = Step {
+            index: self.schedule_depth - 1,
+            current: current.map(|t| self.to_thread_id(t)),
+            runnable: runnable
+                .iter()
+                .filter(|(t, i)| self.is_thread_runnable(t, i))
+                .map(|(t, _)| self.to_thread_id(*t))
+                .collect(),
+            next: self.to_thread_id(next),
+            event: label.pos(),
+            label: label.to_string(),
+        };
+        hook(&step);
+        self.step_hook = Some(hook);
+    }
+
     /// Extract the replay information from a failing execution
     pub(crate) fn store_replay_information(&mut self, pos: Option<Event>) {
         println!("Random schedule seed: {:?}.", self.config().seed);
 
-        if !self.replay_info.error_found() {
+        // The collected failures are stored when their execution ends, see `collect_failure`
+        if !self.replay_info.error_found() && !self.collects_failures() {
             let sorted_error_graph = self.current.graph.top_sort(pos);
 
+            let failure = self.failure.clone();
+            if let Some(failure) = &failure {
+                println!("{}", failure);
+            }
             let replay_info = REPLAY::ReplayInformation::create(
                 sorted_error_graph,
                 self.current.clone(),
                 self.config.clone(),
+                failure,
             );
 
-            let error_trace_file = self.config.error_trace_file.as_ref();
-            match error_trace_file {
-                None => {
-                    warn!("No counterexample trace will because Must is not configured with a filename. Use `Config::with_error_trace()`");
-                }
-                Some(f) => {
-                    let mut file = File::create(f).unwrap();
-                    match serde_json::to_string_pretty(&replay_info) {
-                        Ok(replay_str) => {
-                            writeln!(&mut file, "{}", replay_str).unwrap();
-                        }
-                        Err(err) => {
-                            println!("Can't serialize graph to json: {}", err);
-                        }
-                    };
-                    self.replay_info = replay_info;
-                }
+            if self.config.error_trace_file.is_none() {
+                warn!("No counterexample trace will because Must is not configured with a filename. Use `Config::with_error_trace()`");
+            } else {
+                self.replay_info = replay_info;
+                self.write_error_trace();
             }
         }
     }
 
+    /// The report of a failure in fast mode: the number of the failing execution and a hash of
+    /// its events, which tells failing schedules apart without storing them
+    pub(crate) fn fast_failure_message(&self) -> String {
+        let g = &self.current.graph;
+        let mut hasher = DefaultHasher::new();
+        for tid in g.thread_ids() {
+            for i in 0..g.thread_size(tid) {
+                format!("{}", g.label(Event::new(tid, i as u32))).hash(&mut hasher);
+            }
+        }
+        format!(
+            "TraceForge found a failure in execution {} (schedule {:016x})",
+            self.execution_number(),
+            hasher.finish()
+        )
+    }
+
+    // The number of the current execution, counting from 1
+    fn execution_number(&self) -> usize {
+        let execs = self.telemetry.read_counter(EXECS.to_owned()).unwrap_or(0)
+            + self.telemetry.read_counter(BLOCKED.to_owned()).unwrap_or(0)
+            + 1;
+        execs as usize + self.off_target
+    }
+
+    /// Under a random schedule, how to rerun the exploration that produced the current
+    /// execution. Every random choice is drawn from `rng`, so the schedule only depends on the
+    /// seed.
+    pub(crate) fn reproduction_hint(&self) -> Option<String> {
+        if self.config.schedule_policy == SchedulePolicy::Arbitrary
+            || self.config.mode == ExplorationMode::Estimation
+        {
+            Some(format!(
+                "To reproduce, rerun with seed {} (`Config::builder().with_seed({})`)",
+                self.config.seed, self.config.seed
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Records a step of `task`, ending at the event `pos`, in which it executed `instructions`
+    /// instructions without yielding
+    #[cfg(debug_assertions)]
+    pub(crate) fn record_long_step(&mut self, task: &str, pos: Event, instructions: usize) {
+        self.long_steps += 1;
+        if self.warn_limit > 0 {
+            self.warn(&format!(
+                "Task {} executed {} instructions up to event {} without yielding. \
+                 TraceForge cannot preempt it there, so some interleavings may be hidden; \
+                 consider calling `thread::yield_now()` in long loops.",
+                task, instructions, pos
+            ));
+        }
+    }
+
+    /// Account for the number of tasks the execution spawned
+    pub(crate) fn record_task_count(&mut self, tasks: usize) {
+        if tasks > self.config.task_capacity.max(DEFAULT_INLINE_TASKS) {
+            self.task_spills += 1;
+        }
+    }
+
+    /// Records the state reached at a `checkpoint`, where `tasks` are the positions of all tasks.
+    /// Returns whether an earlier execution already reached the same state, in which case the
+    /// current execution need not go on.
+    pub(crate) fn visit_state(&mut self, hash: u64, tasks: &[(ThreadId, usize)]) -> bool {
+        let graph = &self.current.graph;
+        // While replaying events that are already in the graph, the rest of the execution is
+        // fixed by a revisit and differs from what the earlier execution explored.
+        if tasks
+            .iter()
+            .any(|(tid, pos)| graph.thread_size(*tid) > pos + 1)
+        {
+            return false;
+        }
+        let mut hasher = DefaultHasher::new();
+        hash.hash(&mut hasher);
+        let mut tasks = tasks.to_vec();
+        tasks.sort_by_key(|(tid, _)| format!("{}", tid));
+        for (tid, pos) in &tasks {
+            tid.hash(&mut hasher);
+            pos.hash(&mut hasher);
+        }
+        // The messages in flight, independently of the order they were sent in
+        let mut pending = graph
+            .all_store_iter()
+            .filter(|s| s.reader().is_none() && !s.is_dropped())
+            .map(|s| {
+                let mut h = DefaultHasher::new();
+                format!("{:?} {:?}", s.send_loc(), s.val()).hash(&mut h);
+                h.finish()
+            })
+            .collect::<Vec<_>>();
+        pending.sort_unstable();
+        pending.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if self.visited_states.contains(&key) && !self.current_states.contains(&key) {
+            return true;
+        }
+        self.visited_states.insert(key);
+        self.current_states.insert(key);
+        false
+    }
+
+    /// Whether a failure has been recorded in the current execution
+    pub(crate) fn has_failure(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    /// Whether a counterexample has been stored
+    pub(crate) fn counterexample_found(&self) -> bool {
+        self.replay_info.error_found()
+    }
+
+    /// Attach the output captured during the failing execution to its counterexample
+    pub(crate) fn store_captured_output(&mut self, output: String) {
+        if self.replay_info.error_found() {
+            self.replay_info.set_output(output);
+            self.write_error_trace();
+        }
+    }
+
+    fn write_error_trace(&self) {
+        if let Some(f) = self.config.error_trace_file.as_ref() {
+            write_trace(f, &self.replay_info);
+        }
+    }
+
     /// If the replayed event, i.e., `label` matches the `current_event`, it means
     /// that the `current_event` from the linearization has been replayed.
     /// So, now it's time to replay the next event from the linearization.
@@ -586,6 +1243,9 @@ impl Must {
     pub(crate) fn handle_send(&mut self, slab: SendMsg) -> Vec<Event> {
         let spos = slab.pos();
         let mut stuck: Vec<Event> = Vec::new();
+        if let Some(now) = self.task_clocks.get(&spos.thread).copied() {
+            self.send_clocks.insert(spos, now);
+        }
         if self.is_replay(spos) {
             info!("| Replay Mode for {} with reader {:?}", slab, slab.reader());
             let lab = LabelEnum::SendMsg(slab);
@@ -624,7 +1284,10 @@ impl Must {
         // polynomial but might require some caching to do it efficiently
         // (which sends have implicitly been dropped).
         let slab = self.current.graph.send_label(pos).unwrap();
-        if slab.is_lossy() && self.dropped_messages() < self.config.lossy_budget {
+        if slab.is_lossy()
+            && !self.config.exactly_once
+            && self.dropped_messages() < self.config.lossy_budget
+        {
             push_worklist(
                 &mut self.current.rqueue,
                 slab.stamp(),
@@ -712,6 +1375,11 @@ impl Must {
 
         let tclab = TCreate::new(pos, tid, name, is_daemon, sym_cid, origination_vec, filtered_origination_vec);
 
+        // The new task starts at the time of its parent
+        if let Some(now) = self.task_clocks.get(&pos.thread).copied() {
+            self.task_clocks.insert(tid, now);
+        }
+
         if self.is_replay(pos) {
             info!("| Replay Mode for {}", tclab);
             // Try to see if the `current_event` matches `tclab`
@@ -1090,38 +1758,379 @@ impl Must {
     pub(crate) fn next_task(
         &mut self,
         runnable: &[(TaskId, usize)],
-        _current: Option<TaskId>,
+        current: Option<TaskId>,
+    ) -> Option<TaskId> {
+        let next = self.pick_next_task(runnable, current);
+        if let Some(t) = next.filter(|_| self.schedule.is_some()) {
+            let action = Action::Task(self.to_thread_id(t).to_number());
+            self.schedule.as_mut().unwrap().push(action);
+        }
+        next
+    }
+
+    fn pick_next_task(
+        &mut self,
+        runnable: &[(TaskId, usize)],
+        current: Option<TaskId>,
     ) -> Option<TaskId> {
         if self.is_stopped() {
             return None;
         }
+        if let Some(t) = self.next_scripted(runnable) {
+            return Some(t);
+        }
+
+        let yielded = self.yielded.take();
+        let others: Vec<(TaskId, usize)> = runnable
+            .iter()
+            .filter(|(t, _)| Some(*t) != yielded && !self.delayed.contains(t))
+            .copied()
+            .collect();
+        let runnable = if others.iter().any(|(t, i)| self.is_thread_runnable(t, i)) {
+            &others[..]
+        } else {
+            // A task that yields lets the blocked tasks that can resume run first. They would
+            // otherwise only resume once it blocks itself, which a task spinning on
+            // `yield_now` until they make progress never does.
+            if yielded.is_some() && !self.replay_info.replay_mode() {
+                if let Some(t) = self.unblock_ready(&others) {
+                    self.schedule_depth += 1;
+                    self.last_run = Some(t);
+                    return Some(t);
+                }
+            }
+            // The delays end once no other task can run
+            self.delayed.clear();
+            runnable
+        };
 
         // If in replay mode, use the linearization to obtain the next thread
         // that must be executed
         if self.replay_info.replay_mode() {
-            return self.replay_info.next_task().map(|tid| {
+            let next = self.replay_info.next_task().map(|tid| {
                 self.to_task_id(tid)
                     .expect("task id not found in the execution graph!")
             });
+            if let Some(t) = next {
+                self.schedule_depth += 1;
+                self.last_run = next;
+                self.report_step(runnable, current, t);
+            }
+            return next;
         }
 
         let next = match self.config.schedule_policy {
-            SchedulePolicy::LTR => runnable
-                .iter()
-                .find(|(t, i)| self.is_thread_runnable(t, i))
-                .map(|(t, _)| t.to_owned()),
-            SchedulePolicy::Arbitrary => runnable
-                .sample(&mut self.rng, runnable.len())
+            SchedulePolicy::LTR => {
+                let candidates = self.break_ties(runnable);
+                self.pick_runnable(candidates.into_iter())
+            }
+            SchedulePolicy::Arbitrary if !self.task_speeds.is_empty() => {
+                let candidates = self.sample_by_speed(runnable);
+                self.pick_runnable(candidates.into_iter())
+            }
+            SchedulePolicy::Arbitrary => {
+                let candidates = runnable
+                    .sample(&mut self.rng, runnable.len())
+                    .collect::<Vec<_>>();
+                self.pick_runnable(candidates.into_iter())
+            }
+        };
+        let next = match next {
+            Some(t) if !self.outranked_by_ready(runnable, t) => Some(t),
+            _ => self.unblock_ready(runnable),
+        };
+        if let Some(t) = next {
+            self.schedule_depth += 1;
+            self.last_run = next;
+            if !self.task_speeds.is_empty() {
+                let tid = self.to_thread_id(t);
+                *self.task_progress.entry(tid).or_insert(0.0) += 1.0 / self.task_speed(tid);
+            }
+        }
+        next
+    }
+
+    // Picks the first runnable task among the candidates with the highest priority. With task
+    // speeds, picks the one that is the most behind in virtual time among those instead.
+    fn pick_runnable<'a>(
+        &self,
+        mut candidates: impl Iterator<Item = &'a (TaskId, usize)>,
+    ) -> Option<TaskId> {
+        if self.task_priorities.is_empty() && self.task_speeds.is_empty() {
+            return candidates
                 .find(|(t, i)| self.is_thread_runnable(t, i))
-                .map(|(t, _)| t.to_owned()),
+                .map(|(t, _)| t.to_owned());
+        }
+        let progress = |t: TaskId| {
+            let tid = self.to_thread_id(t);
+            self.task_progress.get(&tid).copied().unwrap_or(0.0)
         };
-        if next.is_some() {
-            next
-        } else {
-            self.unblock_ready(runnable)
+        candidates
+            .filter(|(t, i)| self.is_thread_runnable(t, i))
+            .min_by(|(a, _), (b, _)| {
+                let priority = self.task_priority(*b).cmp(&self.task_priority(*a));
+                priority.then(progress(*a).total_cmp(&progress(*b)))
+            })
+            .map(|(t, _)| t.to_owned())
+    }
+
+    // Orders the runnable tasks, which are sorted by id, by the preference of the tie-break
+    fn break_ties<'a>(&self, runnable: &'a [(TaskId, usize)]) -> Vec<&'a (TaskId, usize)> {
+        let mut candidates: Vec<_> = runnable.iter().collect();
+        let Some(last) = self.last_run else {
+            return candidates;
+        };
+        match self.config.tie_break {
+            TieBreak::LowestId => {}
+            TieBreak::RoundRobin => {
+                let next = candidates.iter().position(|(t, _)| *t > last);
+                candidates.rotate_left(next.unwrap_or(0));
+            }
+            TieBreak::LastRunFirst => {
+                if let Some(i) = candidates.iter().position(|(t, _)| *t == last) {
+                    candidates[..=i].rotate_right(1);
+                }
+            }
+        }
+        candidates
+    }
+
+    // Orders the runnable tasks randomly, the faster ones being more likely to come first
+    // (Efraimidis and Spirakis' weighted sampling)
+    fn sample_by_speed<'a>(&mut self, runnable: &'a [(TaskId, usize)]) -> Vec<&'a (TaskId, usize)> {
+        let mut keyed: Vec<(f64, &(TaskId, usize))> = runnable
+            .iter()
+            .map(|r| {
+                let speed = self.task_speed(self.to_thread_id(r.0));
+                (self.rng.random::<f64>().powf(1.0 / speed), r)
+            })
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        keyed.into_iter().map(|(_, r)| r).collect()
+    }
+
+    pub(crate) fn execution_index(&self) -> usize {
+        self.execution_index
+    }
+
+    pub(crate) fn next_execution_index(&mut self) {
+        self.execution_index += 1;
+    }
+
+    /// Whether the receive `recv` follows the delivery order pinned with
+    /// `Config::with_delivery_order`: if it reads one of the pinned messages, the messages
+    /// pinned before it were received earlier. It must not read a message excluded by
+    /// `minimize_messages` either.
+    pub(crate) fn in_delivery_order(&self, recv: Event) -> bool {
+        let order = &self.config.delivery_order;
+        let excluded = &self.config.excluded_messages;
+        if order.is_empty() && excluded.is_empty() {
+            return true;
+        }
+        let g = &self.current.graph;
+        let Some(send) = g.recv_label(recv).and_then(|r| r.rf()) else {
+            return true;
+        };
+        let id = g.msg_id(send);
+        if excluded.contains(&id) {
+            return false;
+        }
+        let Some(k) = order.iter().position(|m| *m == id) else {
+            return true;
+        };
+        let stamp = g.label(recv).stamp();
+        order[..k]
+            .iter()
+            .all(|m| self.delivery_stamp(m).is_some_and(|s| s < stamp))
+    }
+
+    /// The sends that the receive or inbox `recv` read from
+    pub(crate) fn read_sends(&self, recv: Event) -> Vec<Event> {
+        match self.current.graph.label(recv) {
+            LabelEnum::RecvMsg(r) => r.rf().into_iter().collect(),
+            LabelEnum::Inbox(i) => i.rfs().unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    // The stamp of the receive that read the message `id`, if any
+    fn delivery_stamp(&self, id: &MsgId) -> Option<usize> {
+        let g = &self.current.graph;
+        let reader = g
+            .find_send(id)
+            .and_then(|s| g.send_label(s).unwrap().reader())?;
+        Some(g.label(reader).stamp())
+    }
+
+    // Whether the current execution delivered all the pinned messages, in order
+    fn delivered_in_order(&self) -> bool {
+        let order = &self.config.delivery_order;
+        !order.is_empty()
+            && order
+                .iter()
+                .map(|m| self.delivery_stamp(m))
+                .try_fold(0, |last, stamp| stamp.filter(|s| *s >= last))
+                .is_some()
+    }
+
+    /// Panics if a delivery order was pinned, but no execution followed it
+    pub(crate) fn check_delivery_order_feasible(&self) {
+        let order = &self.config.delivery_order;
+        if !order.is_empty() && !self.delivery_order_seen {
+            let order: Vec<String> = order.iter().map(|m| m.to_string()).collect();
+            panic!(
+                "The pinned delivery order [{}] is not feasible: no execution delivers these messages in this order",
+                order.join(", ")
+            );
         }
     }
 
+    /// Marks the receive at `pos` as run inside an `atomic_region`
+    pub(crate) fn set_atomic_recv(&mut self, pos: Event) {
+        self.atomic_recvs.insert(pos);
+    }
+
+    pub(crate) fn eventually_held(&self, property: usize) -> bool {
+        self.eventually_held.contains(&property)
+    }
+
+    pub(crate) fn set_eventually_held(&mut self, property: usize) {
+        self.eventually_held.insert(property);
+    }
+
+    pub(crate) fn task_clock(&self, tid: ThreadId) -> Duration {
+        self.task_clocks.get(&tid).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn set_task_clock(&mut self, tid: ThreadId, now: Duration) {
+        self.task_clocks.insert(tid, now);
+    }
+
+    /// Advances the clock of the task that executed the receive at `pos` to the clock of the
+    /// sender at the send it read from, if that is later. The timer service keeps its own clock,
+    /// so that firing a timer does not move the clock of the task that set it.
+    pub(crate) fn receive_clock(&mut self, pos: Event) {
+        if self
+            .timer_service
+            .as_ref()
+            .is_some_and(|(_, tid)| *tid == pos.thread)
+        {
+            return;
+        }
+        let send = self.current.graph.recv_label(pos).and_then(|r| r.rf());
+        if let Some(sent) = send.and_then(|send| self.send_clocks.get(&send).copied()) {
+            if sent > self.task_clock(pos.thread) {
+                self.set_task_clock(pos.thread, sent);
+            }
+        }
+    }
+
+    /// Advances the clock of the task `tid` to the final clock of the task `joined`, if that is
+    /// later
+    pub(crate) fn join_clock(&mut self, tid: ThreadId, joined: ThreadId) {
+        let end = self.task_clock(joined);
+        if end > self.task_clock(tid) {
+            self.set_task_clock(tid, end);
+        }
+    }
+
+    pub(crate) fn set_task_priority(&mut self, tid: ThreadId, priority: i32) {
+        self.task_priorities.insert(tid, priority);
+    }
+
+    /// Thread `tid` waits for the mutex with priority inheritance served by `lock`
+    pub(crate) fn lock_waiting(&mut self, lock: ThreadId, tid: ThreadId) {
+        let waiters = &mut self.inheriting_locks.entry(lock).or_default().waiters;
+        waiters.push(tid);
+    }
+
+    /// Thread `tid` stopped waiting for `lock` without getting it
+    pub(crate) fn lock_cancelled(&mut self, lock: ThreadId, tid: ThreadId) {
+        if let Some(l) = self.inheriting_locks.get_mut(&lock) {
+            l.waiters.retain(|t| *t != tid);
+        }
+    }
+
+    /// Thread `tid` holds `lock`
+    pub(crate) fn lock_acquired(&mut self, lock: ThreadId, tid: ThreadId) {
+        let l = self.inheriting_locks.entry(lock).or_default();
+        l.waiters.retain(|t| *t != tid);
+        l.holder = Some(tid);
+    }
+
+    /// The holder of `lock` released it
+    pub(crate) fn lock_released(&mut self, lock: ThreadId) {
+        if let Some(l) = self.inheriting_locks.get_mut(&lock) {
+            l.holder = None;
+        }
+    }
+
+    pub(crate) fn add_finish_order(&mut self, first: ThreadId, second: ThreadId) {
+        self.finish_orders.push((first, second));
+    }
+
+    /// The threads that have to finish before thread `t`, see `assert_finishes_before`
+    pub(crate) fn finish_before(&self, t: ThreadId) -> Vec<ThreadId> {
+        let before = self.finish_orders.iter().filter(|(_, second)| *second == t);
+        before.map(|(first, _)| *first).collect()
+    }
+
+    /// Whether thread `t` has finished, i.e. its end is in the graph
+    pub(crate) fn has_finished(&self, t: ThreadId) -> bool {
+        matches!(self.current.graph.thread_last(t), Some(LabelEnum::End(_)))
+    }
+
+    /// Whether thread `first` finished before the last event of thread `second`, in every
+    /// linearization of the current execution: the end of `first` happens before it
+    pub(crate) fn finished_before(&self, first: ThreadId, second: ThreadId) -> bool {
+        let g = &self.current.graph;
+        let (Some(LabelEnum::End(end)), Some(last)) = (g.thread_last(first), g.thread_last(second))
+        else {
+            return false;
+        };
+        self.happens_before(end.pos(), last.pos())
+    }
+
+    pub(crate) fn set_task_speed(&mut self, tid: ThreadId, speed: f64) {
+        self.task_speeds.insert(tid, speed);
+    }
+
+    fn task_speed(&self, tid: ThreadId) -> f64 {
+        self.task_speeds.get(&tid).copied().unwrap_or(1.0)
+    }
+
+    fn task_priority(&self, t: TaskId) -> i32 {
+        self.thread_priority(self.to_thread_id(t), &mut Vec::new())
+    }
+
+    // The priority of thread `tid`, raised to the priorities of the threads waiting for a mutex
+    // with priority inheritance that it holds or serves. `path` holds the threads whose
+    // priority depends on that of `tid`, to stop at the cycles of a deadlock.
+    fn thread_priority(&self, tid: ThreadId, path: &mut Vec<ThreadId>) -> i32 {
+        let mut priority = self.task_priorities.get(&tid).copied().unwrap_or(0);
+        if path.contains(&tid) {
+            return priority;
+        }
+        path.push(tid);
+        for (synchronizer, lock) in &self.inheriting_locks {
+            if lock.holder == Some(tid) || *synchronizer == tid {
+                for w in &lock.waiters {
+                    priority = priority.max(self.thread_priority(*w, path));
+                }
+            }
+        }
+        path.pop();
+        priority
+    }
+
+    /// Whether the scheduler could pick task `t` next, possibly after unblocking it
+    pub(crate) fn is_task_enabled(&self, t: TaskId, i: usize) -> bool {
+        let thread_id = self.to_thread_id(t);
+        self.is_thread_runnable(&t, &i)
+            || self.is_waiting_on_written(thread_id)
+            || self.is_waiting_on_finished(thread_id)
+    }
+
     fn is_thread_runnable(&self, t: &TaskId, i: &usize) -> bool {
         let thread_id = self.to_thread_id(*t);
         let g = &self.current.graph;
@@ -1141,6 +2150,22 @@ impl Must {
         }
     }
 
+    // Whether a blocked task that can go on has a higher priority than task `t`, so that it
+    // runs first. Only mutexes with priority inheritance rank the blocked tasks, so that the
+    // synchronizer of such a lock, which inherits the priorities of its waiters, grants it
+    // ahead of the tasks of lower priority.
+    fn outranked_by_ready(&self, runnable: &[(TaskId, usize)], t: TaskId) -> bool {
+        if self.inheriting_locks.is_empty() || self.task_priorities.is_empty() {
+            return false;
+        }
+        let priority = self.task_priority(t);
+        runnable.iter().any(|(b, _)| {
+            let tid = self.to_thread_id(*b);
+            self.task_priority(*b) > priority
+                && (self.is_waiting_on_written(tid) || self.is_waiting_on_finished(tid))
+        })
+    }
+
     fn unblock_ready(&mut self, runnable: &[(TaskId, usize)]) -> Option<TaskId> {
         let blocked = runnable
             .iter()
@@ -1154,7 +2179,14 @@ impl Must {
             .iter()
             .for_each(|task| self.current.graph.remove_last(self.to_thread_id(task.0)));
 
-        blocked.first().map(|(t, _)| t.to_owned())
+        // With priority inheritance, the first of the ready tasks with the highest priority runs
+        if self.inheriting_locks.is_empty() {
+            return blocked.first().map(|(t, _)| t.to_owned());
+        }
+        blocked
+            .iter()
+            .min_by_key(|(t, _)| std::cmp::Reverse(self.task_priority(*t)))
+            .map(|(t, _)| t.to_owned())
     }
 
     fn is_waiting_on_written(&self, t: ThreadId) -> bool {
@@ -1222,6 +2254,54 @@ impl Must {
         self.current.graph.check_blocked()
     }
 
+    // Adds the messages of the current execution, which is complete, to the counts of their
+    // channels
+    fn count_messages(&mut self) {
+        let mut counts: BTreeMap<String, MessageCounts> = BTreeMap::new();
+        for send in self.current.graph.all_store_iter() {
+            let channel = counts.entry(send.loc().name()).or_default();
+            channel.sent += 1;
+            if send.reader().is_some() {
+                channel.received += 1;
+            } else if send.is_dropped() {
+                channel.lost += 1;
+            } else {
+                channel.in_flight += 1;
+            }
+        }
+        for (channel, mut execution) in counts {
+            if execution.in_flight > 0 {
+                execution.executions_with_in_flight = 1;
+            }
+            self.message_counts
+                .entry(channel)
+                .or_default()
+                .merge(&execution);
+        }
+    }
+
+    /// Counts a scheduling step of thread `tid`, whose name is `name`, see
+    /// `Config::with_profile`
+    pub(crate) fn profile_step(&mut self, tid: ThreadId, name: impl FnOnce() -> String) {
+        if !self.profiled.contains_key(&tid) {
+            let start = self.task_clock(tid);
+            self.profiled.insert(tid, (name(), start));
+        }
+        self.profile.add_step(&self.profiled[&tid].0);
+    }
+
+    // Adds the logical time of the tasks of the current execution, which ended, to the profile
+    fn profile_execution(&mut self, on_target: bool) {
+        let profiled = std::mem::take(&mut self.profiled);
+        if !on_target {
+            return;
+        }
+        for (tid, (name, start)) in profiled {
+            let time = self.task_clock(tid).saturating_sub(start);
+            self.profile.add_execution(&name, time);
+        }
+    }
+
     /// `complete_execution` is invoked when a particular single execution has finished.
     /// `complete_execution` returns false if there is another execution to do, or
     /// true if there is nothing more to explore.
@@ -1230,20 +2310,64 @@ impl Must {
     /// the ability to call into Must model code (the monitor on_stop) while
     /// not holding a reference to entire Must object.
     pub(crate) fn complete_execution(must: &Rc<RefCell<Must>>) -> bool {
-        let maybe_block = must.borrow_mut().check_blocked();
-        let exceeded_max_executions = must.borrow_mut().record_ending_telemetry(&maybe_block);
-
-        let condition = match maybe_block {
+        let mut maybe_block = must.borrow_mut().check_blocked();
+        if maybe_block.is_none() && Must::check_eventually(must) {
+            // Like a task panic whose failure is collected, the execution blocks there
+            maybe_block = Some(BlockType::Assert);
+        }
+        let mut condition = match &maybe_block {
             None => EndCondition::AllThreadsCompleted,
             Some(block) => match block {
                 BlockType::Assume | BlockType::Assert => EndCondition::FailedAssumption,
                 BlockType::Value(_, _) | BlockType::Join(_) => EndCondition::Deadlock,
             },
         };
+        if Must::call_on_stop_on_monitors(must, &condition) {
+            maybe_block = Some(BlockType::Assert);
+            condition = EndCondition::FailedAssumption;
+        }
+        if !matches!(maybe_block, Some(BlockType::Assume)) && must.borrow().delivered_in_order() {
+            must.borrow_mut().delivery_order_seen = true;
+        }
+        let exceeded_max_executions = must.borrow_mut().record_ending_telemetry(&maybe_block);
+        // The executions that miss the coverage target are only explored to branch off them
+        let on_target = must.borrow().reaches_coverage_target();
+        must.borrow_mut().profile_execution(on_target);
+
+        if on_target
+            && condition == EndCondition::Deadlock
+            && must.borrow().config.collect_deadlocks
+        {
+            let mut must = must.borrow_mut();
+            let deadlock = Deadlock::of(&must.current.graph, &must.wait_reasons);
+            must.deadlocks.insert(deadlock);
+        }
 
-        Must::call_on_stop_on_monitors(must, &condition);
+        if on_target && condition == EndCondition::AllThreadsCompleted {
+            if must.borrow().config.message_counts {
+                must.borrow_mut().count_messages();
+            }
+            let fingerprint = must.borrow().config.fingerprint.clone();
+            if let Some(fingerprint) = fingerprint {
+                let state = (fingerprint.lock().expect("Could not lock fingerprint"))();
+                must.borrow_mut().final_states.insert(state);
+            }
+            let describe = must.borrow().config.reachable_states.clone();
+            if let Some(describe) = describe {
+                let shared = (describe
+                    .lock()
+                    .expect("Could not lock the state description"))();
+                let mut must = must.borrow_mut();
+                let state = FinalState::of(&must.current.graph, shared);
+                if !must.reachable_states.contains(&state) {
+                    must.reachable_states.push(state);
+                }
+            }
+        }
+        Must::score_execution(must, &condition);
         must.borrow_mut().published_values.clear();
-        must.borrow_mut().call_telemetry_after(&condition);
+        must.borrow_mut()
+            .call_telemetry_after(&condition, on_target);
 
         if exceeded_max_executions {
             return true; // no more executions.
@@ -1253,6 +2377,11 @@ impl Must {
         !must.borrow_mut().try_revisit()
     }
 
+    fn record_schedule_depth(&mut self) {
+        self.max_schedule_depth = self.max_schedule_depth.max(self.schedule_depth);
+        self.total_schedule_depth += self.schedule_depth;
+    }
+
     fn record_ending_telemetry(&mut self, maybe_block: &Option<BlockType>) -> bool {
         // Debug: print events that were not replayed during this execution.
         let unreplayed = &self.current.graph.unreplayed_events;
@@ -1268,13 +2397,18 @@ impl Must {
             debug!("[DEBUG] All events were replayed.");
         }
         let elapsed = Instant::now() - self.started_at;
-        if maybe_block.is_some() {
+        if !self.reaches_coverage_target() {
+            if self.is_consistent() {
+                self.off_target += 1;
+            }
+        } else if maybe_block.is_some() {
             if self.is_consistent() {
                 self.telemetry.counter(BLOCKED.to_owned()); // increment BLOCKED
                 let event_count: usize = self.current.graph.threads.iter().map(|t| t.labels.len()).sum();
                 if event_count > self.max_graph_events {
                     self.max_graph_events = event_count;
                 }
+                self.record_schedule_depth();
                 if self.config.verbose >= 2 {
                     println!("One more blocked execution");
                     println!("{}", self.print_graph(None));
@@ -1287,6 +2421,7 @@ impl Must {
             if event_count > self.max_graph_events {
                 self.max_graph_events = event_count;
             }
+            self.record_schedule_depth();
             self.print_turmoil_trace();
             if self.config.verbose >= 1 {
                 println!("One more complete execution");
@@ -1296,7 +2431,7 @@ impl Must {
 
         let num_execs = self.telemetry.read_counter(EXECS.to_owned()).unwrap_or(0);
         let num_blocked = self.telemetry.read_counter(BLOCKED.to_owned()).unwrap_or(0);
-        let num_total = num_execs + num_blocked;
+        let num_total = num_execs + num_blocked + self.off_target as u64;
         let speed: String = if elapsed.as_secs() < 5 {
             "".to_string()
         } else {
@@ -1330,6 +2465,17 @@ impl Must {
         if let Some(n) = self.config.max_iterations {
             if n <= num_total {
                 println!("Stopping exploration because max_iterations was reached.");
+                self.max_iterations_reached = true;
+                return true; // done
+            }
+        }
+
+        if let (Some(budget), Some(allocated)) =
+            (self.config.memory_budget, crate::alloc::allocated())
+        {
+            if allocated.saturating_sub(self.allocated_at_start) > budget {
+                println!("Stopping exploration because the memory budget was exceeded.");
+                self.out_of_memory = true;
                 return true; // done
             }
         }
@@ -1353,14 +2499,76 @@ impl Must {
         p < 10
     }
 
+    /// Fail the execution if one of the properties registered with `Config::with_eventually`
+    /// never held in it, like a task panic. Only called for complete executions. Returns true
+    /// if the failure is collected rather than panicking, see `Config::with_collect_failures`.
+    fn check_eventually(must: &Rc<RefCell<Must>>) -> bool {
+        let never_held = {
+            let must = must.borrow();
+            (0..must.config.eventually.len()).find(|i| !must.eventually_held(*i))
+        };
+        let Some(i) = never_held else {
+            return false;
+        };
+        let msg = format!("eventually property {} never held", i);
+        must.borrow_mut().record_failure(FailureReport {
+            category: FailureCategory::Liveness,
+            message: msg.clone(),
+            task: None,
+            pos: None,
+            location: None,
+        });
+        let message = persist_task_failure(msg, None);
+        if must.borrow().collects_failures() {
+            must.borrow_mut().collect_failure();
+            return true;
+        }
+        panic!("{}", message);
+    }
+
+    // Whether the current execution reached the goal of `Config::with_coverage_target`, if
+    // there is one
+    fn reaches_coverage_target(&self) -> bool {
+        let Some(goal) = &self.config.coverage_target else {
+            return true;
+        };
+        let coverage = &self.telemetry.coverage;
+        coverage.covered_in_exec(coverage.current_eid(), goal.clone()) > 0
+    }
+
+    // Whether the exploration is a best-first search, see `follow_guide`
+    fn is_guided(&self) -> bool {
+        self.config.guide.is_some() || self.config.coverage_target.is_some()
+    }
+
+    /// Score the execution with the guide of a guided search, if there is one.
+    /// Like the monitors, the guide is called without holding a reference to Must.
+    /// Without a guide, the executions that reach the coverage target score higher.
+    fn score_execution(must: &Rc<RefCell<Must>>, condition: &EndCondition) {
+        let Some(guide) = must.borrow().config.guide.clone() else {
+            let score = must.borrow().reaches_coverage_target() as i64;
+            must.borrow_mut().current.score = score;
+            return;
+        };
+        let execution_end = ExecutionEnd {
+            condition: condition.clone(),
+            published_values: must.borrow().published_values.clone(),
+            _unused_lifetime: PhantomData,
+        };
+        let score = (guide.lock().expect("Could not lock the guide"))(&execution_end);
+        must.borrow_mut().current.score = score;
+    }
+
     /// All of the monitors on_stop functions and return an error if there is one.
-    fn call_on_stop_on_monitors(must: &Rc<RefCell<Must>>, condition: &EndCondition) {
+    /// Returns true if the error is collected rather than panicking, see
+    /// `Config::with_collect_failures`; the execution then blocks like a failed assertion.
+    fn call_on_stop_on_monitors(must: &Rc<RefCell<Must>>, condition: &EndCondition) -> bool {
         // Allow panics in Monitor::on_stop to be caught.
-        let _guard = init_panic_hook();
+        let _guard = init_panic_hook(must.borrow().config().install_panic_hook);
 
         if condition == &EndCondition::FailedAssumption {
             // Don't execute the monitor's on_stop since an assumption failed.
-            return;
+            return false;
         }
 
         // Extract all of the monitors from the must.monitor's BTree.
@@ -1393,6 +2601,17 @@ impl Must {
             let res = (*monitor).on_stop(&execution_end);
             if let Err(msg) = res {
                 // Store the replay information first.
+                must.borrow_mut().record_failure(FailureReport {
+                    category: FailureCategory::Monitor,
+                    message: msg.clone(),
+                    task: None,
+                    pos: None,
+                    location: None,
+                });
+                if must.borrow().collects_failures() {
+                    must.borrow_mut().collect_failure();
+                    return true;
+                }
                 must.borrow_mut().store_replay_information(None);
                 println!("{}", must.borrow_mut().print_graph(None));
                 std::io::stderr().flush().unwrap();
@@ -1402,26 +2621,30 @@ impl Must {
                 );
             }
         }
+        false
     }
 
-    fn call_telemetry_after(&mut self, condition: &EndCondition) {
+    fn call_telemetry_after(&mut self, condition: &EndCondition, on_target: bool) {
         // run all registered on-stop handlers with end condition and coverage information
         // This is not ideal that we are locking Must while calling them; we can't
         // generate a counterexample if they panic. OTOH, the callbacks should not.
         // A monitor provides a general solution for generating a counterexample at the end of
         // an execution.
-        for cb in &mut self
-            .config
-            .callbacks
-            .lock()
-            .expect("Could not lock callbacks")
-            .iter_mut()
-        {
-            cb.after(
-                self.telemetry.coverage.current_eid(),
-                condition,
-                self.telemetry.coverage.export_current().into(),
-            );
+        // The executions that miss the coverage target are not reported to them
+        if on_target {
+            for cb in &mut self
+                .config
+                .callbacks
+                .lock()
+                .expect("Could not lock callbacks")
+                .iter_mut()
+            {
+                cb.after(
+                    self.telemetry.coverage.current_eid(),
+                    condition,
+                    self.telemetry.coverage.export_current().into(),
+                );
+            }
         }
 
         // Clean up per-execution coverage data after observers have been notified
@@ -1436,6 +2659,14 @@ impl Must {
         );
 
         self.filter_symmetric_rfs(&mut rfs, pos);
+        // A receive inside an atomic region only reads the first message available
+        if self.atomic_recvs.contains(&pos) {
+            rfs.truncate(1);
+            if !blocking && !rfs.is_empty() {
+                self.current.graph.change_rf(pos, Some(rfs[0]));
+                return self.current.graph.val_copy(pos);
+            }
+        }
 
         // At this point, we have handled all the cases for nonblocking receive
         // so we know blocking == true
@@ -1482,6 +2713,9 @@ impl Must {
             } else {
                 debug!("Forward revisits at {}: {:?}", pos, rfs);
                 self.current.graph.change_rf(pos, Some(rfs[0]));
+                // Reading a send that commutes with rfs[0] leads to an equivalent execution
+                let first = rfs[0];
+                rfs.retain(|&rf| rf == first || !self.are_independent_sends(first, rf));
                 rfs.iter().skip(1).for_each(|&rf| {
                     push_worklist(
                         &mut self.current.rqueue,
@@ -1632,6 +2866,11 @@ impl Must {
 
             match rl {
                 RecvLike::RecvMsg(r) => {
+                    if r.rf().is_some_and(|rf| self.are_independent_sends(rf, pos))
+                        || self.atomic_recvs.contains(&r.pos())
+                    {
+                        continue;
+                    }
                     let rev = Revisit::new(r.pos(), pos);
                     if !self.is_maximal_recv(r, &rev) {
                         break;
@@ -1830,6 +3069,117 @@ impl Must {
         }
     }
 
+    /// Whether the sends `a` and `b` carry tags the user declared independent
+    /// (see [`crate::ConfigBuilder::with_independent_tags`])
+    fn are_independent_sends(&self, a: Event, b: Event) -> bool {
+        if self.config.independent_tags.is_empty() {
+            return false;
+        }
+        let tag = |e: Event| match self.current.graph.send_label(e)?.send_loc().tag.as_deref() {
+            Some(&[t]) => Some(t),
+            _ => None,
+        };
+        match (tag(a), tag(b)) {
+            (Some(ta), Some(tb)) => self
+                .config
+                .independent_tags
+                .iter()
+                .any(|&(x, y)| (x, y) == (ta, tb) || (y, x) == (ta, tb)),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn porf_view(&self, e: Event) -> Option<VectorClock> {
+        let g = &self.current.graph;
+        g.contains(e).then(|| g.porf(e))
+    }
+
+    /// Number of messages to `loc` that are sent before `pos` (in porf) but not received before it
+    pub(crate) fn inflight_count(&self, pos: Event, loc: &Loc) -> usize {
+        let g = &self.current.graph;
+        let view = g.porf(pos);
+        g.all_store_iter()
+            .filter(|slab| {
+                slab.loc() == loc
+                    && !slab.is_dropped()
+                    && view.contains(slab.pos())
+                    && slab.reader().is_none_or(|r| !view.contains(r))
+            })
+            .count()
+    }
+
+    pub(crate) fn happens_before(&self, a: Event, b: Event) -> bool {
+        let g = &self.current.graph;
+        if a == b || !g.contains(a) || !g.contains(b) {
+            return false;
+        }
+        if g.in_porf(a, b) {
+            return true;
+        }
+        // Follow the chains of sync edges that start after `a`
+        let before_or_eq = |x: Event, y: Event| x == y || g.in_porf(x, y);
+        let mut reached: Vec<usize> = (0..self.sync_edges.len())
+            .filter(|&i| before_or_eq(a, self.sync_edges[i].0))
+            .collect();
+        let mut next = 0;
+        while next < reached.len() {
+            let (_, y) = self.sync_edges[reached[next]];
+            if before_or_eq(y, b) {
+                return true;
+            }
+            for (i, (x, _)) in self.sync_edges.iter().enumerate() {
+                if !reached.contains(&i) && before_or_eq(y, *x) {
+                    reached.push(i);
+                }
+            }
+            next += 1;
+        }
+        false
+    }
+
+    /// Adds the happens-before edge `before -> after` to the current execution, see
+    /// `add_sync_edge`. Returns whether the execution is consistent with its edges.
+    pub(crate) fn add_sync_edge(&mut self, before: Event, after: Event) -> bool {
+        let g = &self.current.graph;
+        assert!(
+            g.contains(before) && g.contains(after),
+            "add_sync_edge: both events must be part of the current execution"
+        );
+        self.sync_edges.push((before, after));
+        self.sync_edges_hold()
+    }
+
+    /// Whether the current execution is consistent with the edges added by `add_sync_edge`:
+    /// they create no cycle in happens-before, and the messages of causally ordered channels
+    /// are received in the order of their sends.
+    pub(crate) fn sync_edges_hold(&self) -> bool {
+        if self.sync_edges.is_empty() {
+            return true;
+        }
+        if self
+            .sync_edges
+            .iter()
+            .any(|&(before, after)| self.happens_before(after, before))
+        {
+            return false;
+        }
+        let g = &self.current.graph;
+        let causal = || {
+            g.all_store_iter()
+                .filter(|s| s.comm() == CommunicationModel::CausalOrder && !s.is_dropped())
+        };
+        causal().all(|send| {
+            let Some(reader) = send.reader() else {
+                return true;
+            };
+            causal()
+                .filter(|earlier| {
+                    earlier.loc() == send.loc() && self.happens_before(earlier.pos(), send.pos())
+                })
+                .all(|earlier| earlier.reader().is_some_and(|r| g.in_porf(r, reader)))
+        })
+    }
+
     fn filter_symmetric_rfs(&self, rfs: &mut Vec<Event>, pos: Event) {
         assert!(self.current.graph.is_recv(pos) || self.current.graph.is_inbox(pos));
 
@@ -1953,8 +3303,15 @@ impl Must {
     }
 
     pub(crate) fn try_revisit(&mut self) -> bool {
+        if self.script.is_some() {
+            // A script runs a single execution
+            return false;
+        }
         loop {
             debug!("Finished execution with current rqueue {:?}", self.current.rqueue.clone());
+            if self.is_guided() {
+                self.follow_guide();
+            }
             if self.current.rqueue.is_empty() {
                 if self.try_pop_state() {
                     continue;
@@ -1971,6 +3328,10 @@ impl Must {
             if self.config.verbose >= 3 {
                 println!("Revisit {} <= {}", rev.pos(), rev.rev());
             }
+            if self.sampled_out_branch() {
+                info!("[revisit] skip {} <= {} (sampling)", rev.pos(), rev.rev());
+                continue;
+            }
             // Execute first feasible revisit; if skipped, continue polling worklist.
             if match &rev {
                 RevisitEnum::ForwardRevisit(r) => self.forward_revisit(r),
@@ -1981,6 +3342,19 @@ impl Must {
         }
     }
 
+    /// Whether to skip the next branch of the exploration, see `Config::with_sampling`
+    fn sampled_out_branch(&mut self) -> bool {
+        let (Some((rate, _)), Some(rng)) = (self.config.sampling, self.sampling_rng.as_mut())
+        else {
+            return false;
+        };
+        let skip = rng.random::<f64>() >= rate;
+        if skip {
+            self.sampled_out += 1;
+        }
+        skip
+    }
+
     fn forward_revisit(&mut self, rev: &Revisit) -> bool {
         let placement = self.fmt_revisit_placement(&rev.rev);
         info!("[revisit/forward] start {} <= {}", rev.pos, placement);
@@ -2137,7 +3511,7 @@ impl Must {
     fn pick_ctoss(&mut self, pos: Event) -> bool {
         self.telemetry.histogram(EXECS_EST.to_owned(), 2.0);
 
-        let toss = rand::rng().random_range(0..=1) == 0;
+        let toss = self.rng.random_range(0..=1) == 0;
         cast!(self.current.graph.label_mut(pos), LabelEnum::CToss).set_result(toss);
         toss
     }
@@ -2147,7 +3521,7 @@ impl Must {
         let range = choice.range();
         let start = *range.start();
         let end = *range.end();
-        let rand_value = rand::rng().random_range(start..=end);
+        let rand_value = self.rng.random_range(start..=end);
         choice.set_result(rand_value);
 
         self.telemetry
@@ -2182,7 +3556,7 @@ impl Must {
         self.telemetry
             .histogram(EXECS_EST.to_owned(), (revs.len() + 1) as f64);
 
-        let idx = rand::rng().random_range(0..=revs.len());
+        let idx = self.rng.random_range(0..=revs.len());
         if idx < revs.len() {
             push_worklist(
                 &mut self.current.rqueue,
@@ -2203,6 +3577,41 @@ impl Must {
         }
     }
 
+    /// Best-first search: continue with the pending revisit whose execution got the highest
+    /// score. The revisits of the current execution are split by stamp into states of their
+    /// own, like the states handed to parallel workers, so that each of them can be picked
+    /// independently. Ties go to the most recent revisit, as in depth-first search.
+    fn follow_guide(&mut self) {
+        let MustState {
+            graph,
+            mut rqueue,
+            score,
+        } = std::mem::take(&mut self.current);
+        let mut graph = Some(graph);
+        while let Some((stamp, revs)) = rqueue.pop_first() {
+            let graph = if rqueue.is_empty() {
+                graph.take().unwrap()
+            } else {
+                graph.clone().unwrap()
+            };
+            self.states.push(MustState {
+                graph,
+                rqueue: RQueue::from([(stamp, revs)]),
+                score,
+            });
+        }
+        self.states.retain(|state| !state.rqueue.is_empty());
+        if let Some(best) = self
+            .states
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, state)| state.score)
+            .map(|(i, _)| i)
+        {
+            self.current = self.states.remove(best);
+        }
+    }
+
     fn try_pop_state(&mut self) -> bool {
         if self.states.is_empty() {
             return false;
@@ -2216,7 +3625,7 @@ impl Must {
         self.states.push(std::mem::take(&mut self.current));
     }
 
-    fn is_replay(&self, pos: Event) -> bool {
+    pub(crate) fn is_replay(&self, pos: Event) -> bool {
         self.current.graph.contains(pos)
     }
 
@@ -2235,6 +3644,27 @@ impl Must {
             block: self.telemetry.read_counter(BLOCKED.into()).unwrap_or(0) as usize,
             coverage: self.telemetry.coverage.export_aggregate().into(),
             max_graph_events: self.max_graph_events,
+            max_schedule_depth: self.max_schedule_depth,
+            total_schedule_depth: self.total_schedule_depth,
+            deadlocks: self.deadlocks.iter().cloned().collect(),
+            collected_failures: self.collected_failures.clone(),
+            task_spills: self.task_spills,
+            long_steps: self.long_steps,
+            sampling_rate: self.config.sampling.map(|(rate, _)| rate),
+            sampled_out: self.sampled_out,
+            out_of_memory: self.out_of_memory,
+            max_iterations_reached: self.max_iterations_reached,
+            failures: self.failures,
+            suppressed_races: self.suppressed_races,
+            off_target: self.off_target,
+            profile: self.profile.clone(),
+            code_paths: self.code_paths.clone(),
+            cached: false,
+            elapsed: self.started_at.elapsed(),
+            final_states: self.final_states.clone(),
+            reachable_states: self.reachable_states.clone(),
+            float_ranges: self.float_ranges.clone(),
+            message_counts: self.message_counts.clone(),
         }
     }
 
@@ -2527,6 +3957,19 @@ impl Must {
     }
 }
 
+// Writes the counterexample `replay_info` to the file `path`
+fn write_trace(path: &str, replay_info: &REPLAY::ReplayInformation) {
+    let mut file = File::create(path).unwrap();
+    match serde_json::to_string_pretty(replay_info) {
+        Ok(replay_str) => {
+            writeln!(&mut file, "{}", replay_str).unwrap();
+        }
+        Err(err) => {
+            println!("Can't serialize graph to json: {}", err);
+        }
+    };
+}
+
 fn push_worklist(worklist: &mut RQueue, stamp: usize, r: RevisitEnum) {
     if worklist.get(&stamp).is_none() {
         worklist.insert(stamp, Vec::new());
@@ -2663,7 +4106,7 @@ mod tests {
         ));
         tseg.insert_label(send_at_0.clone());
         let error_state = MustState::new();
-        must.replay_info = ReplayInformation::create(tseg, error_state, config.clone());
+        must.replay_info = ReplayInformation::create(tseg, error_state, config.clone(), None);
         must.replay_info.next_task(); // Advance to (t0, 0)
         must
     }
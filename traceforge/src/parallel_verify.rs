@@ -534,9 +534,12 @@ where
     CONTINUATION_POOL.set(pool, || loop {
         let f = Arc::clone(f);
         let execution = Execution::new(Rc::clone(must));
-        Must::begin_execution(must);
-        execution.run(move || f());
-        if Must::complete_execution(must) {
+        let done = crate::run_with_hooks(must, || {
+            Must::begin_execution(must);
+            execution.run(move || f());
+            Must::complete_execution(must)
+        });
+        if done {
             break;
         }
     });
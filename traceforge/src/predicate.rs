@@ -1,6 +1,7 @@
 use serde::de::Visitor;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::Val;
 use crate::thread::ThreadId;
 
 use std::fmt::Debug;
@@ -57,6 +58,18 @@ impl<'de> Deserialize<'de> for PredicateType {
     }
 }
 
+/// This type represents the type of predicates on message values.
+/// Unlike tag predicates, they are not serialized: they are restored
+/// with the receive locations during replay.
+#[derive(Clone)]
+pub(crate) struct ValuePredicate(pub Arc<dyn Send + Sync + Fn(&Val) -> bool>);
+
+impl Debug for ValuePredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<value_predicate>")
+    }
+}
+
 pub(crate) fn normalize_vec_tag(tag: Option<Vec<u32>>) -> Option<Vec<u32>> {
     match tag {
         Some(tag) if tag.is_empty() => None,
@@ -0,0 +1,96 @@
+//! Where the modeled system does its work, by task, for
+//! [`ConfigBuilder::with_profile`](crate::ConfigBuilder::with_profile).
+//!
+//! The profile counts, for each task, the scheduling steps it was given and the logical time
+//! that its clock advanced while it ran, see [`time`](crate::time). Both are properties of the
+//! model, not of the machine running TraceForge: they tell which modeled component does the
+//! most work, whatever the cost of simulating it. The counts are summed over the explored
+//! executions, with the tasks identified by their names.
+//!
+//! The profile can be rendered in the folded format of flamegraph tools, e.g. `inferno` or
+//! `flamegraph.pl`:
+//!
+//! ```no_run
+//! use traceforge::{thread, Config};
+//!
+//! let stats = traceforge::verify(Config::builder().with_profile(true).build(), || {
+//!     let worker = thread::Builder::new()
+//!         .name("worker".to_string())
+//!         .spawn(|| traceforge::nondet())
+//!         .unwrap();
+//!     worker.join().unwrap();
+//! });
+//! std::fs::write("steps.folded", stats.profile.folded_steps()).unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The work of one task, summed over the executions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskProfile {
+    /// Number of scheduling decisions that picked the task
+    pub steps: usize,
+    /// How far the logical clock of the task advanced between its first step and the end of
+    /// the execution
+    pub logical_time: Duration,
+    /// Number of executions in which the task ran
+    pub executions: usize,
+}
+
+impl TaskProfile {
+    fn merge(&mut self, other: &TaskProfile) {
+        self.steps += other.steps;
+        self.logical_time += other.logical_time;
+        self.executions += other.executions;
+    }
+}
+
+/// The work of the tasks of an exploration, see [the module](self)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// The work of each task, by its name, e.g. `main-0` or `traceforge_runtime::mutex`
+    pub tasks: BTreeMap<String, TaskProfile>,
+}
+
+impl Profile {
+    /// The scheduling steps of the tasks, in the folded format of flamegraphs: one line by task,
+    /// with the components of its name, separated by `::`, as the frames of its stack
+    pub fn folded_steps(&self) -> String {
+        self.folded(|p| p.steps as u128)
+    }
+
+    /// The logical time of the tasks, in nanoseconds, in the format of
+    /// [`Profile::folded_steps`]
+    pub fn folded_time(&self) -> String {
+        self.folded(|p| p.logical_time.as_nanos())
+    }
+
+    fn folded(&self, weight: impl Fn(&TaskProfile) -> u128) -> String {
+        let lines = self.tasks.iter().filter(|(_, p)| weight(p) > 0);
+        let lines = lines.map(|(name, p)| {
+            let stack = name.replace("::", ";").replace(' ', "_");
+            format!("{} {}\n", stack, weight(p))
+        });
+        lines.collect()
+    }
+
+    pub(crate) fn add_step(&mut self, task: &str) {
+        self.tasks.entry(task.to_string()).or_default().steps += 1;
+    }
+
+    // Adds the logical time of a task that ran in an execution that just ended
+    pub(crate) fn add_execution(&mut self, task: &str, logical_time: Duration) {
+        let p = self.tasks.entry(task.to_string()).or_default();
+        p.logical_time += logical_time;
+        p.executions += 1;
+    }
+
+    pub(crate) fn merge(&mut self, other: &Profile) {
+        for (task, p) in &other.tasks {
+            self.tasks.entry(task.clone()).or_default().merge(p);
+        }
+    }
+}
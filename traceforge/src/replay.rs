@@ -4,6 +4,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::event_label::{AsEventLabel, LabelEnum};
 use crate::must::MustState;
+use crate::runtime::failure::FailureReport;
 use crate::{event::*, Config, ThreadId};
 
 /// The topologically sorted execution graph represents a linearization
@@ -26,6 +27,11 @@ impl TopologicallySortedExecutionGraph {
         }
     }
 
+    /// The labels of the linearization, in order
+    pub(crate) fn labels(&self) -> impl Iterator<Item = &LabelEnum> {
+        self.label_order.iter()
+    }
+
     /// Add a new label to the linearization.
     pub(crate) fn insert_label(&mut self, label: LabelEnum) {
         // Prevent duplicates
@@ -130,6 +136,12 @@ pub struct ReplayInformation {
     replay_mode: bool,
     // configuration parameters
     config: Config,
+    // why the execution failed
+    #[serde(default)]
+    failure: Option<FailureReport>,
+    // what the failing execution wrote to stdout and stderr, see `Config::with_capture_output`
+    #[serde(default)]
+    output: Option<String>,
 }
 
 impl ReplayInformation {
@@ -141,6 +153,8 @@ impl ReplayInformation {
             current_event: None,
             replay_mode,
             config,
+            failure: None,
+            output: None,
         }
     }
 
@@ -148,6 +162,7 @@ impl ReplayInformation {
         sorted_error_graph: TopologicallySortedExecutionGraph,
         error_state: MustState,
         config: Config,
+        failure: Option<FailureReport>,
     ) -> Self {
         ReplayInformation {
             sorted_error_graph: Some(sorted_error_graph),
@@ -156,6 +171,8 @@ impl ReplayInformation {
             current_event: None,
             replay_mode: true,
             config,
+            failure,
+            output: None,
         }
     }
 
@@ -165,6 +182,10 @@ impl ReplayInformation {
         self.error_state.take().unwrap()
     }
 
+    pub(crate) fn set_output(&mut self, output: String) {
+        self.output = Some(output);
+    }
+
     pub(crate) fn error_found(&self) -> bool {
         self.error_found
     }
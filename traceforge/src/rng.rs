@@ -0,0 +1,54 @@
+//! A deterministic random number generator for models.
+//!
+//! `rand::rng()` draws from an OS-seeded, thread-local generator that TraceForge cannot
+//! redirect, so code that uses it produces different values on every run and counterexamples
+//! that depend on those values cannot be replayed. [`rng()`](crate::rng()) instead returns a
+//! generator that is seeded from [`Config::with_seed`](crate::ConfigBuilder::with_seed), the
+//! calling thread and the number of generators that thread has already created in the current
+//! execution. Two runs with the same configuration therefore observe exactly the same values,
+//! and replaying a counterexample reproduces them.
+//!
+//! Pass the generator to libraries that accept a `rand::Rng` to make them deterministic as well.
+//!
+//! Note that the values drawn from this generator are not choices of the model checker: TraceForge
+//! does not explore alternative values. Use [`nondet`](crate::nondet) or
+//! [`Nondet`](crate::Nondet) for values that should be explored exhaustively.
+
+use std::convert::Infallible;
+
+use rand::{SeedableRng, TryRng};
+use rand_pcg::Pcg64Mcg;
+
+/// A seeded random number generator returned by [`rng()`](crate::rng()).
+#[derive(Clone, Debug)]
+pub struct DeterministicRng {
+    inner: Pcg64Mcg,
+}
+
+impl DeterministicRng {
+    pub(crate) fn new(seed: u64, thread: u32, stream: u32) -> Self {
+        // Mix the thread and the stream into the seed so that every generator of an execution
+        // produces a different sequence
+        let stream = ((thread as u64) << 32) | stream as u64;
+        let seed = seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        Self {
+            inner: Pcg64Mcg::seed_from_u64(seed),
+        }
+    }
+}
+
+impl TryRng for DeterministicRng {
+    type Error = Infallible;
+
+    fn try_next_u32(&mut self) -> Result<u32, Infallible> {
+        self.inner.try_next_u32()
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Infallible> {
+        self.inner.try_next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Infallible> {
+        self.inner.try_fill_bytes(dst)
+    }
+}
@@ -0,0 +1,82 @@
+//! Request/response calls over message passing.
+//!
+//! [`call`] sends a request to a thread and waits for the reply to that particular request.
+//! Every call is given a correlation id, and only a reply carrying this id completes it, so a
+//! caller never mistakes the response to another request for its own. The server receives the
+//! request as a [`Request`] and answers it with [`Request::reply`]. TraceForge explores the
+//! interleavings of the requests and replies like those of any other messages.
+//!
+//! ```no_run
+//! use traceforge::rpc::{self, Request};
+//! use traceforge::{thread, Config};
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     let server = thread::spawn(|| {
+//!         let req: Request<u32> = traceforge::recv_msg_block();
+//!         req.reply(*req.body() + 1);
+//!     });
+//!     let resp: u32 = rpc::call(server.thread().id(), 41u32);
+//!     assert_eq!(resp, 42);
+//! });
+//! ```
+
+use crate::msg::Message;
+use crate::runtime::execution::ExecutionState;
+use crate::thread::{self, ThreadId};
+
+/// A request received by a server, see [`call`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request<Req> {
+    caller: ThreadId,
+    id: u32,
+    body: Req,
+}
+
+impl<Req> Request<Req> {
+    /// The thread that made the request
+    pub fn caller(&self) -> ThreadId {
+        self.caller
+    }
+
+    /// The payload of the request
+    pub fn body(&self) -> &Req {
+        &self.body
+    }
+
+    /// Consumes the request and returns its payload
+    pub fn into_body(self) -> Req {
+        self.body
+    }
+
+    /// Sends `resp` to the caller as the reply to this request
+    pub fn reply<Resp: Message + 'static>(&self, resp: Resp) {
+        crate::send_vec_tagged_msg(self.caller, correlation_tag(self.caller, self.id), resp);
+    }
+}
+
+/// Sends `req` to thread `target` and blocks the current thread until its reply arrives.
+///
+/// The target receives a [`Request<Req>`](Request) message and is expected to answer it exactly
+/// once with [`Request::reply`]; the call does not complete otherwise.
+pub fn call<Req, Resp>(target: ThreadId, req: Req) -> Resp
+where
+    Req: Clone + std::fmt::Debug + PartialEq + Message + 'static,
+    Resp: Message + 'static,
+{
+    let caller = thread::current_id();
+    let id = ExecutionState::with(|s| s.must.borrow_mut().next_call_id(caller));
+    crate::send_msg(
+        target,
+        Request {
+            caller,
+            id,
+            body: req,
+        },
+    );
+    let tag = correlation_tag(caller, id);
+    crate::recv_vec_tagged_msg_block(move |_, t| t.as_ref() == Some(&tag))
+}
+
+fn correlation_tag(caller: ThreadId, id: u32) -> Vec<u32> {
+    vec![caller.into(), id]
+}
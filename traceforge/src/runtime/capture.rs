@@ -0,0 +1,149 @@
+//! Buffering of the output of a single execution, for `Config::with_capture_output`.
+//!
+//! While an `OutputCapture` is alive, file descriptors 1 and 2 point to an anonymous temporary
+//! file, so everything written to stdout and stderr (by the model, by libraries, or by
+//! TraceForge itself) ends up in the buffer. `OutputCapture::finish` restores the original
+//! descriptors and returns what was written.
+
+pub(crate) struct OutputCapture {
+    #[cfg(unix)]
+    inner: unix::Redirect,
+}
+
+impl OutputCapture {
+    /// Start capturing stdout and stderr. Returns `None` if the output cannot be redirected,
+    /// in which case it is shown as usual.
+    pub(crate) fn start() -> Option<Self> {
+        #[cfg(unix)]
+        {
+            match unix::Redirect::new() {
+                Ok(inner) => Some(Self { inner }),
+                Err(e) => {
+                    log::warn!("Cannot capture the output of the execution: {}", e);
+                    None
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            log::warn!("Capturing the output of an execution is only supported on Unix");
+            None
+        }
+    }
+
+    /// Stop capturing and return everything written since `start`
+    pub(crate) fn finish(self) -> String {
+        #[cfg(unix)]
+        return self.inner.finish().unwrap_or_default();
+        #[cfg(not(unix))]
+        String::new()
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    pub(super) struct Redirect {
+        file: File,
+        saved: [RawFd; 2],
+        restored: bool,
+    }
+
+    const FDS: [RawFd; 2] = [libc::STDOUT_FILENO, libc::STDERR_FILENO];
+
+    fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(ret)
+        }
+    }
+
+    impl Redirect {
+        pub(super) fn new() -> io::Result<Self> {
+            let file = tempfile()?;
+            flush();
+            let mut saved = [-1; 2];
+            for (i, fd) in FDS.iter().enumerate() {
+                // SAFETY: dup and dup2 only operate on file descriptors owned by this process
+                match unsafe { check(libc::dup(*fd)) } {
+                    Ok(s) => saved[i] = s,
+                    Err(e) => {
+                        restore(&saved);
+                        return Err(e);
+                    }
+                }
+                if let Err(e) = unsafe { check(libc::dup2(file.as_raw_fd(), *fd)) } {
+                    restore(&saved);
+                    return Err(e);
+                }
+            }
+            Ok(Self {
+                file,
+                saved,
+                restored: false,
+            })
+        }
+
+        pub(super) fn finish(mut self) -> io::Result<String> {
+            self.restore();
+            let mut output = Vec::new();
+            self.file.seek(SeekFrom::Start(0))?;
+            self.file.read_to_end(&mut output)?;
+            Ok(String::from_utf8_lossy(&output).into_owned())
+        }
+
+        fn restore(&mut self) {
+            if !self.restored {
+                flush();
+                restore(&self.saved);
+                self.restored = true;
+            }
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            self.restore();
+        }
+    }
+
+    fn flush() {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+    }
+
+    // Point stdout and stderr back to the saved descriptors and close them
+    fn restore(saved: &[RawFd; 2]) {
+        for (fd, s) in FDS.iter().zip(saved) {
+            if *s >= 0 {
+                // SAFETY: `s` is a descriptor returned by dup that nothing else refers to
+                unsafe {
+                    libc::dup2(*s, *fd);
+                    libc::close(*s);
+                }
+            }
+        }
+    }
+
+    // An anonymous file that is removed as soon as it is closed
+    fn tempfile() -> io::Result<File> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "traceforge-output-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(file)
+    }
+}
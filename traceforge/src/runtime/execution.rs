@@ -1,13 +1,23 @@
+use crate::deadlock::{TaskSnapshot, TaskState, WaitReason};
 use crate::event::Event;
+use crate::event_label::{Block, BlockType};
 use crate::must::Must;
-use crate::runtime::failure::{init_panic_hook, persist_task_failure};
+use crate::runtime::capture::OutputCapture;
+use crate::runtime::failure::{
+    init_panic_hook, panic_message, persist_task_failure, record_failure,
+    with_panic_hook_disarmed, FailureCategory,
+};
 //experimental. Unfinished. use crate::runtime::storage::{StorageKey, StorageMap};
 use crate::runtime::task::{Task, TaskId, DEFAULT_INLINE_TASKS};
 use crate::runtime::thread::continuation::PooledContinuation;
+use crate::thread::ThreadId;
+use crate::vector_clock::VectorClock;
+use log::warn;
 use scoped_tls::scoped_thread_local;
 use smallvec::SmallVec;
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::panic;
 use std::rc::Rc;
 
@@ -46,14 +56,37 @@ impl Execution {
     {
         let state = RefCell::new(ExecutionState::new(Rc::clone(&self.must)));
 
-        let _guard = init_panic_hook();
+        let _guard = init_panic_hook(self.must.borrow().config().install_panic_hook);
+
+        let (capture, had_counterexample) = {
+            let must = self.must.borrow();
+            let capture = if must.config().capture_output {
+                OutputCapture::start()
+            } else {
+                None
+            };
+            (capture, must.counterexample_found())
+        };
+
+        let clock_drift = self.must.borrow().config().clock_drift;
+        let max_in_flight = self.must.borrow().config().max_in_flight;
+        let f = move || {
+            if let Some(bound) = clock_drift {
+                crate::time::start_timer_service(bound);
+            }
+            if let Some(k) = max_in_flight {
+                crate::in_flight::start_credit_service(k);
+            }
+            f()
+        };
 
         EXECUTION_STATE.set(&state, move || {
             // Spawn `f` as the first task
             ExecutionState::spawn_thread(
                 f,
                 self.must.borrow().config().stack_size,
-                Some(format!("main-thread-{:?}", std::thread::current().id())),
+                // Not named after the OS thread, so that the logs of two runs are the same
+                Some("main-0".to_string()),
             );
 
             // Run the test 
@@ -70,8 +103,27 @@ impl Execution {
                     Some(e)
                 }
             };
-            
-            ExecutionState::cleanup();
+            if panic_payload.is_none() {
+                self.must.borrow_mut().collect_failure();
+            }
+            let wait_reasons = ExecutionState::with(|state| state.wait_reasons());
+            self.must.borrow_mut().set_wait_reasons(wait_reasons);
+
+            let drop_panic = ExecutionState::cleanup();
+            // A failure of the execution comes first, the panic of a Drop handler is only
+            // reported if the execution had none
+            let panic_payload = panic_payload.or(drop_panic);
+
+            if let Some(capture) = capture {
+                let output = capture.finish();
+                let mut must = self.must.borrow_mut();
+                if panic_payload.is_some() || must.has_failure() {
+                    print!("Output of the failing execution:\n{}", output);
+                    if !had_counterexample {
+                        must.store_captured_output(output);
+                    }
+                }
+            }
 
             if let Some(payload) = panic_payload {
                 panic::resume_unwind(payload);
@@ -84,13 +136,13 @@ impl Execution {
     fn step(&mut self) -> bool {
         enum NextStep {
             Task(Rc<RefCell<PooledContinuation>>),
-            Failure(String),
+            Failure(FailureCategory, String),
             Finished,
         }
 
         let next_step = ExecutionState::with(|state| {
             if let Err(msg) = state.schedule() {
-                return NextStep::Failure(msg);
+                return NextStep::Failure(FailureCategory::Panic, msg);
             }
             state.advance_to_next_task();
 
@@ -110,12 +162,13 @@ impl Execution {
                             .map(|t| {
                                 format!(
                                     "{} (task {})",
-                                    t.name().unwrap_or_else(|| "<unknown>".to_string()),
+                                    t.display_name(),
                                     t.id().0,
                                 )
                             })
                             .collect::<Vec<_>>();
                         NextStep::Failure(
+                            FailureCategory::Deadlock,
                             format!("deadlock! blocked tasks: [{}]", blocked_tasks.join(", ")), // ,
                                                                                                 // state.current_schedule.clone(),
                         )
@@ -125,6 +178,7 @@ impl Execution {
                 }
                 ScheduledTask::Stopped => NextStep::Finished,
                 ScheduledTask::None => NextStep::Failure(
+                    FailureCategory::Panic,
                     "no task was scheduled".to_string(),
                     // state.current_schedule.clone(),
                 ),
@@ -133,10 +187,20 @@ impl Execution {
 
         // Run a single step of the chosen task.
         let ret = match next_step {
-            NextStep::Task(continuation) => panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                continuation.borrow_mut().resume()
-            })),
+            NextStep::Task(continuation) => {
+                #[cfg(debug_assertions)]
+                ExecutionState::with(|s| s.step_start = s.current().instructions);
+                let ret = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                    continuation.borrow_mut().resume()
+                }));
+                #[cfg(debug_assertions)]
+                if ret.is_ok() {
+                    ExecutionState::check_long_step();
+                }
+                ret
+            }
             NextStep::Failure(
+                category,
                 msg, // , schedule
             ) => {
                 // // Because we're creating the panic here, we don't need `persist_failure` to print
@@ -145,7 +209,11 @@ impl Execution {
                 // a panic was already caught, and the counterexample was saved, and the panic
                 // handler was disarmed already. But it's hard to tell...
                 let pos = ExecutionState::failure_info().map(|(_, pos)| pos);
+                record_failure(category, msg.clone(), None);
                 let message = persist_task_failure(msg, pos);
+                if self.must.borrow().collects_failures() {
+                    return false;
+                }
                 panic!("{}", message);
             }
             NextStep::Finished => return false,
@@ -156,13 +224,30 @@ impl Execution {
             Ok(true) => {
                 // Inform Must later so that we record the return value
                 ExecutionState::with(|state| state.current_mut().finish());
+                ExecutionState::check_finish_order();
+                // The end of the task ran after its last scheduling point
+                ExecutionState::check_properties();
             }
             // Task yielded
             Ok(false) => {}
             // Task failed
             Err(e) => {
+                // Without the panic hook, this is the first time the failure is seen
+                record_failure(FailureCategory::Panic, panic_message(&*e), None);
                 let (name, pos) = ExecutionState::failure_info().unwrap();
                 let message = persist_task_failure(name, Some(pos));
+                if self.must.borrow().collects_failures() {
+                    // Like a failed `traceforge::assert` under `keep_going_after_error`, the
+                    // failing task blocks and the execution stops there
+                    ExecutionState::with(|s| {
+                        let pos = s.next_pos();
+                        s.must
+                            .borrow_mut()
+                            .handle_block(Block::new(pos, BlockType::Assert));
+                        s.current_task = ScheduledTask::Stopped;
+                    });
+                    return false;
+                }
                 // Try to inject the schedule into the panic payload if we can
                 let payload: Box<dyn Any + Send> = match e.downcast::<String>() {
                     Ok(panic_msg) => {
@@ -193,6 +278,13 @@ pub(crate) struct ExecutionState {
     pub must: Rc<RefCell<Must>>,
     #[cfg(debug_assertions)]
     has_cleaned_up: bool,
+    // the name and the last event of the task that `cleanup` is dropping
+    cleaning_up: Option<(String, Event)>,
+    // the values of the execution globals used so far, by the address of their static
+    globals: HashMap<usize, Rc<dyn Any>>,
+    // the instructions of the current task when its step started, see `check_long_step`
+    #[cfg(debug_assertions)]
+    step_start: usize,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -218,14 +310,19 @@ impl ScheduledTask {
 
 impl ExecutionState {
     fn new(must: Rc<RefCell<Must>>) -> ExecutionState {
+        let task_capacity = must.borrow().config().task_capacity;
         Self {
-            tasks: SmallVec::new(),
+            tasks: SmallVec::with_capacity(task_capacity),
             current_task: ScheduledTask::None,
             next_task: ScheduledTask::None,
             //storage: StorageMap::new(),
             must,
             #[cfg(debug_assertions)]
             has_cleaned_up: false,
+            cleaning_up: None,
+            globals: HashMap::new(),
+            #[cfg(debug_assertions)]
+            step_start: 0,
         }
     }
 
@@ -240,6 +337,12 @@ impl ExecutionState {
         Self::try_with(f).expect("The TraceForge API (spawn, recv_msg, etc.) should be used only from threads launched using traceforge::spawn and only inside a TraceForge test.")
     }
 
+    /// Whether there is a current ExecutionState, i.e. whether the caller runs inside an execution
+    #[inline]
+    pub(crate) fn is_set() -> bool {
+        EXECUTION_STATE.is_set()
+    }
+
     /// Like `with`, but returns None instead of panicing if there is no current ExecutionState or
     /// if the current ExecutionState is already borrowed.
     #[inline]
@@ -277,7 +380,15 @@ impl ExecutionState {
         })
     }
 
-    fn cleanup() {
+    /// Drops the tasks of the execution, cancelling the unfinished ones. The panic of a Drop
+    /// handler that runs meanwhile, e.g. the one of a value captured by a task that never ran,
+    /// is caught and returned as a failure of the task that owned the value, unless the
+    /// execution already failed or `Config::with_ignore_drop_panics` is set. All the tasks are
+    /// dropped regardless.
+    ///
+    /// The Drop handlers of the values on the stack of a cancelled task run while its stack
+    /// unwinds, where Rust aborts on a panic, so they must not panic.
+    fn cleanup() -> Option<Box<dyn Any + Send>> {
         let (mut tasks, final_state) = Self::with(|state| {
             assert!(
                 state.current_task == ScheduledTask::Stopped
@@ -288,15 +399,34 @@ impl ExecutionState {
                 state.current_task,
             )
         });
+        let ignore = Self::with(|state| {
+            let mut must = state.must.borrow_mut();
+            must.record_task_count(tasks.len());
+            must.config().ignore_drop_panics
+        });
 
+        let mut drop_panic = None;
         for task in tasks.drain(..) {                                                                                                                                                                                   
             let finished = task.finished();
             assert!(                                                                                                                                                                                                    
                 final_state == ScheduledTask::Stopped || finished,                                                                                                                                                    
                 "execution finished but task is not"
             );
-            match Rc::try_unwrap(task.continuation) {
-                Ok(refcell) => {
+            let name = task.display_name();
+            Self::with(|state| {
+                // The graph may have lost the task, e.g. when the execution stopped on a
+                // nondeterminism error
+                let tid = state.must.borrow().try_to_thread_id(task.id());
+                let pos = tid.map(|tid| Event::new(tid, task.instructions as u32));
+                state.cleaning_up = pos.map(|pos| (name.clone(), pos));
+            });
+            let refcell = match Rc::try_unwrap(task.continuation) {
+                Ok(refcell) => refcell,
+                Err(_) => panic!("couldn't cleanup a future"),
+            };
+            // The panic is reported below, if at all
+            let dropped = with_panic_hook_disarmed(|| {
+                panic::catch_unwind(panic::AssertUnwindSafe(|| {
                     if !finished {
                         let mut pc = refcell.borrow_mut();
                         pc.cancel_gen();
@@ -304,15 +434,46 @@ impl ExecutionState {
                         drop(pc);
                     }
                     drop(refcell);
+                }))
+            });
+            if let Err(e) = dropped {
+                let failed = Self::with(|state| state.must.borrow().has_failure());
+                if ignore || failed || drop_panic.is_some() {
+                    warn!(
+                        "ignoring the panic of a Drop handler of task {} during cleanup: {}",
+                        name,
+                        panic_message(&*e)
+                    );
+                } else {
+                    drop_panic = Some(Self::drop_failure(name, e));
                 }
-                Err(_) => panic!("couldn't cleanup a future"),
             }
+            Self::with(|state| state.cleaning_up = None);
         }
 
         // while Self::with(|state| state.storage.pop()).is_some() {}
 
         #[cfg(debug_assertions)]
         Self::with(|state| state.has_cleaned_up = true);
+        drop_panic
+    }
+
+    // Reports the panic of a Drop handler during cleanup as a failure of the task `name`, and
+    // returns the payload to resume the panic with
+    fn drop_failure(name: String, e: Box<dyn Any + Send>) -> Box<dyn Any + Send> {
+        let message = format!(
+            "a Drop handler of task {} panicked during cleanup: {}",
+            name,
+            panic_message(&*e)
+        );
+        record_failure(FailureCategory::Panic, message.clone(), None);
+        let pos = Self::failure_info().map(|(_, pos)| pos);
+        let persisted = persist_task_failure(message.clone(), pos);
+        if persisted.contains(&message) {
+            Box::new(persisted)
+        } else {
+            Box::new(format!("{}\n{}", persisted, message))
+        }
     }
 
     /// Invoke the scheduler to decide which task to schedule next. Returns true if the chosen task
@@ -326,6 +487,7 @@ impl ExecutionState {
                 "we're inside a task and scheduler should not yet have run"
             );
 
+            state.update_clock();
             let result = state.schedule();
             // If scheduling failed, yield so that the outer scheduling loop can handle it.
             if result.is_err() {
@@ -343,18 +505,119 @@ impl ExecutionState {
         })
     }
 
+    /// Check the invariants registered with `ConfigBuilder::with_invariant`, and record which of
+    /// the `ConfigBuilder::with_eventually` properties hold. Called at every scheduling point, so
+    /// a violation is reported at the last event of the current task, i.e. the event after which
+    /// the invariant first broke.
+    pub(crate) fn check_properties() {
+        let (invariants, eventually) = Self::with(|state| {
+            let must = state.must.borrow();
+            (must.config().invariants.clone(), must.config().eventually.clone())
+        });
+        for (i, property) in eventually.iter().enumerate() {
+            if !Self::with(|state| state.must.borrow().eventually_held(i))
+                && (property.lock().unwrap())()
+            {
+                Self::with(|state| state.must.borrow_mut().set_eventually_held(i));
+            }
+        }
+        for (i, invariant) in invariants.iter().enumerate() {
+            let holds = (invariant.lock().unwrap())();
+            // As for assertions, only report violations in consistent executions
+            if !holds && Self::with(|state| state.must.borrow().is_consistent()) {
+                let (name, pos) = Self::failure_info().unzip();
+                let msg = match (name, pos) {
+                    (Some(name), Some(pos)) => format!(
+                        "invariant {} violated after event {} of task {}",
+                        i, pos, name
+                    ),
+                    _ => format!("invariant {} violated", i),
+                };
+                record_failure(FailureCategory::Invariant, msg.clone(), None);
+                let message = persist_task_failure(msg, pos);
+                panic!("{}", message);
+            }
+        }
+    }
+
+    // The name of thread `tid`, or its id if it has no name
+    fn thread_name(&self, tid: ThreadId) -> String {
+        let task = self.must.borrow().to_task_id(tid);
+        let name = task.and_then(|t| self.try_get(t)).and_then(|t| t.name());
+        name.unwrap_or_else(|| format!("thread {}", tid))
+    }
+
+    /// Fails the execution if the current task, which just finished, had to finish after a task
+    /// that did not finish before it, see `assert_finishes_before`
+    pub(crate) fn check_finish_order() {
+        let unfinished = Self::with(|state| {
+            let must = state.must.borrow();
+            let me = must.to_thread_id(state.current().id());
+            let before = must.finish_before(me);
+            let first = before.into_iter().find(|t| !must.finished_before(*t, me))?;
+            if !must.is_consistent() {
+                return None;
+            }
+            Some((me, first))
+        });
+        if let Some((me, first)) = unfinished {
+            Self::fail_finish_order(me, first);
+        }
+    }
+
+    /// Fails the execution because thread `second` finished before thread `first`, which had to
+    /// finish first, like a violated invariant
+    pub(crate) fn fail_finish_order(second: ThreadId, first: ThreadId) -> ! {
+        let (second, first) =
+            Self::with(|state| (state.thread_name(second), state.thread_name(first)));
+        let msg = format!("{} finished before {}, which has to finish first", second, first);
+        let pos = Self::failure_info().map(|(_, pos)| pos);
+        record_failure(FailureCategory::Invariant, msg.clone(), None);
+        let message = persist_task_failure(msg, pos);
+        panic!("{}", message);
+    }
+
+    /// Warn if the current task executed more instructions than
+    /// `ConfigBuilder::with_long_step_warning` in the step that just ended, i.e., since the
+    /// scheduler last switched to it. The position of the task tells where the step ended.
+    #[cfg(debug_assertions)]
+    fn check_long_step() {
+        let (instructions, threshold) = Self::with(|state| {
+            let instructions = state.current().instructions.saturating_sub(state.step_start);
+            (instructions, state.must.borrow().config().long_step_warning)
+        });
+        if instructions > threshold {
+            if let Some((name, pos)) = Self::failure_info() {
+                Self::with(|state| {
+                    state
+                        .must
+                        .borrow_mut()
+                        .record_long_step(&name, pos, instructions)
+                });
+            }
+        }
+    }
+
+    /// The value of the execution global `key`, if it was used in this execution
+    pub(crate) fn global(&self, key: usize) -> Option<Rc<dyn Any>> {
+        self.globals.get(&key).cloned()
+    }
+
+    pub(crate) fn set_global(&mut self, key: usize, value: Rc<dyn Any>) {
+        self.globals.insert(key, value);
+    }
+
     /// Generate some diagnostic information used when persisting failures.
     ///
     /// Because this method may be called from a panic hook, it must not panic.
     pub(crate) fn failure_info() -> Option<(String, Event)> {
         let fi: Option<Option<(String, Event)>> = Self::try_with(|state| {
             if let Some(task) = state.try_current() {
-                let name = task
-                    .name()
-                    .unwrap_or_else(|| format!("task-{:?}", task.id().0));
+                let name = task.display_name();
                 Some((name, state.curr_pos()))
             } else {
-                None
+                // A failure during cleanup belongs to the task being dropped
+                state.cleaning_up.clone()
             }
         });
         fi.flatten()
@@ -417,6 +680,77 @@ impl ExecutionState {
         Event::new(tid, icount)
     }
 
+    /// Refreshes the clock of the current task from the execution graph and returns it
+    pub(crate) fn update_clock(&mut self) -> VectorClock {
+        let pos = self.curr_pos();
+        let clock = self.must.borrow().porf_view(pos);
+        if let Some(clock) = clock {
+            self.current_mut().clock = clock;
+        }
+        self.current().clock.clone()
+    }
+
+    /// Number of tasks that the scheduler could pick next
+    pub(crate) fn runnable_count(&self) -> usize {
+        let must = self.must.borrow();
+        self.tasks
+            .iter()
+            .filter(|t| t.runnable() && must.is_task_enabled(t.id, t.instructions))
+            .count()
+    }
+
+    /// The tasks that cannot run until another task acts, and what they wait for
+    pub(crate) fn blocked_tasks(&self) -> Vec<(ThreadId, WaitReason)> {
+        let must = self.must.borrow();
+        self.tasks
+            .iter()
+            .filter(|t| !t.finished() && !must.is_task_enabled(t.id, t.instructions))
+            .filter_map(|t| {
+                let tid = must.to_thread_id(t.id);
+                let reason = must.block_reason(tid)?;
+                Some((tid, t.wait_reason.clone().unwrap_or(reason)))
+            })
+            .collect()
+    }
+
+    // What the unfinished tasks waited for in the primitives they blocked in
+    fn wait_reasons(&self) -> HashMap<ThreadId, WaitReason> {
+        let must = self.must.borrow();
+        self.tasks
+            .iter()
+            .filter(|t| !t.finished())
+            .filter_map(|t| Some((must.try_to_thread_id(t.id)?, t.wait_reason.clone()?)))
+            .collect()
+    }
+
+    pub(crate) fn task_graph(&self) -> Vec<TaskSnapshot> {
+        let must = self.must.borrow();
+        self.tasks
+            .iter()
+            .map(|t| {
+                let id = must.to_thread_id(t.id);
+                let state = if t.finished() {
+                    TaskState::Finished
+                } else if must.is_task_enabled(t.id, t.instructions) {
+                    TaskState::Runnable
+                } else {
+                    TaskState::Blocked
+                };
+                let wait_reason = match state {
+                    TaskState::Blocked => t.wait_reason.clone().or_else(|| must.block_reason(id)),
+                    _ => None,
+                };
+                TaskSnapshot {
+                    id,
+                    name: t.name(),
+                    state,
+                    wait_reason,
+                    detached: must.is_thread_daemon(id),
+                }
+            })
+            .collect()
+    }
+
     /// Run the scheduler to choose the next task to run. `has_yielded` should be false if the
     /// scheduler is being invoked from within a running task. If scheduling fails, returns an Err
     /// with a String describing the failure.
@@ -427,6 +761,18 @@ impl ExecutionState {
             return Ok(());
         }
 
+        // No context switch inside an atomic region, unless the task cannot go on. Replayed
+        // events keep the schedule they were explored with.
+        if let Some(task) = self.try_current() {
+            if task.atomic_depth > 0
+                && task.runnable()
+                && !self.must.borrow().is_replay(self.curr_pos().next())
+            {
+                self.next_task = self.current_task;
+                return Ok(());
+            }
+        }
+
         let runnable = self
             .tasks
             .iter()
@@ -446,6 +792,13 @@ impl ExecutionState {
             .next_task(&runnable, self.current_task.id())
             .map(ScheduledTask::Some)
             .unwrap_or(ScheduledTask::Stopped);
+        if let ScheduledTask::Some(next) = self.next_task {
+            let mut must = self.must.borrow_mut();
+            if must.config().profile {
+                let tid = must.to_thread_id(next);
+                must.profile_step(tid, || self.get(next).display_name());
+            }
+        }
 
         // trace!(?runnable, next_task=?self.next_task);
 
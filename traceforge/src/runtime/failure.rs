@@ -19,15 +19,104 @@
 //!    and again when we catch the panic (where we can modify the payload). We don't want to print
 //!    the schedule twice, so we keep track of whether the info has already been printed.
 
+use std::any::Any;
+use std::fmt;
 use std::panic;
 use std::sync::{Mutex, Once};
 
 use log::error;
+use serde::{Deserialize, Serialize};
 
 use crate::event::Event;
 use crate::must::Must;
 use crate::runtime::execution::ExecutionState;
 
+/// The kind of failure recorded in a counterexample
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureCategory {
+    /// A user assertion (`traceforge::assert!` and friends) failed
+    Assertion,
+    /// Any other panic raised by the model or by the runtime
+    Panic,
+    /// No task can make progress
+    Deadlock,
+    /// A monitor reported an error when the execution ended
+    Monitor,
+    /// A global invariant did not hold after a step
+    Invariant,
+    /// An execution completed without an eventually property ever holding
+    Liveness,
+}
+
+impl FailureCategory {
+    /// The name of the category, e.g. `assertion`
+    pub fn name(&self) -> &'static str {
+        match self {
+            FailureCategory::Assertion => "assertion",
+            FailureCategory::Panic => "panic",
+            FailureCategory::Deadlock => "deadlock",
+            FailureCategory::Monitor => "monitor",
+            FailureCategory::Invariant => "invariant",
+            FailureCategory::Liveness => "liveness",
+        }
+    }
+}
+
+/// Describes why an execution failed and where the failure was raised
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct FailureReport {
+    pub(crate) category: FailureCategory,
+    pub(crate) message: String,
+    // name of the task that raised the failure and the event it was about to execute
+    pub(crate) task: Option<String>,
+    pub(crate) pos: Option<Event>,
+    // source location of the failing assertion
+    pub(crate) location: Option<String>,
+}
+
+impl fmt::Display for FailureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.category.name(), self.message)?;
+        if let Some(task) = &self.task {
+            write!(f, "\n  in task: {}", task)?;
+        }
+        if let Some(pos) = &self.pos {
+            write!(f, "\n  at event: {}", pos)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, "\n  at: {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// Record why the current execution failed, so that the reason ends up in the counterexample.
+/// Only the first failure of an execution is kept.
+///
+/// Because this method may be called from a panic hook, it must not panic.
+pub(crate) fn record_failure(
+    category: FailureCategory,
+    message: String,
+    location: Option<String>,
+) {
+    let (task, pos) = match ExecutionState::failure_info() {
+        Some((name, pos)) => (Some(name), Some(pos)),
+        None => (None, None),
+    };
+    if let Some(must) = Must::current() {
+        if let Ok(mut must) = must.try_borrow_mut() {
+            must.record_failure(FailureReport {
+                category,
+                message,
+                task,
+                pos,
+                location,
+            });
+        }
+    }
+}
+
 pub(crate) fn persist_task_failure(message: String, pos: Option<Event>) -> String {
     // Disarm the panic hook so that we don't print the failure twice
     if let PanicHookState::Persisted(persisted_message) =
@@ -35,9 +124,20 @@ pub(crate) fn persist_task_failure(message: String, pos: Option<Event>) -> Strin
     {
         return persisted_message;
     }
+    let mut persisted_message = message;
     if let Some(must) = Must::current() {
         if let Ok(mut must) = must.try_borrow_mut() {
+            if must.config().fast_failure {
+                let message = must.fast_failure_message();
+                PANIC_HOOK.with(|lock| {
+                    *lock.lock().unwrap() = PanicHookState::Persisted(message.clone())
+                });
+                return message;
+            }
             must.store_replay_information(pos);
+            if let Some(hint) = must.reproduction_hint() {
+                persisted_message = format!("{}\n{}", persisted_message, hint);
+            }
         } else {
             error!("Couldn't generate a counterexample because Must::current is borrowed");
         }
@@ -45,7 +145,6 @@ pub(crate) fn persist_task_failure(message: String, pos: Option<Event>) -> Strin
         error!("Couldn't generate a counterexample because Must::current returned None");
     }
 
-    let persisted_message = message;
     PANIC_HOOK
         .with(|lock| *lock.lock().unwrap() = PanicHookState::Persisted(persisted_message.clone()));
     println!("{}", persisted_message);
@@ -63,6 +162,16 @@ thread_local! {
     static PANIC_HOOK: Mutex<PanicHookState> = const { Mutex::new(PanicHookState::Disarmed) };
 }
 
+/// Runs `f` with the panic hook disarmed, so that its panics are not reported as failures
+pub(crate) fn with_panic_hook_disarmed<T>(f: impl FnOnce() -> T) -> T {
+    let state = PANIC_HOOK.with(|lock| {
+        std::mem::replace(&mut *lock.lock().unwrap(), PanicHookState::Disarmed)
+    });
+    let result = f();
+    PANIC_HOOK.with(|lock| *lock.lock().unwrap() = state);
+    result
+}
+
 /// A guard that disarms the panic hook when dropped
 #[derive(Debug)]
 #[non_exhaustive]
@@ -75,13 +184,39 @@ impl Drop for PanicHookGuard {
     }
 }
 
+// Whether the current exploration reports failures in fast mode, see
+// `Config::with_fast_failure`
+fn fast_failure() -> bool {
+    Must::current()
+        .and_then(|must| must.try_borrow().ok().map(|m| m.config().fast_failure))
+        .unwrap_or(false)
+}
+
+/// The message of a panic payload, if it carries one
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "A panic was detected".to_string()
+    }
+}
+
 /// Set up a panic hook that will try to print the current schedule to stderr so that the failure
 /// can be replayed. Returns a guard that will disarm the panic hook when dropped.
 ///
+/// If `install` is false (see `Config::with_install_panic_hook`), the process' panic hook is left
+/// alone and failures are only reported once the panic has been caught.
+///
 /// See the module documentation for more details on how this method fits into the failure reporting
 /// story.
 #[must_use = "the panic hook will be disarmed when the returned guard is dropped"]
-pub(crate) fn init_panic_hook() -> PanicHookGuard {
+pub(crate) fn init_panic_hook(install: bool) -> PanicHookGuard {
+    if !install {
+        PANIC_HOOK.with(|lock| *lock.lock().unwrap() = PanicHookState::Disarmed);
+        return PanicHookGuard;
+    }
     static INIT: Once = Once::new();
     INIT.call_once(|| {
         let original_hook = panic::take_hook();
@@ -91,13 +226,19 @@ pub(crate) fn init_panic_hook() -> PanicHookGuard {
             });
             // The hook is armed if this is the first time it's fired
             if let PanicHookState::Armed = state {
+                let message = panic_message(panic_info.payload());
+                let location = panic_info.location().map(|l| l.to_string());
+                record_failure(FailureCategory::Panic, message, location);
                 if let Some((name, pos)) = ExecutionState::failure_info() {
                     persist_task_failure(name, Some(pos));
                 } else {
                     persist_task_failure("A panic was detected".to_string(), None);
                 }
             }
-            original_hook(panic_info);
+            // In fast failure mode, the panic is reported by its payload only
+            if !fast_failure() {
+                original_hook(panic_info);
+            }
         }));
     });
 
@@ -1,3 +1,4 @@
+pub(crate) mod capture;
 pub(crate) mod execution;
 pub(crate) mod failure;
 //UNFINISHED pub(crate) mod storage;
@@ -1,6 +1,11 @@
+use crate::deadlock::WaitReason;
+use crate::loc::Loc;
+use crate::msg::Val;
 use crate::runtime::thread::continuation::{ContinuationPool, PooledContinuation};
+use crate::vector_clock::VectorClock;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::rc::Rc;
 
@@ -34,6 +39,16 @@ pub(crate) struct Task {
     pub(super) continuation: Rc<RefCell<PooledContinuation>>,
     pub(crate) instructions: usize,
     name: Option<String>,
+    // The causal view of the task's latest event, refreshed at every scheduling point
+    pub(crate) clock: VectorClock,
+    // Messages of received batches that have not been handed out yet
+    pub(crate) batched: VecDeque<(Loc, Val)>,
+    // Nesting depth of the `atomic_region`s the task is running
+    pub(crate) atomic_depth: usize,
+    // Whether the task is polling a future, from `block_on` or as an async task
+    pub(crate) polling: bool,
+    // What the task waits for, as recorded by the primitive it blocks in, see `blocked_tasks`
+    pub(crate) wait_reason: Option<WaitReason>,
 }
 
 impl Task {
@@ -52,6 +67,11 @@ impl Task {
             continuation,
             instructions: 0,
             name,
+            clock: VectorClock::new(),
+            batched: VecDeque::new(),
+            atomic_depth: 0,
+            polling: false,
+            wait_reason: None,
         }
     }
 
@@ -68,6 +88,16 @@ impl Task {
         Self::new(f, stack_size, id, name)
     }
 
+    /// Takes the oldest pending batched message sent to one of `locs`,
+    /// together with the index of its location in `locs`
+    pub(crate) fn take_batched(&mut self, locs: &[&Loc]) -> Option<(Val, usize)> {
+        let (i, ind) =
+            self.batched.iter().enumerate().find_map(|(i, (loc, _))| {
+                locs.iter().position(|l| *l == loc).map(|ind| (i, ind))
+            })?;
+        self.batched.remove(i).map(|(_, val)| (val, ind))
+    }
+
     pub(crate) fn id(&self) -> TaskId {
         self.id
     }
@@ -102,6 +132,12 @@ impl Task {
     pub(crate) fn name(&self) -> Option<String> {
         self.name.clone()
     }
+
+    /// The name of the task in the reports of TraceForge, e.g. `task-3` if it has no name. It
+    /// only depends on the execution, so that the logs of two runs can be compared.
+    pub(crate) fn display_name(&self) -> String {
+        self.name().unwrap_or_else(|| format!("task-{}", self.id.0))
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
@@ -326,6 +326,7 @@ pub(crate) fn switch() {
     if std::thread::panicking() {
         return;
     }
+    ExecutionState::check_properties();
     if ExecutionState::maybe_yield() {
         let r = generator::yield_(ContinuationOutput::Yielded).unwrap();
         assert!(matches!(r, ContinuationInput::Resume));
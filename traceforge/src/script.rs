@@ -0,0 +1,75 @@
+//! Handwritten schedules, for [`run_script`].
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::must::Must;
+use crate::thread::{self, ThreadId};
+use crate::{explore, Config, Stats};
+
+/// A scheduling decision of a script, see [`run_script`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Runs the task `tn`, as numbered in the execution graphs, to its next scheduling point.
+    /// The main task is `t0`, and the other tasks are numbered in the order in which they were
+    /// spawned, the runtime tasks of the primitives included.
+    Task(u32),
+}
+
+impl Action {
+    pub(crate) fn thread_id(&self) -> ThreadId {
+        match self {
+            Action::Task(n) => thread::construct_thread_id(*n),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Task(n) => write!(f, "Task({})", n),
+        }
+    }
+}
+
+/// Model Checker API
+///
+/// Runs `f` once, on the schedule of `script`, instead of exploring its executions, e.g. to
+/// unit test one particular interleaving. Each action of the script is a scheduling decision:
+/// the first one starts the execution, so it is usually `Action::Task(0)`, and each of the
+/// next ones picks the task that runs from the scheduling point reached by the previous one,
+/// like the steps of [`replay_steps`](crate::replay_steps). Once the script is over, the
+/// execution goes on with the schedule policy of `config`, so a script only needs to spell
+/// out the interleaving up to the point of interest.
+///
+/// ```no_run
+/// use traceforge::script::Action::Task;
+/// use traceforge::{thread, Config};
+///
+/// // The main task starts, spawns t1, and lets it send before receiving
+/// let script = [Task(0), Task(0), Task(1), Task(0)];
+/// traceforge::run_script(Config::default(), &script, || {
+///     let h = thread::spawn(|| traceforge::send_msg(thread::main_thread_id(), 1));
+///     let v: i32 = traceforge::recv_msg_block();
+///     assert_eq!(v, 1);
+///     h.join().unwrap();
+/// });
+/// ```
+///
+/// # Panics
+///
+/// Panics if the script is infeasible, i.e. if one of its actions picks a task that cannot
+/// run at that point, because it does not exist yet, is blocked or has finished. Also panics
+/// if the execution fails, like [`verify`](crate::verify).
+pub fn run_script<F>(config: Config, script: &[Action], f: F) -> Stats
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let must = Rc::new(RefCell::new(Must::new(config, false)));
+    must.borrow_mut().set_script(script);
+    explore(&must, &Arc::new(f));
+    let stats = must.borrow().stats();
+    stats
+}
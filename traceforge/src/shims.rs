@@ -0,0 +1,112 @@
+//! Concurrency primitives that resolve to TraceForge's or to the native ones, so that the same
+//! code can run in production and be model checked.
+//!
+//! Code written against `traceforge::shims` uses the [`native`] primitives of `std` and `tokio`,
+//! unless the `model` feature of TraceForge is enabled, in which case it uses the [`model`]
+//! primitives of TraceForge, with the same signatures. A crate typically enables the feature
+//! for its model-checking tests only:
+//!
+//! ```toml
+//! [features]
+//! model = ["traceforge/model"]
+//! ```
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use traceforge::shims::{self, atomic::{AtomicUsize, Ordering}};
+//!
+//! fn count_in_parallel() -> usize {
+//!     let count = Arc::new(AtomicUsize::new(0));
+//!     let workers: Vec<_> = (0..2)
+//!         .map(|_| {
+//!             let count = count.clone();
+//!             shims::spawn(move || {
+//!                 count.store(count.load(Ordering::SeqCst) + 1, Ordering::SeqCst)
+//!             })
+//!         })
+//!         .collect();
+//!     workers.into_iter().for_each(|w| w.join().unwrap());
+//!     count.load(Ordering::SeqCst)
+//! }
+//! ```
+//!
+//! Only the functionality that TraceForge models is available: the atomics only support
+//! `SeqCst` accesses, and the `Mutex` is `tokio`'s asynchronous one.
+
+/// The primitives of `std` and `tokio`
+pub mod native {
+    pub use std::thread::{sleep, spawn, yield_now, JoinHandle};
+    pub use tokio::sync::{Mutex, MutexGuard};
+
+    /// Atomic types
+    pub mod atomic {
+        pub use std::sync::atomic::{
+            AtomicBool, AtomicI16, AtomicI32, AtomicI64, AtomicI8, AtomicIsize, AtomicU16,
+            AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+        };
+    }
+}
+
+/// The primitives of TraceForge, with the signatures of their [`native`] counterparts. They
+/// can only be used in a model run by TraceForge.
+pub mod model {
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    pub use crate::sync::{Mutex, MutexGuard};
+    pub use crate::thread::{sleep, yield_now};
+
+    use crate::thread::{self, Thread};
+
+    /// Atomic types
+    pub mod atomic {
+        pub use crate::sync::atomic::{
+            AtomicBool, AtomicI16, AtomicI32, AtomicI64, AtomicI8, AtomicIsize, AtomicU16,
+            AtomicU32, AtomicU64, AtomicU8, AtomicUsize,
+        };
+        pub use std::sync::atomic::Ordering;
+    }
+
+    /// An owned permission to join on a thread, like [`std::thread::JoinHandle`]
+    #[derive(Debug)]
+    pub struct JoinHandle<T> {
+        inner: thread::JoinHandle<()>,
+        // Unlike the result of a TraceForge thread, the result of a native thread need not be a
+        // message, so it is handed back here
+        result: Arc<StdMutex<Option<T>>>,
+    }
+
+    impl<T> JoinHandle<T> {
+        /// Waits for the associated thread to finish, and returns its result
+        pub fn join(self) -> std::thread::Result<T> {
+            self.inner.join()?;
+            let result = self.result.lock().unwrap().take();
+            Ok(result.expect("the thread finished without a result"))
+        }
+
+        /// Extracts a handle to the underlying thread
+        pub fn thread(&self) -> &Thread {
+            self.inner.thread()
+        }
+    }
+
+    /// Spawns a new thread, like [`std::thread::spawn`]
+    pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let result = Arc::new(StdMutex::new(None));
+        let slot = result.clone();
+        let inner = thread::spawn(move || {
+            let value = f();
+            *slot.lock().unwrap() = Some(value);
+        });
+        JoinHandle { inner, result }
+    }
+}
+
+#[cfg(not(feature = "model"))]
+pub use native::*;
+
+#[cfg(feature = "model")]
+pub use model::*;
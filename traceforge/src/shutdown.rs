@@ -0,0 +1,72 @@
+//! A broadcast-style shutdown signal for modeling cooperative shutdown.
+//!
+//! [`channel`] returns a [`Trigger`] and a [`Shutdown`] handle. The `Shutdown` handle can be
+//! cloned and given to any number of tasks, which wait for the signal with
+//! [`Shutdown::cancelled`]. Once [`Trigger::cancel`] is called, every pending and future call to
+//! `cancelled()` completes. TraceForge explores the orderings in which the tasks observe the
+//! cancellation.
+//!
+//! ```no_run
+//! use traceforge::{future, shutdown, Config};
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     future::block_on(async {
+//!         let (trigger, shutdown) = shutdown::channel();
+//!         let worker = future::spawn(async move {
+//!             shutdown.cancelled().await;
+//!         });
+//!         trigger.cancel();
+//!         worker.await.unwrap();
+//!     });
+//! });
+//! ```
+
+use crate::sync::CancellationToken;
+
+/// Creates a new shutdown signal, returning the trigger and a handle for the tasks to observe.
+pub fn channel() -> (Trigger, Shutdown) {
+    let token = CancellationToken::new();
+    (
+        Trigger {
+            token: token.clone(),
+        },
+        Shutdown { token },
+    )
+}
+
+/// The sending side of a shutdown signal
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    token: CancellationToken,
+}
+
+impl Trigger {
+    /// Signals shutdown to all tasks holding a [`Shutdown`] handle.
+    /// Calling `cancel` more than once has no further effect.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns whether [`Trigger::cancel`] has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+/// The receiving side of a shutdown signal
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    token: CancellationToken,
+}
+
+impl Shutdown {
+    /// Completes once shutdown has been signaled. Returns immediately if it already was.
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// Returns whether shutdown has been signaled
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
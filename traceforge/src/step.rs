@@ -0,0 +1,43 @@
+//! The scheduling decisions of a replayed execution, for [`replay_steps`](crate::replay_steps).
+
+use std::fmt;
+
+use crate::event::Event;
+use crate::thread::ThreadId;
+
+// Called at every scheduling decision of a replay, see `replay_steps`
+pub(crate) type StepHook = Box<dyn FnMut(&Step)>;
+
+/// The state of a replayed execution at one of its scheduling decisions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The number of scheduling decisions taken before this one
+    pub index: usize,
+    /// The task that reached the scheduling point, or `None` at the start of the execution
+    pub current: Option<ThreadId>,
+    /// The tasks that could run, the scheduled one included
+    pub runnable: Vec<ThreadId>,
+    /// The task scheduled to run next
+    pub next: ThreadId,
+    /// The event that the scheduled task executes next
+    pub event: Event,
+    /// The event, as printed in the execution graphs, e.g. `(t1, 2): RECV(...)`
+    pub label: String,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let runnable = self
+            .runnable
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "step {}: runnable [{}], running {}",
+            self.index,
+            runnable.join(", "),
+            self.label
+        )
+    }
+}
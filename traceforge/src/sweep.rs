@@ -0,0 +1,114 @@
+//! Verification of a model over a sweep of inputs.
+//!
+//! [`verify_for_each`] explores a parameterized model once per input, and groups the
+//! statistics of the explorations by input, so that the outcome of each value of the
+//! parameter can be told apart.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::must::Must;
+use crate::runtime::failure::panic_message;
+use crate::{Config, Stats};
+
+/// The exploration of one input of [`verify_for_each`]
+#[derive(Clone, Debug)]
+pub struct InputStats<T> {
+    /// The input the model was explored with
+    pub input: T,
+    /// The statistics of the executions explored with this input
+    pub stats: Stats,
+    /// The failure that stopped the exploration of this input, if it failed
+    pub failure: Option<String>,
+}
+
+/// The statistics of a [`verify_for_each`] sweep, grouped by input
+#[derive(Clone, Debug)]
+pub struct SweepReport<T> {
+    /// The explorations, in the order of their inputs
+    pub inputs: Vec<InputStats<T>>,
+}
+
+impl<T> SweepReport<T> {
+    /// The statistics of the explorations of all the inputs together
+    pub fn total(&self) -> Stats {
+        let mut total = Stats::default();
+        for input in &self.inputs {
+            total.add(&input.stats);
+        }
+        total
+    }
+
+    /// The explorations that failed
+    pub fn failures(&self) -> impl Iterator<Item = &InputStats<T>> {
+        self.inputs.iter().filter(|i| i.failure.is_some())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for SweepReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in &self.inputs {
+            write!(
+                f,
+                "{:?}: {} executions, {} blocked",
+                i.input, i.stats.execs, i.stats.block
+            )?;
+            match &i.failure {
+                Some(failure) => writeln!(f, ", failed: {}", failure)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verifies `f` like [`verify`](crate::verify), once for each of the `inputs`, and reports the
+/// statistics of each input.
+///
+/// A failing input does not stop the sweep: its exploration stops at the failure, which is
+/// reported along with the statistics of the executions explored until then. The inputs are
+/// explored one after the other, each exploration being sequential.
+pub fn verify_for_each<T, I, F>(mut conf: Config, inputs: I, f: F) -> SweepReport<T>
+where
+    I: IntoIterator<Item = T>,
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T) + Send + Sync + 'static,
+{
+    conf.parallel = false;
+    conf.partitioned_parallelization = false;
+    let f = Arc::new(f);
+    let inputs = inputs
+        .into_iter()
+        .map(|input| {
+            let (f, arg) = (f.clone(), input.clone());
+            let (stats, failure) = explore_input(&conf, Arc::new(move || f(&arg)));
+            InputStats {
+                input,
+                stats,
+                failure,
+            }
+        })
+        .collect();
+    SweepReport { inputs }
+}
+
+// Explores `f`, and returns its statistics with the failure that stopped the exploration, if any
+fn explore_input<F>(conf: &Config, f: Arc<F>) -> (Stats, Option<String>)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let must = Rc::new(RefCell::new(Must::new(conf.clone(), false)));
+    let explored = catch_unwind(AssertUnwindSafe(|| crate::explore(&must, &f)));
+    let must = must.borrow();
+    let failure = match explored {
+        Ok(()) => None,
+        Err(payload) => Some(match must.failure() {
+            Some(failure) => failure.to_string(),
+            None => panic_message(&*payload),
+        }),
+    };
+    (must.stats(), failure)
+}
@@ -90,8 +90,8 @@ impl<T: Clone + Debug + PartialEq + std::marker::Send + 'static> AtomicRegister<
         if order != Ordering::SeqCst {
             panic!("Load accesses to AtomicRegister are only implemented for SeqCst")
         }
-        let chan = channel::Builder::<MsgResponse<T>>::new().build();
-        send_msg(*self.synchronizer, MsgRequest::Read(chan.0));
+        let chan = channel::Builder::<MsgResponse<T>>::new().internal().build();
+        send_internal_msg(*self.synchronizer, MsgRequest::Read(chan.0));
         match chan.1.recv_msg_block() {
             MsgResponse::ReadResponse(x) => x,
             MsgResponse::WriteResponse => panic!("Error in the implementation of AtomicRegister"),
@@ -103,8 +103,8 @@ impl<T: Clone + Debug + PartialEq + std::marker::Send + 'static> AtomicRegister<
         if order != Ordering::SeqCst {
             panic!("Store accesses to AtomicRegister are only implemented for SeqCst")
         }
-        let chan = channel::Builder::<MsgResponse<T>>::new().build();
-        send_msg(*self.synchronizer, MsgRequest::Write(new, chan.0));
+        let chan = channel::Builder::<MsgResponse<T>>::new().internal().build();
+        send_internal_msg(*self.synchronizer, MsgRequest::Write(new, chan.0));
         match chan.1.recv_msg_block() {
             MsgResponse::ReadResponse(_) => panic!("Error in the implementation of AtomicRegister"),
             MsgResponse::WriteResponse => (),
@@ -121,8 +121,8 @@ impl<T: Clone + Debug + PartialEq + std::marker::Send + 'static> AtomicRegister<
         _failure: Ordering,
     ) -> Result<T, T> {
         info!("This is an incomplete implementation. It only handles Sequential Consistency");
-        let chan = channel::Builder::<MsgResponse<T>>::new().build();
-        send_msg(
+        let chan = channel::Builder::<MsgResponse<T>>::new().internal().build();
+        send_internal_msg(
             *self.synchronizer,
             MsgRequest::CAS(current.clone(), new.clone(), chan.0),
         );
@@ -0,0 +1,102 @@
+//! TraceForge's implementation of `tokio_util::sync::CancellationToken`.
+
+use std::sync::Arc;
+
+use crate::sync::oneshot;
+use crate::sync::Mutex;
+
+#[derive(Debug)]
+struct State {
+    cancelled: bool,
+    waiters: Vec<oneshot::Sender<()>>,
+    children: Vec<Arc<Mutex<State>>>,
+}
+
+impl State {
+    fn new(cancelled: bool) -> Arc<Mutex<State>> {
+        Arc::new(Mutex::new(State {
+            cancelled,
+            waiters: Vec::new(),
+            children: Vec::new(),
+        }))
+    }
+}
+
+/// A token which can be used to signal a cancellation request to one or more tasks.
+///
+/// Cancelling a token also cancels all the tokens derived from it with
+/// [`CancellationToken::child_token`], but cancelling a child token does not affect its parent.
+/// The cancellation is propagated to the children one at a time, so TraceForge explores the
+/// interleavings of the propagation with the rest of the model.
+#[derive(Clone, Debug)]
+pub struct CancellationToken {
+    state: Arc<Mutex<State>>,
+}
+
+impl CancellationToken {
+    /// Creates a new `CancellationToken` in the non-cancelled state.
+    pub fn new() -> Self {
+        CancellationToken {
+            state: State::new(false),
+        }
+    }
+
+    /// Creates a token that is cancelled whenever this token is cancelled.
+    /// If this token is already cancelled, so is the child.
+    pub fn child_token(&self) -> CancellationToken {
+        let mut state = self.state.blocking_lock();
+        let child = State::new(state.cancelled);
+        if !state.cancelled {
+            state.children.push(child.clone());
+        }
+        CancellationToken { state: child }
+    }
+
+    /// Cancels the token and all of its descendants.
+    /// Calling `cancel` on an already cancelled token has no effect.
+    pub fn cancel(&self) {
+        Self::cancel_state(&self.state);
+    }
+
+    fn cancel_state(state: &Arc<Mutex<State>>) {
+        let children = {
+            let mut state = state.blocking_lock();
+            if state.cancelled {
+                return;
+            }
+            state.cancelled = true;
+            for waiter in state.waiters.drain(..) {
+                let _ = waiter.send(());
+            }
+            std::mem::take(&mut state.children)
+        };
+        for child in children.iter() {
+            Self::cancel_state(child);
+        }
+    }
+
+    /// Returns whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.blocking_lock().cancelled
+    }
+
+    /// Completes once the token has been cancelled. Returns immediately if it already was.
+    pub async fn cancelled(&self) {
+        let rx = {
+            let mut state = self.state.blocking_lock();
+            if state.cancelled {
+                return;
+            }
+            let (tx, rx) = oneshot::channel::<()>();
+            state.waiters.push(tx);
+            rx
+        };
+        let _ = rx.await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
+    }
+}
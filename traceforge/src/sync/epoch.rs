@@ -0,0 +1,150 @@
+//! Epoch-based memory reclamation, like `crossbeam-epoch`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::sync::Mutex;
+use crate::thread::{self, ThreadId};
+
+type Deferred = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct State {
+    // The global epoch
+    epoch: u64,
+    // The pinned threads, with the nesting depth of their pins and the global epoch at their
+    // outermost pin
+    pinned: HashMap<ThreadId, (usize, u64)>,
+    // The deferred functions that did not run yet, with the global epoch at which they were
+    // deferred
+    deferred: Vec<(u64, Deferred)>,
+}
+
+impl State {
+    // Advances the global epoch if every pinned thread has seen it, and takes out the deferred
+    // functions that no pinned thread can still be concerned with
+    fn collect(&mut self) -> Vec<Deferred> {
+        if self.pinned.values().all(|(_, e)| *e == self.epoch) {
+            self.epoch += 1;
+        }
+        // A thread pinned at epoch `e` may use what was unlinked at `e`, but it is unpinned
+        // once the global epoch reaches `e + 2`
+        let epoch = self.epoch;
+        let (ready, pending) = std::mem::take(&mut self.deferred)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(e, _)| e + 2 <= epoch);
+        self.deferred = pending;
+        ready.into_iter().map(|(_, f)| f).collect()
+    }
+}
+
+/// A domain of epoch-based reclamation, like the collector of `crossbeam-epoch`.
+///
+/// Threads [`Collector::pin`] themselves while they hold references to shared objects, and a
+/// thread that has unlinked an object [`Guard::defer`]s its reclamation instead of running it
+/// at once. The deferred functions run in [`Collector::flush`], once every thread that was
+/// pinned when they were deferred has unpinned itself: the global epoch advances when all the
+/// pinned threads have seen it, and a function deferred at an epoch runs two epochs later.
+/// The moments at which the threads pin, unpin and flush are explored like any other
+/// interleaving, so a model can check that no reclamation runs while a thread still uses the
+/// object, e.g. because it kept a reference after unpinning.
+///
+/// Pins are per thread, and may be nested: a thread is unpinned when its outermost [`Guard`]
+/// is dropped.
+#[derive(Clone, Default)]
+pub struct Collector {
+    state: Arc<Mutex<State>>,
+}
+
+impl Collector {
+    /// Creates a new `Collector` without pinned threads.
+    pub fn new() -> Self {
+        Collector::default()
+    }
+
+    /// Pins the current thread, which stays pinned until the returned guard is dropped.
+    pub fn pin(&self) -> Guard {
+        let me = thread::current().id();
+        let mut state = self.state.blocking_lock();
+        let epoch = state.epoch;
+        state.pinned.entry(me).or_insert((0, epoch)).0 += 1;
+        Guard {
+            collector: self.clone(),
+        }
+    }
+
+    /// Returns whether the current thread is pinned
+    pub fn is_pinned(&self) -> bool {
+        let me = thread::current().id();
+        self.state.blocking_lock().pinned.contains_key(&me)
+    }
+
+    /// Advances the global epoch if every pinned thread has seen it, and runs the deferred
+    /// functions that are then safe to run. Reclaiming everything that was deferred takes two
+    /// flushes without pinned threads.
+    pub fn flush(&self) {
+        let ready = self.state.blocking_lock().collect();
+        // The functions may use the collector
+        for f in ready {
+            f();
+        }
+    }
+
+    /// Returns the number of deferred functions that did not run yet
+    pub fn pending(&self) -> usize {
+        self.state.blocking_lock().deferred.len()
+    }
+}
+
+impl fmt::Debug for Collector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector").finish_non_exhaustive()
+    }
+}
+
+/// A pin of the current thread, see [`Collector::pin`]. Dropping it unpins the thread, unless
+/// the thread has other guards.
+pub struct Guard {
+    collector: Collector,
+}
+
+impl Guard {
+    /// Defers `f` until no thread pinned now can still be using what it reclaims, e.g. an
+    /// object that the current thread has just unlinked. `f` runs in a later
+    /// [`Collector::flush`].
+    pub fn defer<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut state = self.collector.state.blocking_lock();
+        let epoch = state.epoch;
+        state.deferred.push((epoch, Box::new(f)));
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let me = thread::current().id();
+        let mut state = self.collector.state.blocking_lock();
+        let unpinned = match state.pinned.get_mut(&me) {
+            Some((depth, _)) => {
+                *depth -= 1;
+                *depth == 0
+            }
+            None => false,
+        };
+        if unpinned {
+            state.pinned.remove(&me);
+        }
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Guard").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,76 @@
+//! A gate that tasks wait on until it is opened, also known as a manual-reset event.
+
+use std::sync::Arc;
+
+use crate::sync::oneshot;
+use crate::sync::Mutex;
+
+#[derive(Debug)]
+struct State {
+    open: bool,
+    waiters: Vec<oneshot::Sender<()>>,
+}
+
+/// A sticky signal that is either open or closed.
+///
+/// [`Gate::wait`] completes immediately while the gate is open, and otherwise waits until it is
+/// opened. Unlike [`Notify`](crate::sync::Notify), opening the gate releases all the current
+/// waiters, and every later waiter too, until the gate is closed again. This is useful to
+/// model "initialization complete" barriers. A gate is created closed.
+#[derive(Clone, Debug)]
+pub struct Gate {
+    state: Arc<Mutex<State>>,
+}
+
+impl Gate {
+    /// Creates a new, closed `Gate`.
+    pub fn new() -> Self {
+        Gate {
+            state: Arc::new(Mutex::new(State {
+                open: false,
+                waiters: Vec::new(),
+            })),
+        }
+    }
+
+    /// Opens the gate, releasing all the tasks that wait on it.
+    /// Opening an open gate has no effect.
+    pub fn open(&self) {
+        let mut state = self.state.blocking_lock();
+        state.open = true;
+        for waiter in state.waiters.drain(..) {
+            let _ = waiter.send(());
+        }
+    }
+
+    /// Closes the gate, so that the following calls to [`Gate::wait`] wait until it is opened
+    /// again. Tasks that were already released are not affected.
+    pub fn close(&self) {
+        self.state.blocking_lock().open = false;
+    }
+
+    /// Returns whether the gate is open
+    pub fn is_open(&self) -> bool {
+        self.state.blocking_lock().open
+    }
+
+    /// Completes once the gate is open. Returns immediately if it already is.
+    pub async fn wait(&self) {
+        let rx = {
+            let mut state = self.state.blocking_lock();
+            if state.open {
+                return;
+            }
+            let (tx, rx) = oneshot::channel::<()>();
+            state.waiters.push(tx);
+            rx
+        };
+        let _ = rx.await;
+    }
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Gate::new()
+    }
+}
@@ -1,4 +1,8 @@
 pub mod atomic;
+pub mod cancellation_token;
+pub use cancellation_token::CancellationToken;
+pub mod gate;
+pub use gate::Gate;
 pub mod mpsc;
 
 pub mod mutex;
@@ -13,6 +17,15 @@ pub use rwlock::{
 
 pub mod oneshot;
 
+pub mod rcu;
+pub use rcu::Rcu;
+
+pub mod epoch;
+pub use epoch::Collector;
+
+pub mod shared;
+pub use shared::Shared;
+
 pub mod notify;
-pub use notify::{Notified, Notify};
+pub use notify::{wait_while, Notified, Notify};
 //pub mod watch;
@@ -66,6 +66,12 @@ impl<T: Message + Clone + 'static> Receiver<T> {
         Some(self.receiver.async_recv_msg().await)
     }
 
+    /// Blocks the current task until a message arrives, for synchronous code. Like
+    /// [`recv`](Self::recv), never returns `None`.
+    pub fn blocking_recv(&self) -> Option<T> {
+        Some(self.receiver.recv_msg_block())
+    }
+
     // This is incomplete as it does not model receive errors.
     // A complete implementation would non-deterministically return an error.
     pub fn try_recv(&self) -> Result<T, error::TryRecvError> {
@@ -156,6 +162,12 @@ impl<T: Message + Clone + 'static> UnboundedReceiver<T> {
         Some(self.receiver.async_recv_msg().await)
     }
 
+    /// Blocks the current task until a message arrives, for synchronous code. Like
+    /// [`recv`](Self::recv), never returns `None`.
+    pub fn blocking_recv(&self) -> Option<T> {
+        Some(self.receiver.recv_msg_block())
+    }
+
     // This is incomplete as it does not model receive errors.
     // A complete implementation would non-deterministically return an error.
     pub fn try_recv(&self) -> Result<T, error::TryRecvError> {
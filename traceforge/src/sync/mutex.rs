@@ -1,14 +1,22 @@
 //! An asynchronous `Mutex`-like type.
 
 use std::cell::UnsafeCell;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::{self, Debug, Display};
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
-use crate::channel::{self, Sender};
+use crate::channel::{self, Receiver, Sender};
+use crate::deadlock::{pending_on, waiting_for, WaitReason};
+use crate::loc::CommunicationModel;
+use crate::must::Must;
+use crate::runtime::execution::ExecutionState;
 use crate::thread::{self, ThreadId};
-use crate::{recv_tagged_msg_block, send_tagged_msg};
+use crate::{recv_tagged_msg_block, send_internal_tagged_msg};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum LockRequest {
@@ -107,9 +115,134 @@ impl Default for Synchronizer {
     }
 }
 
+// The requests to the synchronizer of a fair mutex. A `Lock` request carries the waker of the
+// task, which is woken once the lock is granted.
+#[derive(Clone, Debug, PartialEq)]
+enum FairRequest {
+    Lock(ThreadId, Sender<MsgResponse>, RequestWaker),
+    TryLock(ThreadId, Sender<MsgResponse>),
+    Unlock(ThreadId),
+    // The `Lock` request of the thread was dropped before it completed
+    Cancel(ThreadId),
+}
+
+#[derive(Clone, Debug)]
+struct RequestWaker(Waker);
+
+impl PartialEq for RequestWaker {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.will_wake(&other.0)
+    }
+}
+
+// The synchronizer of a fair mutex. It reads every request as it arrives, queues the `Lock`
+// requests made while the mutex is held, and grants them in that order.
+fn serve_fair(requests: Receiver<FairRequest>) {
+    let mut holder: Option<ThreadId> = None;
+    let mut waiters: VecDeque<(ThreadId, Sender<MsgResponse>, RequestWaker)> = VecDeque::new();
+    let grant = |(tid, chan, waker): (ThreadId, Sender<MsgResponse>, RequestWaker)| {
+        chan.send_msg(MsgResponse::LockGranted);
+        waker.0.wake();
+        tid
+    };
+    loop {
+        match requests.recv_msg_block() {
+            FairRequest::Lock(tid, chan, waker) => match holder {
+                None => holder = Some(grant((tid, chan, waker))),
+                Some(_) => waiters.push_back((tid, chan, waker)),
+            },
+            FairRequest::TryLock(tid, chan) => match holder {
+                None => {
+                    holder = Some(tid);
+                    chan.send_msg(MsgResponse::LockGranted);
+                }
+                Some(_) => chan.send_msg(MsgResponse::LockAlreadyHeld),
+            },
+            FairRequest::Unlock(tid) => match holder {
+                Some(t) if t == tid => holder = waiters.pop_front().map(grant),
+                Some(_) => panic!("Unlocking by different thread id"),
+                None => panic!("Unlocking a lock that is not held"),
+            },
+            FairRequest::Cancel(tid) => {
+                if holder == Some(tid) {
+                    // The lock was granted, but the grant was never taken
+                    holder = waiters.pop_front().map(grant);
+                } else {
+                    waiters.retain(|(t, _, _)| *t != tid);
+                }
+            }
+        }
+    }
+}
+
+// The future of a fair `lock()`. The request is sent when it is first polled; afterwards, the
+// future waits for the lock to be granted.
+struct FairLock<'a> {
+    requests: &'a Sender<FairRequest>,
+    response: Option<Receiver<MsgResponse>>,
+    done: bool,
+}
+
+impl Future for FairLock<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match &self.response {
+            None => {
+                let (tx, rx) = channel::Builder::<MsgResponse>::new().internal().build();
+                self.requests.send_msg(FairRequest::Lock(
+                    thread::current().id(),
+                    tx,
+                    RequestWaker(cx.waker().clone()),
+                ));
+                self.response = Some(rx);
+                pending_on(WaitReason::Lock);
+                Poll::Pending
+            }
+            Some(rx) => match waiting_for(WaitReason::Lock, || rx.recv_msg_block()) {
+                MsgResponse::LockGranted => {
+                    self.done = true;
+                    Poll::Ready(())
+                }
+                _ => panic!("Error in the implementation of Mutex"),
+            },
+        }
+    }
+}
+
+impl Drop for FairLock<'_> {
+    fn drop(&mut self) {
+        if self.response.is_some() && !self.done && !std::thread::panicking() {
+            let tid = thread::current().id();
+            self.requests.send_msg(FairRequest::Cancel(tid));
+        }
+    }
+}
+
+// A task waiting in `Mutex::acquire`, which stops waiting if the future of the lock is dropped
+struct Waiting<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T: ?Sized> Drop for Waiting<'_, T> {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.mutex.inherit(Must::lock_cancelled);
+        }
+    }
+}
+
 /// An asynchronous semaphore
 pub struct Mutex<T: ?Sized> {
     synchronizer: ThreadId,
+    // Where the requests go if the mutex is fair
+    fair: Option<Sender<FairRequest>>,
+    // The thread holding the lock, to detect reentrant locking. Only the holder sets and clears
+    // it, so a thread finds itself here exactly when it holds the lock. It sits under its own
+    // lock, since the `Mutex` is shared between threads.
+    holder: std::sync::Mutex<Option<ThreadId>>,
+    // Whether the holder inherits the priorities of the waiting tasks
+    inheritance: bool,
     inner: UnsafeCell<T>,
 }
 
@@ -168,21 +301,123 @@ impl<T: ?Sized> Mutex<T> {
 
         Self {
             synchronizer: tsync_handle.thread().id(),
+            holder: std::sync::Mutex::new(None),
+            fair: None,
+            inheritance: false,
+            inner: UnsafeCell::new(t),
+        }
+    }
+
+    /// Creates a new lock in an unlocked state ready for use. If `fair` is true, the lock is
+    /// granted to the waiting tasks in the order in which their requests reached it: a
+    /// [`Mutex::lock`] request that happens before another one is served first.
+    /// Otherwise, any waiting task may get the lock.
+    ///
+    /// A fair [`Mutex::lock`] sends its request when the future is first polled, so a task can
+    /// queue up and then go on, e.g., to tell another task, before awaiting the lock.
+    pub fn with_fairness(t: T, fair: bool) -> Self
+    where
+        T: Sized,
+    {
+        if !fair {
+            return Self::new(t);
+        }
+        let (tx, rx) = channel::Builder::<FairRequest>::new()
+            .with_comm(CommunicationModel::CausalOrder)
+            .internal()
+            .build();
+        let tsync_handle = thread::Builder::new()
+            .name("traceforge_runtime::mutex".to_string())
+            .spawn_daemon(move || serve_fair(rx))
+            .unwrap();
+
+        Self {
+            synchronizer: tsync_handle.thread().id(),
+            holder: std::sync::Mutex::new(None),
+            fair: Some(tx),
+            inheritance: false,
             inner: UnsafeCell::new(t),
         }
     }
 
+    /// Makes the lock use priority inheritance: while tasks wait for the lock, the task holding
+    /// it is scheduled with the highest of their priorities, as set by
+    /// [`set_task_priority`](crate::set_task_priority), if that is higher than its own. This
+    /// keeps a task of medium priority from running ahead of a task of high priority that waits
+    /// for a task of low priority, i.e., it prevents a priority inversion. Once tasks waited
+    /// for such a lock, the blocked tasks that can go on, e.g., the task granting the lock when
+    /// it is released, also run before the runnable tasks with a lower priority.
+    ///
+    /// Like the priorities themselves, the inheritance only changes the order in which the
+    /// executions are explored.
+    pub fn with_priority_inheritance(mut self) -> Self
+    where
+        T: Sized,
+    {
+        self.inheritance = true;
+        self
+    }
+
+    // Tells the scheduler about a change of the lock, if it has priority inheritance
+    fn inherit(&self, f: impl FnOnce(&mut Must, ThreadId, ThreadId)) {
+        if self.inheritance {
+            let tid = thread::current().id();
+            ExecutionState::with(|s| f(&mut s.must.borrow_mut(), self.synchronizer, tid));
+        }
+    }
+
+    fn holder(&self) -> Option<ThreadId> {
+        *self.holder.lock().unwrap()
+    }
+
+    fn set_holder(&self, holder: Option<ThreadId>) {
+        *self.holder.lock().unwrap() = holder;
+    }
+
     async fn acquire(&self) {
-        let chan = channel::Builder::<MsgResponse>::new().build();
-        send_tagged_msg(
-            self.synchronizer,
-            LOCK_TAG,
-            LockRequest::Lock(thread::current().id(), chan.0),
-        );
-        match chan.1.recv_msg_block() {
-            MsgResponse::LockGranted => (),
-            _ => panic!("Error in the implementation of Mutex"),
+        let tid = thread::current().id();
+        if self.holder() == Some(tid) {
+            let name = thread::current().name().map(str::to_owned);
+            panic!(
+                "Reentrant lock: thread {} tried to lock a Mutex that it already holds, \
+                 which would deadlock",
+                name.unwrap_or_else(|| tid.to_string())
+            );
+        }
+        self.inherit(Must::lock_waiting);
+        let waiting = Waiting { mutex: self };
+        if let Some(requests) = &self.fair {
+            FairLock {
+                requests,
+                response: None,
+                done: false,
+            }
+            .await;
+        } else {
+            let chan = channel::Builder::<MsgResponse>::new().internal().build();
+            send_internal_tagged_msg(self.synchronizer, LOCK_TAG, LockRequest::Lock(tid, chan.0));
+            match waiting_for(WaitReason::Lock, || chan.1.recv_msg_block()) {
+                MsgResponse::LockGranted => (),
+                _ => panic!("Error in the implementation of Mutex"),
+            }
         }
+        std::mem::forget(waiting);
+        self.set_holder(Some(tid));
+        self.inherit(Must::lock_acquired);
+    }
+
+    fn release(&self) {
+        let tid = thread::current().id();
+        self.set_holder(None);
+        match &self.fair {
+            Some(requests) => requests.send_msg(FairRequest::Unlock(tid)),
+            None => {
+                send_internal_tagged_msg(self.synchronizer, UNLOCK_TAG, LockRequest::Unlock(tid))
+            }
+        }
+        // The holder gives up its inherited priority, which the synchronizer keeps as long as
+        // tasks wait for the lock, so that it grants the lock next
+        self.inherit(|must, lock, _| must.lock_released(lock));
     }
 
     /// Locks this mutex, causing the current task to yield until the lock has
@@ -200,11 +435,11 @@ impl<T: ?Sized> Mutex<T> {
     /// This method is intended for use cases where you
     /// need to use this mutex in asynchronous code as well as in synchronous code.    
     pub fn blocking_lock(&self) -> MutexGuard<'_, T> {
-        crate::future::block_on(self.lock())
+        crate::future::block_on_unchecked(self.lock())
     }
 
     pub fn blocking_lock_owned(self: Arc<Self>) -> OwnedMutexGuard<T> {
-        crate::future::block_on(self.lock_owned())
+        crate::future::block_on_unchecked(self.lock_owned())
     }
 
     /// Locks this mutex, causing the current task to yield until the lock has
@@ -217,14 +452,22 @@ impl<T: ?Sized> Mutex<T> {
     }
 
     fn try_acquire(&self) -> Result<(), TryLockError> {
-        let chan = channel::Builder::<MsgResponse>::new().build();
-        send_tagged_msg(
-            self.synchronizer,
-            TRYLOCK_TAG,
-            LockRequest::TryLock(thread::current().id(), chan.0),
-        );
+        let chan = channel::Builder::<MsgResponse>::new().internal().build();
+        let tid = thread::current().id();
+        match &self.fair {
+            Some(requests) => requests.send_msg(FairRequest::TryLock(tid, chan.0)),
+            None => send_internal_tagged_msg(
+                self.synchronizer,
+                TRYLOCK_TAG,
+                LockRequest::TryLock(tid, chan.0),
+            ),
+        }
         match chan.1.recv_msg_block() {
-            MsgResponse::LockGranted => Ok(()),
+            MsgResponse::LockGranted => {
+                self.set_holder(Some(tid));
+                self.inherit(Must::lock_acquired);
+                Ok(())
+            }
             MsgResponse::LockAlreadyHeld => Err(TryLockError(())),
             MsgResponse::Unlocked => panic!("Error in implementation of Mutex"),
         }
@@ -248,6 +491,20 @@ impl<T: ?Sized> Mutex<T> {
         }
     }
 
+    /// Panics if the current thread doesn't hold the lock, e.g., in a function that must be
+    /// called with the lock held. TraceForge reports the panic like any other failure, with the
+    /// execution that reached the call without the lock.
+    pub fn assert_held(&self) {
+        let tid = thread::current().id();
+        if self.holder() != Some(tid) {
+            let name = thread::current().name().map(str::to_owned);
+            panic!(
+                "Lock not held: thread {} expected to hold a Mutex that it doesn't hold",
+                name.unwrap_or_else(|| tid.to_string())
+            );
+        }
+    }
+
     /// Returns a mutable reference to the underlying data.
     ///
     /// Since this call borrows the `Mutex` mutably, no actual locking needs to
@@ -295,11 +552,7 @@ impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
 impl<T: ?Sized> Drop for MutexGuard<'_, T> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
-        send_tagged_msg(
-            self.mutex.synchronizer,
-            UNLOCK_TAG,
-            LockRequest::Unlock(thread::current().id()),
-        );
+        self.mutex.release();
     }
 }
 
@@ -312,11 +565,7 @@ impl<T: ?Sized + Debug> Debug for MutexGuard<'_, T> {
 impl<T: ?Sized> Drop for OwnedMutexGuard<T> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
-        send_tagged_msg(
-            self.mutex.synchronizer,
-            UNLOCK_TAG,
-            LockRequest::Unlock(thread::current().id()),
-        );
+        self.mutex.release();
     }
 }
 
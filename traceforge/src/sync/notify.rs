@@ -1,29 +1,56 @@
 use std::pin::Pin;
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::Waker;
 use std::{future::Future, task::Poll};
 
+use crate::deadlock::{pending_on, WaitReason};
+use crate::runtime::execution::ExecutionState;
 use crate::sync::atomic::AtomicUsize;
 use crate::sync::oneshot::{self, Receiver};
 use crate::sync::Mutex;
+use crate::Nondet;
+
+// A registered waiter, with the waker of the task polling it, and a handle that is dead once its
+// `Notified` is gone
+type Waiter = (oneshot::Sender<bool>, Waker, Weak<()>);
 
 #[derive(Clone, Debug)]
 pub struct Notify {
+    // The number of stored permits, at most `permit_limit`
     state: AtomicUsize,
-    waiters: Arc<Mutex<Vec<oneshot::Sender<bool>>>>,
+    permit_limit: usize,
+    waiters: Arc<Mutex<Vec<Waiter>>>,
+    // Whether a wait already returned spuriously, see `ConfigBuilder::with_spurious_wakeups`
+    woke_spuriously: Arc<AtomicBool>,
 }
 
 #[derive(Debug)]
 pub struct Notified<'a> {
     notify: &'a Notify,
+    // The channel the waiter was registered with, once it is
     receiver: Option<Receiver<bool>>,
+    done: bool,
+    // Keeps the registered waiter alive for `notify_one`
+    alive: Arc<()>,
 }
 
 impl Notify {
     pub fn new() -> Notify {
+        Self::with_permit_limit(1)
+    }
+
+    /// Creates a `Notify` that stores up to `n` permits: each `notify_one` call made while no
+    /// task is waiting adds a permit (saturating at `n`), and each `notified()` consumes one.
+    /// [`Notify::new`] stores a single permit, so that several notifications sent before a
+    /// wait collapse into one.
+    pub fn with_permit_limit(n: usize) -> Notify {
+        assert!(n > 0, "a Notify needs to store at least one permit");
         Notify {
             state: AtomicUsize::new(0),
+            permit_limit: n,
             waiters: Arc::new(Mutex::new(Vec::new())),
+            woke_spuriously: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -35,19 +62,78 @@ impl Notify {
         Notified {
             notify: self,
             receiver: None,
+            done: false,
+            alive: Arc::new(()),
         }
     }
 
     pub fn notify_one(&self) {
         let mut waiters = self.waiters.blocking_lock();
-        if !waiters.is_empty() {
-            // there is a waiter, notify them by writing to their channel
-            let ch = waiters.pop().unwrap();
-            let _ = ch.send(true);
-        } else {
-            // mark that a notify has been sent for the next notified() call
+        // notify a waiter by writing to their channel. A waiter that is gone without
+        // deregistering, e.g. because its task panicked, cannot take the notification, which
+        // must not be lost, so try the next waiter
+        while let Some((ch, waker, alive)) = waiters.pop() {
+            if alive.strong_count() > 0 && ch.send(true).is_ok() {
+                waker.wake();
+                return;
+            }
+        }
+        // mark that a notify has been sent for the next notified() call
+        self.add_permit();
+    }
+
+    fn add_permit(&self) {
+        if self.permit_limit == 1 {
             self.state.store(1, Ordering::SeqCst);
+            return;
+        }
+        loop {
+            let permits = self.state.load(Ordering::SeqCst);
+            if permits == self.permit_limit
+                || self
+                    .state
+                    .compare_exchange(permits, permits + 1, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            {
+                return;
+            }
+        }
+    }
+
+    // Consumes a stored permit, if there is one
+    fn take_permit(&self) -> bool {
+        if self.permit_limit == 1 {
+            // A single permit can be taken without reading it first
+            return self
+                .state
+                .compare_exchange(1, 0, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+        }
+        loop {
+            let permits = self.state.load(Ordering::SeqCst);
+            if permits == 0 {
+                return false;
+            }
+            if self
+                .state
+                .compare_exchange(permits, permits - 1, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                return true;
+            }
+        }
+    }
+
+    // Whether a wait that was not notified returns anyway, if the exploration models spurious
+    // wakeups. Only one wait returns spuriously, so that the waiters that loop until their
+    // condition holds are not explored forever.
+    fn wakes_up_spuriously(&self) -> bool {
+        let enabled = ExecutionState::with(|s| s.must.borrow().config.spurious_wakeups);
+        if !enabled || self.woke_spuriously.load(Ordering::SeqCst) || (0..2).nondet() == 0 {
+            return false;
         }
+        self.woke_spuriously.store(true, Ordering::SeqCst);
+        true
     }
 
     pub fn notify_last(&self) {
@@ -59,6 +145,41 @@ impl Notify {
     }
 }
 
+/// Waits on `notify` for as long as `cond` returns true.
+///
+/// A notification only says that something may have changed, so the condition has to be
+/// checked again after every wakeup. `cond` is evaluated before each wait: a notification
+/// sent after it was evaluated, but before the waiter registered, is kept as a permit, so
+/// the wakeup is not missed.
+///
+/// ```no_run
+/// use std::sync::atomic::Ordering;
+/// use std::sync::Arc;
+/// use traceforge::sync::{atomic::AtomicBool, wait_while, Notify};
+/// use traceforge::{future, Config};
+///
+/// traceforge::verify(Config::builder().build(), || {
+///     future::block_on(async {
+///         let ready = Arc::new(AtomicBool::new(false));
+///         let notify = Arc::new(Notify::new());
+///         let (r, n) = (ready.clone(), notify.clone());
+///         future::spawn(async move {
+///             r.store(true, Ordering::SeqCst);
+///             n.notify_one();
+///         });
+///         wait_while(&notify, || !ready.load(Ordering::SeqCst)).await;
+///     });
+/// });
+/// ```
+pub async fn wait_while<F>(notify: &Notify, mut cond: F)
+where
+    F: FnMut() -> bool,
+{
+    while cond() {
+        notify.notified().await;
+    }
+}
+
 impl Default for Notify {
     fn default() -> Self {
         Notify::new()
@@ -74,31 +195,68 @@ unsafe impl<'a> Sync for Notified<'a> {}
 
 impl<'a> Notified<'a> {
     fn poll_notified(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<()> {
-        // First check if there's already a notification available
-        let cas = self
-            .notify
-            .state
-            .compare_exchange(1, 0, Ordering::SeqCst, Ordering::SeqCst);
-        if cas.is_ok() {
-            return Poll::Ready(());
+        // Blocking inside `poll` is safe here. Every TraceForge task runs on its own
+        // continuation, so waiting for the lock only suspends the polling task, and acquiring
+        // it is an ordinary scheduling point that the model checker explores like any other.
+        // Nobody can hold the lock indefinitely either: the critical sections on `waiters`
+        // (here, in `notify_one` and in `Drop`) only send messages and access the permit
+        // counter, whose synchronizer thread always answers; none of them awaits or waits for
+        // another task.
+        let mut waiters = self.notify.waiters.blocking_lock();
+        match &self.receiver {
+            None => {
+                // Permits are stored while holding the lock, so checking with the lock held
+                // cannot miss a concurrent notification
+                if self.notify.take_permit() || self.notify.wakes_up_spuriously() {
+                    drop(waiters);
+                    self.done = true;
+                    return Poll::Ready(());
+                }
+                let (tx, rx) = oneshot::channel::<bool>();
+                waiters.push((tx, cx.waker().clone(), Arc::downgrade(&self.alive)));
+                self.receiver = Some(rx);
+                pending_on(WaitReason::Notify);
+                Poll::Pending
+            }
+            Some(rx) => {
+                if let Some((_, waker, _)) =
+                    waiters.iter_mut().find(|(tx, _, _)| tx.is_connected_to(rx))
+                {
+                    // Not notified yet
+                    *waker = cx.waker().clone();
+                    pending_on(WaitReason::Notify);
+                    return Poll::Pending;
+                }
+                drop(waiters);
+                // `notify_one` deregistered us and sent the notification
+                let rx = self.receiver.take().unwrap();
+                let _ = rx.blocking_recv();
+                self.done = true;
+                Poll::Ready(())
+            }
         }
+    }
+}
 
-        // If we don't have a receiver yet, create one and register it
-        if self.receiver.is_none() {
-            let (tx, rx) = oneshot::channel::<bool>();
-            let mut waiters = self.notify.waiters.blocking_lock();
-            waiters.push(tx);
-            self.receiver = Some(rx);
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(rx) = &self.receiver else {
+            return;
+        };
+        if self.done || std::thread::panicking() {
+            return;
         }
-
-        // Poll the receiver
-        if let Some(ref mut receiver) = self.receiver {
-            match Pin::new(receiver).poll(cx) {
-                Poll::Ready(_) => Poll::Ready(()),
-                Poll::Pending => Poll::Pending,
+        let mut waiters = self.notify.waiters.blocking_lock();
+        match waiters.iter().position(|(tx, _, _)| tx.is_connected_to(rx)) {
+            Some(i) => {
+                waiters.remove(i);
+            }
+            None => {
+                // We were notified but are dropped before completing: pass the
+                // notification on, so that it is not lost
+                drop(waiters);
+                self.notify.notify_one();
             }
-        } else {
-            Poll::Pending
         }
     }
 }
@@ -113,3 +271,39 @@ impl Future for Notified<'_> {
         self.poll_notified(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Weak;
+
+    use crate::sync::oneshot;
+    use crate::Config;
+
+    use super::Notify;
+
+    #[test]
+    fn notify_one_skips_a_waiter_that_is_gone() {
+        crate::verify(Config::default(), || {
+            crate::future::block_on(async {
+                let notify = Notify::new();
+                let mut live = Box::pin(notify.notified());
+                assert!(futures::poll!(live.as_mut()).is_pending());
+                // A waiter that is gone without deregistering, registered after the live one
+                // so that `notify_one` tries it first
+                let (tx, _) = oneshot::channel::<bool>();
+                let waker = futures::task::noop_waker();
+                notify
+                    .waiters
+                    .blocking_lock()
+                    .push((tx, waker, Weak::new()));
+
+                notify.notify_one();
+                // The live waiter got the notification, which did not become a permit
+                assert!(notify.waiters.blocking_lock().is_empty());
+                assert_eq!(notify.state.load(Ordering::SeqCst), 0);
+                live.await;
+            });
+        });
+    }
+}
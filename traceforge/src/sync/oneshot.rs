@@ -1,5 +1,6 @@
 //! TraceForge's implementation of [`tokio::sync::oneshot`].
 
+use crate::runtime::execution::ExecutionState;
 use crate::*;
 
 use futures::task::Context;
@@ -7,27 +8,105 @@ use futures::task::Poll;
 use std::pin::Pin;
 //use futures::task::Poll::Ready;
 
-pub type Receiver<T> = crate::channel::Receiver<T>;
+/// The sending half of a oneshot channel, see [`channel()`].
+///
+/// Dropping the sender without sending a value closes the channel: the receiver then gets a
+/// [`RecvError`](error::RecvError) instead of waiting forever.
+#[derive(Debug)]
+pub struct Sender<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> {
+    // `None` once the value is sent
+    sender: Option<crate::channel::Sender<Option<T>>>,
+}
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Sender<T> {
-    sender: crate::channel::Sender<T>,
+/// The receiving half of a oneshot channel, see [`channel()`].
+///
+/// Awaiting the receiver completes with the value sent, or with a
+/// [`RecvError`](error::RecvError) if the sender was dropped without sending one.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    // Receives `Some(v)` when `v` is sent, and `None` when the sender is dropped
+    receiver: crate::channel::Receiver<Option<T>>,
+    // Whether the value, or the error, was already received
+    terminated: bool,
 }
 
-unsafe impl<T: Send> Send for Sender<T> {}
+unsafe impl<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> Send for Sender<T> {}
+
+unsafe impl<T: Clone + std::fmt::Debug + PartialEq + Send + Sync + 'static> Sync for Sender<T> {}
 
-unsafe impl<T: Sync> Sync for Sender<T> {}
+// The receiver never holds a `T` itself, only a handle to the channel
+impl<T> Unpin for Receiver<T> {}
 
-impl<T: Message + 'static> Sender<T> {
-    fn new(sender: crate::channel::Sender<T>) -> Self {
-        Sender { sender }
+impl<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> Sender<T> {
+    fn new(sender: crate::channel::Sender<Option<T>>) -> Self {
+        Sender {
+            sender: Some(sender),
+        }
     }
 
-    #[allow(unused_mut)]
     pub fn send(mut self, v: T) -> Result<(), T> {
-        self.sender.send_msg(v);
+        self.sender.take().unwrap().send_msg(Some(v));
         Ok(())
     }
+
+    // Whether `receiver` is the other half of this channel
+    pub(crate) fn is_connected_to(&self, receiver: &Receiver<T>) -> bool {
+        self.sender
+            .as_ref()
+            .is_some_and(|sender| sender.inner == receiver.receiver.inner)
+    }
+}
+
+impl<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let Some(sender) = self.sender.take() else {
+            return;
+        };
+        // Skip during panic unwinding, and once the execution has stopped scheduling tasks:
+        // nobody can receive the notification anymore
+        let running = ExecutionState::try_with(|state| state.is_running()).unwrap_or(false);
+        if running && !std::thread::panicking() {
+            sender.send_msg(None);
+        }
+    }
+}
+
+impl<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> Receiver<T> {
+    /// Waits for the value, blocking the current thread.
+    /// Returns an error if the sender is dropped without sending a value.
+    pub fn blocking_recv(mut self) -> Result<T, error::RecvError> {
+        if self.terminated {
+            return Err(error::RecvError(()));
+        }
+        self.terminate(self.receiver.recv_msg_block())
+    }
+
+    /// Receives the value if it was sent, without waiting. Returns `Ok(None)` if the sender has
+    /// neither sent a value nor been dropped yet, and an error if the sender was dropped without
+    /// sending, or if the value was already received.
+    ///
+    /// Whether a concurrent send is seen is an explored choice, like for any other non-blocking
+    /// receive.
+    pub fn try_recv(&mut self) -> Result<Option<T>, error::RecvError> {
+        if self.terminated {
+            return Err(error::RecvError(()));
+        }
+        match self.receiver.recv_msg() {
+            Some(msg) => self.terminate(msg).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether the value, or the error, was already received, so that receiving again
+    /// would return an error.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    fn terminate(&mut self, msg: Option<T>) -> Result<T, error::RecvError> {
+        self.terminated = true;
+        msg.ok_or(error::RecvError(()))
+    }
 }
 
 // The current version ignores the buffer size
@@ -35,15 +114,23 @@ pub fn channel<T>() -> (Sender<T>, Receiver<T>)
 where
     T: Clone + std::fmt::Debug + PartialEq + Message + 'static,
 {
-    let (tx, rx) = crate::channel::Builder::<T>::new().build();
+    let (tx, rx) = crate::channel::Builder::<Option<T>>::new().build();
+    let rx = Receiver {
+        receiver: rx,
+        terminated: false,
+    };
     (Sender::new(tx), rx)
 }
 
-impl<T: Message + Clone + 'static> Future for Receiver<T> {
+impl<T: Clone + std::fmt::Debug + PartialEq + Send + 'static> Future for Receiver<T> {
     type Output = Result<T, error::RecvError>;
 
     fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Poll::Ready(Ok(self.recv_msg_block()))
+        let this = self.get_mut();
+        if this.terminated {
+            return Poll::Ready(Err(error::RecvError(())));
+        }
+        Poll::Ready(this.terminate(this.receiver.recv_msg_block()))
     }
 }
 
@@ -0,0 +1,117 @@
+//! Read-copy-update (RCU) style read-side critical sections and grace periods.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::sync::oneshot;
+use crate::sync::Mutex;
+use crate::thread::{self, ThreadId};
+
+#[derive(Debug, Default)]
+struct State {
+    // The nesting depth of the read-side critical section of each reader
+    readers: HashMap<ThreadId, usize>,
+    // The grace periods in progress, with the readers that they still wait for
+    grace_periods: Vec<(HashSet<ThreadId>, oneshot::Sender<()>)>,
+}
+
+/// A domain of read-copy-update synchronization, like the one of the Linux kernel.
+///
+/// Readers delimit their read-side critical sections with [`Rcu::read_lock`] and
+/// [`Rcu::read_unlock`], which never block. A writer that has unpublished an object awaits
+/// [`Rcu::synchronize`] before reclaiming it: the grace period ends once every reader that was
+/// in a critical section when it started has left it. Readers that enter a critical section
+/// after the start of the grace period are not waited for, since they cannot see the
+/// unpublished object. The moments at which the readers leave their critical sections, and
+/// thus at which grace periods end, are explored like any other interleaving.
+///
+/// Read-side critical sections are per thread, and may be nested: a thread leaves its critical
+/// section at its outermost [`Rcu::read_unlock`].
+#[derive(Clone, Debug)]
+pub struct Rcu {
+    state: Arc<Mutex<State>>,
+}
+
+impl Rcu {
+    /// Creates a new `Rcu` domain without readers.
+    pub fn new() -> Self {
+        Rcu {
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Enters a read-side critical section of the current thread, like `rcu_read_lock`.
+    pub fn read_lock(&self) {
+        let mut state = self.state.blocking_lock();
+        *state.readers.entry(thread::current().id()).or_default() += 1;
+    }
+
+    /// Leaves a read-side critical section of the current thread, like `rcu_read_unlock`.
+    /// Leaving the outermost critical section ends the grace periods that only waited for this
+    /// thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread is not in a read-side critical section.
+    pub fn read_unlock(&self) {
+        let me = thread::current().id();
+        let mut state = self.state.blocking_lock();
+        let depth = state
+            .readers
+            .get_mut(&me)
+            .expect("read_unlock outside of a read-side critical section");
+        *depth -= 1;
+        if *depth > 0 {
+            return;
+        }
+        state.readers.remove(&me);
+        let (ended, pending) = std::mem::take(&mut state.grace_periods)
+            .into_iter()
+            .map(|(mut waited, tx)| {
+                waited.remove(&me);
+                (waited, tx)
+            })
+            .partition::<Vec<_>, _>(|(waited, _)| waited.is_empty());
+        state.grace_periods = pending;
+        for (_, tx) in ended {
+            let _ = tx.send(());
+        }
+    }
+
+    /// Returns whether the current thread is in a read-side critical section
+    pub fn in_read_section(&self) -> bool {
+        let me = thread::current().id();
+        self.state.blocking_lock().readers.contains_key(&me)
+    }
+
+    /// Waits for a grace period, like `synchronize_rcu`: completes once every thread that is in
+    /// a read-side critical section now has left it. Returns immediately if there is none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread is in a read-side critical section, which would never end.
+    pub async fn synchronize(&self) {
+        let rx = {
+            let mut state = self.state.lock().await;
+            let me = thread::current().id();
+            assert!(
+                !state.readers.contains_key(&me),
+                "synchronize within a read-side critical section never completes"
+            );
+            if state.readers.is_empty() {
+                return;
+            }
+            let waited = state.readers.keys().cloned().collect();
+            let (tx, rx) = oneshot::channel::<()>();
+            state.grace_periods.push((waited, tx));
+            rx
+        };
+        let _ = rx.await;
+    }
+}
+
+impl Default for Rcu {
+    fn default() -> Self {
+        Rcu::new()
+    }
+}
@@ -7,6 +7,7 @@ use std::fmt;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 
+use crate::deadlock::{waiting_for, WaitReason};
 use crate::sync::TryLockError;
 
 const MAX_READERS: usize = usize::MAX >> 3;
@@ -112,13 +113,15 @@ where
     }
 
     pub async fn read(&self) -> RwLockReadGuard<'_, T> {
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             self.backing_tid,
             ACQUIRE_READ_TAG,
             Request::AcquireRead(crate::thread::current().id()),
         );
         let backing_tid = self.backing_tid;
-        let resp = crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid);
+        let resp = waiting_for(WaitReason::Lock, || {
+            crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid)
+        });
         match resp {
             Response::AcquireRead => RwLockReadGuard {
                 lock: self,
@@ -128,13 +131,13 @@ where
         }
     }
     pub fn blocking_read(&self) -> RwLockReadGuard<'_, T> {
-        crate::future::block_on(self.read())
+        crate::future::block_on_unchecked(self.read())
     }
 
     pub async fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
         todo!()
         /*
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             self.backing_tid,
             TRY_ACQUIRE_READ_TAG,
             Request::TryAcquireRead(crate::thread::current().id()),
@@ -156,13 +159,15 @@ where
     }
 
     pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             self.backing_tid,
             ACQUIRE_WRITE_TAG,
             Request::AcquireWrite(crate::thread::current().id()),
         );
         let backing_tid = self.backing_tid;
-        let resp = crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid);
+        let resp = waiting_for(WaitReason::Lock, || {
+            crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid)
+        });
         match resp {
             Response::AcquireWrite => RwLockWriteGuard {
                 lock: self,
@@ -173,18 +178,20 @@ where
     }
 
     pub fn blocking_write(&self) -> RwLockWriteGuard<'_, T> {
-        crate::future::block_on(self.write())
+        crate::future::block_on_unchecked(self.write())
     }
 
     pub async fn write_owned(self: Arc<Self>) -> OwnedRwLockWriteGuard<T> {
         // TODO: Do we need ManuallyDrop for the lock?
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             self.backing_tid,
             ACQUIRE_WRITE_TAG,
             Request::AcquireWrite(crate::thread::current().id()),
         );
         let backing_tid = self.backing_tid;
-        let resp = crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid);
+        let resp = waiting_for(WaitReason::Lock, || {
+            crate::recv_tagged_msg_block(move |tid, _| tid == backing_tid)
+        });
         match resp {
             Response::AcquireWrite => OwnedRwLockWriteGuard {
                 data: self.data.get(),
@@ -229,7 +236,7 @@ impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
         let tid = self.lock.backing_tid;
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             tid,
             RELEASE_READ_TAG,
             Request::ReleaseRead(crate::thread::current().id()),
@@ -242,7 +249,7 @@ impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
         let tid = self.lock.backing_tid;
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             tid,
             RELEASE_WRITE_TAG,
             Request::ReleaseWrite(crate::thread::current().id()),
@@ -320,7 +327,7 @@ impl<T: ?Sized, U: ?Sized> Drop for OwnedRwLockReadGuard<T, U> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
         let tid = self.lock.backing_tid;
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             tid,
             RELEASE_READ_TAG,
             Request::ReleaseWrite(crate::thread::current().id()),
@@ -358,7 +365,7 @@ impl<T: ?Sized, U: ?Sized> Drop for OwnedRwLockWriteGuard<T, U> {
     fn drop(&mut self) {
         if std::thread::panicking() { return; }
         let tid = self.lock.backing_tid;
-        crate::send_tagged_msg(
+        crate::send_internal_tagged_msg(
             tid,
             RELEASE_WRITE_TAG,
             Request::ReleaseWrite(crate::thread::current().id()),
@@ -414,7 +421,7 @@ impl MustRwLock {
 
             match req {
                 Request::AcquireWrite(locking_tid) => {
-                    crate::send_msg(locking_tid, Response::AcquireWrite);
+                    crate::send_internal_msg(locking_tid, Response::AcquireWrite);
 
                     // This case is easy--the only kind of request we can accept now is to unlock the request
                     // from the correct thread.
@@ -424,7 +431,7 @@ impl MustRwLock {
                     match req {
                         Request::ReleaseWrite(tid) => {
                             assert!(locking_tid == tid);
-                            crate::send_msg(tid, Response::ReleaseWrite);
+                            crate::send_internal_msg(tid, Response::ReleaseWrite);
                         }
                         _ => unreachable!(),
                     }
@@ -438,7 +445,7 @@ impl MustRwLock {
     }
 
     fn reader_lock_loop(&mut self, tid: crate::thread::ThreadId) {
-        crate::send_msg(tid, Response::AcquireRead);
+        crate::send_internal_msg(tid, Response::AcquireRead);
 
         let mut reader_tids = HashSet::new();
         reader_tids.insert(tid);
@@ -465,12 +472,12 @@ impl MustRwLock {
                 Request::AcquireRead(thread_id) => {
                     reader_tids.insert(thread_id);
                     self.current_readers += 1;
-                    crate::send_msg(thread_id, Response::AcquireRead);
+                    crate::send_internal_msg(thread_id, Response::AcquireRead);
                 }
                 Request::ReleaseRead(thread_id) => {
                     reader_tids.remove(&thread_id);
                     self.current_readers -= 1;
-                    crate::send_msg(thread_id, Response::ReleaseRead);
+                    crate::send_internal_msg(thread_id, Response::ReleaseRead);
                 }
                 m => panic!("Logic error; unexpected message {:?}", m),
             }
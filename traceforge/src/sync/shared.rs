@@ -0,0 +1,213 @@
+//! Plain shared memory, whose unsynchronized accesses are reported as data races.
+//!
+//! A [`Shared`] value models memory that is not atomic, such as a field behind an
+//! `UnsafeCell`: the tasks may only access it concurrently if they synchronize, i.e. if the
+//! accesses are ordered by [`happens_before`](crate::happens_before), e.g. through a message, a
+//! lock, a spawn or a join. Two accesses from different tasks, at least one of which is a
+//! write, that are not ordered are a data race, which fails the execution.
+//!
+//! ```no_run
+//! use traceforge::sync::Shared;
+//! use traceforge::thread;
+//!
+//! traceforge::verify(traceforge::Config::default(), || {
+//!     let counter = Shared::new(0u32);
+//!     let c = counter.clone();
+//!     let t = thread::spawn(move || c.write(1));
+//!     // Races with the write of the spawned thread, since nothing orders them
+//!     counter.write(2);
+//!     t.join().unwrap();
+//! });
+//! ```
+//!
+//! The races are detected with the vector clocks of the tasks, in every explored execution,
+//! whatever the order in which the racing accesses ran. The accesses are not events,
+//! so they do not add executions to the exploration: a read returns the latest write of the
+//! execution, which is the only one it can see when there is no race.
+//!
+//! Races that are known to be benign, e.g. on a performance counter, can be suppressed with
+//! [`ConfigBuilder::with_benign_race`](crate::ConfigBuilder::with_benign_race), by the tag of
+//! the value or by the location of an access. They are counted in
+//! [`Stats::suppressed_races`](crate::Stats::suppressed_races).
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::panic::Location;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::event::Event;
+use crate::runtime::execution::ExecutionState;
+use crate::thread::ThreadId;
+
+// An access by a task, which happens before what sees the next event of the task
+#[derive(Clone, Debug)]
+struct Access {
+    task: String,
+    next: Event,
+    location: &'static Location<'static>,
+}
+
+impl Access {
+    #[track_caller]
+    fn current() -> Self {
+        let location = Location::caller();
+        ExecutionState::with(|s| {
+            let tid = s.must.borrow().to_thread_id(s.current().id());
+            let next = Event::new(tid, s.current().instructions as u32 + 1);
+            Access {
+                task: s.current().display_name(),
+                next,
+                location,
+            }
+        })
+    }
+
+    // Whether `race` of `ConfigBuilder::with_benign_race` is the location of the access
+    fn is_at(&self, race: &str) -> bool {
+        let line = format!("{}:{}", self.location.file(), self.location.line());
+        race == line || race == self.location.to_string()
+    }
+
+    // Whether the access happens before the point of the current task, which made `me`
+    fn ordered_before(&self, me: &Access) -> bool {
+        self.next.thread == me.next.thread || crate::current_clock().contains(self.next)
+    }
+}
+
+#[derive(Debug)]
+struct State<T> {
+    value: T,
+    tag: Option<String>,
+    last_write: Option<Access>,
+    // The last read of each task since the last write
+    reads: HashMap<ThreadId, Access>,
+}
+
+/// A value of plain shared memory, see the [module documentation](self). Clones refer to the
+/// same value. It can only be used in the execution that created it.
+pub struct Shared<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Shared<T> {
+    // A race fails the execution while the state is locked, which the other tasks can go on
+    // using
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Creates a shared value, which the creating task writes
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        Shared {
+            state: Arc::new(Mutex::new(State {
+                value,
+                tag: None,
+                last_write: Some(Access::current()),
+                reads: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Tags the value, e.g. to declare its races benign with
+    /// [`ConfigBuilder::with_benign_race`](crate::ConfigBuilder::with_benign_race)
+    pub fn with_tag(self, tag: &str) -> Self {
+        self.lock().tag = Some(tag.to_string());
+        self
+    }
+
+    /// Reads the value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the read races with a write of another task.
+    #[track_caller]
+    pub fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        let me = Access::current();
+        let mut state = self.lock();
+        if let Some(write) = &state.last_write {
+            check(&state.tag, write, "write", &me, "read");
+        }
+        state.reads.insert(me.next.thread, me);
+        state.value.clone()
+    }
+
+    /// Writes the value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write races with a read or a write of another task.
+    #[track_caller]
+    pub fn write(&self, value: T) {
+        let me = Access::current();
+        let mut state = self.lock();
+        if let Some(write) = &state.last_write {
+            check(&state.tag, write, "write", &me, "write");
+        }
+        for read in state.reads.values() {
+            check(&state.tag, read, "read", &me, "write");
+        }
+        state.value = value;
+        state.reads.clear();
+        state.last_write = Some(me);
+    }
+
+    /// Updates the value with `f`, which is a read followed by a write
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update races with a read or a write of another task.
+    #[track_caller]
+    pub fn update(&self, f: impl FnOnce(&T) -> T)
+    where
+        T: Clone,
+    {
+        let value = self.read();
+        self.write(f(&value));
+    }
+}
+
+// Fails the execution if the access `earlier` does not happen before the access `me` of the
+// current task, unless the race is declared benign
+fn check(tag: &Option<String>, earlier: &Access, earlier_kind: &str, me: &Access, kind: &str) {
+    if earlier.ordered_before(me) {
+        return;
+    }
+    let suppressed = ExecutionState::with(|s| {
+        let mut must = s.must.borrow_mut();
+        let benign = must
+            .config()
+            .benign_races
+            .iter()
+            .any(|race| tag.as_ref() == Some(race) || earlier.is_at(race) || me.is_at(race));
+        if benign {
+            must.suppressed_races += 1;
+        }
+        benign
+    });
+    if !suppressed {
+        panic!(
+            "data race on a Shared value: the {} of {} is concurrent with the {} of {} (at {} and {})",
+            kind, me.task, earlier_kind, earlier.task, me.location, earlier.location
+        );
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Debug> Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shared")
+            .field("value", &self.lock().value)
+            .finish()
+    }
+}
@@ -0,0 +1,94 @@
+//! Small helpers that keep test models and their tests short.
+//!
+//! ```no_run
+//! use traceforge::testing::Counter;
+//! use traceforge::{thread, Config};
+//!
+//! traceforge::verify(Config::builder().build(), || {
+//!     let counter = Counter::new();
+//!     let c = counter.clone();
+//!     let h = thread::spawn(move || c.incr());
+//!     counter.incr();
+//!     h.join().unwrap();
+//!     assert_eq!(counter.get(), 2);
+//! });
+//! ```
+
+use std::sync::atomic::Ordering;
+
+use crate::sync::atomic::AtomicI64;
+use crate::{Config, Stats};
+
+/// A shared counter.
+///
+/// Clones of a `Counter` refer to the same value, so there is no need to wrap it in an `Arc`.
+/// Every operation is a scheduling point: TraceForge explores how the accesses of different
+/// threads interleave, and `incr` and `decr` are atomic.
+#[derive(Clone, Debug)]
+pub struct Counter {
+    value: AtomicI64,
+}
+
+impl Counter {
+    /// Creates a counter starting at 0. It can only be used in the execution that created it.
+    pub fn new() -> Self {
+        Counter {
+            value: AtomicI64::new(0),
+        }
+    }
+
+    /// Adds one to the counter
+    pub fn incr(&self) {
+        self.add(1);
+    }
+
+    /// Subtracts one from the counter
+    pub fn decr(&self) {
+        self.add(-1);
+    }
+
+    /// Returns the current value of the counter
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    fn add(&self, delta: i64) {
+        loop {
+            let v = self.value.load(Ordering::SeqCst);
+            if self
+                .value
+                .compare_exchange(v, v + delta, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Counter::new()
+    }
+}
+
+/// Verifies `f` under `config`, and panics unless the exploration explores exactly `expected`
+/// complete executions. Returns the statistics of the exploration.
+///
+/// Pinning the number of executions of a small model turns an unexpected change of the
+/// explored schedules, e.g. a regression of the model or of TraceForge, into a test failure.
+#[track_caller]
+pub fn assert_exec_count<F>(config: Config, f: F, expected: usize) -> Stats
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let stats = crate::verify(config, f);
+    if stats.execs != expected {
+        panic!(
+            "expected {} complete executions, but TraceForge explored {} ({} blocked): the \
+             explored schedules changed",
+            expected, stats.execs, stats.block
+        );
+    }
+    stats
+}
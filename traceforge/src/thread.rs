@@ -8,7 +8,9 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize, Serializer};
 
+use crate::channel;
 use crate::event_label::{End, TJoin};
+use crate::loc::CommunicationModel;
 use crate::msg::Message;
 use crate::must::Must;
 use crate::runtime::execution::ExecutionState;
@@ -175,6 +177,59 @@ impl Thread {
     pub fn id(&self) -> ThreadId {
         self.id
     }
+
+    /// Makes the token of the thread available, see [`park`]. If the thread is parked, it is
+    /// woken up; otherwise its next call to [`park`] returns right away. The token is not a
+    /// counter: unparking a thread that already has its token does nothing.
+    pub fn unpark(&self) {
+        park_channel(self.id).0.send_msg(ParkToken);
+    }
+}
+
+// What `Thread::unpark` hands to the thread it unparks
+#[derive(Clone, Debug, PartialEq)]
+struct ParkToken;
+
+// The channel of the tokens of thread `tid`: a token is available when the channel holds a
+// message. Naming the channel after the thread lets any thread unpark it.
+fn park_channel(tid: ThreadId) -> (channel::Sender<ParkToken>, channel::Receiver<ParkToken>) {
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Parking(ThreadId);
+
+    channel::Builder::new()
+        .with_name(Parking(tid))
+        .with_comm(CommunicationModel::NoOrder)
+        .internal()
+        .build()
+}
+
+// Consumes the tokens that were made available while the current thread waited for one, since
+// tokens do not add up. A token that is not consumed here counts as made available later.
+fn consume_tokens(tokens: &channel::Receiver<ParkToken>) {
+    while tokens.recv_msg().is_some() {}
+}
+
+/// Blocks the current thread until its token is available, and consumes the token, like
+/// [`std::thread::park`]. Each thread has one token, which [`Thread::unpark`] makes available:
+/// a thread that is unparked before it parks does not block.
+///
+/// TraceForge explores the orders in which the calls to [`park`] and [`Thread::unpark`] can
+/// happen. A thread that parks forever is reported as a deadlock. Like any message, the token
+/// of an unpark may reach the thread late, so a thread unparked twice may return from two
+/// parks: this is one of the spurious wakeups that `std` allows.
+pub fn park() {
+    let (_, tokens) = park_channel(current_id());
+    tokens.recv_msg_block();
+    consume_tokens(&tokens);
+}
+
+/// Like [`park`], but the thread may also wake up without its token, as if `dur` elapsed, like
+/// [`std::thread::park_timeout`]. TraceForge explores both outcomes whatever the duration is.
+pub fn park_timeout(_dur: Duration) {
+    let (_, tokens) = park_channel(current_id());
+    if tokens.recv_msg().is_some() {
+        consume_tokens(&tokens);
+    }
 }
 
 /// Spawn a new thread, returning a JoinHandle for it.
@@ -193,6 +248,8 @@ where
     jh
 }
 
+/// Like [`spawn`], but the execution can complete without the thread finishing: once the other
+/// threads are done, a daemon thread that waits forever for a message is not a deadlock.
 pub fn spawn_daemon<F, T>(f: F) -> JoinHandle<T>
 where
     F: FnOnce() -> T,
@@ -296,7 +353,11 @@ impl<T: 'static> JoinHandle<T> {
                 let target_task_id = s.get(self.task_id).id();
                 let target_id = s.must.borrow().to_thread_id(target_task_id);
                 let pos = s.next_pos();
-                s.must.borrow_mut().handle_tjoin(TJoin::new(pos, target_id))
+                let val = s.must.borrow_mut().handle_tjoin(TJoin::new(pos, target_id));
+                if val.as_ref().is_some_and(|v| !v.is_pending()) {
+                    s.must.borrow_mut().join_clock(pos.thread, target_id);
+                }
+                val
             });
 
             if let Some(message) = val {
@@ -332,6 +393,16 @@ pub fn sleep(_dur: Duration) {
     thread::switch();
 }
 
+/// Adds a scheduling point, where TraceForge runs another task if one can run. TraceForge can
+/// only switch tasks at its primitives, so this is useful in long loops that call none.
+pub fn yield_now() {
+    ExecutionState::with(|s| {
+        let me = s.current().id();
+        s.must.borrow_mut().yield_task(me);
+    });
+    switch();
+}
+
 /// Get a handle to the thread that invokes it
 pub fn current() -> Thread {
     let (tid, name) = ExecutionState::with(|s| {
@@ -0,0 +1,92 @@
+//! Simulated time: per-task logical clocks and timers.
+//!
+//! Every task has a logical clock, which starts at the clock of the task that spawned it and
+//! advances when the task sleeps. Receiving a message or joining a task also advances the clock,
+//! up to the clock of the sender at the send or the final clock of the joined task, so that a
+//! timer set after hearing from another task never fires before that task's earlier timers.
+//! By default, time is not modeled further: timers on different tasks fire in any order,
+//! regardless of their durations.
+//!
+//! With [`ConfigBuilder::with_clock_drift`](crate::ConfigBuilder::with_clock_drift), timers fire
+//! in the order of their deadlines, except that the clocks of different tasks may drift apart by
+//! up to the given bound: a timer may fire before one with an earlier deadline, as long as the
+//! two deadlines are at most the bound apart. TraceForge explores all the orders allowed by the
+//! bound.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use traceforge::{thread, time, Config};
+//!
+//! let config = Config::builder()
+//!     .with_clock_drift(Duration::from_millis(5))
+//!     .build();
+//! traceforge::verify(config, || {
+//!     // These two timers can fire in either order, they are within the drift bound
+//!     let a = thread::spawn(|| time::sleep(Duration::from_millis(10)));
+//!     let b = thread::spawn(|| time::sleep(Duration::from_millis(12)));
+//!     a.join().unwrap();
+//!     b.join().unwrap();
+//! });
+//! ```
+
+use std::time::Duration;
+
+use crate::channel::{self, Sender};
+use crate::runtime::execution::ExecutionState;
+use crate::runtime::thread::switch;
+use crate::thread;
+
+// A timer that fires at the deadline, acknowledged on the channel
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Fire(Duration, Sender<()>);
+
+/// Returns the logical time of the calling task
+pub fn now() -> Duration {
+    ExecutionState::with(|s| {
+        let tid = s.must.borrow().to_thread_id(s.current().id());
+        s.must.borrow().task_clock(tid)
+    })
+}
+
+/// Puts the calling task to sleep for `dur` of logical time, advancing its clock accordingly.
+/// Under [`ConfigBuilder::with_clock_drift`](crate::ConfigBuilder::with_clock_drift), the timer
+/// fires in order with the timers of the other tasks, up to the drift bound.
+pub fn sleep(dur: Duration) {
+    let (deadline, service) = ExecutionState::with(|s| {
+        let tid = s.must.borrow().to_thread_id(s.current().id());
+        let mut must = s.must.borrow_mut();
+        let deadline = must.task_clock(tid) + dur;
+        must.set_task_clock(tid, deadline);
+        let service = must.timer_service.as_ref().map(|(tx, _)| tx.clone());
+        (deadline, service)
+    });
+    match service {
+        Some(service) => {
+            let (tx, rx) = channel::Builder::<()>::new().internal().build();
+            service.send_msg(Fire(deadline, tx));
+            rx.recv_msg_block();
+        }
+        None => switch(),
+    }
+}
+
+// Starts the thread that orders the timers of the execution. Called by the main thread, before
+// the model runs, if the clock drift is bounded.
+pub(crate) fn start_timer_service(bound: Duration) {
+    let (tx, rx) = channel::Builder::<Fire>::new().internal().build();
+    let service = thread::Builder::new()
+        .name("traceforge_runtime::time".to_string())
+        .spawn_daemon(move || {
+            // The latest deadline fired so far
+            let mut latest = Duration::ZERO;
+            loop {
+                let Fire(deadline, ack) = rx.recv_msg_block();
+                crate::assume!(deadline + bound >= latest);
+                latest = latest.max(deadline);
+                ack.send_msg(());
+            }
+        })
+        .unwrap();
+    let tid = service.thread().id();
+    ExecutionState::with(|s| s.must.borrow_mut().timer_service = Some((tx, tid)));
+}
@@ -4,8 +4,12 @@ use std::cmp;
 
 use serde::{Deserialize, Serialize};
 
+/// A vector clock: for every thread, the index of the latest event of that thread that is
+/// causally before (or is) the point the clock describes.
+///
+/// See [`current_clock`](crate::current_clock).
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub(crate) struct VectorClock {
+pub struct VectorClock {
     clock: IndexedMap<u32>,
 }
 
@@ -16,13 +20,15 @@ impl VectorClock {
         }
     }
 
-    pub(crate) fn entries(&self) -> impl Iterator<Item = (ThreadId, u32)> + '_ {
+    /// Iterates over the threads present in the clock, together with their indices
+    pub fn entries(&self) -> impl Iterator<Item = (ThreadId, u32)> + '_ {
         self.clock
             .enumerate()
             .map(|(tid, &idx)| (construct_thread_id(tid as u32), idx))
     }
 
-    pub(crate) fn get(&self, i: ThreadId) -> Option<u32> {
+    /// Returns the index of the latest event of thread `i` in the clock, if any
+    pub fn get(&self, i: ThreadId) -> Option<u32> {
         self.clock.get(usize::from(i)).copied()
     }
 
@@ -35,8 +41,8 @@ impl VectorClock {
         self.clock.set(usize::from(e.thread), e.index);
     }
 
-    // returns true iff self is a vector clock which represents a view that contains the event e; this is used for the "program order" vector clocks hb
-    pub(crate) fn contains(&self, e: Event) -> bool {
+    /// Returns true iff the clock represents a view that contains the event `e`
+    pub fn contains(&self, e: Event) -> bool {
         self.get(e.thread).is_some_and(|i| e.index <= i)
     }
 
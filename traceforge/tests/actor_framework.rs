@@ -0,0 +1,60 @@
+use std::collections::BTreeSet;
+
+use traceforge::actor::{self, Actor};
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+#[derive(Default)]
+struct Counter {
+    count: u32,
+    // The increments, in the order they were handled
+    log: Vec<u32>,
+}
+
+impl Actor for Counter {
+    type Msg = u32;
+
+    fn handle(&mut self, n: u32) {
+        self.count += n;
+        self.log.push(n);
+    }
+}
+
+#[test]
+fn counter_actor_counts_the_increments_of_two_senders() {
+    let logs = Outcomes::new();
+    let seen = logs.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let counter = actor::spawn(Counter::default());
+        let senders: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|n| {
+                let addr = counter.addr();
+                thread::spawn(move || addr.send(n))
+            })
+            .collect();
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        // Both increments happen before the stop, so they are handled first
+        let counter = counter.stop();
+        traceforge::assert(counter.count == 3);
+        seen.insert(counter.log);
+    });
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 0);
+    assert_eq!(logs.distinct(), BTreeSet::from([vec![1, 2], vec![2, 1]]));
+}
+
+#[test]
+fn actors_need_not_be_stopped() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let counter = actor::spawn(Counter::default());
+        counter.addr().send(1);
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 0);
+}
@@ -0,0 +1,94 @@
+use traceforge::assertions;
+use traceforge::thread;
+use traceforge::Config;
+use utils::{assert_panic_msg, read_trace, remove_trace};
+
+mod utils;
+
+#[test]
+fn assert_eq_is_reported_as_assertion() {
+    let trace_filename = "/tmp/assertions.rs_assert_eq_is_reported_as_assertion";
+    remove_trace(trace_filename);
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(
+            Config::builder().with_error_trace(trace_filename).build(),
+            || {
+                let h = thread::spawn(|| {
+                    let v: i32 = traceforge::recv_msg_block();
+                    assertions::assert_eq!(v, 2);
+                });
+                traceforge::send_msg(h.thread().id(), 1);
+            },
+        );
+    });
+    assert!(result.is_err());
+
+    let trace = read_trace(trace_filename);
+    let failure = &trace["failure"];
+    assert_eq!(failure["category"], "assertion");
+    assert_eq!(
+        failure["message"],
+        "assertion `left == right` failed\n  left: 1\n right: 2"
+    );
+    assert!(failure["location"]
+        .as_str()
+        .unwrap()
+        .contains("tests/assertions.rs:"));
+}
+
+#[test]
+fn assert_keeps_std_payload() {
+    let trace_filename = "/tmp/assertions.rs_assert_keeps_std_payload";
+    remove_trace(trace_filename);
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(
+            Config::builder().with_error_trace(trace_filename).build(),
+            || {
+                let b = traceforge::nondet();
+                assertions::assert!(b);
+            },
+        );
+    });
+    assert_panic_msg(result, "assertion failed: b");
+
+    let trace = read_trace(trace_filename);
+    let failure = &trace["failure"];
+    assert_eq!(failure["category"], "assertion");
+    assert_eq!(failure["message"], "assertion failed: b");
+}
+
+#[test]
+fn panic_is_reported_as_panic() {
+    let trace_filename = "/tmp/assertions.rs_panic_is_reported_as_panic";
+    remove_trace(trace_filename);
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(
+            Config::builder().with_error_trace(trace_filename).build(),
+            || {
+                if traceforge::nondet() {
+                    panic!("boom");
+                }
+            },
+        );
+    });
+    assert!(result.is_err());
+
+    let trace = read_trace(trace_filename);
+    let failure = &trace["failure"];
+    assert_eq!(failure["category"], "panic");
+    assert_eq!(failure["message"], "boom");
+}
+
+#[test]
+fn assertions_outside_of_a_test() {
+    let result = std::panic::catch_unwind(|| assertions::assert!(1 + 1 == 3));
+    assert_panic_msg(result, "assertion failed: 1 + 1 == 3");
+    let result = std::panic::catch_unwind(|| assertions::assert!(false, "custom {}", 7));
+    assert_panic_msg(result, "custom 7");
+    let result = std::panic::catch_unwind(|| assertions::assert_eq!(1, 2, "values differ"));
+    assert_panic_msg(
+        result,
+        "assertion `left == right` failed: values differ\n  left: 1\n right: 2",
+    );
+    assertions::assert_eq!(2, 2);
+}
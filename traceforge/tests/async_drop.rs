@@ -0,0 +1,102 @@
+use std::collections::BTreeSet;
+
+use traceforge::future::{self, on_drop};
+use traceforge::thread::{self, ThreadId};
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Op {
+    // A task uses the shared resource
+    Use(usize),
+    // A task is done with the shared resource
+    Release(usize),
+    // The resource is freed, whoever still uses it
+    Free,
+}
+
+// Two tasks share a resource owned by the main thread; each task has an async cleanup that
+// uses the resource one last time. With `refcount`, the resource is freed once both tasks
+// are released; otherwise the cleanup of task 0 frees it right away.
+fn teardown(refcount: bool) {
+    let main = thread::current().id();
+    let handles: Vec<_> = (0..2)
+        .map(|i| {
+            future::spawn(async move {
+                let _guard = on_drop(async move {
+                    if i == 0 && !refcount {
+                        traceforge::send_msg(main, Op::Free);
+                    } else {
+                        traceforge::send_msg(main, Op::Use(i));
+                        traceforge::send_msg(main, Op::Release(i));
+                    }
+                });
+            })
+        })
+        .collect();
+    drop(handles);
+
+    let expected = if refcount { 4 } else { 3 };
+    let (mut freed, mut released) = (false, 0);
+    for _ in 0..expected {
+        match traceforge::recv_msg_block::<Op>() {
+            Op::Use(_) => traceforge::assert(!freed),
+            Op::Release(_) => {
+                released += 1;
+                freed = released == 2;
+            }
+            Op::Free => freed = true,
+        }
+    }
+}
+
+#[test]
+fn cleanups_of_cancelled_futures_interleave() {
+    let orders = Outcomes::new();
+    let seen = orders.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let main = thread::current().id();
+        let handles: Vec<_> = (0..2usize)
+            .map(|i| {
+                future::spawn(async move {
+                    let _guard = on_drop(async move { traceforge::send_msg(main, i) });
+                    // Never completes, the future is cancelled when its handle is dropped
+                    std::future::pending::<()>().await;
+                })
+            })
+            .collect();
+        drop(handles);
+        let order: Vec<usize> = (0..2).map(|_| traceforge::recv_msg_block()).collect();
+        seen.insert(order);
+    });
+    assert_eq!(stats.block, 0);
+    assert_eq!(orders.distinct(), BTreeSet::from([vec![0, 1], vec![1, 0]]));
+}
+
+#[test]
+fn use_after_free_in_teardown_is_found() {
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(Config::builder().build(), || teardown(false))
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn refcounted_teardown_never_uses_a_freed_resource() {
+    let stats = traceforge::verify(Config::builder().build(), || teardown(true));
+    // The uses and releases of the two cleanups interleave in all ways allowed by FIFO delivery
+    assert_eq!(stats.execs, 6);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn disarmed_guard_does_not_run_the_cleanup() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let main: ThreadId = thread::current().id();
+        on_drop(async move { traceforge::send_msg(main, ()) }).disarm();
+        traceforge::assert(traceforge::recv_msg::<()>().is_none());
+    });
+    assert_eq!(stats.execs, 1);
+}
@@ -0,0 +1,49 @@
+use traceforge::thread;
+use traceforge::Config;
+
+// Three clients send their ids to the main thread, which receives two of them in a row, and
+// expects them in increasing order
+fn model(atomic: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let me = thread::current().id();
+        for i in 0..3 {
+            thread::spawn(move || traceforge::send_msg(me, i));
+        }
+        let recv_two = || {
+            let a: i32 = traceforge::recv_msg_block();
+            let b: i32 = traceforge::recv_msg_block();
+            (a, b)
+        };
+        let (a, b) = if atomic {
+            traceforge::atomic_region(recv_two)
+        } else {
+            recv_two()
+        };
+        traceforge::assert(a < b);
+        let _: i32 = traceforge::recv_msg_block();
+    }
+}
+
+fn config() -> Config {
+    Config::builder().with_keep_going_after_error(true).build()
+}
+
+#[test]
+fn atomic_region_reduces_the_explored_executions() {
+    let plain = traceforge::verify(config(), model(false));
+    // Any two of the three ids, in any order
+    assert_eq!(plain.execs + plain.block, 6);
+    let atomic = traceforge::verify(config(), model(true));
+    assert_eq!(atomic.execs + atomic.block, 1);
+}
+
+#[test]
+fn bugs_inside_an_atomic_region_are_not_found() {
+    let plain =
+        std::panic::catch_unwind(|| traceforge::verify(Config::builder().build(), model(false)));
+    assert!(plain.is_err());
+    // The only order explored inside the region receives the ids in increasing order: the
+    // assertion never fails, as the warning of `atomic_region` says
+    let atomic = traceforge::verify(Config::builder().build(), model(true));
+    assert_eq!(atomic.execs, 1);
+}
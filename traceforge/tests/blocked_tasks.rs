@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::sync::Notify;
+use traceforge::thread::{self, ThreadId};
+use traceforge::{future, Config, WaitReason};
+
+mod utils;
+use utils::Outcomes;
+
+// Lets the other tasks run until none of them can, and returns the blocked tasks
+fn wait_for_blocked() -> Vec<(ThreadId, WaitReason)> {
+    while traceforge::runnable_count() > 1 {
+        thread::yield_now();
+    }
+    let mut blocked = traceforge::blocked_tasks();
+    blocked.sort();
+    blocked
+}
+
+#[test]
+fn task_waiting_on_notify_reports_notify() {
+    let observed = Outcomes::new();
+    let seen = observed.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        // Nobody notifies the waiter, which waits forever
+        let notify = Arc::new(Notify::new());
+        let waiter_id = Arc::new(Mutex::new(None));
+        let _waiter = {
+            let waiter_id = waiter_id.clone();
+            future::spawn(async move {
+                *waiter_id.lock().unwrap() = Some(thread::current().id());
+                notify.notified().await;
+            })
+        };
+
+        let blocked = wait_for_blocked();
+        let waiter_id = waiter_id.lock().unwrap().unwrap();
+        seen.insert((waiter_id, blocked));
+    });
+    let observed = observed.all();
+    assert!(stats.execs > 0);
+    assert_eq!(observed.len(), stats.execs + stats.block);
+    for (waiter_id, blocked) in observed.iter() {
+        assert_eq!(*blocked, vec![(*waiter_id, WaitReason::Notify)]);
+    }
+}
+
+#[test]
+fn receive_and_join_report_what_they_wait_for() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let receiver = thread::spawn(|| {
+            let _: u32 = traceforge::recv_msg_block();
+        });
+        let receiver_id = receiver.thread().id();
+        let joiner = thread::spawn(move || receiver.join().unwrap());
+        let joiner_id = joiner.thread().id();
+
+        let blocked = wait_for_blocked();
+        assert_eq!(
+            blocked,
+            vec![
+                (receiver_id, WaitReason::Receive),
+                (joiner_id, WaitReason::Join(receiver_id)),
+            ]
+        );
+
+        traceforge::send_msg(receiver_id, 1u32);
+        joiner.join().unwrap();
+    });
+    assert_eq!(stats.block, 0);
+}
@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::{channel, check_cancel_safe, thread, Config};
+
+// Moves an amount between two accounts, waiting for the amount and then for an
+// acknowledgment, each sent by another thread
+fn transfer(debit_early: bool) {
+    check_cancel_safe(Config::builder().build(), move || {
+        let accounts = Arc::new(Mutex::new([100u32, 0]));
+        let (tx, rx) = channel::Builder::<u32>::new().build();
+        let _ = thread::spawn(move || {
+            tx.send_msg(10);
+            tx.send_msg(0);
+        });
+        let a = accounts.clone();
+        let transfer = async move {
+            let amount = rx.async_recv_msg().await;
+            if debit_early {
+                a.lock().unwrap()[0] -= amount;
+            }
+            let _ack = rx.async_recv_msg().await;
+            let mut a = a.lock().unwrap();
+            if !debit_early {
+                a[0] -= amount;
+            }
+            a[1] += amount;
+        };
+        let invariant = move || accounts.lock().unwrap().iter().sum::<u32>() == 100;
+        (transfer, invariant)
+    });
+}
+
+#[test]
+#[should_panic(expected = "not cancellation safe")]
+fn a_cancellation_between_the_updates_is_flagged() {
+    transfer(true);
+}
+
+#[test]
+fn updates_after_the_last_await_are_cancel_safe() {
+    transfer(false);
+}
@@ -0,0 +1,38 @@
+use std::io::Write;
+
+use traceforge::{Config, Nondet};
+use utils::{read_trace, remove_trace};
+
+mod utils;
+
+// The test harness only intercepts `println!`, so the model writes to the streams directly.
+// There is a single test in this file because capturing redirects the output of the whole
+// process, including the harness.
+#[test]
+fn only_failing_execution_output_is_kept() {
+    let trace_filename = "/tmp/capture_output.rs_only_failing_execution_output_is_kept";
+    remove_trace(trace_filename);
+    let stats = traceforge::verify(
+        Config::builder()
+            .with_capture_output(true)
+            .with_keep_going_after_error(true)
+            .with_error_trace(trace_filename)
+            .build(),
+        || {
+            let v = (0..3).nondet();
+            writeln!(std::io::stdout(), "running with value {}", v).unwrap();
+            if v == 1 {
+                writeln!(std::io::stderr(), "about to fail on value {}", v).unwrap();
+            }
+            traceforge::assert(v != 1);
+        },
+    );
+    assert_eq!(stats.execs + stats.block, 3);
+
+    let trace = read_trace(trace_filename);
+    let output = trace["output"].as_str().expect("no output in trace");
+    assert!(output.contains("running with value 1\n"));
+    assert!(output.contains("about to fail on value 1\n"));
+    assert!(!output.contains("value 0"));
+    assert!(!output.contains("value 2"));
+}
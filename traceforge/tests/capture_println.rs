@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use traceforge::{Config, Nondet};
+use utils::{read_trace, remove_trace};
+
+mod utils;
+
+// Whether the harness leaves `println!` alone, see `libtest`'s `--nocapture`
+fn nocapture() -> bool {
+    std::env::args().any(|arg| arg == "--nocapture")
+        || std::env::var("RUST_TEST_NOCAPTURE").is_ok_and(|v| v != "0")
+}
+
+// Under the test harness, `println!` goes to the harness's buffer instead of stdout, so only the
+// direct writes to the stream are captured. The test is alone in its file, like the one of
+// `capture_output.rs`, because capturing redirects the output of the whole process.
+#[test]
+fn println_under_the_harness_is_not_captured() {
+    let trace_filename = "/tmp/capture_println.rs_println_under_the_harness_is_not_captured";
+    remove_trace(trace_filename);
+    traceforge::verify(
+        Config::builder()
+            .with_capture_output(true)
+            .with_keep_going_after_error(true)
+            .with_error_trace(trace_filename)
+            .build(),
+        || {
+            let v = (0..2).nondet();
+            println!("printed on value {}", v);
+            writeln!(std::io::stdout(), "written on value {}", v).unwrap();
+            traceforge::assert(v != 1);
+        },
+    );
+
+    let trace = read_trace(trace_filename);
+    let output = trace["output"].as_str().expect("no output in trace");
+    assert!(output.contains("written on value 1\n"));
+    assert_eq!(output.contains("printed on value 1"), nocapture());
+    remove_trace(trace_filename);
+}
@@ -0,0 +1,183 @@
+use std::panic::catch_unwind;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use traceforge::monitor_types::{ExecutionEnd, Monitor, MonitorResult};
+use traceforge::{channel, thread, CollectedFailure, Config, FailureCategory};
+use traceforge_macros::monitor;
+
+mod utils;
+use utils::{assert_panic_contains, read_trace, remove_trace};
+
+// Two senders race, and each receive order hits a bug of its own. The extra choice doubles the
+// executions, so that each bug is found twice.
+fn two_bugs() {
+    let (tx, rx) = channel::Builder::<u32>::new().build();
+    let tx2 = tx.clone();
+    let _ = thread::spawn(move || tx.send_msg(1));
+    let _ = thread::spawn(move || tx2.send_msg(2));
+    let _ = traceforge::nondet();
+    let first: u32 = rx.recv_msg_block();
+    assert!(first != 1, "one came first");
+    if first == 2 {
+        panic!("two came first");
+    }
+}
+
+fn find<'a>(failures: &'a [CollectedFailure], message: &str) -> &'a CollectedFailure {
+    failures
+        .iter()
+        .find(|f| f.message.contains(message))
+        .unwrap_or_else(|| panic!("{} is not in {:?}", message, failures))
+}
+
+// The value of the message received in the schedule of `f`, whose events look like
+// `(t0, 5): RECV() [(t1, 1)]` and `(t1, 1): SEND(..., Val { val: 1, type_name: "u32" })`
+fn received(f: &CollectedFailure) -> u32 {
+    let recv = f.schedule.iter().find(|e| e.contains("RECV")).unwrap();
+    let send = &recv[recv.find('[').unwrap() + 1..recv.find(']').unwrap()];
+    let send = f
+        .schedule
+        .iter()
+        .find(|e| e.starts_with(&format!("{}: SEND", send)))
+        .unwrap();
+    let val = &send[send.find("val: ").unwrap() + 5..];
+    val[..val.find(',').unwrap()].parse().unwrap()
+}
+
+#[test]
+fn all_distinct_failures_are_returned() {
+    let config = Config::builder().with_collect_failures(true).build();
+    let stats = traceforge::verify(config, two_bugs);
+
+    assert_eq!(stats.collected_failures.len(), 2);
+    assert_eq!(stats.failures, 4);
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.block, 4);
+    let one = find(&stats.collected_failures, "one came first");
+    let two = find(&stats.collected_failures, "two came first");
+    for f in [one, two] {
+        assert_eq!(f.category, FailureCategory::Panic);
+        assert_eq!(f.count, 2);
+    }
+    assert_ne!(one.execution, two.execution);
+
+    // The schedule of each failure receives the message that triggers it
+    assert_eq!(received(one), 1, "{:?}", one.schedule);
+    assert_eq!(received(two), 2, "{:?}", two.schedule);
+}
+
+#[test]
+fn keep_going_assertions_are_collected() {
+    let config = Config::builder()
+        .with_keep_going_after_error(true)
+        .with_collect_failures(true)
+        .build();
+    let stats = traceforge::verify(config, || {
+        let b = traceforge::nondet();
+        traceforge::assert(b);
+    });
+
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.collected_failures.len(), 1);
+    let failure = &stats.collected_failures[0];
+    assert_eq!(failure.category, FailureCategory::Assertion);
+    assert_eq!(failure.count, 1);
+}
+
+#[monitor()]
+#[derive(Clone, Debug, Default)]
+struct FailingMonitor {
+    fail: bool,
+}
+
+impl Monitor for FailingMonitor {
+    fn on_stop(&mut self, _execution_end: &ExecutionEnd) -> MonitorResult {
+        if self.fail {
+            return Err("the monitor was told to fail".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn monitor_errors_are_collected() {
+    let config = Config::builder().with_collect_failures(true).build();
+    let stats = traceforge::verify(config, || {
+        start_monitor_failing_monitor(FailingMonitor {
+            fail: traceforge::nondet(),
+        });
+    });
+
+    // The exploration goes on past the failing execution, which is counted as blocked
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 1);
+    assert_eq!(stats.collected_failures.len(), 1);
+    let failure = find(&stats.collected_failures, "the monitor was told to fail");
+    assert_eq!(failure.category, FailureCategory::Monitor);
+    assert_eq!(failure.count, 1);
+}
+
+#[test]
+fn liveness_violations_are_collected() {
+    let held = Arc::new(AtomicBool::new(false));
+    let flag = held.clone();
+    let config = Config::builder()
+        .with_eventually(move || flag.load(Ordering::SeqCst))
+        .with_collect_failures(true)
+        .build();
+    let stats = traceforge::verify(config, move || {
+        held.store(false, Ordering::SeqCst);
+        if traceforge::nondet() {
+            held.store(true, Ordering::SeqCst);
+        }
+    });
+
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 1);
+    assert_eq!(stats.collected_failures.len(), 1);
+    let failure = find(
+        &stats.collected_failures,
+        "eventually property 0 never held",
+    );
+    assert_eq!(failure.category, FailureCategory::Liveness);
+    assert_eq!(failure.count, 1);
+}
+
+#[test]
+fn each_distinct_failure_has_a_trace_file() {
+    let dir = std::env::temp_dir();
+    let base = dir.join("collect_failures.json");
+    let base = base.to_str().unwrap();
+    let traces = [1, 2].map(|n| {
+        let trace = dir.join(format!("collect_failures-{}.json", n));
+        trace.to_str().unwrap().to_string()
+    });
+    for trace in &traces {
+        remove_trace(trace);
+    }
+
+    let config = Config::builder()
+        .with_collect_failures(true)
+        .with_error_trace(base)
+        .build();
+    let stats = traceforge::verify(config, two_bugs);
+
+    assert_eq!(stats.collected_failures.len(), 2);
+    for (f, expected) in stats.collected_failures.iter().zip(&traces) {
+        assert_eq!(f.trace.as_ref(), Some(expected));
+        let trace = read_trace(expected);
+        assert_eq!(trace["failure"]["message"], f.message.as_str());
+        // The trace replays its own failure
+        let result = catch_unwind(|| traceforge::replay(two_bugs, expected));
+        assert_panic_contains(result, &f.message);
+        remove_trace(expected);
+    }
+    assert!(!std::path::Path::new(base).exists());
+}
+
+#[test]
+#[should_panic(expected = "one came first")]
+fn failures_are_fatal_by_default() {
+    traceforge::verify(Config::builder().build(), two_bugs);
+}
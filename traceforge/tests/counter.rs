@@ -0,0 +1,40 @@
+use traceforge::testing::Counter;
+use traceforge::thread;
+use traceforge::Config;
+
+#[test]
+fn concurrent_increments_are_not_lost() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let counter = Counter::new();
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let c = counter.clone();
+                thread::spawn(move || {
+                    c.incr();
+                    c.incr();
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(counter.get(), 4);
+    });
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn decrement_undoes_increment() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let counter = Counter::new();
+        let c = counter.clone();
+        let h = thread::spawn(move || c.decr());
+        counter.incr();
+        let v = counter.get();
+        assert!(v == 0 || v == 1);
+        h.join().unwrap();
+        assert_eq!(counter.get(), 0);
+    });
+    assert!(stats.execs > 1);
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 //use traceforge::{cover, named_cover, probe, Nondet};
@@ -189,3 +191,98 @@ impl ExecutionObserver for TimeMetric {
         );
     }
 }
+
+const FEATURE: &str = "FEATURE";
+
+// Three messages race to the main thread, and the feature is only reached when the message 2
+// comes first, in 2 of the 6 orders. With `buggy`, the order 2, 0, 1 fails behind the feature.
+fn feature_model(buggy: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let main = thread::main_thread_id();
+        for i in 0..3 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let first: i32 = traceforge::recv_msg_block();
+        let second: i32 = traceforge::recv_msg_block();
+        let _: i32 = traceforge::recv_msg_block();
+        if first == 2 {
+            cover!(FEATURE);
+            assert!(!buggy || second != 0, "bug behind the feature");
+        }
+    }
+}
+
+#[test]
+fn coverage_target_reports_reaching_executions() {
+    let all = traceforge::verify(Config::builder().build(), feature_model(false));
+    assert_eq!(all.execs, 6);
+    assert_eq!(all.off_target, 0);
+
+    let config = Config::builder().with_coverage_target(FEATURE).build();
+    let targeted = traceforge::verify(config, feature_model(false));
+    assert_eq!(targeted.execs, 2);
+    assert_eq!(targeted.off_target, 4);
+    assert_eq!(targeted.coverage.covered(FEATURE.into()), 2);
+}
+
+#[test]
+#[should_panic(expected = "bug behind the feature")]
+fn coverage_target_finds_bugs_behind_it() {
+    let config = Config::builder().with_coverage_target(FEATURE).build();
+    traceforge::verify(config, feature_model(true));
+}
+
+#[test]
+fn off_target_executions_have_no_final_state() {
+    static ORDER: AtomicU64 = AtomicU64::new(0);
+    let config = Config::builder()
+        .with_coverage_target(FEATURE)
+        .with_fingerprint(|| ORDER.load(Ordering::SeqCst))
+        .build();
+    let stats = traceforge::verify(config, || {
+        let main = thread::main_thread_id();
+        for i in 0..3u64 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let order = (0..3).fold(0, |order, _| {
+            order * 10 + traceforge::recv_msg_block::<u64>()
+        });
+        ORDER.store(order, Ordering::SeqCst);
+        if order / 100 == 2 {
+            cover!(FEATURE);
+        }
+    });
+    assert_eq!(stats.off_target, 4);
+    assert_eq!(stats.final_states, BTreeSet::from([201, 210]));
+}
+
+#[test]
+fn off_target_deadlocks_are_not_collected() {
+    // Only the executions that miss the feature deadlock
+    let model = || {
+        let main = thread::main_thread_id();
+        for i in 0..2 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let first: i32 = traceforge::recv_msg_block();
+        if first == 1 {
+            cover!(FEATURE);
+        } else {
+            let _: i32 = traceforge::recv_msg_block();
+            let _: i32 = traceforge::recv_msg_block();
+        }
+    };
+    let all = traceforge::verify(
+        Config::builder().with_collect_deadlocks(true).build(),
+        model,
+    );
+    assert_eq!(all.deadlocks.len(), 1);
+
+    let config = Config::builder()
+        .with_collect_deadlocks(true)
+        .with_coverage_target(FEATURE)
+        .build();
+    let targeted = traceforge::verify(config, model);
+    assert_eq!(targeted.off_target, 1);
+    assert!(targeted.deadlocks.is_empty());
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeSet;
+
+use traceforge::db::{Db, Isolation};
+use traceforge::{thread, Config};
+
+mod utils;
+use utils::Outcomes;
+
+// Two doctors are on call, and each one goes off call if the other is still on call. Returns
+// the numbers of doctors on call that the explored executions end with.
+fn on_call_outcomes(isolation: Isolation) -> BTreeSet<usize> {
+    let outcomes = Outcomes::new();
+    let seen = outcomes.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let db = Db::new(isolation);
+        let mut setup = db.begin();
+        setup.write("alice", true);
+        setup.write("bob", true);
+        setup.commit().unwrap();
+
+        let go_off_call = |db: Db<bool>, me: &'static str, other: &'static str| {
+            move || {
+                let mut tx = db.begin();
+                if tx.read(me).unwrap() && tx.read(other).unwrap() {
+                    tx.write(me, false);
+                    let _ = tx.commit();
+                }
+            }
+        };
+        let a = thread::spawn(go_off_call(db.clone(), "alice", "bob"));
+        let b = thread::spawn(go_off_call(db.clone(), "bob", "alice"));
+        a.join().unwrap();
+        b.join().unwrap();
+        let on_call = ["alice", "bob"]
+            .iter()
+            .filter(|d| db.get(d).unwrap())
+            .count();
+        seen.insert(on_call);
+    });
+    outcomes.distinct()
+}
+
+#[test]
+fn write_skew_under_snapshot_isolation() {
+    // Both transactions see the other doctor on call in their snapshot
+    assert_eq!(
+        on_call_outcomes(Isolation::Snapshot),
+        BTreeSet::from([0, 1])
+    );
+    assert_eq!(
+        on_call_outcomes(Isolation::ReadCommitted),
+        BTreeSet::from([0, 1])
+    );
+}
+
+#[test]
+fn no_write_skew_when_serializable() {
+    assert_eq!(
+        on_call_outcomes(Isolation::Serializable),
+        BTreeSet::from([1])
+    );
+}
+
+#[test]
+fn lost_update_only_under_read_committed() {
+    let outcomes = |isolation: Isolation| {
+        let outcomes = Outcomes::new();
+        let seen = outcomes.clone();
+        traceforge::verify(Config::builder().build(), move || {
+            let db = Db::new(isolation);
+            let mut setup = db.begin();
+            setup.write("x", 0);
+            setup.commit().unwrap();
+            let increment = |db: Db<i32>| {
+                move || {
+                    let mut tx = db.begin();
+                    let x = tx.read("x").unwrap();
+                    tx.write("x", x + 1);
+                    tx.commit().is_ok()
+                }
+            };
+            let a = thread::spawn(increment(db.clone()));
+            let b = thread::spawn(increment(db.clone()));
+            let commits = [a.join().unwrap(), b.join().unwrap()];
+            let commits = commits.iter().filter(|c| **c).count() as i32;
+            seen.insert((commits, db.get("x").unwrap()));
+        });
+        outcomes.distinct()
+    };
+    // Both commit, but one increment overwrites the other
+    assert!(outcomes(Isolation::ReadCommitted).contains(&(2, 1)));
+    // A transaction that read a stale value fails to commit
+    let snapshot = outcomes(Isolation::Snapshot);
+    assert_eq!(snapshot, BTreeSet::from([(1, 1), (2, 2)]));
+}
@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::deadlock::BlockedTask;
+use traceforge::sync::Mutex as Lock;
+use traceforge::thread;
+use traceforge::{Config, Deadlock, WaitReason};
+
+fn blocked(name: &str, reason: WaitReason) -> BlockedTask {
+    BlockedTask {
+        name: name.to_string(),
+        reason,
+    }
+}
+
+#[test]
+fn all_distinct_deadlocks_are_reported() {
+    let consumer_id = Arc::new(Mutex::new(None));
+    let seen = consumer_id.clone();
+    let stats = traceforge::verify(
+        Config::builder().with_collect_deadlocks(true).build(),
+        move || {
+            if traceforge::nondet() {
+                // The consumer waits for a message that is never sent,
+                // and main waits for the consumer
+                let h = thread::Builder::new()
+                    .name("consumer".to_string())
+                    .spawn(|| {
+                        let _: u32 = traceforge::recv_msg_block();
+                    })
+                    .unwrap();
+                *seen.lock().unwrap() = Some(h.thread().id());
+                h.join().unwrap();
+            } else {
+                // Two workers wait for messages that are never sent. The choice does not
+                // change the deadlock, which is reported once.
+                let _ = traceforge::nondet();
+                let a = thread::Builder::new()
+                    .name("ping".to_string())
+                    .spawn(|| {
+                        let _: u32 = traceforge::recv_msg_block();
+                    })
+                    .unwrap();
+                let b = thread::Builder::new()
+                    .name("pong".to_string())
+                    .spawn(|| {
+                        let _: u32 = traceforge::recv_msg_block();
+                    })
+                    .unwrap();
+                let _ = (a, b);
+            }
+        },
+    );
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.block, 3);
+
+    let consumer_id = consumer_id.lock().unwrap().unwrap();
+    assert_eq!(stats.deadlocks.len(), 2);
+    assert!(stats.deadlocks.contains(&Deadlock {
+        blocked: vec![
+            blocked("consumer", WaitReason::Receive),
+            blocked("main", WaitReason::Join(consumer_id)),
+        ],
+    }));
+    assert!(stats.deadlocks.contains(&Deadlock {
+        blocked: vec![
+            blocked("ping", WaitReason::Receive),
+            blocked("pong", WaitReason::Receive),
+        ],
+    }));
+}
+
+#[test]
+fn lock_order_deadlock_is_reported_as_lock() {
+    let stats = traceforge::verify(
+        Config::builder().with_collect_deadlocks(true).build(),
+        || {
+            // The workers take the two locks in opposite orders
+            let a = Arc::new(Lock::new(()));
+            let b = Arc::new(Lock::new(()));
+            let worker = |name: &str, first: Arc<Lock<()>>, second: Arc<Lock<()>>| {
+                thread::Builder::new()
+                    .name(name.to_string())
+                    .spawn(move || {
+                        let _first = first.blocking_lock();
+                        let _second = second.blocking_lock();
+                    })
+                    .unwrap()
+            };
+            let h1 = worker("left", a.clone(), b.clone());
+            let h2 = worker("right", b, a);
+            h1.join().unwrap();
+            h2.join().unwrap();
+        },
+    );
+    assert!(stats.block > 0);
+    assert_eq!(stats.deadlocks.len(), 1);
+    let tasks = &stats.deadlocks[0].blocked;
+    assert!(tasks.contains(&blocked("left", WaitReason::Lock)));
+    assert!(tasks.contains(&blocked("right", WaitReason::Lock)));
+}
+
+#[test]
+fn deadlocks_are_not_collected_by_default() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let _: u32 = traceforge::recv_msg_block();
+    });
+    assert_eq!(stats.block, 1);
+    assert!(stats.deadlocks.is_empty());
+}
@@ -0,0 +1,61 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::{thread, Config};
+
+// Two clients withdraw 10 from an account holding 10, checking the balance before withdrawing.
+// Both withdraw only if both check before either withdraws. The balance is not a TraceForge
+// primitive, so TraceForge cannot switch tasks between the check and the withdrawal, unless it
+// is told to delay the clients there.
+fn double_withdrawal() {
+    let balance = Arc::new(Mutex::new(10));
+    let clients: Vec<_> = (0..2)
+        .map(|_| {
+            let balance = balance.clone();
+            thread::spawn(move || {
+                let seen = *balance.lock().unwrap();
+                if seen < 10 {
+                    return false;
+                }
+                traceforge::point("checked");
+                traceforge::point("withdraw");
+                *balance.lock().unwrap() = seen - 10;
+                true
+            })
+        })
+        .collect();
+    let withdrawals = clients
+        .into_iter()
+        .map(|client| client.join().unwrap())
+        .filter(|withdrew| *withdrew)
+        .count();
+    assert!(withdrawals <= 1, "double withdrawal");
+}
+
+#[test]
+fn race_window_is_not_explored_without_delay() {
+    let stats = traceforge::verify(Config::builder().build(), double_withdrawal);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+#[should_panic(expected = "double withdrawal")]
+fn delay_in_the_window_surfaces_the_race() {
+    let config = Config::builder().with_delay_between("checked", "withdraw");
+    traceforge::verify(config.build(), double_withdrawal);
+}
+
+#[test]
+#[should_panic(expected = "double withdrawal")]
+fn delay_surfaces_the_race_in_the_first_schedule() {
+    let config = Config::builder()
+        .with_max_iterations(1)
+        .with_delay_between("checked", "withdraw");
+    traceforge::verify(config.build(), double_withdrawal);
+}
+
+#[test]
+fn points_in_the_other_order_do_not_delay() {
+    let config = Config::builder().with_delay_between("withdraw", "checked");
+    let stats = traceforge::verify(config.build(), double_withdrawal);
+    assert_eq!(stats.block, 0);
+}
@@ -0,0 +1,68 @@
+use traceforge::thread;
+use traceforge::{Config, MsgId};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Op {
+    Write,
+    Close,
+}
+
+// A writer and a closer race to the file server: the write must not follow the close
+fn model() {
+    let server = thread::Builder::new()
+        .name("server".to_string())
+        .spawn(|| {
+            let mut closed = false;
+            for _ in 0..2 {
+                match traceforge::recv_msg_block::<Op>() {
+                    Op::Write => traceforge::assert(!closed),
+                    Op::Close => closed = true,
+                }
+            }
+        })
+        .unwrap();
+    let server = server.thread().id();
+    for (name, op) in [("writer", Op::Write), ("closer", Op::Close)] {
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || traceforge::send_msg(server, op))
+            .unwrap();
+    }
+}
+
+fn pinned(order: &[MsgId]) -> Config {
+    Config::builder()
+        .with_delivery_order(order)
+        .with_keep_going_after_error(true)
+        .build()
+}
+
+#[test]
+fn pinned_order_reproduces_the_bug() {
+    let bug = [MsgId::new("closer", 0), MsgId::new("writer", 0)];
+    let stats = traceforge::verify(pinned(&bug), model);
+    // The failing execution, and the other order, cut short at the first receive
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 1);
+
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(Config::builder().with_delivery_order(&bug).build(), model)
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn pinned_order_excludes_the_other_orders() {
+    let fixed = [MsgId::new("writer", 0), MsgId::new("closer", 0)];
+    let stats = traceforge::verify(pinned(&fixed), model);
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 1);
+}
+
+#[test]
+#[should_panic(expected = "The pinned delivery order [closer#0, writer#1] is not feasible")]
+fn infeasible_order_fails_loudly() {
+    // The writer only sends one message
+    let order = [MsgId::new("closer", 0), MsgId::new("writer", 1)];
+    traceforge::verify(pinned(&order), model);
+}
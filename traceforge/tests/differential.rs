@@ -0,0 +1,139 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use traceforge::script::Action::{self, Task};
+use traceforge::sync::atomic::AtomicU32;
+use traceforge::sync::Mutex;
+use traceforge::{thread, Config};
+
+mod utils;
+use utils::Outcomes;
+
+struct Counter {
+    value: AtomicU32,
+    lock: Mutex<()>,
+}
+
+// Two workers each add one to a shared counter, whose final value is the output
+fn count(add: fn(&Counter)) -> u32 {
+    let counter = Arc::new(Counter {
+        value: AtomicU32::new(0),
+        lock: Mutex::new(()),
+    });
+    let workers: Vec<_> = (0..2)
+        .map(|_| {
+            let counter = counter.clone();
+            thread::spawn(move || add(&counter))
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    counter.value.load(Ordering::SeqCst)
+}
+
+fn compare_exchange_loop(counter: &Counter) {
+    loop {
+        let current = counter.value.load(Ordering::SeqCst);
+        if counter
+            .value
+            .compare_exchange(current, current + 1, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            return;
+        }
+    }
+}
+
+fn locked_load_then_store(counter: &Counter) {
+    let _guard = counter.lock.blocking_lock();
+    let current = counter.value.load(Ordering::SeqCst);
+    counter.value.store(current + 1, Ordering::SeqCst);
+}
+
+// The refactor drops the lock, which loses an update when the workers interleave
+fn load_then_store(counter: &Counter) {
+    let current = counter.value.load(Ordering::SeqCst);
+    counter.value.store(current + 1, Ordering::SeqCst);
+}
+
+#[test]
+fn equivalent_refactor_passes() {
+    let stats = traceforge::differential(
+        Config::builder().build(),
+        || count(compare_exchange_loop),
+        || count(locked_load_then_store),
+    );
+    assert!(stats.execs > 1);
+}
+
+// Returns the message of the failure of `differential`
+fn divergence(reference: fn(&Counter), test: fn(&Counter)) -> String {
+    let result = std::panic::catch_unwind(|| {
+        traceforge::differential(
+            Config::builder().build(),
+            move || count(reference),
+            move || count(test),
+        )
+    });
+    let payload = result.unwrap_err();
+    payload.downcast_ref::<String>().unwrap().clone()
+}
+
+// The schedules that the message of a divergence spells out, of the reference first
+fn schedules(message: &str) -> Vec<Vec<Action>> {
+    message
+        .split(" under the schedule [")
+        .skip(1)
+        .map(|schedule| {
+            let tasks = &schedule[..schedule.find(']').unwrap()];
+            tasks
+                .split(", ")
+                .map(|task| Task(task["Task(".len()..task.len() - 1].parse().unwrap()))
+                .collect()
+        })
+        .collect()
+}
+
+// The output of the counter on the schedule of `script`
+fn count_on(script: &[Action], add: fn(&Counter)) -> u32 {
+    let outputs = Outcomes::new();
+    let output = outputs.clone();
+    traceforge::run_script(Config::builder().build(), script, move || {
+        output.insert(count(add))
+    });
+    outputs.all()[0]
+}
+
+#[test]
+fn diverging_refactor_is_reported() {
+    let message = divergence(locked_load_then_store, load_then_store);
+    assert!(
+        message.contains("the reference returned 2 under the schedule "),
+        "{message}"
+    );
+    assert!(
+        message.contains("the implementation under test returned 1 under the schedule "),
+        "{message}"
+    );
+    let [reference, test] = &schedules(&message)[..] else {
+        panic!("{message}");
+    };
+    // Each implementation returns its output again on its schedule
+    assert_eq!(count_on(reference, locked_load_then_store), 2);
+    assert_eq!(count_on(test, load_then_store), 1);
+}
+
+#[test]
+fn divergence_in_either_direction_is_reported() {
+    let message = divergence(load_then_store, locked_load_then_store);
+    assert!(
+        message.contains("the reference returned 1 under the schedule "),
+        "{message}"
+    );
+    let [reference, test] = &schedules(&message)[..] else {
+        panic!("{message}");
+    };
+    assert_eq!(count_on(reference, load_then_store), 1);
+    assert_eq!(count_on(test, locked_load_then_store), 2);
+}
@@ -0,0 +1,74 @@
+use traceforge::dist::{self, Node};
+use traceforge::Config;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Election {
+    // Whether the sender runs for leader
+    Candidacy(bool),
+    // The candidate the sender votes for
+    Vote(usize),
+}
+
+// Node 0 always runs, the others may. Once it heard of every node, a node votes for the
+// candidate with the largest id, and the candidate that gets a majority of the votes leads.
+fn elect(node: Node<Election>) -> bool {
+    let runs = node.id() == 0 || traceforge::nondet();
+    node.broadcast(Election::Candidacy(runs));
+    let mut candidates = if runs { vec![node.id()] } else { vec![] };
+    let mut heard = 1;
+    let mut votes = vec![];
+    let mut voted = false;
+    while !voted || votes.len() < node.size() {
+        if !voted && heard == node.size() {
+            let vote = *candidates.iter().max().unwrap();
+            node.broadcast(Election::Vote(vote));
+            votes.push(vote);
+            voted = true;
+            continue;
+        }
+        match node.recv() {
+            (from, Election::Candidacy(runs)) => {
+                heard += 1;
+                if runs {
+                    candidates.push(from);
+                }
+            }
+            (_, Election::Vote(vote)) => votes.push(vote),
+        }
+    }
+    votes.iter().filter(|&&v| v == node.id()).count() >= node.quorum()
+}
+
+#[test]
+fn exactly_one_leader_is_elected() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let leaders = dist::spawn(3, elect).join();
+        assert_eq!(leaders.iter().filter(|&&l| l).count(), 1, "{:?}", leaders);
+    });
+    assert!(stats.execs > 1);
+}
+
+#[test]
+fn symmetric_nodes_are_explored_up_to_renaming() {
+    // Each node learns the largest value of the cluster, which does not depend on the ids
+    let learn_max = |node: Node<u32>| {
+        let value = traceforge::nondet() as u32;
+        node.broadcast(value);
+        (1..node.size())
+            .map(|_| node.recv().1)
+            .fold(value, u32::max)
+    };
+    let check = |maxima: Vec<u32>| assert!(maxima.windows(2).all(|w| w[0] == w[1]));
+    let plain = traceforge::verify(Config::builder().build(), move || {
+        check(dist::spawn(3, learn_max).join())
+    });
+    let symmetric = traceforge::verify(Config::builder().build(), move || {
+        check(dist::spawn_symmetric(3, learn_max).join())
+    });
+    assert!(
+        symmetric.execs < plain.execs,
+        "{} {}",
+        symmetric.execs,
+        plain.execs
+    );
+}
@@ -0,0 +1,97 @@
+use std::panic::catch_unwind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use traceforge::{thread, Config, Nondet};
+
+#[derive(Debug)]
+struct PanicsOnDrop;
+
+impl Drop for PanicsOnDrop {
+    fn drop(&mut self) {
+        panic!("PanicsOnDrop dropped");
+    }
+}
+
+// Spawns a task that owns a `PanicsOnDrop`, which it defuses when it runs, and cuts the
+// execution short before the task runs; the cleanup then drops the value
+fn owner_never_runs() {
+    let value = PanicsOnDrop;
+    thread::Builder::new()
+        .name("owner".to_string())
+        .spawn(move || std::mem::forget(value))
+        .unwrap();
+    // The main thread runs first, and its size stops the execution
+    let main = thread::current().id();
+    for i in 0..10u32 {
+        traceforge::send_msg(main, i);
+    }
+}
+
+fn config() -> traceforge::ConfigBuilder {
+    Config::builder().with_thread_threshold(4)
+}
+
+#[test]
+fn panic_in_drop_during_cleanup_is_a_failure_of_the_owner() {
+    let result =
+        std::panic::catch_unwind(|| traceforge::verify(config().build(), owner_never_runs));
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(
+        message
+            .contains("a Drop handler of task owner panicked during cleanup: PanicsOnDrop dropped"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn panics_in_drop_during_cleanup_can_be_ignored() {
+    let config = config().with_ignore_drop_panics(true).build();
+    traceforge::verify(config, owner_never_runs);
+}
+
+#[test]
+fn failure_of_the_execution_comes_before_panics_in_drop() {
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(Config::builder().build(), || {
+            let value = PanicsOnDrop;
+            thread::spawn(move || std::mem::forget(value));
+            panic!("the main thread failed");
+        })
+    });
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<&str>(),
+        Some(&"the main thread failed")
+    );
+}
+
+// The model only makes a choice in its first execution, so the checker stops the execution that
+// revisits it while the spawned tasks are still alive, and cleans them up
+#[test]
+fn nondeterminism_error_with_a_live_task() {
+    static EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+    let result = catch_unwind(|| {
+        traceforge::verify(Config::builder().build(), || {
+            let first = EXECUTIONS.fetch_add(1, Ordering::SeqCst) == 0;
+            let _live = thread::spawn(|| ());
+            if first {
+                let _ = (0..3).nondet();
+            }
+            let receiver = thread::spawn(|| {
+                let _: u32 = traceforge::recv_msg_block();
+            });
+            let id = receiver.thread().id();
+            traceforge::send_msg(id, 1u32);
+            thread::spawn(move || traceforge::send_msg(id, 2u32));
+        })
+    });
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("Incorrect TraceForge Program"),
+        "{}",
+        message
+    );
+}
@@ -0,0 +1,26 @@
+use traceforge::thread::construct_thread_id;
+use traceforge::Event;
+
+#[test]
+fn accessors_return_the_constructor_arguments() {
+    let tid = construct_thread_id(3);
+    let event = Event::new(tid, 7);
+    assert_eq!(event.thread(), tid);
+    assert_eq!(event.index(), 7);
+    assert_eq!(event.to_string(), "(t3, 7)");
+}
+
+#[test]
+fn events_of_a_thread_are_in_program_order() {
+    let tid = construct_thread_id(1);
+    let first = Event::new(tid, 1);
+    let second = Event::new(tid, 2);
+    assert!(first < second);
+    assert!(second > first);
+    assert!(first <= Event::new(tid, 1));
+
+    let mut events = [Event::new(tid, 5), second, Event::new(tid, 3), first];
+    events.sort();
+    let counts: Vec<u32> = events.iter().map(Event::index).collect();
+    assert_eq!(counts, [1, 2, 3, 5]);
+}
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use traceforge::thread;
+use traceforge::{Config, ConfigBuilder};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Msg {
+    Request,
+    // The primary hands its work off to the backup
+    Handoff,
+}
+
+// A client sends a request to the primary, which is concurrently told to hand off to the backup.
+// The request must eventually be acknowledged, by either server. With `forward`, a primary that
+// is handed off before receiving the request forwards it to the backup; otherwise it is lost.
+fn handoff(acked: Arc<AtomicBool>, forward: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        acked.store(false, Ordering::SeqCst);
+        let by_backup = acked.clone();
+        let backup = thread::spawn(move || {
+            if traceforge::recv_msg_block::<bool>() {
+                by_backup.store(true, Ordering::SeqCst);
+            }
+        });
+        let backup = backup.thread().id();
+        let by_primary = acked.clone();
+        let primary = thread::spawn(move || match traceforge::recv_msg_block::<Msg>() {
+            Msg::Request => {
+                by_primary.store(true, Ordering::SeqCst);
+                traceforge::recv_msg_block::<Msg>();
+                traceforge::send_msg(backup, false);
+            }
+            Msg::Handoff => {
+                if forward {
+                    traceforge::recv_msg_block::<Msg>();
+                }
+                traceforge::send_msg(backup, forward);
+            }
+        });
+        let primary = primary.thread().id();
+        thread::spawn(move || traceforge::send_msg(primary, Msg::Request));
+        traceforge::send_msg(primary, Msg::Handoff);
+    }
+}
+
+fn builder(acked: &Arc<AtomicBool>) -> ConfigBuilder {
+    let acked = acked.clone();
+    Config::builder().with_eventually(move || acked.load(Ordering::SeqCst))
+}
+
+fn config(acked: &Arc<AtomicBool>) -> Config {
+    builder(acked).build()
+}
+
+#[test]
+fn lost_request_is_reported() {
+    let acked = Arc::new(AtomicBool::new(false));
+    let model = handoff(acked.clone(), false);
+    let result = std::panic::catch_unwind(|| traceforge::verify(config(&acked), model));
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("eventually property 0 never held"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn fast_mode_reports_the_lost_request() {
+    let acked = Arc::new(AtomicBool::new(false));
+    let model = handoff(acked.clone(), false);
+    let config = builder(&acked).with_fast_failure(true).build();
+    let result = std::panic::catch_unwind(|| traceforge::verify(config, model));
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("TraceForge found a failure in execution"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn forwarded_request_is_eventually_acknowledged() {
+    let acked = Arc::new(AtomicBool::new(false));
+    let stats = traceforge::verify(config(&acked), handoff(acked.clone(), true));
+    // The primary receives either the request or the handoff first
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn blocked_executions_are_not_checked() {
+    let stats = traceforge::verify(Config::builder().with_eventually(|| false).build(), || {
+        traceforge::assume!(false)
+    });
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.block, 1);
+}
@@ -0,0 +1,30 @@
+use traceforge::testing::assert_exec_count;
+use traceforge::{thread, Config, Nondet};
+
+// Three workers send their id to the main thread, which receives them in any order
+fn model(extra_choice: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let main = thread::current().id();
+        for i in 0..3u32 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        for _ in 0..3 {
+            let _: u32 = traceforge::recv_msg_block();
+        }
+        if extra_choice {
+            let _ = (0..2).nondet();
+        }
+    }
+}
+
+#[test]
+fn exec_count_is_pinned() {
+    let stats = assert_exec_count(Config::builder().build(), model(false), 6);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+#[should_panic(expected = "expected 6 complete executions, but TraceForge explored 12")]
+fn drift_of_the_exec_count_is_caught() {
+    assert_exec_count(Config::builder().build(), model(true), 6);
+}
@@ -0,0 +1,58 @@
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use traceforge::{thread, Config};
+
+traceforge::execution_global! {
+    static HITS: Cell<u32> = Cell::new(0);
+    static ORDER: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+}
+
+static INITS: AtomicUsize = AtomicUsize::new(0);
+
+traceforge::execution_global! {
+    static COUNTED: u32 = {
+        INITS.fetch_add(1, Ordering::SeqCst);
+        7
+    };
+}
+
+#[test]
+fn globals_start_fresh_in_every_execution() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        assert_eq!(HITS.with(Cell::get), 0);
+        assert!(ORDER.with(|o| o.borrow().is_empty()));
+        let workers: Vec<_> = (0..3u32)
+            .map(|i| {
+                thread::spawn(move || {
+                    // The channel makes the order of the workers observable, so that every
+                    // order is explored
+                    traceforge::send_msg(thread::main_thread_id(), i);
+                    HITS.with(|h| h.set(h.get() + 1));
+                })
+            })
+            .collect();
+        for _ in 0..3 {
+            let i: u32 = traceforge::recv_msg_block();
+            ORDER.with(|o| o.borrow_mut().push(i));
+        }
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(HITS.with(Cell::get), 3);
+        assert_eq!(ORDER.with(|o| o.borrow().len()), 3);
+    });
+    assert_eq!(stats.execs, 6);
+}
+
+#[test]
+fn globals_are_initialized_on_first_use() {
+    INITS.store(0, Ordering::SeqCst);
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let worker = thread::spawn(|| COUNTED.with(|c| *c));
+        assert_eq!(COUNTED.with(|c| *c), 7);
+        assert_eq!(worker.join().unwrap(), 7);
+    });
+    // Once per execution, whichever task uses the global first
+    assert_eq!(INITS.load(Ordering::SeqCst), stats.execs + stats.block);
+}
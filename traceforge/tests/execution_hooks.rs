@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::{Config, Nondet};
+
+// Records the calls to the hooks, and the index seen by the model
+#[derive(Default)]
+struct Calls {
+    before: Vec<usize>,
+    after: Vec<usize>,
+    model: Vec<usize>,
+}
+
+fn config(calls: &Arc<Mutex<Calls>>) -> traceforge::ConfigBuilder {
+    let before = calls.clone();
+    let after = calls.clone();
+    Config::builder()
+        .with_before_each(move |i| {
+            let mut calls = before.lock().unwrap();
+            // Every execution is torn down before the next one is set up
+            assert_eq!(calls.before.len(), calls.after.len());
+            calls.before.push(i);
+        })
+        .with_after_each(move |i| after.lock().unwrap().after.push(i))
+}
+
+#[test]
+fn hooks_run_around_every_execution() {
+    let calls = Arc::new(Mutex::new(Calls::default()));
+    let seen = calls.clone();
+    let stats = traceforge::verify(config(&calls).build(), move || {
+        let v = (0..3).nondet();
+        let w = (0..2).nondet();
+        seen.lock()
+            .unwrap()
+            .model
+            .push(traceforge::execution_index());
+        traceforge::assume!(v + w != 3);
+    });
+    assert_eq!(stats.execs + stats.block, 6);
+
+    let calls = calls.lock().unwrap();
+    let indices: Vec<usize> = (0..6).collect();
+    assert_eq!(calls.before, indices);
+    assert_eq!(calls.after, indices);
+    assert_eq!(calls.model, indices);
+}
+
+#[test]
+fn after_each_runs_when_an_execution_fails() {
+    let calls = Arc::new(Mutex::new(Calls::default()));
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(config(&calls).build(), || {
+            let v = (0..3).nondet();
+            traceforge::assert(v != 1);
+        })
+    });
+    assert!(result.is_err());
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.before, vec![0, 1]);
+    assert_eq!(calls.after, vec![0, 1]);
+}
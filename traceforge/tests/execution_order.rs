@@ -3,6 +3,9 @@ use std::sync::Arc;
 use traceforge::{nondet, thread::current_id, Config};
 use futures::lock::Mutex;
 
+mod utils;
+use utils::Outcomes;
+
 #[test]
 fn test_out_of_order_blocking_recv() {
     traceforge::verify(Config::builder().build(), || {
@@ -180,3 +183,73 @@ fn two_receives_two_sends() {
     });
     println!("Executions: {:?}", stats);
 }
+
+// Returns the message received first by main in each explored execution, in exploration order
+fn first_received(prioritize: Option<i32>) -> Vec<i32> {
+    let received = Outcomes::new();
+    let r = received.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let main_tid = current_id();
+        let _h1 = traceforge::thread::spawn(move || traceforge::send_msg(main_tid, 1));
+        let h2 = traceforge::thread::spawn(move || traceforge::send_msg(main_tid, 2));
+        if let Some(p) = prioritize {
+            traceforge::set_task_priority(&h2, p);
+        }
+        let v: i32 = traceforge::recv_msg_block();
+        let _: i32 = traceforge::recv_msg_block();
+        r.insert(v);
+    });
+    received.all()
+}
+
+#[test]
+fn test_task_priority() {
+    let default = first_received(None);
+    let prioritized = first_received(Some(10));
+    assert_eq!(default[0], 1);
+    assert_eq!(prioritized[0], 2);
+
+    let mut default = default;
+    let mut prioritized = prioritized;
+    default.sort();
+    prioritized.sort();
+    assert_eq!(default, prioritized);
+}
+
+#[test]
+fn test_runnable_count() {
+    traceforge::verify(Config::builder().build(), || {
+        assert_eq!(traceforge::runnable_count(), 1);
+        let h = traceforge::thread::spawn(|| {});
+        assert_eq!(traceforge::runnable_count(), 2);
+        h.join().unwrap();
+        assert_eq!(traceforge::runnable_count(), 1);
+    });
+}
+
+#[test]
+fn test_detached_blocked_task_is_not_a_deadlock() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let d = traceforge::thread::spawn_daemon(|| {
+            // Waits for a second message that never arrives
+            let _: i32 = traceforge::recv_msg_block();
+            let _: i32 = traceforge::recv_msg_block();
+        });
+        traceforge::send_msg(d.thread().id(), 1);
+        let _ = nondet();
+    });
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn test_schedule_depth() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let _ = nondet();
+        let _ = nondet();
+    });
+    // One decision to start the main thread, plus one at the scheduling point of each nondet()
+    assert_eq!(stats.execs, 4);
+    assert_eq!(stats.max_schedule_depth, 3);
+    assert_eq!(stats.avg_schedule_depth(), 3.0);
+}
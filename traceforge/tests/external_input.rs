@@ -0,0 +1,52 @@
+use std::collections::BTreeSet;
+
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+#[test]
+fn every_input_is_explored_with_every_schedule() {
+    let seen = Outcomes::new();
+    let outcomes = seen.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let main = thread::current().id();
+        // A node forwards the reading of a sensor, while another one sends a heartbeat
+        thread::spawn(move || {
+            let reading = traceforge::external_input(&[false, true]);
+            traceforge::send_msg(main, Some(reading));
+        });
+        thread::spawn(move || traceforge::send_msg(main, None::<bool>));
+        let received: Vec<Option<bool>> = (0..2).map(|_| traceforge::recv_msg_block()).collect();
+        outcomes.insert(received);
+    });
+    assert_eq!(stats.execs, 4);
+    let expected = BTreeSet::from([
+        vec![Some(false), None],
+        vec![Some(true), None],
+        vec![None, Some(false)],
+        vec![None, Some(true)],
+    ]);
+    assert_eq!(seen.distinct(), expected);
+}
+
+#[test]
+fn every_value_of_the_domain_is_explored() {
+    let seen = Outcomes::new();
+    let inputs = seen.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let command = traceforge::external_input(&["start", "stop", "reset"]);
+        inputs.insert(command);
+    });
+    assert_eq!(stats.execs, 3);
+    assert_eq!(seen.distinct(), BTreeSet::from(["reset", "start", "stop"]));
+}
+
+#[test]
+#[should_panic(expected = "The domain of an external input is empty")]
+fn empty_domain_is_rejected() {
+    traceforge::verify(Config::builder().build(), || {
+        traceforge::external_input::<bool>(&[]);
+    });
+}
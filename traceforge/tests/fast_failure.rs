@@ -0,0 +1,95 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use traceforge::thread;
+use traceforge::{Config, ConfigBuilder};
+
+mod utils;
+use utils::remove_trace;
+
+// Two tasks send a message to the main thread, which fails after receiving either of them.
+// Returns the panic message and the number of explored executions.
+fn explore(builder: ConfigBuilder) -> (String, usize) {
+    let explored = Arc::new(AtomicUsize::new(0));
+    let counter = explored.clone();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        traceforge::verify(builder.build(), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let main = thread::current().id();
+            for i in 0..2u32 {
+                thread::spawn(move || traceforge::send_msg(main, i));
+            }
+            let received: u32 = traceforge::recv_msg_block();
+            assert!(received > 1, "received {received}");
+        });
+    }));
+    let payload = result.expect_err("the bug was not flagged");
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+    };
+    (message, explored.load(Ordering::SeqCst))
+}
+
+fn trace_file(name: &str) -> String {
+    let trace = std::env::temp_dir().join(name);
+    trace.to_str().unwrap().to_string()
+}
+
+#[test]
+fn fast_mode_flags_the_bug_at_a_lower_cost() {
+    let trace = trace_file("fast_failure_lower_cost.trace");
+    remove_trace(&trace);
+
+    let (fast_message, fast_executions) = explore(
+        Config::builder()
+            .with_error_trace(&trace)
+            .with_fast_failure(true),
+    );
+    // Both modes stop at the first failure, but fast mode does not store a counterexample
+    assert!(
+        fast_message.contains("TraceForge found a failure in execution 1 (schedule "),
+        "{fast_message}"
+    );
+    assert_eq!(fast_executions, 1);
+    assert!(!Path::new(&trace).exists());
+
+    let (message, executions) = explore(Config::builder().with_error_trace(&trace));
+    assert!(!message.contains("schedule"));
+    assert_eq!(executions, 1);
+    assert!(Path::new(&trace).exists());
+
+    remove_trace(&trace);
+}
+
+#[test]
+fn fast_mode_reports_the_same_schedule_id() {
+    let (first, _) = explore(Config::builder().with_fast_failure(true));
+    let (again, _) = explore(Config::builder().with_fast_failure(true));
+    assert_eq!(first, again);
+}
+
+#[test]
+fn fast_mode_stops_at_the_first_failure() {
+    let explored = Arc::new(AtomicUsize::new(0));
+    let counter = explored.clone();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let config = Config::builder()
+            .with_keep_going_after_error(true)
+            .with_fast_failure(true)
+            .build();
+        traceforge::verify(config, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let main = thread::current().id();
+            for _ in 0..2 {
+                thread::spawn(move || traceforge::send_msg(main, ()));
+            }
+            let _: () = traceforge::recv_msg_block();
+            traceforge::assert(false);
+        });
+    }));
+    assert!(result.is_err());
+    assert_eq!(explored.load(Ordering::SeqCst), 1);
+}
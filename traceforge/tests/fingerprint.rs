@@ -0,0 +1,47 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use traceforge::thread;
+use traceforge::Config;
+
+// Three workers send their id to the main thread, which folds them into its final state with
+// `fold`
+fn model(state: Arc<Mutex<u64>>, fold: fn(u64, u64) -> u64) -> impl Fn() + Send + Sync {
+    move || {
+        let main = thread::current().id();
+        for i in 1..=3u64 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let folded = (0..3).fold(0, |acc, _| fold(acc, traceforge::recv_msg_block()));
+        *state.lock().unwrap() = folded;
+    }
+}
+
+fn final_states(fold: fn(u64, u64) -> u64) -> BTreeSet<u64> {
+    let state = Arc::new(Mutex::new(0));
+    let fingerprint = state.clone();
+    let config = Config::builder()
+        .with_fingerprint(move || *fingerprint.lock().unwrap())
+        .build();
+    let stats = traceforge::verify(config, model(state, fold));
+    assert_eq!(stats.execs, 6);
+    stats.final_states
+}
+
+#[test]
+fn deterministic_algorithm_has_one_final_state() {
+    // The sum does not depend on the order of the messages
+    assert_eq!(final_states(|acc, i| acc + i), BTreeSet::from([6]));
+}
+
+#[test]
+fn racy_algorithm_has_several_final_states() {
+    // The last message wins
+    assert_eq!(final_states(|_, i| i), BTreeSet::from([1, 2, 3]));
+}
+
+#[test]
+fn no_final_states_without_a_fingerprint() {
+    let stats = traceforge::verify(Config::builder().build(), || {});
+    assert!(stats.final_states.is_empty());
+}
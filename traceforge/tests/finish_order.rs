@@ -0,0 +1,71 @@
+use traceforge::thread::{self, JoinHandle};
+use traceforge::Config;
+
+fn named<F: FnOnce() + Send + 'static>(name: &str, f: F) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(f)
+        .unwrap()
+}
+
+#[test]
+#[should_panic(expected = "b finished before a, which has to finish first")]
+fn independent_tasks_can_finish_in_any_order() {
+    traceforge::verify(Config::builder().build(), || {
+        let a = named("a", || {});
+        let b = named("b", || {});
+        traceforge::assert_finishes_before(&a, &b);
+        a.join().unwrap();
+        b.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic(expected = "b finished before a, which has to finish first")]
+fn receiving_from_a_task_does_not_wait_for_its_end() {
+    traceforge::verify(Config::builder().build(), || {
+        let b = named("b", || {
+            let _: u32 = traceforge::recv_msg_block();
+        });
+        let b_id = b.thread().id();
+        let a = named("a", move || traceforge::send_msg(b_id, 1u32));
+        traceforge::assert_finishes_before(&a, &b);
+        a.join().unwrap();
+        b.join().unwrap();
+    });
+}
+
+#[test]
+fn waiting_for_the_end_of_a_task_finishes_after_it() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let a = named("a", || {});
+        let b = named("b", || {
+            let _: () = traceforge::recv_msg_block();
+        });
+        traceforge::assert_finishes_before(&a, &b);
+        let b_id = b.thread().id();
+        a.join().unwrap();
+        traceforge::send_msg(b_id, ());
+        b.join().unwrap();
+    });
+    assert!(stats.execs > 0);
+}
+
+#[test]
+#[should_panic(expected = "b finished before a, which has to finish first")]
+fn a_task_that_already_finished_too_early_fails_at_once() {
+    traceforge::verify(Config::builder().build(), || {
+        let a = named("a", || {
+            let _: () = traceforge::recv_msg_block();
+        });
+        let b = named("b", || {});
+        // Let b finish while a waits
+        while traceforge::runnable_count() > 1 {
+            thread::yield_now();
+        }
+        traceforge::assert_finishes_before(&a, &b);
+        traceforge::send_msg(a.thread().id(), ());
+        a.join().unwrap();
+        b.join().unwrap();
+    });
+}
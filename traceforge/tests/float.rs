@@ -0,0 +1,46 @@
+use traceforge::{float, thread, Config};
+
+// Adding 1.0 to 1e16 rounds it away, so these sum to 0 or 1 depending on the order
+const VALUES: [f64; 3] = [1e16, 1.0, -1e16];
+
+#[test]
+fn parallel_sum_depends_on_the_arrival_order() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let main = thread::current().id();
+        for value in VALUES {
+            thread::spawn(move || traceforge::send_msg(main, value));
+        }
+        let mut sum = 0.0;
+        for _ in 0..VALUES.len() {
+            let partial: f64 = traceforge::recv_msg_block();
+            sum += partial;
+        }
+        float::observe("sum", sum);
+    });
+    assert_eq!(stats.execs, 6);
+    let range = &stats.float_ranges["sum"];
+    assert!(range.varies());
+    assert_eq!((range.min, range.max), (0.0, 1.0));
+    assert_eq!(range.count, stats.execs);
+    assert!(range.spread() <= 1.0, "the sum varies by {}", range);
+}
+
+#[test]
+fn sum_in_any_order_explores_every_order() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        float::observe("sum", float::sum_in_any_order(&VALUES));
+    });
+    assert_eq!(stats.execs, 6);
+    let range = &stats.float_ranges["sum"];
+    assert_eq!((range.min, range.max), (0.0, 1.0));
+}
+
+#[test]
+fn exact_sums_do_not_vary() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        float::observe("sum", float::sum_in_any_order(&[1.0, 2.0, 4.0]));
+    });
+    let range = &stats.float_ranges["sum"];
+    assert!(!range.varies());
+    assert_eq!(range.spread(), 0.0);
+}
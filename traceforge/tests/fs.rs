@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+
+use traceforge::fs::Fs;
+use traceforge::{thread, Config};
+
+mod utils;
+use utils::Outcomes;
+
+#[test]
+fn concurrent_writes_to_the_same_file_interleave() {
+    let contents = Outcomes::new();
+    let seen = contents.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let fs = Fs::new();
+        fs.create("log");
+        let writers: Vec<_> = [b'a', b'b']
+            .into_iter()
+            .map(|byte| {
+                let fs = fs.clone();
+                thread::spawn(move || {
+                    let mut file = fs.open("log").unwrap();
+                    file.write(&[byte]).unwrap();
+                    file.write(&[byte]).unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let content = String::from_utf8(fs.read("log").unwrap()).unwrap();
+        seen.insert(content);
+    });
+    assert_eq!(stats.block, 0);
+    let contents = contents.distinct();
+    let expected = ["aa", "ab", "ba", "bb"].map(String::from);
+    assert_eq!(contents, BTreeSet::from(expected));
+}
+
+// Replaces the content of `config` by writing a temporary file and renaming it, and returns
+// the content of `config` after a crash that happens at any point
+fn replace_then_crash(sync_before_rename: bool) -> BTreeSet<Option<Vec<u8>>> {
+    let contents = Outcomes::new();
+    let seen = contents.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let fs = Fs::new();
+        let mut config = fs.create("config");
+        config.write(b"old").unwrap();
+        config.fsync();
+        fs.fsync_dir();
+
+        let writer = {
+            let fs = fs.clone();
+            thread::spawn(move || {
+                let mut tmp = fs.create("config.tmp");
+                tmp.write(b"new").unwrap();
+                if sync_before_rename {
+                    tmp.fsync();
+                }
+                fs.rename("config.tmp", "config").unwrap();
+                fs.fsync_dir();
+            })
+        };
+        let content = fs.after_crash().read("config").ok();
+        seen.insert(content);
+        writer.join().unwrap();
+    });
+    contents.distinct()
+}
+
+#[test]
+fn fsync_before_rename_is_crash_consistent() {
+    let contents = replace_then_crash(true);
+    let expected = [Some(b"old".to_vec()), Some(b"new".to_vec())];
+    assert_eq!(contents, BTreeSet::from(expected));
+}
+
+#[test]
+fn rename_without_fsync_can_lose_the_content() {
+    let contents = replace_then_crash(false);
+    // The rename can be durable before the content of the renamed file
+    assert!(contents.contains(&Some(Vec::new())));
+}
+
+#[test]
+fn crash_recovery_explores_the_surviving_writes() {
+    let contents = Outcomes::new();
+    let seen = contents.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let fs = Fs::new();
+        fs.create("log");
+        fs.fsync_dir();
+
+        let seen = seen.clone();
+        let crash = fs.inject_crash(move |recovered| {
+            let content = String::from_utf8(recovered.read("log").unwrap()).unwrap();
+            seen.insert(content);
+        });
+        let mut log = fs.open("log").unwrap();
+        log.write(b"a").unwrap();
+        log.fsync();
+        log.write(b"b").unwrap();
+        crash.join().unwrap();
+    });
+    let contents = contents.distinct();
+    // "a" is a crash after the last write that loses it, and the writes reach the disk in
+    // order, so "b" alone never survives
+    let expected = ["", "a", "ab"].map(String::from);
+    assert_eq!(contents, BTreeSet::from(expected));
+}
+
+#[test]
+fn missing_files_are_reported() {
+    traceforge::verify(Config::builder().build(), || {
+        let fs = Fs::new();
+        assert!(!fs.exists("missing"));
+        let err = fs.open("missing").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(fs.rename("missing", "other").is_err());
+    });
+}
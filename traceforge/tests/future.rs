@@ -142,3 +142,33 @@ fn test_abort_future() {
     // nothing to cancel for a send_msg.
     assert_eq!((1, 0), (stats.execs, stats.block));
 }
+
+#[test]
+#[should_panic(expected = "nested block_on is not supported here")]
+fn test_nested_block_on() {
+    traceforge::verify(Config::builder().build(), || {
+        traceforge::future::block_on(async {
+            traceforge::future::block_on(async {});
+        });
+    });
+}
+
+#[test]
+#[should_panic(expected = "nested block_on is not supported here")]
+fn test_block_on_in_async_task() {
+    traceforge::verify(Config::builder().build(), || {
+        let task = traceforge::future::spawn(async {
+            traceforge::future::block_on(async {});
+        });
+        let _ = traceforge::future::block_on(task);
+    });
+}
+
+#[test]
+fn test_consecutive_block_on() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        traceforge::future::block_on(async {});
+        traceforge::future::block_on(async {});
+    });
+    assert_eq!((1, 0), (stats.execs, stats.block));
+}
@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use traceforge::thread;
+use traceforge::Config;
+
+const SENDERS: u32 = 6;
+
+// Fails when the messages are received in one particular order, out of SENDERS! orders.
+// Publishes how many messages were received in the right position.
+fn model(target: Vec<u32>) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let main = thread::main_thread_id();
+        for i in 0..SENDERS {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let mut matched = 0;
+        for expected in &target {
+            let v: u32 = traceforge::recv_msg_block();
+            if v == *expected {
+                matched += 1;
+            }
+        }
+        traceforge::publish(matched);
+        assert!(matched < SENDERS as usize, "found the target order");
+    }
+}
+
+// Number of executions explored until the bug is found
+fn executions_until_bug(config: Config, target: Vec<u32>) -> usize {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let r = runs.clone();
+    let f = model(target);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        traceforge::verify(config, move || {
+            r.fetch_add(1, Ordering::SeqCst);
+            f()
+        })
+    }));
+    assert!(result.is_err(), "the bug was not found");
+    runs.load(Ordering::SeqCst)
+}
+
+fn score(end: &traceforge::monitor_types::ExecutionEnd) -> i64 {
+    end.get_published::<usize>()
+        .values()
+        .map(|m| *m as i64)
+        .sum()
+}
+
+#[test]
+fn guided_search_finds_bug_faster() {
+    // Depth-first search only reaches this order after most of the others
+    let target = vec![1, 0, 2, 3, 4, 5];
+    let unguided = executions_until_bug(Config::builder().build(), target.clone());
+    let guided = executions_until_bug(Config::builder().with_guide(score).build(), target.clone());
+    assert!(
+        guided * 4 < unguided,
+        "guided: {guided}, unguided: {unguided}"
+    );
+}
+
+#[test]
+fn guided_search_explores_everything() {
+    let no_bug = vec![0; SENDERS as usize];
+    let unguided = traceforge::verify(Config::builder().build(), model(no_bug.clone()));
+    let guided = traceforge::verify(Config::builder().with_guide(score).build(), model(no_bug));
+    assert_eq!(unguided.execs, 720);
+    assert_eq!(guided.execs, unguided.execs);
+    assert_eq!(guided.block, unguided.block);
+}
@@ -0,0 +1,83 @@
+use traceforge::thread::{self, ThreadId};
+use traceforge::*;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Msg {
+    Request(ThreadId),
+    Ack(Event),
+    Noise,
+}
+
+// A client sends a request and waits for the ack, while another thread sends unrelated messages
+// to the server. However the server's receives are ordered, the ack is observed after the request
+#[test]
+fn ack_is_observed_after_request() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let server = thread::spawn(|| {
+            for _ in 0..2 {
+                if let Msg::Request(client) = traceforge::recv_msg_block() {
+                    traceforge::send_msg(client, Msg::Ack(traceforge::current_event()));
+                }
+            }
+        });
+        let sid = server.thread().id();
+        let noise = thread::spawn(move || traceforge::send_msg(sid, Msg::Noise));
+
+        traceforge::send_msg(sid, Msg::Request(thread::current().id()));
+        let request = traceforge::current_event();
+        let Msg::Ack(received) = traceforge::recv_msg_block() else {
+            panic!("expected an ack");
+        };
+        let ack = traceforge::current_event();
+
+        assert!(traceforge::happens_before(request, received));
+        assert!(traceforge::happens_before(received, ack));
+        assert!(traceforge::happens_before(request, ack));
+        assert!(!traceforge::happens_before(ack, request));
+        assert!(!traceforge::happens_before(ack, ack));
+
+        noise.join().unwrap();
+        server.join().unwrap();
+    });
+    assert_eq!(stats.execs, 2);
+}
+
+#[test]
+fn concurrent_events_are_unordered() {
+    traceforge::verify(Config::builder().build(), || {
+        let t1 = thread::spawn(traceforge::current_event);
+        let t2 = thread::spawn(traceforge::current_event);
+        let e1 = t1.join().unwrap();
+        let e2 = t2.join().unwrap();
+        assert!(!traceforge::happens_before(e1, e2));
+        assert!(!traceforge::happens_before(e2, e1));
+        // Joining orders the threads' events before the joiner's
+        let joined = traceforge::current_event();
+        assert!(traceforge::happens_before(e1, joined));
+        assert!(traceforge::happens_before(e2, joined));
+    });
+}
+
+#[test]
+fn receive_advances_clock_past_send() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        // Clocks start afresh in every execution
+        let initial = traceforge::current_clock();
+        assert_eq!(initial.entries().count(), 1);
+
+        let main = thread::current().id();
+        let sender = thread::spawn(move || {
+            traceforge::send_msg(main, traceforge::nondet());
+            traceforge::current_event()
+        });
+        let before = traceforge::current_clock();
+        let _: bool = traceforge::recv_msg_block();
+        let after = traceforge::current_clock();
+        let send = sender.join().unwrap();
+
+        assert!(!before.contains(send));
+        assert!(after.contains(send));
+        assert!(after.get(send.thread()).unwrap() >= before.get(send.thread()).unwrap_or(0));
+    });
+    assert_eq!(stats.execs, 2);
+}
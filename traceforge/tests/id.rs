@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+
+use traceforge::thread;
+use traceforge::{Config, Nondet};
+
+mod utils;
+use utils::Outcomes;
+
+#[test]
+fn executions_generate_the_same_ids() {
+    let generated = Outcomes::new();
+    let seen = generated.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let worker = thread::spawn(|| {
+            (0..3)
+                .map(|_| traceforge::id::next_id())
+                .collect::<Vec<u64>>()
+        });
+        let mut ids: Vec<u64> = (0..3).map(|_| traceforge::id::next_id()).collect();
+        // Executions differ by a choice, not by the ids
+        let _ = (0..2).nondet();
+        ids.extend(worker.join().unwrap());
+        seen.insert(ids);
+    });
+    let generated = generated.all();
+    assert_eq!(stats.execs, 2);
+    assert_eq!(generated.len(), 2);
+    assert_eq!(generated[0], generated[1]);
+}
+
+#[test]
+fn ids_are_unique_within_an_execution() {
+    traceforge::verify(Config::builder().build(), || {
+        let workers: Vec<_> = (0..3)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..4)
+                        .map(|_| traceforge::id::next_id())
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+        let mut ids: Vec<u64> = (0..4).map(|_| traceforge::id::next_id()).collect();
+        for worker in workers {
+            let theirs: Vec<u64> = worker.join().unwrap();
+            assert!(theirs.windows(2).all(|w| w[0] < w[1]));
+            ids.extend(theirs);
+        }
+        let distinct: HashSet<u64> = ids.iter().copied().collect();
+        assert_eq!(distinct.len(), ids.len());
+    });
+}
@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use traceforge::{incremental, thread, Config, Stats};
+
+static EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+// The code under test: the model runs `transfer`, but not `report`
+fn transfer(to: thread::ThreadId, amount: u32) {
+    incremental::depends_on("transfer");
+    traceforge::send_msg(to, amount);
+}
+
+fn explore(file: &str, transfer_version: u32, report_version: u32) -> (Stats, usize) {
+    EXECUTIONS.store(0, Ordering::SeqCst);
+    let config = Config::builder()
+        .with_incremental(file)
+        .with_code_version("transfer", transfer_version)
+        .with_code_version("report", report_version)
+        .build();
+    let stats = traceforge::verify(config, || {
+        EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+        let bank = thread::current().id();
+        for amount in [10, 20] {
+            thread::spawn(move || transfer(bank, amount));
+        }
+        let total: u32 = (0..2).map(|_| traceforge::recv_msg_block::<u32>()).sum();
+        assert_eq!(total, 30);
+    });
+    (stats, EXECUTIONS.load(Ordering::SeqCst))
+}
+
+#[test]
+fn unchanged_code_paths_are_not_explored_again() {
+    let file = std::env::temp_dir().join(format!(
+        "traceforge-incremental-{}.json",
+        std::process::id()
+    ));
+    let file = file.to_str().unwrap();
+    let _ = std::fs::remove_file(file);
+
+    let (first, explored) = explore(file, 1, 1);
+    assert!(!first.cached);
+    assert_eq!(explored, first.execs);
+    assert!(first.code_paths.contains("transfer"));
+
+    // `report` is not run by the model
+    let (second, explored) = explore(file, 1, 2);
+    assert!(second.cached);
+    assert_eq!(explored, 0);
+    assert_eq!(second.execs, first.execs);
+
+    // `transfer` is
+    let (third, explored) = explore(file, 2, 2);
+    assert!(!third.cached);
+    assert_eq!(explored, first.execs);
+
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "no version is registered for the code path transfer")]
+fn code_paths_need_a_version() {
+    let file = std::env::temp_dir().join(format!(
+        "traceforge-unversioned-{}.json",
+        std::process::id()
+    ));
+    let config = Config::builder()
+        .with_incremental(file.to_str().unwrap())
+        .build();
+    traceforge::verify(config, || incremental::depends_on("transfer"));
+}
+
+#[test]
+fn models_without_code_paths_are_not_saved() {
+    let file = std::env::temp_dir().join(format!(
+        "traceforge-no-code-paths-{}.json",
+        std::process::id()
+    ));
+    let file = file.to_str().unwrap();
+    let _ = std::fs::remove_file(file);
+
+    let config = || {
+        Config::builder()
+            .with_incremental(file)
+            .with_code_version("transfer", 1)
+            .build()
+    };
+    let first = traceforge::verify(config(), || {});
+    assert!(!first.cached);
+    assert!(!std::path::Path::new(file).exists());
+
+    let second = traceforge::verify(config(), || {});
+    assert!(!second.cached);
+}
@@ -0,0 +1,59 @@
+use traceforge::thread::{self, ThreadId};
+use traceforge::Config;
+
+const REQUESTS: usize = 2;
+
+// A client that only sends its next request once the previous one was answered
+fn client(server: ThreadId, pipelined: bool) -> usize {
+    let mut max_inflight = 0;
+    for _ in 0..REQUESTS {
+        traceforge::send_msg(server, thread::current().id());
+        max_inflight = max_inflight.max(traceforge::inflight_count(server));
+        if !pipelined {
+            let () = traceforge::recv_msg_block();
+            assert_eq!(traceforge::inflight_count(server), 0);
+        }
+    }
+    if pipelined {
+        for _ in 0..REQUESTS {
+            let () = traceforge::recv_msg_block();
+        }
+    }
+    max_inflight
+}
+
+fn run(pipelined: bool) -> (traceforge::Stats, usize) {
+    let max = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let m = max.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let server = thread::spawn(|| {
+            for _ in 0..2 * REQUESTS {
+                let client: ThreadId = traceforge::recv_msg_block();
+                traceforge::send_msg(client, ());
+            }
+        });
+        let sid = server.thread().id();
+        let clients: Vec<_> = (0..2)
+            .map(|_| thread::spawn(move || client(sid, pipelined)))
+            .collect();
+        for c in clients {
+            let observed = c.join().unwrap();
+            m.fetch_max(observed, std::sync::atomic::Ordering::Relaxed);
+        }
+        server.join().unwrap();
+    });
+    (stats, max.load(std::sync::atomic::Ordering::Relaxed))
+}
+
+#[test]
+fn one_request_outstanding() {
+    let (stats, max) = run(false);
+    assert!(stats.execs > 1);
+    assert_eq!(max, 1);
+}
+
+#[test]
+fn pipelined_requests_are_counted() {
+    let (_, max) = run(true);
+    assert_eq!(max, REQUESTS);
+}
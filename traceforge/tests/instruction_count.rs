@@ -0,0 +1,34 @@
+use traceforge::{current_event, thread, Config};
+
+// The number of events the calling thread executed so far
+fn current_instruction_count() -> u32 {
+    current_event().index()
+}
+
+#[test]
+fn the_count_grows_at_every_event() {
+    traceforge::verify(Config::builder().build(), || {
+        let main = thread::current().id();
+        let worker = thread::spawn(move || {
+            let mut counts = vec![current_instruction_count()];
+            for i in 0..3u32 {
+                traceforge::send_msg(main, i);
+                counts.push(current_instruction_count());
+            }
+            let _ = traceforge::nondet();
+            counts.push(current_instruction_count());
+            // Yielding is a scheduling point, but not an event
+            thread::yield_now();
+            assert_eq!(current_instruction_count(), *counts.last().unwrap());
+            assert!(counts.windows(2).all(|w| w[0] < w[1]), "{:?}", counts);
+        });
+        let before = current_instruction_count();
+        // Plain computations are not scheduling points
+        let sum: u32 = (0..3).sum();
+        assert_eq!(current_instruction_count(), before);
+        let received: u32 = (0..3).map(|_| traceforge::recv_msg_block::<u32>()).sum();
+        assert_eq!(received, sum);
+        assert!(current_instruction_count() >= before + 3);
+        worker.join().unwrap();
+    });
+}
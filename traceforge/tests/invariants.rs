@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use traceforge::thread::{self, ThreadId};
+use traceforge::Config;
+
+// Two clients withdraw from an account with a balance of 100. The bank acknowledges every
+// withdrawal; with `check`, it rejects the withdrawals that would overdraw the account.
+fn bank(balance: Arc<Mutex<i64>>, check: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        *balance.lock().unwrap() = 100;
+        let account = balance.clone();
+        let bank = thread::Builder::new()
+            .name("bank".to_string())
+            .spawn(move || {
+                for _ in 0..2 {
+                    let (client, amount): (ThreadId, i64) = traceforge::recv_msg_block();
+                    let mut balance = account.lock().unwrap();
+                    if !check || *balance >= amount {
+                        *balance -= amount;
+                    }
+                    drop(balance);
+                    traceforge::send_msg(client, ());
+                }
+            })
+            .unwrap();
+        let bank = bank.thread().id();
+        for amount in [60i64, 70] {
+            thread::spawn(move || {
+                traceforge::send_msg(bank, (thread::current().id(), amount));
+                traceforge::recv_msg_block::<()>();
+            });
+        }
+    }
+}
+
+fn config(balance: &Arc<Mutex<i64>>) -> traceforge::ConfigBuilder {
+    let balance = balance.clone();
+    Config::builder().with_invariant(move || *balance.lock().unwrap() >= 0)
+}
+
+#[test]
+fn invariant_violation_points_to_the_breaking_event() {
+    let balance = Arc::new(Mutex::new(0));
+    let model = bank(balance.clone(), false);
+    let result = std::panic::catch_unwind(|| traceforge::verify(config(&balance).build(), model));
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    // The account is overdrawn by the bank, when it receives the second withdrawal: the events
+    // of the bank are its start, the first receive, the first ack and the second receive
+    assert!(
+        message.contains("invariant 0 violated after event (t1, 3) of task bank"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn invariant_is_checked_after_every_step() {
+    let balance = Arc::new(Mutex::new(0));
+    let checks = Arc::new(AtomicUsize::new(0));
+    let counter = checks.clone();
+    let stats = traceforge::verify(
+        config(&balance)
+            .with_invariant(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                true
+            })
+            .build(),
+        bank(balance.clone(), true),
+    );
+    // Either withdrawal can be received first
+    assert_eq!(stats.execs, 2);
+    // There is a scheduling point before every event of the bank
+    assert!(checks.load(Ordering::SeqCst) >= 2 * 5);
+}
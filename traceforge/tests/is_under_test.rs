@@ -0,0 +1,17 @@
+use traceforge::thread;
+use traceforge::Config;
+
+#[test]
+fn under_test_only_inside_verify() {
+    assert!(!traceforge::is_under_test());
+    let stats = traceforge::verify(Config::builder().build(), || {
+        assert!(traceforge::is_under_test());
+        let child = thread::spawn(traceforge::is_under_test);
+        assert!(child.join().unwrap());
+    });
+    assert_eq!(stats.execs, 1);
+    assert!(!traceforge::is_under_test());
+    assert!(!std::thread::spawn(traceforge::is_under_test)
+        .join()
+        .unwrap());
+}
@@ -0,0 +1,60 @@
+#![cfg(debug_assertions)]
+
+use traceforge::thread;
+use traceforge::{channel, Config};
+
+fn config() -> Config {
+    Config::builder().with_long_step_warning(20).build()
+}
+
+// Sends `n` messages that nobody receives, yielding after every `every` of them
+fn busy(n: usize, every: Option<usize>) {
+    let (tx, _rx) = channel::Builder::<usize>::new().build();
+    for i in 1..=n {
+        tx.send_msg(i);
+        if every.is_some_and(|every| i % every == 0) {
+            thread::yield_now();
+        }
+    }
+}
+
+#[test]
+fn long_loop_without_yielding_is_reported() {
+    let stats = traceforge::verify(config(), || busy(50, None));
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.long_steps, 1);
+}
+
+#[test]
+fn short_loop_is_not_reported() {
+    let stats = traceforge::verify(config(), || busy(10, None));
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.long_steps, 0);
+}
+
+#[test]
+fn long_loop_with_yields_is_not_reported() {
+    let stats = traceforge::verify(config(), || {
+        // The other task can run whenever the loop yields
+        let other = thread::spawn(|| {
+            for _ in 0..10 {
+                thread::yield_now();
+            }
+        });
+        busy(50, Some(5));
+        other.join().unwrap();
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.long_steps, 0);
+}
+
+#[test]
+fn long_loops_are_reported_once_per_step_and_execution() {
+    let stats = traceforge::verify(config(), || {
+        let other = thread::spawn(|| busy(50, None));
+        busy(50, None);
+        other.join().unwrap();
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.long_steps, 2);
+}
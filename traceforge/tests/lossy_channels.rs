@@ -66,3 +66,35 @@ fn lossy_channel_rev_max() {
         assert_eq!(stats.execs, 2);
     }
 }
+
+// Three lossy messages are sent while the receiver waits for all of them
+fn receive_all(config: Config) -> Stats {
+    traceforge::verify(config, move || {
+        let (sender, receiver) = unique_channel();
+        let h = thread::spawn(move || {
+            let received: Vec<i32> = (0..3).map(|_| receiver.recv_msg_block()).collect();
+            received.len()
+        });
+        for i in 0..3 {
+            sender.send_lossy_msg(i);
+        }
+        assert_eq!(h.join().unwrap(), 3);
+    })
+}
+
+#[test]
+fn exactly_once() {
+    // Executions where a message is dropped leave the receiver blocked
+    let lossy = receive_all(Config::builder().with_lossy(3).build());
+    assert!(lossy.block > 0);
+
+    // With exactly-once delivery the receiver gets every message in every execution
+    let exact = receive_all(
+        Config::builder()
+            .with_lossy(3)
+            .with_exactly_once(true)
+            .build(),
+    );
+    assert_eq!(exact.block, 0);
+    assert_eq!(exact.execs, 1);
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+
+use traceforge::mailbox::{self, OverflowPolicy};
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+// A producer sends 1, 2 and 3 and only then tells the consumer to take a message
+fn outpaced(policy: OverflowPolicy) -> BTreeSet<i32> {
+    let seen = Outcomes::new();
+    let s = seen.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let (tx, mailbox) = mailbox::bounded(1, policy);
+        let consumer = thread::spawn(move || {
+            let () = traceforge::recv_msg_block();
+            mailbox.recv()
+        });
+        let cid = consumer.thread().id();
+        thread::spawn(move || {
+            for i in 1..=3 {
+                tx.send(i);
+            }
+            traceforge::send_msg(cid, ());
+        });
+        s.insert(consumer.join().unwrap());
+    });
+    seen.distinct()
+}
+
+#[test]
+fn drop_oldest_keeps_most_recent() {
+    assert_eq!(outpaced(OverflowPolicy::DropOldest), [3].into());
+}
+
+#[test]
+fn drop_newest_keeps_first() {
+    assert_eq!(outpaced(OverflowPolicy::DropNewest), [1].into());
+}
+
+#[test]
+fn block_delivers_everything_in_order() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let (tx, mailbox) = mailbox::bounded(1, OverflowPolicy::Block);
+        let producer = thread::spawn(move || {
+            for i in 1..=3 {
+                tx.send(i);
+            }
+        });
+        let received: Vec<i32> = (0..3).map(|_| mailbox.recv()).collect();
+        assert_eq!(received, vec![1, 2, 3]);
+        producer.join().unwrap();
+    });
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn competing_senders_drop_different_messages() {
+    let seen = Outcomes::new();
+    let s = seen.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let (tx, mailbox) = mailbox::bounded(1, OverflowPolicy::DropNewest);
+        let senders: Vec<_> = (1..=2)
+            .map(|i| {
+                let tx = tx.clone();
+                thread::spawn(move || tx.send(i))
+            })
+            .collect();
+        for h in senders {
+            h.join().unwrap();
+        }
+        // Both messages reached the mailbox before this receive
+        s.insert(mailbox.recv());
+    });
+    // Whichever message arrives first is kept
+    assert_eq!(seen.distinct(), [1, 2].into());
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use traceforge::channel::Builder;
+use traceforge::sync::Mutex;
+use traceforge::thread::{self, ThreadId};
+use traceforge::{Config, Stats};
+
+// Two peers each send a message to the other before receiving the message of the other
+fn exchange(config: Config) -> Stats {
+    traceforge::verify(config, || {
+        let (to_a, inbox_a) = Builder::<u32>::new().build();
+        let (to_b, inbox_b) = Builder::<u32>::new().build();
+        let a = thread::spawn(move || {
+            to_b.send_msg(1);
+            inbox_a.recv_msg_block()
+        });
+        let b = thread::spawn(move || {
+            to_a.send_msg(2);
+            inbox_b.recv_msg_block()
+        });
+        assert_eq!(a.join().unwrap(), 2);
+        assert_eq!(b.join().unwrap(), 1);
+    })
+}
+
+#[test]
+fn unbounded_sends_never_deadlock() {
+    let stats = exchange(Config::builder().build());
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn a_cap_of_one_reveals_the_cyclic_wait() {
+    let stats = exchange(Config::builder().with_max_in_flight(1).build());
+    assert!(stats.block > 0, "{:?}", stats);
+}
+
+#[test]
+fn a_cap_of_two_lets_both_peers_send() {
+    let stats = exchange(Config::builder().with_max_in_flight(2).build());
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn a_consumer_makes_room_for_the_producer() {
+    let stats = traceforge::verify(Config::builder().with_max_in_flight(1).build(), || {
+        let consumer = thread::spawn(|| {
+            let received: Vec<u32> = (0..3).map(|_| traceforge::recv_msg_block()).collect();
+            assert_eq!(received, vec![0, 1, 2]);
+        });
+        for i in 0..3u32 {
+            traceforge::send_msg(consumer.thread().id(), i);
+        }
+        consumer.join().unwrap();
+    });
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn user_messages_of_traceforge_types_are_counted() {
+    let stats = traceforge::verify(Config::builder().with_max_in_flight(1).build(), || {
+        let (to_a, inbox_a) = Builder::<ThreadId>::new().build();
+        let (to_b, inbox_b) = Builder::<ThreadId>::new().build();
+        let a = thread::spawn(move || {
+            to_b.send_msg(thread::current().id());
+            inbox_a.recv_msg_block()
+        });
+        let b = thread::spawn(move || {
+            to_a.send_msg(thread::current().id());
+            inbox_b.recv_msg_block()
+        });
+        let (a_id, b_id) = (a.thread().id(), b.thread().id());
+        assert_eq!(a.join().unwrap(), b_id);
+        assert_eq!(b.join().unwrap(), a_id);
+    });
+    assert!(stats.block > 0, "{:?}", stats);
+}
+
+#[test]
+fn the_messages_of_the_primitives_are_not_counted() {
+    let stats = traceforge::verify(Config::builder().with_max_in_flight(1).build(), || {
+        // The only credit is held by a message that is never received
+        let (tx, _rx) = Builder::<u32>::new().build();
+        tx.send_msg(0);
+        let lock = Arc::new(Mutex::new(0));
+        let other = {
+            let lock = lock.clone();
+            thread::spawn(move || *lock.blocking_lock() += 1)
+        };
+        *lock.blocking_lock() += 1;
+        other.join().unwrap();
+        assert_eq!(*lock.blocking_lock(), 2);
+    });
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
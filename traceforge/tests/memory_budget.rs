@@ -0,0 +1,33 @@
+use traceforge::alloc::CountingAllocator;
+use traceforge::thread;
+use traceforge::Config;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Six workers send their id to the main thread, which receives them in any of the 720 orders
+fn model() {
+    let main = thread::current().id();
+    for i in 0..6u32 {
+        thread::spawn(move || traceforge::send_msg(main, i));
+    }
+    for _ in 0..6 {
+        let _: u32 = traceforge::recv_msg_block();
+    }
+}
+
+#[test]
+fn exploration_stops_when_over_budget() {
+    let stats = traceforge::verify(Config::builder().with_memory_budget(1).build(), model);
+    assert!(stats.out_of_memory);
+    assert!(stats.execs < 720, "explored {} executions", stats.execs);
+}
+
+#[test]
+fn exploration_within_budget_is_exhaustive() {
+    let config = Config::builder().with_memory_budget(1 << 30).build();
+    let stats = traceforge::verify(config, model);
+    assert!(!stats.out_of_memory);
+    assert_eq!(stats.execs, 720);
+    assert!(traceforge::alloc::allocated().is_some());
+}
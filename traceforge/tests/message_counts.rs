@@ -0,0 +1,70 @@
+use traceforge::channel::{Builder, MessageCounts};
+use traceforge::{thread, Config};
+
+// Two clients send a request each to a server, which handles both or only the first
+fn request_reply(handle_all: bool) -> traceforge::Stats {
+    let config = Config::builder().with_message_counts(true).build();
+    traceforge::verify(config, move || {
+        let (requests_tx, requests_rx) = Builder::<u32>::new().with_name("requests").build();
+        let server = thread::spawn(move || {
+            let handled = if handle_all { 2 } else { 1 };
+            for _ in 0..handled {
+                let _client: u32 = requests_rx.recv_msg_block();
+            }
+        });
+        let clients: Vec<_> = (0..2u32)
+            .map(|client| {
+                let requests_tx = requests_tx.clone();
+                thread::spawn(move || {
+                    requests_tx.send_msg(client);
+                })
+            })
+            .collect();
+        for client in clients {
+            client.join().unwrap();
+        }
+        server.join().unwrap();
+    })
+}
+
+#[test]
+fn every_sent_message_is_received() {
+    let stats = request_reply(true);
+    assert!(stats.execs > 1);
+    let requests = stats.message_counts["requests"];
+    assert!(requests.all_received(), "{:?}", requests);
+    assert_eq!(requests.sent, 2 * stats.execs);
+    assert_eq!(requests.executions_with_in_flight, 0);
+    for counts in stats.message_counts.values() {
+        assert_eq!(
+            counts.sent,
+            counts.received + counts.lost + counts.in_flight
+        );
+    }
+}
+
+#[test]
+fn messages_left_in_flight_are_counted() {
+    let stats = request_reply(false);
+    let requests = stats.message_counts["requests"];
+    assert_eq!(
+        requests,
+        MessageCounts {
+            sent: 2 * stats.execs,
+            received: stats.execs,
+            lost: 0,
+            in_flight: stats.execs,
+            executions_with_in_flight: stats.execs,
+        }
+    );
+}
+
+#[test]
+fn messages_are_only_counted_on_request() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let (tx, rx) = Builder::<u32>::new().with_name("requests").build();
+        tx.send_msg(1);
+        rx.recv_msg_block();
+    });
+    assert!(stats.message_counts.is_empty());
+}
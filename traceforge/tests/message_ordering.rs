@@ -1,6 +1,6 @@
 use traceforge::*;
 use serial_test::serial;
-use utils::assert_panic_msg;
+use utils::{assert_panic_msg, Outcomes};
 
 mod utils;
 
@@ -90,3 +90,25 @@ fn ordering_test2() {
 
     assert_eq!(stats.execs, 1); // just one exec with FIFO
 }
+
+#[test]
+fn batch_is_never_split() {
+    let orders = Outcomes::new();
+    let o = orders.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let receiver = traceforge::thread::spawn(|| {
+            (0..3)
+                .map(|_| traceforge::recv_msg_block())
+                .collect::<Vec<u32>>()
+        });
+        let rid = receiver.thread().id();
+        let batcher = traceforge::thread::spawn(move || traceforge::send_all(rid, [1u32, 2]));
+        traceforge::send_msg(rid, 3u32);
+        batcher.join().unwrap();
+        o.insert(receiver.join().unwrap());
+    });
+
+    // The competing message is received before or after the batch, never in between
+    assert_eq!(stats.execs, 2);
+    assert_eq!(orders.distinct(), [vec![1, 2, 3], vec![3, 1, 2]].into());
+}
@@ -346,3 +346,30 @@ fn wakeup_stress() {
     let elapsed = now.elapsed();
     println!("Elapsed: {:.2?}", elapsed);
 }
+
+// Compares the default task capacity with one that fits all the tasks of the model
+#[test]
+fn many_tasks_capacity() {
+    let n: u32 = 100;
+    let model = move || {
+        let main = thread::current().id();
+        for i in 0..n {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let _first: u32 = traceforge::recv_msg_block();
+    };
+
+    for capacity in [16, 128] {
+        let now = Instant::now();
+        let stats = traceforge::verify(
+            Config::builder().with_task_capacity(capacity).build(),
+            model,
+        );
+        assert_eq!(stats.execs as u32, n);
+        let elapsed = now.elapsed();
+        println!(
+            "Capacity {}: {} spills, elapsed: {:.2?}",
+            capacity, stats.task_spills, elapsed
+        );
+    }
+}
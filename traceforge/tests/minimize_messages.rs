@@ -0,0 +1,37 @@
+use traceforge::thread;
+use traceforge::{Config, MsgId};
+
+// Five clients send their id to a server. The server mishandles the request of client 1 when
+// it comes after the one of client 3; the three other requests are irrelevant to the bug.
+fn server(buggy: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let server = thread::current().id();
+        for i in 0..5usize {
+            thread::Builder::new()
+                .name(format!("client{i}"))
+                .spawn(move || traceforge::send_msg(server, i))
+                .unwrap();
+        }
+        let mut seen_3 = false;
+        for _ in 0..5 {
+            let id: usize = traceforge::recv_msg_block();
+            traceforge::assert(!(buggy && id == 1 && seen_3));
+            seen_3 |= id == 3;
+        }
+    }
+}
+
+#[test]
+fn minimized_trace_keeps_only_the_messages_of_the_bug() {
+    let trace = traceforge::minimize_messages(Config::builder().build(), server(true));
+    assert_eq!(
+        trace,
+        Some(vec![MsgId::new("client3", 0), MsgId::new("client1", 0)])
+    );
+}
+
+#[test]
+fn nothing_to_minimize_without_a_failure() {
+    let trace = traceforge::minimize_messages(Config::builder().build(), server(false));
+    assert_eq!(trace, None);
+}
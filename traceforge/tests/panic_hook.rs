@@ -0,0 +1,47 @@
+use traceforge::thread;
+use traceforge::Config;
+use utils::{read_trace, remove_trace};
+
+mod utils;
+
+fn model() {
+    let h = thread::spawn(|| {
+        let v: i32 = traceforge::recv_msg_block();
+        assert!(v == 2, "unexpected value {}", v);
+    });
+    traceforge::send_msg(h.thread().id(), 1);
+}
+
+#[test]
+fn failure_is_reported_without_panic_hook() {
+    let trace_filename = "/tmp/panic_hook.rs_failure_is_reported_without_panic_hook";
+    remove_trace(trace_filename);
+    let result = std::panic::catch_unwind(|| {
+        traceforge::verify(
+            Config::builder()
+                .with_install_panic_hook(false)
+                .with_error_trace(trace_filename)
+                .build(),
+            model,
+        );
+    });
+    let payload = result.expect_err("the model was expected to fail");
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.contains("original panic: unexpected value 1"));
+
+    let trace = read_trace(trace_filename);
+    let failure = &trace["failure"];
+    assert_eq!(failure["category"], "panic");
+    assert_eq!(failure["message"], "unexpected value 1");
+    assert!(failure["task"].is_string());
+    assert!(failure["pos"].is_object());
+}
+
+#[test]
+#[should_panic(expected = "unexpected value 1")]
+fn should_panic_without_panic_hook() {
+    traceforge::verify(
+        Config::builder().with_install_panic_hook(false).build(),
+        model,
+    );
+}
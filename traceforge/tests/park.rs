@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+fn collecting_deadlocks() -> Config {
+    Config::builder().with_collect_deadlocks(true).build()
+}
+
+#[test]
+fn unpark_before_park_is_remembered() {
+    let stats = traceforge::verify(collecting_deadlocks(), || {
+        let parker = thread::spawn(thread::park);
+        parker.thread().unpark();
+        parker.join().unwrap();
+    });
+    assert!(stats.execs >= 1);
+    assert!(stats.deadlocks.is_empty());
+}
+
+#[test]
+fn park_without_unpark_blocks() {
+    let stats = traceforge::verify(collecting_deadlocks(), || {
+        thread::spawn(thread::park).join().unwrap();
+    });
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.deadlocks.len(), 1);
+}
+
+// Tokens do not add up: when both unparks come before the first park, the second park blocks
+// forever. When the parker consumes the first token in between, both parks return.
+#[test]
+fn unparks_do_not_accumulate_tokens() {
+    let stats = traceforge::verify(collecting_deadlocks(), || {
+        let parker = thread::spawn(|| {
+            thread::park();
+            thread::park();
+        });
+        parker.thread().unpark();
+        parker.thread().unpark();
+        parker.join().unwrap();
+    });
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 2);
+    assert_eq!(stats.deadlocks.len(), 1);
+}
+
+#[test]
+fn park_timeout_may_wake_up_without_token() {
+    let woken = Outcomes::new();
+    let seen = woken.clone();
+    let stats = traceforge::verify(collecting_deadlocks(), move || {
+        let seen = seen.clone();
+        let parker = thread::spawn(move || {
+            thread::park_timeout(Duration::from_millis(10));
+            // A second park returns only if the first one timed out before the unpark
+            thread::park_timeout(Duration::from_millis(10));
+            seen.insert(());
+        });
+        parker.thread().unpark();
+        parker.join().unwrap();
+    });
+    assert!(stats.deadlocks.is_empty());
+    assert_eq!(stats.execs, woken.all().len());
+    assert_eq!(stats.execs, 3);
+}
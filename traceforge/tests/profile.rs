@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use traceforge::{channel, thread, time, Config};
+
+fn spawn_named<F: FnOnce() + Send + 'static>(name: &str, f: F) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(f)
+        .unwrap()
+}
+
+// A busy task sends 5 messages and a sleepy one sleeps for 10ms before sending 1, in each of
+// the explored executions.
+fn profiled() -> traceforge::Stats {
+    let config = Config::builder().with_profile(true).build();
+    traceforge::verify(config, || {
+        let (tx, rx) = channel::Builder::<u32>::new().build();
+        let tx2 = tx.clone();
+        let busy = spawn_named("busy", move || {
+            for i in 0..5 {
+                tx.send_msg(i);
+            }
+        });
+        let sleepy = spawn_named("sleepy", move || {
+            time::sleep(Duration::from_millis(10));
+            tx2.send_msg(9);
+        });
+        for _ in 0..6 {
+            let _ = rx.recv_msg_block();
+        }
+        busy.join().unwrap();
+        sleepy.join().unwrap();
+    })
+}
+
+#[test]
+fn profile_attributes_steps_to_tasks() {
+    let stats = profiled();
+    let execs = stats.execs;
+    assert_eq!(execs, 6);
+    let tasks = &stats.profile.tasks;
+    assert_eq!(
+        tasks.keys().collect::<Vec<_>>(),
+        ["busy", "main-0", "sleepy"]
+    );
+
+    // A step for each send and sleep, and one to finish
+    assert_eq!(tasks["busy"].steps, 6 * execs);
+    assert_eq!(tasks["sleepy"].steps, 3 * execs);
+    assert!(tasks["main-0"].steps > tasks["busy"].steps);
+    for p in tasks.values() {
+        assert_eq!(p.executions, execs);
+    }
+
+    // The sleepy task spends logical time, and so does the main task, which waits for its message
+    assert_eq!(
+        tasks["sleepy"].logical_time,
+        Duration::from_millis(10) * execs as u32
+    );
+    assert_eq!(tasks["busy"].logical_time, Duration::ZERO);
+    assert_eq!(tasks["main-0"].logical_time, tasks["sleepy"].logical_time);
+}
+
+#[test]
+fn profile_renders_as_folded_stacks() {
+    let stats = profiled();
+    let steps = stats.profile.folded_steps();
+    let lines: Vec<&str> = steps.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "busy 36");
+    assert_eq!(lines[2], "sleepy 18");
+    // Tasks that spent no logical time are left out
+    assert_eq!(
+        stats.profile.folded_time(),
+        "main-0 60000000\nsleepy 60000000\n"
+    );
+}
+
+#[test]
+fn no_profile_by_default() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        spawn_named("worker", || {
+            traceforge::send_msg(thread::main_thread_id(), 1)
+        })
+        .join()
+        .unwrap();
+    });
+    assert!(stats.profile.tasks.is_empty());
+}
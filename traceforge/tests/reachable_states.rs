@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::final_state::FinalTask;
+use traceforge::thread::{self, Builder};
+use traceforge::{Config, FinalState, Nondet, Val};
+
+// Worker `a` sends 1 to the main thread and returns `true`, worker `b` sends 2 and returns a
+// coin toss. The main thread folds the messages into the shared state with `fold`.
+fn reachable_states(fold: fn(u64, u64) -> u64) -> Vec<FinalState> {
+    let state = Arc::new(Mutex::new(0));
+    let shared = state.clone();
+    let config = Config::builder()
+        .with_reachable_states(move || format!("state = {}", shared.lock().unwrap()))
+        .build();
+    let stats = traceforge::verify(config, move || {
+        let main = thread::current().id();
+        let a = Builder::new().name("a".to_string());
+        a.spawn(move || {
+            traceforge::send_msg(main, 1u64);
+            true
+        })
+        .unwrap();
+        let b = Builder::new().name("b".to_string());
+        b.spawn(move || {
+            traceforge::send_msg(main, 2u64);
+            (0..2).nondet() == 1
+        })
+        .unwrap();
+        let folded = (0..2).fold(0, |acc, _| fold(acc, traceforge::recv_msg_block()));
+        *state.lock().unwrap() = folded;
+    });
+    assert_eq!(stats.execs, 4);
+    stats.reachable_states
+}
+
+fn final_state(b: bool, state: u64) -> FinalState {
+    let task = |name: &str, result: Val| FinalTask {
+        name: name.to_string(),
+        result: Some(result),
+    };
+    FinalState {
+        tasks: vec![
+            task("a", Val::new(true)),
+            task("b", Val::new(b)),
+            task("main", Val::new(())),
+        ],
+        shared: format!("state = {}", state),
+    }
+}
+
+fn assert_same_states(reached: &[FinalState], expected: &[FinalState]) {
+    assert_eq!(reached.len(), expected.len(), "reached {:?}", reached);
+    for state in expected {
+        assert!(reached.contains(state), "{} was not reached", state);
+    }
+}
+
+#[test]
+fn every_final_state_is_enumerated() {
+    // The last message wins
+    let reached = reachable_states(|_, i| i);
+    let expected = [
+        final_state(false, 1),
+        final_state(false, 2),
+        final_state(true, 1),
+        final_state(true, 2),
+    ];
+    assert_same_states(&reached, &expected);
+}
+
+#[test]
+fn final_states_are_deduplicated() {
+    // The sum does not depend on the order of the messages
+    let reached = reachable_states(|acc, i| acc + i);
+    assert_same_states(&reached, &[final_state(false, 3), final_state(true, 3)]);
+}
+
+#[test]
+fn no_reachable_states_without_a_description() {
+    let stats = traceforge::verify(Config::builder().build(), || {});
+    assert!(stats.reachable_states.is_empty());
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+enum Kind {
+    A(u32),
+    B(u32),
+}
+
+fn is_a(m: &Kind) -> bool {
+    matches!(m, Kind::A(_))
+}
+
+#[test]
+fn selective_receive_leaves_other_messages_queued() {
+    let received = Outcomes::new();
+    let seen = received.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let seen = seen.clone();
+        let receiver = thread::spawn(move || {
+            let first: Kind = traceforge::recv_msg_block_where(is_a);
+            let second: Kind = traceforge::recv_msg_block_where(is_a);
+            let rest: Kind = traceforge::recv_msg_block();
+            seen.insert((first, second, rest));
+        });
+        let tid = receiver.thread().id();
+        thread::spawn(move || {
+            traceforge::send_msg(tid, Kind::B(0));
+            traceforge::send_msg(tid, Kind::A(1));
+        });
+        traceforge::send_msg(tid, Kind::A(2));
+    });
+    assert_eq!(stats.execs, 2);
+    let received = received.distinct();
+    let expected = BTreeSet::from([
+        (Kind::A(1), Kind::A(2), Kind::B(0)),
+        (Kind::A(2), Kind::A(1), Kind::B(0)),
+    ]);
+    assert_eq!(received, expected);
+}
+
+#[test]
+fn nonblocking_selective_receive_skips_other_messages() {
+    let received = Outcomes::new();
+    let seen = received.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let seen = seen.clone();
+        let receiver = thread::spawn(move || {
+            let selected = traceforge::recv_msg_where(is_a);
+            let rest: Kind = traceforge::recv_msg_block();
+            seen.insert((selected, rest));
+        });
+        traceforge::send_msg(receiver.thread().id(), Kind::B(0));
+    });
+    let received = received.distinct();
+    assert_eq!(received, BTreeSet::from([(None, Kind::B(0))]));
+}
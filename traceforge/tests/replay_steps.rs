@@ -0,0 +1,75 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::thread;
+use traceforge::{Config, Step};
+
+// The main thread and its peer each wait for the hello of the other before sending their own.
+// The handshake deadlocks when the peer waits first; it gives up on a timeout, failing the
+// execution, so that the deadlocked schedule is saved as a counterexample.
+fn handshake() {
+    let main = thread::current().id();
+    let peer = thread::spawn(move || {
+        let hello: Option<u32> = traceforge::recv_msg();
+        traceforge::assert(hello.is_some());
+        traceforge::send_msg(main, 2u32);
+    });
+    let _hello: Option<u32> = traceforge::recv_msg();
+    traceforge::send_msg(peer.thread().id(), 1u32);
+    peer.join().unwrap();
+}
+
+fn trace_file() -> String {
+    let mut p = std::env::temp_dir();
+    p.push("replay_steps.json");
+    p.to_str().unwrap().to_owned()
+}
+
+#[test]
+fn deadlocked_schedule_is_replayed_step_by_step() {
+    let trace = trace_file();
+    let failed = std::panic::catch_unwind(|| {
+        traceforge::verify(
+            Config::builder().with_error_trace(&trace).build(),
+            handshake,
+        )
+    });
+    assert!(failed.is_err());
+
+    let steps = Arc::new(Mutex::new(Vec::<Step>::new()));
+    let seen = steps.clone();
+    let replayed = std::panic::catch_unwind(|| {
+        traceforge::replay_steps(handshake, &trace, move |step| {
+            // Every decision schedules a task that can run
+            assert!(step.runnable.contains(&step.next));
+            assert_eq!(step.event.thread(), step.next);
+            seen.lock().unwrap().push(step.clone());
+        });
+    });
+    assert!(replayed.is_err(), "the replay reaches the failure again");
+
+    let steps = steps.lock().unwrap();
+    let summary: Vec<(usize, Option<String>, Vec<String>, String)> = steps
+        .iter()
+        .map(|s| {
+            (
+                s.index,
+                s.current.map(|t| t.to_string()),
+                s.runnable.iter().map(|t| t.to_string()).collect(),
+                s.next.to_string(),
+            )
+        })
+        .collect();
+    let t = |s: &str| s.to_string();
+    assert_eq!(
+        summary,
+        [
+            (0, None, vec![t("t0")], t("t0")),
+            (1, Some(t("t0")), vec![t("t0")], t("t0")),
+            (2, Some(t("t0")), vec![t("t0"), t("t1")], t("t1")),
+            (3, Some(t("t1")), vec![t("t0"), t("t1")], t("t1")),
+        ]
+    );
+    // The main thread spawns its peer, which then times out waiting for the hello
+    assert!(steps[0].label.contains("TCREATE(t1)"), "{}", steps[0]);
+    assert!(steps[2].label.contains("RECV() [TIMEOUT]"), "{}", steps[2]);
+}
@@ -0,0 +1,47 @@
+use rand::RngExt;
+use traceforge::thread;
+use traceforge::Config;
+
+mod utils;
+use utils::Outcomes;
+
+// Runs a model whose behavior depends on RNG values and records what each execution observed
+fn run(config: Config) -> Vec<Vec<u64>> {
+    let observed = Outcomes::new();
+    let o = observed.clone();
+    traceforge::verify(config, move || {
+        let h = thread::spawn(|| {
+            let mut rng = traceforge::rng();
+            let v = rng.random_range(0..1000u64);
+            traceforge::send_msg(thread::main_thread_id(), v);
+        });
+        let mut rng = traceforge::rng();
+        let mut vals = vec![rng.random::<u64>(), rng.random::<u64>()];
+        if traceforge::nondet() {
+            vals.push(traceforge::recv_msg_block());
+        }
+        h.join().unwrap();
+        o.insert(vals);
+    });
+    observed.all()
+}
+
+#[test]
+fn same_config_same_values() {
+    let config = Config::builder().with_seed(42).build();
+    let first = run(config.clone());
+    let second = run(config);
+    assert_eq!(first.len(), 2);
+    assert_eq!(first, second);
+    // Every execution draws the same values
+    assert_eq!(first[0][..2], first[1][..2]);
+    // Each generator has its own stream
+    assert_ne!(first[0][0], first[0][1]);
+}
+
+#[test]
+fn different_seed_different_values() {
+    let first = run(Config::builder().with_seed(1).build());
+    let second = run(Config::builder().with_seed(2).build());
+    assert_ne!(first, second);
+}
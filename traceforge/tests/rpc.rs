@@ -0,0 +1,50 @@
+use traceforge::rpc::{self, Request};
+use traceforge::{thread, Config};
+
+// Serves `n` requests; when `reverse` is set, replies to the requests in the opposite order
+fn server(n: usize, reverse: bool) {
+    let mut reqs: Vec<Request<u32>> = (0..n).map(|_| traceforge::recv_msg_block()).collect();
+    if reverse {
+        reqs.reverse();
+    }
+    for req in reqs {
+        req.reply(*req.body() * 10);
+    }
+}
+
+fn two_callers(reverse: bool) -> traceforge::Stats {
+    traceforge::verify(Config::builder().build(), move || {
+        let server = thread::spawn(move || server(2, reverse));
+        let sid = server.thread().id();
+        let a = thread::spawn(move || rpc::call::<u32, u32>(sid, 1));
+        let b = thread::spawn(move || rpc::call::<u32, u32>(sid, 2));
+        assert_eq!(a.join().unwrap(), 10);
+        assert_eq!(b.join().unwrap(), 20);
+        server.join().unwrap();
+    })
+}
+
+#[test]
+fn concurrent_calls_get_their_own_response() {
+    let stats = two_callers(false);
+    // Both orders in which the requests reach the server are explored
+    assert_eq!(stats.execs, 2);
+}
+
+#[test]
+fn out_of_order_replies_reach_the_right_caller() {
+    let stats = two_callers(true);
+    assert_eq!(stats.execs, 2);
+}
+
+#[test]
+fn sequential_calls_from_one_thread() {
+    traceforge::verify(Config::builder().build(), || {
+        let server = thread::spawn(|| server(2, false));
+        let sid = server.thread().id();
+        let first: u32 = rpc::call(sid, 3u32);
+        let second: u32 = rpc::call(sid, 4u32);
+        assert_eq!((first, second), (30, 40));
+        server.join().unwrap();
+    });
+}
@@ -0,0 +1,55 @@
+use traceforge::{Config, Nondet, Stats};
+
+mod utils;
+use utils::Outcomes;
+
+// Explores the 2^12 combinations of 12 coin tosses, and returns the combinations explored
+fn explore(config: Config) -> (Stats, Vec<Vec<bool>>) {
+    let explored = Outcomes::new();
+    let seen = explored.clone();
+    let stats = traceforge::verify(config, move || {
+        let tosses: Vec<bool> = (0..12).map(|_| (0..2).nondet() == 1).collect();
+        seen.insert(tosses);
+    });
+    (stats, explored.all())
+}
+
+fn sampling(rate: f64, seed: u64) -> Config {
+    Config::builder().with_sampling(rate, seed).build()
+}
+
+#[test]
+fn about_rate_of_the_branches_are_explored() {
+    let (stats, explored) = explore(sampling(0.5, 7));
+    assert_eq!(stats.sampling_rate, Some(0.5));
+    assert_eq!(stats.execs, explored.len());
+    assert!(stats.execs < 1 << 12);
+
+    // Every execution but the first one is reached through one explored branch
+    let branches = stats.execs - 1 + stats.sampled_out;
+    let rate = (stats.execs - 1) as f64 / branches as f64;
+    assert!(
+        (0.4..0.6).contains(&rate),
+        "explored {rate} of the branches"
+    );
+}
+
+#[test]
+fn the_same_seed_explores_the_same_sample() {
+    let (_, first) = explore(sampling(0.5, 7));
+    let (_, again) = explore(sampling(0.5, 7));
+    let (_, other) = explore(sampling(0.5, 8));
+    assert_eq!(first, again);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn full_rate_is_exhaustive() {
+    let (stats, _) = explore(sampling(1.0, 7));
+    assert_eq!(stats.execs, 1 << 12);
+    assert_eq!(stats.sampled_out, 0);
+
+    let (stats, _) = explore(Config::builder().build());
+    assert_eq!(stats.sampling_rate, None);
+    assert_eq!(stats.sampled_out, 0);
+}
@@ -0,0 +1,44 @@
+use traceforge::script::Action::{self, Task};
+use traceforge::{channel, thread, Config};
+
+mod utils;
+use utils::Outcomes;
+
+// The main task and a spawned one each write a shared value twice, with a message sent in
+// between. Returns the writes in the order in which they happened on the schedule of `script`.
+fn writes_on(script: &[Action]) -> Vec<&'static str> {
+    let writes = Outcomes::new();
+    let w = writes.clone();
+    let stats = traceforge::run_script(Config::builder().build(), script, move || {
+        let (tx, rx) = channel::Builder::<()>::new().build();
+        let w1 = w.clone();
+        let h = thread::spawn(move || {
+            w1.insert("t1 a");
+            tx.send_msg(());
+            w1.insert("t1 b");
+        });
+        w.insert("t0 a");
+        rx.recv_msg_block();
+        w.insert("t0 b");
+        h.join().unwrap();
+    });
+    assert_eq!(stats.execs, 1);
+    writes.all()
+}
+
+#[test]
+fn script_picks_the_interleaving() {
+    // The main task starts, and spawns t1 at its second step
+    let spawned = [Task(0), Task(0)];
+    let t1_first = [&spawned[..], &[Task(1), Task(1), Task(0)]].concat();
+    assert_eq!(writes_on(&t1_first), ["t1 a", "t1 b", "t0 a", "t0 b"]);
+    let t0_first = [&spawned[..], &[Task(0), Task(1)]].concat();
+    assert_eq!(writes_on(&t0_first), ["t0 a", "t1 a", "t1 b", "t0 b"]);
+}
+
+#[test]
+#[should_panic(expected = "Infeasible script: Task(1) at step 1")]
+fn infeasible_script_fails() {
+    // t1 is not spawned yet
+    writes_on(&[Task(0), Task(1)]);
+}
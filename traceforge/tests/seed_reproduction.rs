@@ -0,0 +1,59 @@
+use std::any::Any;
+
+use traceforge::thread;
+use traceforge::Config;
+
+// Fails only when the second sender's message is received first
+fn model() {
+    let main = thread::main_thread_id();
+    for i in 0..3u32 {
+        thread::spawn(move || traceforge::send_msg(main, i));
+    }
+    let first: u32 = traceforge::recv_msg_block();
+    assert!(first != 1, "received {} first", first);
+}
+
+fn panic_message(result: Result<f64, Box<dyn Any + Send>>) -> String {
+    let payload = result.expect_err("the model was expected to fail");
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .expect("the panic did not return a string")
+}
+
+fn reported_seed(message: &str) -> u64 {
+    let start = message.find("with_seed(").expect("no seed in the failure") + "with_seed(".len();
+    let end = start + message[start..].find(')').unwrap();
+    message[start..end].parse().unwrap()
+}
+
+#[test]
+fn reported_seed_reproduces_failure() {
+    // Every sample explores a single execution
+    let config = |seed| {
+        Config::builder()
+            .with_seed(seed)
+            .with_max_iterations(1)
+            .build()
+    };
+    let failure = panic_message(std::panic::catch_unwind(|| {
+        traceforge::test(config(7), model, 100)
+    }));
+    assert!(failure.contains("received 1 first"));
+
+    // Running only the reported sample fails in the same way
+    let seed = reported_seed(&failure);
+    let replayed = panic_message(std::panic::catch_unwind(|| {
+        traceforge::test(config(seed), model, 1)
+    }));
+    assert_eq!(replayed, failure);
+}
+
+#[test]
+fn systematic_failure_has_no_seed() {
+    let failure = panic_message(std::panic::catch_unwind(|| {
+        traceforge::verify(Config::builder().build(), model);
+        0.0
+    }));
+    assert!(!failure.contains("with_seed("));
+}
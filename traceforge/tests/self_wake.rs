@@ -0,0 +1,90 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use traceforge::sync::Notify;
+use traceforge::{future, thread, Config, SchedulePolicy};
+
+// A future that wakes itself up until `done` holds, as an internal polling loop does, and
+// returns how many times it was polled
+struct PollUntil<F: FnMut() -> bool> {
+    done: F,
+    polls: usize,
+}
+
+impl<F: FnMut() -> bool + Unpin> Future for PollUntil<F> {
+    type Output = usize;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+        self.polls += 1;
+        if (self.done)() {
+            Poll::Ready(self.polls)
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn poll_until<F: FnMut() -> bool + Unpin>(done: F) -> PollUntil<F> {
+    PollUntil { done, polls: 0 }
+}
+
+fn config() -> Config {
+    Config::builder().with_policy(SchedulePolicy::LTR).build()
+}
+
+#[test]
+fn a_self_waking_future_is_polled_until_it_is_ready() {
+    let stats = traceforge::verify(config(), || {
+        let mut wakes = 0;
+        let polls = future::block_on(poll_until(move || {
+            wakes += 1;
+            wakes > 100
+        }));
+        assert_eq!(polls, 101);
+    });
+    assert_eq!((stats.execs, stats.block), (1, 0));
+}
+
+// The flag is not modeled, so only a fair scheduler lets the thread that sets it run
+#[test]
+fn a_polling_loop_lets_the_other_tasks_run() {
+    let stats = traceforge::verify(config(), || {
+        let flag = Arc::new(AtomicBool::new(false));
+        let setter = flag.clone();
+        thread::spawn(move || setter.store(true, Ordering::SeqCst));
+        let polls = future::block_on(poll_until(move || flag.load(Ordering::SeqCst)));
+        assert_eq!(polls, 2);
+    });
+    assert_eq!((stats.execs, stats.block), (1, 0));
+}
+
+#[test]
+fn a_spawned_polling_loop_lets_the_other_tasks_run() {
+    let stats = traceforge::verify(config(), || {
+        let flag = Arc::new(AtomicBool::new(false));
+        let setter = flag.clone();
+        let polling = future::spawn(poll_until(move || flag.load(Ordering::SeqCst)));
+        thread::spawn(move || setter.store(true, Ordering::SeqCst));
+        let polls = future::block_on(polling).unwrap();
+        assert!(polls > 1);
+    });
+    assert_eq!((stats.execs, stats.block), (1, 0));
+}
+
+// The waiter blocks when it first polls the notification, and can only go on to wait for it
+// once the loop yields to it
+#[test]
+fn a_yielding_loop_lets_a_blocked_task_go_on() {
+    let stats = traceforge::verify(config(), || {
+        let notify = Arc::new(Notify::new());
+        let _waiter = future::spawn(async move { notify.notified().await });
+        while traceforge::runnable_count() > 1 {
+            thread::yield_now();
+        }
+    });
+    assert_eq!((stats.execs, stats.block), (1, 0));
+}
@@ -0,0 +1,45 @@
+// A counter shared by threads, written once against `shims` and built with both backends
+
+use std::sync::Arc;
+
+use shims::atomic::{AtomicUsize, Ordering};
+
+pub struct Counter {
+    hits: AtomicUsize,
+    log: shims::Mutex<Vec<usize>>,
+}
+
+impl Counter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Counter {
+            hits: AtomicUsize::new(0),
+            log: shims::Mutex::new(Vec::new()),
+        })
+    }
+
+    // Counts a hit with an unsynchronized read-modify-write, and logs the count it observed
+    pub fn hit(&self) -> usize {
+        let seen = self.hits.load(Ordering::SeqCst);
+        self.hits.store(seen + 1, Ordering::SeqCst);
+        self.log.blocking_lock().push(seen);
+        seen
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
+
+// Hits the counter from two threads, and returns the final count
+pub fn hit_twice(counter: &Arc<Counter>) -> usize {
+    let workers: Vec<shims::JoinHandle<usize>> = (0..2)
+        .map(|_| {
+            let counter = counter.clone();
+            shims::spawn(move || counter.hit())
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    counter.hits()
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeSet;
+
+use traceforge::Config;
+
+#[path = "../utils/mod.rs"]
+mod utils;
+use utils::Outcomes;
+
+mod native {
+    use traceforge::shims::native as shims;
+    include!("counter.rs");
+}
+
+mod model {
+    use traceforge::shims::model as shims;
+    include!("counter.rs");
+}
+
+#[test]
+fn native_build_runs_without_traceforge() {
+    let counter = native::Counter::new();
+    let hits = native::hit_twice(&counter);
+    assert!((1..=2).contains(&hits));
+}
+
+#[test]
+fn model_build_is_checked_by_traceforge() {
+    let outcomes = Outcomes::new();
+    let seen = outcomes.clone();
+    traceforge::verify(Config::builder().build(), move || {
+        let counter = model::Counter::new();
+        seen.insert(model::hit_twice(&counter));
+    });
+    // The lost update of the unsynchronized increments is found
+    assert_eq!(outcomes.distinct(), BTreeSet::from([1, 2]));
+}
+
+#[cfg(not(feature = "model"))]
+#[test]
+fn shims_are_native_by_default() {
+    let handle: std::thread::JoinHandle<()> = traceforge::shims::spawn(|| ());
+    handle.join().unwrap();
+}
@@ -0,0 +1,62 @@
+use traceforge::thread;
+use traceforge::{Config, Nondet};
+
+const WORKERS: i32 = 3;
+
+// The workers are symmetric: once all of their ids have been received, the order of the
+// receives makes no difference to the rest of the execution.
+fn model() {
+    let me = thread::current().id();
+    for i in 0..WORKERS {
+        thread::spawn(move || traceforge::send_msg(me, i));
+    }
+    let sum: i32 = (0..WORKERS)
+        .map(|_| traceforge::recv_msg_block::<i32>())
+        .sum();
+    traceforge::checkpoint(&sum);
+
+    let a = (0..3).nondet();
+    let b = (0..3).nondet();
+    traceforge::assert(!(a == 2 && b == 1 && sum == 3));
+}
+
+fn config(dedup: bool) -> Config {
+    Config::builder()
+        .with_state_dedup(dedup)
+        .with_keep_going_after_error(true)
+        .build()
+}
+
+#[test]
+fn dedup_prunes_equivalent_states() {
+    let plain = traceforge::verify(config(false), model);
+    let dedup = traceforge::verify(config(true), model);
+
+    // 6 orders of the receives, times 9 values of the suffix, one of which fails
+    assert_eq!(plain.execs + plain.block, 54);
+    assert_eq!(plain.block, 6);
+    // The suffix is explored once; the 5 other orders stop at the checkpoint
+    assert_eq!(dedup.execs, 8);
+    assert_eq!(dedup.block, 1 + 5);
+}
+
+#[test]
+fn dedup_finds_the_same_bug() {
+    for dedup in [false, true] {
+        let result = std::panic::catch_unwind(|| {
+            traceforge::verify(Config::builder().with_state_dedup(dedup).build(), model)
+        });
+        assert!(result.is_err(), "bug not found with dedup = {}", dedup);
+    }
+}
+
+#[test]
+fn checkpoint_without_dedup_is_a_no_op() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let v = (0..4).nondet();
+        traceforge::checkpoint(&0);
+        traceforge::assert(v < 4);
+    });
+    assert_eq!(stats.execs, 4);
+    assert_eq!(stats.block, 0);
+}
@@ -0,0 +1,53 @@
+use traceforge::{thread, Config, Stats};
+
+fn two_senders(config: Config) -> Stats {
+    traceforge::verify(config, || {
+        let main = thread::current().id();
+        for i in 0..2u32 {
+            thread::spawn(move || traceforge::send_msg(main, i));
+        }
+        let first: u32 = traceforge::recv_msg_block();
+        traceforge::assert(first == 0);
+    })
+}
+
+#[test]
+fn stats_round_trip_through_json() {
+    let config = Config::builder()
+        .with_keep_going_after_error(true)
+        .with_collect_deadlocks(true)
+        .with_message_counts(true)
+        .build();
+    let stats = two_senders(config);
+    let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+    assert_eq!(json["execs"], stats.execs);
+    assert_eq!(json["block"], stats.block);
+    assert_eq!(json["failures"], 1);
+    assert_eq!(json["max_schedule_depth"], stats.max_schedule_depth);
+    assert_eq!(json["max_iterations_reached"], false);
+    assert_eq!(json["out_of_memory"], false);
+    assert_eq!(json["elapsed"]["secs"], stats.elapsed.as_secs());
+    assert!(json.get("reachable_states").is_none());
+
+    let back: Stats = serde_json::from_value(json).unwrap();
+    assert_eq!(back.execs, stats.execs);
+    assert_eq!(back.block, stats.block);
+    assert_eq!(back.failures, stats.failures);
+    assert_eq!(back.elapsed, stats.elapsed);
+    assert_eq!(back.deadlocks, stats.deadlocks);
+    assert_eq!(back.message_counts, stats.message_counts);
+    assert_eq!(back.avg_schedule_depth(), stats.avg_schedule_depth());
+}
+
+#[test]
+fn the_iteration_bound_is_reported() {
+    let stats = two_senders(
+        Config::builder()
+            .with_keep_going_after_error(true)
+            .with_max_iterations(1)
+            .build(),
+    );
+    assert!(stats.max_iterations_reached);
+    let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+    assert_eq!(json["max_iterations_reached"], true);
+}
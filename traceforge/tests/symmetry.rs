@@ -0,0 +1,65 @@
+use traceforge::thread::{self, ThreadId};
+use traceforge::Config;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Request {
+    Get(ThreadId),
+    Put(i32),
+}
+
+// Three identical workers increment a counter held by a server, without making the read and the
+// write atomic, so some increments are lost.
+fn model(symmetric: bool) {
+    let server = thread::spawn_daemon(|| {
+        let mut value = 0;
+        loop {
+            match traceforge::recv_msg_block::<Request>() {
+                Request::Get(t) => traceforge::send_msg(t, value),
+                Request::Put(v) => value = v,
+            }
+        }
+    })
+    .thread()
+    .id();
+    let worker = move || {
+        traceforge::send_msg(server, Request::Get(thread::current().id()));
+        let v: i32 = traceforge::recv_msg_block();
+        traceforge::send_msg(server, Request::Put(v + 1));
+    };
+    let workers = if symmetric {
+        traceforge::spawn_symmetric_group(3, worker)
+    } else {
+        (0..3).map(|_| thread::spawn(worker)).collect()
+    };
+    for w in workers {
+        w.join().unwrap();
+    }
+    traceforge::send_msg(server, Request::Get(thread::current().id()));
+    let v: i32 = traceforge::recv_msg_block();
+    traceforge::assert(v == 3);
+}
+
+#[test]
+fn symmetric_group_reduces_executions() {
+    let config = || Config::builder().with_keep_going_after_error(true).build();
+    let plain = traceforge::verify(config(), || model(false));
+    let symmetric = traceforge::verify(config(), || model(true));
+    assert_eq!(plain.execs + plain.block, 288);
+    // One execution for every 3! orderings of the workers
+    assert_eq!(symmetric.execs + symmetric.block, 288 / 6);
+    assert_eq!(symmetric.execs, 1);
+}
+
+#[test]
+fn symmetric_group_finds_lost_update() {
+    for symmetric in [false, true] {
+        let result = std::panic::catch_unwind(|| {
+            traceforge::verify(Config::builder().build(), move || model(symmetric))
+        });
+        assert!(
+            result.is_err(),
+            "lost update not found (symmetric = {})",
+            symmetric
+        );
+    }
+}
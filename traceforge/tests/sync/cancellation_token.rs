@@ -0,0 +1,55 @@
+use traceforge::sync::CancellationToken;
+use traceforge::*;
+
+#[test]
+fn parent_cancel_propagates() {
+    let stats = verify(Config::builder().build(), || {
+        future::block_on(async {
+            let parent = CancellationToken::new();
+            let child1 = parent.child_token();
+            let child2 = parent.child_token();
+
+            let waiters = [child1.clone(), child2.clone()]
+                .into_iter()
+                .map(|token| {
+                    future::spawn(async move {
+                        token.cancelled().await;
+                        assert!(token.is_cancelled());
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            parent.cancel();
+            assert!(parent.is_cancelled());
+            for waiter in waiters {
+                waiter.await.unwrap();
+            }
+            assert!(child1.is_cancelled());
+            assert!(child2.is_cancelled());
+        });
+    });
+    assert!(stats.execs > 1);
+}
+
+#[test]
+fn child_cancel_is_isolated() {
+    verify(Config::builder().build(), || {
+        future::block_on(async {
+            let parent = CancellationToken::new();
+            let child1 = parent.child_token();
+            let child2 = parent.child_token();
+
+            let c = child1.clone();
+            let canceller = future::spawn(async move { c.cancel() });
+            child1.cancelled().await;
+            canceller.await.unwrap();
+
+            assert!(child1.is_cancelled());
+            assert!(!child2.is_cancelled());
+            assert!(!parent.is_cancelled());
+
+            // Children created from a cancelled token start out cancelled
+            assert!(child1.child_token().is_cancelled());
+        });
+    });
+}
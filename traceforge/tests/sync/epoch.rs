@@ -0,0 +1,103 @@
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
+
+use traceforge::sync::atomic::{AtomicBool, AtomicUsize};
+use traceforge::sync::Collector;
+use traceforge::*;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Reclaim {
+    // The writer defers the reclamation, and the readers only use the object while pinned
+    Deferred,
+    // The writer reclaims the object as soon as it unlinks it
+    AtOnce,
+    // The readers keep using the object after unpinning
+    AfterUnpin,
+}
+
+// Two readers use the published object while a writer unpublishes the first object and
+// reclaims it. Every reclamation has run by the end of the execution.
+fn reclaim(how: Reclaim) -> Stats {
+    verify(Config::builder().build(), move || {
+        let collector = Collector::new();
+        let published = Arc::new(AtomicUsize::new(0));
+        let freed = Arc::new([AtomicBool::new(false), AtomicBool::new(false)]);
+
+        let readers = (0..2)
+            .map(|_| {
+                let (collector, published) = (collector.clone(), published.clone());
+                let freed = freed.clone();
+                thread::spawn(move || {
+                    let guard = collector.pin();
+                    let object = published.load(SeqCst);
+                    if how == Reclaim::AfterUnpin {
+                        drop(guard);
+                        assert!(!freed[object].load(SeqCst), "use after free");
+                    } else {
+                        assert!(!freed[object].load(SeqCst), "use after free");
+                        drop(guard);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let guard = collector.pin();
+        published.store(1, SeqCst);
+        let f = freed.clone();
+        match how {
+            Reclaim::AtOnce => f[0].store(true, SeqCst),
+            Reclaim::Deferred | Reclaim::AfterUnpin => {
+                guard.defer(move || f[0].store(true, SeqCst))
+            }
+        }
+        drop(guard);
+        collector.flush();
+        collector.flush();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        collector.flush();
+        collector.flush();
+        assert_eq!(collector.pending(), 0);
+        assert!(freed[0].load(SeqCst));
+    })
+}
+
+#[test]
+fn deferred_reclamation_waits_for_pinned_readers() {
+    let stats = reclaim(Reclaim::Deferred);
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+#[should_panic(expected = "use after free")]
+fn reclaiming_at_once_is_caught() {
+    reclaim(Reclaim::AtOnce);
+}
+
+#[test]
+#[should_panic(expected = "use after free")]
+fn using_an_object_after_unpinning_is_caught() {
+    reclaim(Reclaim::AfterUnpin);
+}
+
+#[test]
+fn nested_pins_unpin_at_the_outermost_guard() {
+    verify(Config::builder().build(), || {
+        let collector = Collector::new();
+        let outer = collector.pin();
+        let inner = collector.pin();
+        inner.defer(|| {});
+        drop(inner);
+        assert!(collector.is_pinned());
+        collector.flush();
+        collector.flush();
+        // The outer pin holds the epoch back
+        assert_eq!(collector.pending(), 1);
+        drop(outer);
+        assert!(!collector.is_pinned());
+        collector.flush();
+        assert_eq!(collector.pending(), 0);
+    });
+}
@@ -0,0 +1,50 @@
+use traceforge::sync::Gate;
+use traceforge::*;
+
+#[test]
+fn open_releases_all_waiters() {
+    let stats = verify(Config::builder().build(), || {
+        future::block_on(async {
+            let gate = Gate::new();
+            let waiters = (0..3)
+                .map(|_| {
+                    let gate = gate.clone();
+                    future::spawn(async move {
+                        gate.wait().await;
+                        assert!(gate.is_open());
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            gate.open();
+            for waiter in waiters {
+                waiter.await.unwrap();
+            }
+
+            // The gate stays open for later waiters
+            let late = gate.clone();
+            future::spawn(async move { late.wait().await })
+                .await
+                .unwrap();
+        });
+    });
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn closed_gate_blocks_again() {
+    let stats = verify(Config::builder().build(), || {
+        future::block_on(async {
+            let gate = Gate::new();
+            gate.open();
+            gate.wait().await;
+            gate.close();
+            assert!(!gate.is_open());
+            gate.wait().await;
+        });
+    });
+    // The second wait never completes
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.block, 1);
+}
@@ -1,4 +1,13 @@
+#[path = "../utils/mod.rs"]
+mod utils;
+
+mod cancellation_token;
+mod epoch;
+mod gate;
 mod mpsc;
 mod mutex;
 mod notify;
 mod oneshot;
+mod rcu;
+mod shared;
+mod shutdown;
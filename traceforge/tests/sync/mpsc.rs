@@ -56,3 +56,49 @@ fn multiple_producer() {
     );
     println!("Stats = {}, {}", stats.execs, stats.block);
 }
+
+#[test]
+fn blocking_recv_from_async_senders() {
+    let f = || {
+        let (tx, rx) = channel(100);
+        let receiver = thread::spawn(move || {
+            let first = rx.blocking_recv().unwrap();
+            let second = rx.blocking_recv().unwrap();
+            assert(first + second == 3);
+        });
+        traceforge::future::block_on(async move {
+            let senders: Vec<_> = (1..=2)
+                .map(|i| {
+                    let tx = tx.clone();
+                    traceforge::future::spawn(async move {
+                        let _ = tx.send(i).await;
+                    })
+                })
+                .collect();
+            for sender in senders {
+                sender.await.unwrap();
+            }
+        });
+        receiver.join().unwrap();
+    };
+
+    // Either sender can be first
+    let stats = verify(Config::builder().build(), f);
+    assert_eq!((stats.execs, stats.block), (2, 0));
+}
+
+#[test]
+fn unbounded_blocking_recv() {
+    let stats = verify(Config::builder().build(), || {
+        let (tx, rx) = unbounded_channel();
+        thread::spawn(move || {
+            traceforge::future::block_on(async move {
+                let _ = tx.send(1);
+                let _ = tx.send(2);
+            })
+        });
+        assert(rx.blocking_recv() == Some(1));
+        assert(rx.blocking_recv() == Some(2));
+    });
+    assert_eq!((stats.execs, stats.block), (1, 0));
+}
@@ -1,7 +1,10 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use traceforge::sync::mutex::Mutex;
 use traceforge::thread;
+
+use crate::utils::Outcomes;
 // this file shows some example usage of the Must's `sync::mutex` library
 
 #[test]
@@ -141,3 +144,189 @@ fn mutex_doc_test() {
 
     assert!(!stats.coverage.is_covered("7".into()));
 }
+
+// Three waiters queue up on a mutex held by the main thread and record the order in which they
+// get it. Waiter `i` makes its request after hearing from waiter `i - 1`. With
+// `register_first`, a waiter makes its request before telling the next one, so the requests
+// reach the mutex in the order 0, 1, 2.
+fn grant_orders(fair: bool, register_first: bool) -> (BTreeSet<Vec<usize>>, traceforge::Stats) {
+    let orders = Outcomes::new();
+    let seen = orders.clone();
+    let stats = traceforge::verify(traceforge::Config::builder().build(), move || {
+        let m = Arc::new(Mutex::with_fairness(Vec::new(), fair));
+        let guard = m.blocking_lock();
+        let mut next: Option<thread::ThreadId> = None;
+        let mut waiters = Vec::new();
+        for i in (0..3).rev() {
+            let m = m.clone();
+            let h = thread::spawn(move || {
+                if i > 0 {
+                    let _: () = traceforge::recv_msg_block();
+                }
+                traceforge::future::block_on(async move {
+                    let mut lock = Box::pin(m.lock());
+                    if register_first {
+                        assert!(futures::poll!(lock.as_mut()).is_pending());
+                    }
+                    if let Some(t) = next {
+                        traceforge::send_msg(t, ());
+                    }
+                    lock.await.push(i);
+                });
+            });
+            next = Some(h.thread().id());
+            waiters.push(h);
+        }
+        drop(guard);
+        for h in waiters {
+            h.join().unwrap();
+        }
+        seen.insert(m.blocking_lock().clone());
+    });
+    (orders.distinct(), stats)
+}
+
+#[test]
+fn fair_mutex_grants_in_arrival_order() {
+    let (orders, stats) = grant_orders(true, true);
+    assert_eq!(orders, BTreeSet::from([vec![0, 1, 2]]));
+    // The interleavings of the requests with the release are still explored
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn unfair_mutex_grant_order_is_unconstrained() {
+    let (orders, _) = grant_orders(false, false);
+    assert_eq!(orders.len(), 6);
+}
+
+#[test]
+#[should_panic(
+    expected = "Reentrant lock: thread worker tried to lock a Mutex that it already holds"
+)]
+fn reentrant_lock_is_reported() {
+    traceforge::verify(traceforge::Config::builder().build(), || {
+        let m = Arc::new(Mutex::new(0));
+        let worker = thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(move || {
+                traceforge::future::block_on(async {
+                    let _outer = m.lock().await;
+                    let _inner = m.lock().await;
+                });
+            })
+            .unwrap();
+        worker.join().unwrap();
+    });
+}
+
+// Must be called with the lock of `m` held
+fn bump_locked(m: &Mutex<u32>, value: &mut u32) {
+    m.assert_held();
+    *value += 1;
+}
+
+#[test]
+fn assert_held_passes_with_the_lock() {
+    traceforge::verify(traceforge::Config::builder().build(), || {
+        let m = Arc::new(Mutex::new(0));
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                let m = m.clone();
+                thread::spawn(move || {
+                    let mut guard = m.blocking_lock();
+                    let mut value = *guard;
+                    bump_locked(&m, &mut value);
+                    *guard = value;
+                })
+            })
+            .collect();
+        workers.into_iter().for_each(|w| w.join().unwrap());
+    });
+}
+
+#[test]
+#[should_panic(expected = "Lock not held: thread worker expected to hold a Mutex")]
+fn assert_held_flags_a_path_without_the_lock() {
+    traceforge::verify(traceforge::Config::builder().build(), || {
+        let m = Arc::new(Mutex::new(0));
+        let worker = thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(move || {
+                let mut value = 0;
+                // The fast path forgets to take the lock
+                if traceforge::recv_msg::<()>().is_some() {
+                    let _guard = m.blocking_lock();
+                    bump_locked(&m, &mut value);
+                } else {
+                    bump_locked(&m, &mut value);
+                }
+            })
+            .unwrap();
+        traceforge::send_msg(worker.thread().id(), ());
+        worker.join().unwrap();
+    });
+}
+
+// A task of low priority holds a mutex that a task of high priority waits for, while a task of
+// medium priority is ready to run. Returns the order in which the tasks finish their work in
+// the first explored execution.
+fn first_finish_order(inheritance: bool) -> Vec<&'static str> {
+    let orders = Outcomes::new();
+    let seen = orders.clone();
+    let config = traceforge::Config::builder().build();
+    traceforge::verify(config, move || {
+        let m = Mutex::new(());
+        let m = Arc::new(if inheritance {
+            m.with_priority_inheritance()
+        } else {
+            m
+        });
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let (start_tx, start_rx) = traceforge::channel::Builder::<()>::new().build();
+        let (locked_tx, locked_rx) = traceforge::channel::Builder::<()>::new().build();
+        let (go_tx, go_rx) = traceforge::channel::Builder::<()>::new().build();
+        let (medium_tx, medium_rx) = traceforge::channel::Builder::<()>::new().build();
+
+        let (m1, o1) = (m.clone(), order.clone());
+        let low = thread::spawn(move || {
+            start_rx.recv_msg_block();
+            let _guard = m1.blocking_lock();
+            locked_tx.send_msg(());
+            // Critical section, ready to go on once the high task waits
+            go_rx.recv_msg_block();
+            o1.lock().unwrap().push("low");
+        });
+        let o2 = order.clone();
+        let medium = thread::spawn(move || {
+            medium_rx.recv_msg_block();
+            o2.lock().unwrap().push("medium");
+        });
+        let (m3, o3) = (m.clone(), order.clone());
+        let high = thread::spawn(move || {
+            locked_rx.recv_msg_block();
+            go_tx.send_msg(());
+            medium_tx.send_msg(());
+            let _guard = m3.blocking_lock();
+            o3.lock().unwrap().push("high");
+        });
+        traceforge::set_task_priority(&low, 1);
+        traceforge::set_task_priority(&medium, 5);
+        traceforge::set_task_priority(&high, 10);
+        start_tx.send_msg(());
+        for h in [low, medium, high] {
+            h.join().unwrap();
+        }
+        seen.insert(order.lock().unwrap().clone());
+    });
+    orders.all()[0].clone()
+}
+
+#[test]
+fn priority_inheritance_prevents_inversion() {
+    // The medium task runs ahead of the high one, which waits for the low one
+    assert_eq!(first_finish_order(false), ["low", "medium", "high"]);
+    // The low task runs at the priority of the high one until it releases the lock
+    assert_eq!(first_finish_order(true), ["low", "high", "medium"]);
+}
@@ -159,3 +159,247 @@ fn two_notifies_one_received() {
         stats.execs, stats.block
     );
 }
+
+#[test]
+fn two_notifies_two_received_with_permit_limit() {
+    let f = || {
+        future::block_on(async {
+            let notify = Arc::new(Notify::with_permit_limit(2));
+            let notify_clone = notify.clone();
+
+            // Both notifications are stored as permits
+            notify.notify_one();
+            notify.notify_one();
+
+            let handle = future::spawn(async move {
+                notify_clone.notified().await;
+                notify_clone.notified().await;
+            });
+
+            // The waiter consumes one permit per notified() call
+            handle.await.unwrap();
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn permits_saturate_at_the_limit() {
+    let f = || {
+        future::block_on(async {
+            let notify = Arc::new(Notify::with_permit_limit(2));
+            let notify_clone = notify.clone();
+
+            // The third notification is lost
+            for _ in 0..3 {
+                notify.notify_one();
+            }
+
+            let handle = future::spawn(async move {
+                for _ in 0..3 {
+                    notify_clone.notified().await;
+                }
+            });
+            handle.await.unwrap();
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert_eq!(stats.execs, 0);
+    assert!(stats.block > 0);
+}
+
+#[test]
+fn dropped_waiter_does_not_consume_notification() {
+    let f = || {
+        future::block_on(async {
+            let notify = Arc::new(Notify::new());
+
+            // The waiter registers and then loses the select against a ready future
+            let lost =
+                futures::future::select(Box::pin(notify.notified()), futures::future::ready(()))
+                    .await;
+            assert!(matches!(lost, futures::future::Either::Right(_)));
+            drop(lost);
+
+            notify.notify_one();
+
+            let notify_clone = notify.clone();
+            let handle = future::spawn(async move {
+                notify_clone.notified().await;
+            });
+            // The notification was kept for the live waiter
+            handle.await.unwrap();
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn live_waiter_is_notified_after_another_is_dropped() {
+    let f = || {
+        future::block_on(async {
+            let notify = Arc::new(Notify::new());
+            let notify_clone = notify.clone();
+            let handle = future::spawn(async move {
+                notify_clone.notified().await;
+            });
+
+            let lost =
+                futures::future::select(Box::pin(notify.notified()), futures::future::ready(()))
+                    .await;
+            drop(lost);
+
+            notify.notify_one();
+            handle.await.unwrap();
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn single_notify_reaches_waiter_after_first_is_dropped() {
+    let f = || {
+        future::block_on(async {
+            let notify = Notify::new();
+
+            // Register two waiters, in this order
+            let mut first = Box::pin(notify.notified());
+            assert!(futures::poll!(first.as_mut()).is_pending());
+            let mut second = Box::pin(notify.notified());
+            assert!(futures::poll!(second.as_mut()).is_pending());
+
+            drop(first);
+            notify.notify_one();
+            second.await;
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 0);
+    assert_eq!(stats.block, 0);
+}
+
+// Many waiters contend for the waiters lock inside `poll`. Every interleaving of the
+// registrations and notifications completes, without deadlocks or lost wakeups.
+#[test]
+fn many_concurrent_waiters() {
+    const WAITERS: usize = 3;
+    let f = || {
+        future::block_on(async {
+            // Notifications sent before a waiter registers are kept, not collapsed
+            let notify = Arc::new(Notify::with_permit_limit(WAITERS));
+            let handles: Vec<_> = (0..WAITERS)
+                .map(|_| {
+                    let notify = notify.clone();
+                    future::spawn(async move { notify.notified().await })
+                })
+                .collect();
+            for _ in 0..WAITERS {
+                notify.notify_one();
+            }
+            for h in handles {
+                h.await.unwrap();
+            }
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+// The waiter checks the flag, and waits only while it is unset. Whatever the order of the
+// check, the store and the notification, the waiter never misses the wakeup.
+#[test]
+fn wait_while_never_misses_wakeup() {
+    use std::sync::atomic::Ordering;
+    use traceforge::sync::{atomic::AtomicBool, wait_while};
+
+    let f = || {
+        future::block_on(async {
+            let ready = Arc::new(AtomicBool::new(false));
+            let notify = Arc::new(Notify::new());
+            let (r, n) = (ready.clone(), notify.clone());
+            let setter = future::spawn(async move {
+                r.store(true, Ordering::SeqCst);
+                n.notify_one();
+            });
+            wait_while(&notify, || !ready.load(Ordering::SeqCst)).await;
+            traceforge::assert(ready.load(Ordering::SeqCst));
+            setter.await.unwrap();
+        });
+    };
+
+    let stats = verify(Config::builder().build(), f);
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
+
+// A waiter that proceeds after one wakeup, without checking its condition again
+fn non_looping_waiter() {
+    use std::sync::atomic::Ordering;
+    use traceforge::sync::atomic::AtomicBool;
+
+    future::block_on(async {
+        let ready = Arc::new(AtomicBool::new(false));
+        let notify = Arc::new(Notify::new());
+        let (r, n) = (ready.clone(), notify.clone());
+        let setter = future::spawn(async move {
+            r.store(true, Ordering::SeqCst);
+            n.notify_one();
+        });
+        notify.notified().await;
+        traceforge::assert(ready.load(Ordering::SeqCst));
+        setter.await.unwrap();
+    });
+}
+
+#[test]
+fn non_looping_waiter_is_correct_without_spurious_wakeups() {
+    let stats = verify(Config::builder().build(), non_looping_waiter);
+    assert!(stats.execs > 0);
+}
+
+#[test]
+#[should_panic(expected = "assertion failed")]
+fn non_looping_waiter_is_caught_by_a_spurious_wakeup() {
+    verify(
+        Config::builder().with_spurious_wakeups(true).build(),
+        non_looping_waiter,
+    );
+}
+
+#[test]
+fn wait_while_survives_spurious_wakeups() {
+    use std::sync::atomic::Ordering;
+    use traceforge::sync::{atomic::AtomicBool, wait_while};
+
+    let stats = verify(
+        Config::builder().with_spurious_wakeups(true).build(),
+        || {
+            future::block_on(async {
+                let ready = Arc::new(AtomicBool::new(false));
+                let notify = Arc::new(Notify::new());
+                let (r, n) = (ready.clone(), notify.clone());
+                let setter = future::spawn(async move {
+                    r.store(true, Ordering::SeqCst);
+                    n.notify_one();
+                });
+                wait_while(&notify, || !ready.load(Ordering::SeqCst)).await;
+                traceforge::assert(ready.load(Ordering::SeqCst));
+                setter.await.unwrap();
+            });
+        },
+    );
+    assert_eq!(stats.block, 0);
+}
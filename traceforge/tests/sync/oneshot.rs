@@ -1,4 +1,8 @@
+use std::collections::BTreeSet;
+
 use traceforge::{sync::oneshot::*, *};
+
+use crate::utils::Outcomes;
 // this file shows some example usage of the Must's `sync::oneshot` library
 
 #[test]
@@ -19,3 +23,82 @@ fn single_producer() {
     );
     println!("Stats = {}, {}", stats.execs, stats.block);
 }
+
+#[test]
+fn dropped_sender_is_reported() {
+    let stats = verify(Config::builder().build(), || {
+        let (tx, rx) = channel::<u32>();
+        let _t = thread::spawn(move || drop(tx));
+        traceforge::future::block_on(async {
+            assert(rx.await.is_err());
+        });
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn receiver_tells_a_value_from_a_dropped_sender() {
+    let stats = verify(Config::builder().build(), || {
+        let (tx, rx) = channel::<u32>();
+        let _t = thread::spawn(move || {
+            if traceforge::nondet() {
+                let _ = tx.send(10);
+            }
+        });
+        if let Ok(v) = rx.blocking_recv() {
+            assert(v == 10);
+        }
+    });
+    assert_eq!(stats.execs, 2);
+}
+
+#[test]
+fn try_recv_polls_a_concurrent_send() {
+    let polls = Outcomes::new();
+    let seen = polls.clone();
+    let stats = verify(Config::builder().build(), move || {
+        let (tx, mut rx) = channel::<u32>();
+        let t = thread::spawn(move || {
+            let _ = tx.send(10);
+        });
+        let mut n = 0;
+        while n < 3 {
+            n += 1;
+            match rx.try_recv() {
+                Ok(Some(v)) => {
+                    assert(v == 10);
+                    break;
+                }
+                Ok(None) => thread::yield_now(),
+                Err(_) => panic!("the sender is not dropped before sending"),
+            }
+        }
+        assert(rx.is_terminated() || n == 3);
+        seen.insert(rx.is_terminated().then_some(n));
+        t.join().unwrap();
+    });
+    assert_eq!(stats.block, 0);
+    // The value is received at the first poll, at a later one, or not at all
+    assert_eq!(
+        polls.distinct(),
+        BTreeSet::from([None, Some(1), Some(2), Some(3)])
+    );
+}
+
+#[test]
+fn received_oneshot_is_terminated() {
+    verify(Config::builder().build(), || {
+        let (tx, mut rx) = channel::<u32>();
+        assert(!rx.is_terminated());
+        let _ = tx.send(10);
+        let v = rx.try_recv();
+        if v == Ok(None) {
+            // The send is not seen in this execution
+            return;
+        }
+        assert(v == Ok(Some(10)));
+        assert(rx.is_terminated());
+        assert(rx.try_recv().is_err());
+    });
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use traceforge::sync::atomic::{AtomicBool, AtomicUsize};
+use traceforge::sync::Rcu;
+use traceforge::*;
+
+use std::sync::atomic::Ordering::SeqCst;
+
+// Two readers dereference the published object while a writer unpublishes the first object,
+// optionally waits for a grace period, and reclaims it
+fn reclaim(wait_for_readers: bool) -> Stats {
+    verify(Config::builder().build(), move || {
+        let rcu = Rcu::new();
+        let published = Arc::new(AtomicUsize::new(0));
+        let freed = Arc::new([AtomicBool::new(false), AtomicBool::new(false)]);
+        let inside = Arc::new([AtomicBool::new(false), AtomicBool::new(false)]);
+
+        let readers = (0..2)
+            .map(|i| {
+                let (rcu, published, freed) = (rcu.clone(), published.clone(), freed.clone());
+                let inside = inside.clone();
+                thread::spawn(move || {
+                    rcu.read_lock();
+                    inside[i].store(true, SeqCst);
+                    cover!("overlapping readers", inside[1 - i].load(SeqCst));
+                    let object = published.load(SeqCst);
+                    assert!(!freed[object].load(SeqCst), "use after free");
+                    inside[i].store(false, SeqCst);
+                    rcu.read_unlock();
+                })
+            })
+            .collect::<Vec<_>>();
+
+        future::block_on(async {
+            published.store(1, SeqCst);
+            if wait_for_readers {
+                rcu.synchronize().await;
+            }
+            freed[0].store(true, SeqCst);
+        });
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    })
+}
+
+#[test]
+fn synchronize_waits_out_overlapping_readers() {
+    let stats = reclaim(true);
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+    assert!(stats.coverage.is_covered("overlapping readers".into()));
+}
+
+#[test]
+#[should_panic(expected = "use after free")]
+fn reclaiming_without_grace_period_is_caught() {
+    reclaim(false);
+}
+
+#[test]
+fn nested_sections_end_at_the_outermost_unlock() {
+    let stats = verify(Config::builder().build(), || {
+        let rcu = Rcu::new();
+        let published = Arc::new(AtomicBool::new(true));
+        let freed = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (rcu, published, freed) = (rcu.clone(), published.clone(), freed.clone());
+            thread::spawn(move || {
+                rcu.read_lock();
+                let seen = published.load(SeqCst);
+                rcu.read_lock();
+                rcu.read_unlock();
+                // The reader is still in its outer section, which the grace period waits for
+                assert!(rcu.in_read_section());
+                if seen {
+                    assert!(!freed.load(SeqCst), "use after free");
+                }
+                rcu.read_unlock();
+                assert!(!rcu.in_read_section());
+            })
+        };
+        future::block_on(async {
+            published.store(false, SeqCst);
+            rcu.synchronize().await;
+            freed.store(true, SeqCst);
+        });
+        reader.join().unwrap();
+    });
+    assert!(stats.execs > 1);
+    assert_eq!(stats.block, 0);
+}
@@ -0,0 +1,110 @@
+use traceforge::sync::{Mutex, Shared};
+use traceforge::*;
+
+fn collect() -> Config {
+    Config::builder().with_collect_failures(true).build()
+}
+
+#[test]
+fn unsynchronized_writes_race() {
+    let stats = verify(collect(), || {
+        let x = Shared::new(0u32);
+        let (a, b) = (x.clone(), x.clone());
+        let t1 = thread::spawn(move || a.write(1));
+        let t2 = thread::spawn(move || b.write(2));
+        t1.join().unwrap();
+        t2.join().unwrap();
+    });
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.collected_failures.len(), 1);
+    let race = &stats.collected_failures[0];
+    assert!(
+        race.message
+            .contains("the write of task-2 is concurrent with the write of task-1"),
+        "{}",
+        race.message
+    );
+}
+
+#[test]
+#[should_panic(expected = "data race on a Shared value")]
+fn a_read_races_with_a_write() {
+    verify(Config::builder().build(), || {
+        let x = Shared::new(0u32);
+        let a = x.clone();
+        let t = thread::spawn(move || a.write(1));
+        let _ = x.read();
+        t.join().unwrap();
+    });
+}
+
+#[test]
+fn synchronized_accesses_do_not_race() {
+    let stats = verify(collect(), || {
+        let x = Shared::new(0u32);
+        let lock = std::sync::Arc::new(Mutex::new(()));
+        let handles = (0..2)
+            .map(|_| {
+                let (x, lock) = (x.clone(), lock.clone());
+                thread::spawn(move || {
+                    let _guard = lock.blocking_lock();
+                    x.update(|v| v + 1);
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        // The joins order the writes before the read
+        assert_eq!(x.read(), 2);
+    });
+    assert!(stats.execs > 0);
+    assert!(stats.collected_failures.is_empty());
+}
+
+// Two tasks bump a racy counter, tagged "hits", and write an untagged value
+fn counter_and_value() {
+    let hits = Shared::new(0u32).with_tag("hits");
+    let value = Shared::new(0u32);
+    let handles = (1..=2)
+        .map(|i| {
+            let (hits, value) = (hits.clone(), value.clone());
+            thread::spawn(move || {
+                hits.update(|h| h + 1);
+                value.write(i);
+            })
+        })
+        .collect::<Vec<_>>();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+}
+
+#[test]
+fn tagged_benign_races_are_suppressed() {
+    let config = Config::builder()
+        .with_collect_failures(true)
+        .with_benign_race("hits")
+        .build();
+    let stats = verify(config, counter_and_value);
+    // The update of the second task races with both the read and the write of the first
+    assert_eq!(stats.suppressed_races, 2);
+    assert_eq!(stats.collected_failures.len(), 1);
+    assert!(stats.collected_failures[0]
+        .message
+        .contains("the write of task-2 is concurrent with the write of task-1"));
+}
+
+#[test]
+fn benign_races_are_suppressed_by_location() {
+    // The line of `x.write(2)` below
+    let line = line!() + 8;
+    let config = Config::builder()
+        .with_benign_race(&format!("{}:{}", file!(), line))
+        .build();
+    let stats = verify(config, || {
+        let x = Shared::new(0u32);
+        let a = x.clone();
+        let t = thread::spawn(move || a.write(1));
+        x.write(2);
+        t.join().unwrap();
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.suppressed_races, 1);
+}
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use traceforge::*;
+
+#[test]
+fn workers_observe_shutdown() {
+    let stats = verify(Config::builder().build(), || {
+        future::block_on(async {
+            let (trigger, shutdown) = shutdown::channel();
+            let observed = Arc::new(AtomicUsize::new(0));
+
+            let workers = (0..3)
+                .map(|_| {
+                    let shutdown = shutdown.clone();
+                    let observed = observed.clone();
+                    future::spawn(async move {
+                        shutdown.cancelled().await;
+                        assert!(shutdown.is_cancelled());
+                        observed.fetch_add(1, Ordering::SeqCst);
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            assert!(!shutdown.is_cancelled());
+            trigger.cancel();
+            for worker in workers {
+                worker.await.unwrap();
+            }
+            assert_eq!(observed.load(Ordering::SeqCst), 3);
+        });
+    });
+    // Each worker either registers before the cancellation or observes it directly
+    assert!(stats.execs > 1);
+}
@@ -0,0 +1,82 @@
+use traceforge::channel::Builder;
+use traceforge::loc::CommunicationModel;
+use traceforge::thread;
+use traceforge::{Config, Event};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Msg {
+    Reading(u32),
+    // The event of the send of a reading
+    Sent(Event),
+}
+
+// Two sensors send a reading to the main thread over a causally ordered channel, then report
+// the event of their send on the side. The sensor 0 is known to sample before the sensor 1,
+// e.g. because it runs at boot, but nothing in the model orders them.
+fn sensors(known_order: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let (tx, rx) = Builder::<Msg>::new()
+            .with_comm(CommunicationModel::CausalOrder)
+            .build();
+        let (side_tx, side_rx) = Builder::<Msg>::new().build();
+        for i in 0..2 {
+            let (tx, side_tx) = (tx.clone(), side_tx.clone());
+            thread::spawn(move || {
+                tx.send_msg(Msg::Reading(i));
+                side_tx.send_msg(Msg::Sent(traceforge::current_event()));
+            });
+        }
+        let first = rx.recv_msg_block();
+        let _second = rx.recv_msg_block();
+        if known_order {
+            let mut sent = [side_rx.recv_msg_block(), side_rx.recv_msg_block()];
+            sent.sort_by_key(|m| match m {
+                Msg::Sent(e) => e.thread(),
+                Msg::Reading(_) => unreachable!(),
+            });
+            if let [Msg::Sent(a), Msg::Sent(b)] = sent {
+                traceforge::add_sync_edge(a, b);
+            }
+        }
+        traceforge::assert(first == Msg::Reading(0));
+    }
+}
+
+#[test]
+fn sync_edge_removes_a_spurious_counterexample() {
+    let config = || Config::builder().build();
+    let result = std::panic::catch_unwind(|| traceforge::verify(config(), sensors(false)));
+    assert!(result.is_err());
+
+    // The executions that receive the reading of the sensor 1 first now end at the edge: one
+    // for each order of the side messages
+    let stats = traceforge::verify(config(), sensors(true));
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 2);
+}
+
+#[test]
+fn sync_edge_against_happens_before_ends_the_execution() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let child = thread::spawn(traceforge::current_event);
+        let spawned = child.join().unwrap();
+        let joined = traceforge::current_event();
+        traceforge::add_sync_edge(joined, spawned);
+        unreachable!("the edge contradicts the join");
+    });
+    assert_eq!(stats.execs, 0);
+    assert_eq!(stats.block, 1);
+}
+
+#[test]
+fn sync_edges_extend_happens_before() {
+    traceforge::verify(Config::builder().build(), || {
+        let t1 = thread::spawn(traceforge::current_event);
+        let t2 = thread::spawn(traceforge::current_event);
+        let (e1, e2) = (t1.join().unwrap(), t2.join().unwrap());
+        assert!(!traceforge::happens_before(e1, e2));
+        traceforge::add_sync_edge(e1, e2);
+        assert!(traceforge::happens_before(e1, e2));
+        assert!(!traceforge::happens_before(e2, e1));
+    });
+}
@@ -1,9 +1,14 @@
 extern crate traceforge;
 
+use std::collections::BTreeSet;
+
 use traceforge::thread::{self, ThreadId};
 use traceforge::*;
 use SchedulePolicy::*;
 
+mod utils;
+use utils::Outcomes;
+
 #[derive(Clone, PartialEq, Debug)]
 enum Msg {
     Val(i32),
@@ -86,3 +91,40 @@ fn tag_on_sender() {
     println!("Number of blocked executions explored {}", stats.block);
     assert_eq!(stats.execs, 1);
 }
+
+// Three writers send commuting increments to a counter that also receives a reset. Declaring the
+// increment tag independent of itself removes redundant interleavings of the increments, while
+// every position of the reset among them is still explored
+#[test]
+fn independent_tags_reduce_executions() {
+    const INC: u32 = 1;
+    const RESET: u32 = 2;
+
+    let run = |config: Config| {
+        let positions = Outcomes::new();
+        let p = positions.clone();
+        let stats = traceforge::verify(config, move || {
+            let counter = thread::spawn(|| {
+                let msgs: Vec<Msg> = (0..4).map(|_| traceforge::recv_msg_block()).collect();
+                msgs.iter().position(|m| *m == Msg::Val(0)).unwrap()
+            });
+            let cid = counter.thread().id();
+            let writers: Vec<_> = (1..=3)
+                .map(|i| thread::spawn(move || traceforge::send_tagged_msg(cid, INC, Msg::Val(i))))
+                .collect();
+            traceforge::send_tagged_msg(cid, RESET, Msg::Val(0));
+            for w in writers {
+                w.join().unwrap();
+            }
+            p.insert(counter.join().unwrap());
+        });
+        (stats, positions.distinct())
+    };
+
+    let (baseline, all_positions) = run(Config::builder().build());
+    let (reduced, positions) = run(Config::builder().with_independent_tags(INC, INC).build());
+    assert_eq!(baseline.execs, 24);
+    assert!(reduced.execs < baseline.execs);
+    assert_eq!(all_positions, BTreeSet::from([0, 1, 2, 3]));
+    assert_eq!(positions, all_positions);
+}
@@ -0,0 +1,39 @@
+use traceforge::thread;
+use traceforge::Config;
+
+const SENDERS: u32 = 20;
+
+fn model() {
+    let main = thread::current().id();
+    for i in 0..SENDERS {
+        thread::spawn(move || traceforge::send_msg(main, i));
+    }
+    let first: u32 = traceforge::recv_msg_block();
+    assert!(first < SENDERS);
+}
+
+#[test]
+fn capacity_does_not_change_exploration() {
+    let small = traceforge::verify(Config::builder().with_task_capacity(1).build(), model);
+    let large = traceforge::verify(Config::builder().with_task_capacity(64).build(), model);
+    assert_eq!(small.execs, SENDERS as usize);
+    assert_eq!(small.execs, large.execs);
+    assert_eq!(small.block, large.block);
+}
+
+#[test]
+fn spills_are_reported() {
+    // The main thread and the senders do not fit in the 16 inline tasks
+    let small = traceforge::verify(Config::builder().build(), model);
+    assert_eq!(small.task_spills, small.execs + small.block);
+
+    let large = traceforge::verify(Config::builder().with_task_capacity(64).build(), model);
+    assert_eq!(large.task_spills, 0);
+
+    // A model with few tasks never spills
+    let few = traceforge::verify(Config::builder().build(), || {
+        let h = thread::spawn(|| ());
+        h.join().unwrap();
+    });
+    assert_eq!(few.task_spills, 0);
+}
@@ -0,0 +1,46 @@
+use traceforge::{thread, Config, TaskState, WaitReason};
+
+#[test]
+fn the_snapshot_shows_finished_runnable_and_blocked_tasks() {
+    traceforge::verify(Config::builder().build(), || {
+        let done = thread::Builder::new()
+            .name("done".to_string())
+            .spawn(|| ())
+            .unwrap();
+        let waiting = thread::spawn(|| {
+            let _: u32 = traceforge::recv_msg_block();
+        });
+        while traceforge::runnable_count() > 1 {
+            thread::yield_now();
+        }
+
+        let tasks = traceforge::task_graph();
+        let states = tasks
+            .iter()
+            .map(|t| (t.id, t.name.as_deref(), t.state, t.wait_reason.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                (
+                    thread::current().id(),
+                    Some("main-0"),
+                    TaskState::Runnable,
+                    None
+                ),
+                (done.thread().id(), Some("done"), TaskState::Finished, None),
+                (
+                    waiting.thread().id(),
+                    None,
+                    TaskState::Blocked,
+                    Some(WaitReason::Receive)
+                ),
+            ]
+        );
+        assert!(tasks.iter().all(|t| !t.detached));
+
+        traceforge::send_msg(waiting.thread().id(), 1u32);
+        done.join().unwrap();
+        waiting.join().unwrap();
+    });
+}
@@ -0,0 +1,37 @@
+use traceforge::{thread, Config};
+
+// Fails in the main task or in an unnamed task, depending on the choice
+fn fails_somewhere() {
+    assert_eq!(thread::current().name(), Some("main-0"));
+    let in_main = traceforge::nondet();
+    let t = thread::spawn(move || assert!(in_main, "in the spawned task"));
+    assert!(!in_main, "in the main task");
+    t.join().unwrap();
+}
+
+fn failing_tasks() -> Vec<Option<String>> {
+    let config = Config::builder()
+        .with_collect_failures(true)
+        .with_seed(7)
+        .build();
+    let stats = traceforge::verify(config, fails_somewhere);
+    stats
+        .collected_failures
+        .into_iter()
+        .map(|f| f.task)
+        .collect()
+}
+
+#[test]
+fn task_names_do_not_depend_on_the_run() {
+    let first = failing_tasks();
+    let mut names = first.clone();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![Some("main-0".to_string()), Some("task-1".to_string())]
+    );
+    // The second run is on another OS thread
+    let second = std::thread::spawn(failing_tasks).join().unwrap();
+    assert_eq!(first, second);
+}
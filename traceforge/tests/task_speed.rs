@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+
+use traceforge::thread;
+use traceforge::{Config, SchedulePolicy};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Msg {
+    Replica,
+    Primary(u32),
+}
+
+// The replica must acknowledge before the primary has sent its six updates: the bug shows up
+// when the replica lags behind, i.e. its message is received last
+fn model(slow_replica: bool, first_bug: Arc<Mutex<Option<usize>>>) -> impl Fn() + Send + Sync {
+    move || {
+        let main = thread::current().id();
+        let replica = thread::spawn(move || traceforge::send_msg(main, Msg::Replica));
+        if slow_replica {
+            traceforge::set_task_speed(&replica, 0.1);
+        }
+        thread::spawn(move || {
+            for i in 0..6 {
+                traceforge::send_msg(main, Msg::Primary(i));
+            }
+        });
+        let msgs: Vec<Msg> = (0..7).map(|_| traceforge::recv_msg_block()).collect();
+        if msgs[6] == Msg::Replica {
+            first_bug
+                .lock()
+                .unwrap()
+                .get_or_insert(traceforge::execution_index());
+        }
+    }
+}
+
+#[test]
+fn slow_task_surfaces_a_timing_bug_sooner() {
+    let explore = |slow| {
+        let first_bug = Arc::new(Mutex::new(None));
+        let stats = traceforge::verify(Config::builder().build(), model(slow, first_bug.clone()));
+        let first = first_bug.lock().unwrap().expect("the bug is found");
+        (stats, first)
+    };
+    let (uniform, uniform_first) = explore(false);
+    let (slow, slow_first) = explore(true);
+    // The same executions are explored, in another order: the default scheduler runs the
+    // replica first, and finds the bug last
+    assert_eq!(uniform.execs, 7);
+    assert_eq!(slow.execs, uniform.execs);
+    assert_eq!(uniform_first, 6);
+    assert_eq!(slow_first, 0);
+}
+
+#[test]
+fn random_scheduler_favors_fast_tasks() {
+    // How many of the explorations find the bug in their first execution
+    let found_first = |slow| {
+        (0..20)
+            .filter(|seed| {
+                let first_bug = Arc::new(Mutex::new(None));
+                let config = Config::builder()
+                    .with_policy(SchedulePolicy::Arbitrary)
+                    .with_seed(*seed)
+                    .build();
+                traceforge::verify(config, model(slow, first_bug.clone()));
+                let first = *first_bug.lock().unwrap();
+                first == Some(0)
+            })
+            .count()
+    };
+    let (uniform, slow) = (found_first(false), found_first(true));
+    // A uniform random scheduler rarely lets the replica lag behind six steps of the primary
+    assert!(slow > uniform + 4, "{slow} vs {uniform}");
+}
@@ -0,0 +1,88 @@
+use traceforge::thread;
+use traceforge::{Config, TieBreak};
+
+mod utils;
+use utils::Outcomes;
+
+const TIE_BREAKS: [TieBreak; 3] = [
+    TieBreak::LowestId,
+    TieBreak::RoundRobin,
+    TieBreak::LastRunFirst,
+];
+
+// Explores `model`, and returns what it polled in each execution, in exploration order
+fn explore(tie_break: TieBreak, model: fn() -> Vec<Option<u32>>) -> Vec<Vec<Option<u32>>> {
+    let explored = Outcomes::new();
+    let seen = explored.clone();
+    let config = Config::builder().with_tie_break(tie_break).build();
+    traceforge::verify(config, move || seen.insert(model()));
+    explored.all()
+}
+
+// Two workers send two messages each to the main thread, which polls its inbox twice
+fn main_polls() -> Vec<Option<u32>> {
+    let main = thread::current().id();
+    for i in 1..=2u32 {
+        thread::spawn(move || {
+            traceforge::send_msg(main, 10 * i + 1);
+            traceforge::send_msg(main, 10 * i + 2);
+        });
+    }
+    (0..2).map(|_| traceforge::recv_msg()).collect()
+}
+
+// A worker yields, then polls its inbox twice, while another one sends it two messages
+fn worker_polls() -> Vec<Option<u32>> {
+    let receiver = thread::spawn(|| {
+        thread::yield_now();
+        (0..2).map(|_| traceforge::recv_msg()).collect()
+    });
+    let to = receiver.thread().id();
+    thread::spawn(move || {
+        traceforge::send_msg(to, 1u32);
+        traceforge::send_msg(to, 2u32);
+    });
+    receiver.join().unwrap()
+}
+
+// The index of the first execution that fails, the bug being that the first poll finds
+// nothing while the second one finds a message
+fn first_bug(explored: &[Vec<Option<u32>>]) -> Option<usize> {
+    explored
+        .iter()
+        .position(|p| p[0].is_none() && p[1].is_some())
+}
+
+#[test]
+fn every_tie_break_explores_the_same_executions() {
+    for model in [main_polls, worker_polls] {
+        let mut expected = explore(TieBreak::LowestId, model);
+        expected.sort();
+        for tie_break in TIE_BREAKS {
+            let mut explored = explore(tie_break, model);
+            explored.sort();
+            assert_eq!(explored, expected, "{:?}", tie_break);
+        }
+    }
+}
+
+#[test]
+fn tie_breaks_change_which_bug_is_found_first() {
+    let lowest_id = first_bug(&explore(TieBreak::LowestId, main_polls));
+    let round_robin = first_bug(&explore(TieBreak::RoundRobin, main_polls));
+    assert_eq!((lowest_id, round_robin), (Some(4), Some(1)));
+
+    let lowest_id = first_bug(&explore(TieBreak::LowestId, worker_polls));
+    let last_run_first = first_bug(&explore(TieBreak::LastRunFirst, worker_polls));
+    assert_eq!((lowest_id, last_run_first), (Some(3), Some(1)));
+}
+
+#[test]
+fn tie_breaks_are_deterministic() {
+    for tie_break in TIE_BREAKS {
+        assert_eq!(
+            explore(tie_break, worker_polls),
+            explore(tie_break, worker_polls)
+        );
+    }
+}
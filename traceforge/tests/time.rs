@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use traceforge::{channel, future, thread, time, Config};
+
+mod utils;
+use utils::Outcomes;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+// Two tasks set timers 2ms apart
+fn two_timers() {
+    let a = thread::spawn(|| time::sleep(ms(10)));
+    let b = thread::spawn(|| time::sleep(ms(12)));
+    a.join().unwrap();
+    b.join().unwrap();
+}
+
+#[test]
+fn timers_within_drift_fire_in_either_order() {
+    let stats = traceforge::verify(
+        Config::builder().with_clock_drift(ms(5)).build(),
+        two_timers,
+    );
+    assert_eq!(stats.execs, 2);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn timers_beyond_drift_fire_in_deadline_order() {
+    let stats = traceforge::verify(
+        Config::builder().with_clock_drift(ms(1)).build(),
+        two_timers,
+    );
+    // The order where the later timer fires first is not allowed
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 1);
+}
+
+#[test]
+fn clocks_advance_per_task() {
+    traceforge::verify(Config::builder().build(), || {
+        assert_eq!(time::now(), Duration::ZERO);
+        time::sleep(ms(5));
+        assert_eq!(time::now(), ms(5));
+        let child = thread::spawn(|| {
+            // A task starts at the time of its parent
+            let start = time::now();
+            time::sleep(ms(3));
+            (start, time::now())
+        });
+        time::sleep(ms(1));
+        assert_eq!(time::now(), ms(6));
+        assert_eq!(child.join().unwrap(), (ms(5), ms(8)));
+    });
+}
+
+#[test]
+fn timers_after_a_receive_or_join_are_not_pruned() {
+    let stats = traceforge::verify(Config::builder().with_clock_drift(ms(1)).build(), || {
+        let (tx, rx) = channel::Builder::<()>::new().build();
+        let sender = thread::spawn(move || {
+            time::sleep(ms(10));
+            tx.send_msg(());
+        });
+        let receiver = thread::spawn(move || {
+            rx.recv_msg_block();
+            // The receiver caught up with the clock of the sender
+            assert_eq!(time::now(), ms(10));
+            time::sleep(ms(1));
+        });
+        sender.join().unwrap();
+        receiver.join().unwrap();
+        // So did the main task, with the clocks of the tasks it joined
+        assert_eq!(time::now(), ms(11));
+        time::sleep(ms(1));
+    });
+    // Each timer was set after the earlier ones fired, no execution goes past the drift bound
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 0);
+}
+
+#[test]
+fn a_future_completes_or_elapses_at_its_deadline() {
+    let outcomes = Outcomes::new();
+    let seen = outcomes.clone();
+    let stats = traceforge::verify(Config::builder().build(), move || {
+        let (tx, rx) = channel::Builder::<u32>::new().build();
+        let _ = thread::spawn(move || tx.send_msg(7));
+        let reply = future::block_on(future::timeout_at(ms(10), rx.async_recv_msg()));
+        seen.insert((reply.ok(), time::now()));
+    });
+    // The executions in which the receive that the deadline cancels already took the message
+    // are blocked, since the message would be lost
+    assert_eq!(stats.execs, 2);
+    assert_eq!(
+        outcomes.distinct(),
+        BTreeSet::from([(Some(7), Duration::ZERO), (None, ms(10))])
+    );
+}
+
+#[test]
+fn a_future_that_never_completes_elapses() {
+    let stats = traceforge::verify(Config::builder().build(), || {
+        let (_tx, rx) = channel::Builder::<u32>::new().build();
+        time::sleep(ms(2));
+        let reply = future::block_on(future::timeout(ms(3), rx.async_recv_msg()));
+        assert!(matches!(reply, Err(future::Elapsed { .. })));
+        assert_eq!(time::now(), ms(5));
+    });
+    assert_eq!(stats.execs, 1);
+    assert_eq!(stats.block, 0);
+}
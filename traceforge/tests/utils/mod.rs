@@ -1,7 +1,10 @@
 use log::LevelFilter;
 use simplelog::{CombinedLogger, SimpleLogger};
 use std::any::Any;
-use std::sync::Once;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::{Arc, Mutex, Once};
 
 static INIT_LOG: Once = Once::new();
 
@@ -63,3 +66,62 @@ pub fn assert_panic_contains(result: Result<(), Box<dyn Any + Send>>, expected_m
         }
     }
 }
+
+/// The contents of the error trace file `trace_filename`
+#[allow(dead_code)] // Only used in tests
+pub fn read_trace(trace_filename: &str) -> serde_json::Value {
+    let contents = fs::read_to_string(trace_filename).expect("can't read trace file");
+    serde_json::from_str(&contents).expect("invalid trace file")
+}
+
+/// Removes the error trace file `trace_filename`, if it exists
+#[allow(dead_code)] // Only used in tests
+pub fn remove_trace(trace_filename: &str) {
+    match fs::remove_file(trace_filename) {
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        r => r.expect("Couldn't delete file"),
+    }
+}
+
+/// The outcomes of the executions of a model, e.g. the values that a race ends with, recorded
+/// from within the model. The clones of the collector share its outcomes.
+#[allow(dead_code)] // Only used in tests
+pub struct Outcomes<T>(Arc<Mutex<Vec<T>>>);
+
+impl<T> Clone for Outcomes<T> {
+    fn clone(&self) -> Self {
+        Outcomes(self.0.clone())
+    }
+}
+
+impl<T> Default for Outcomes<T> {
+    fn default() -> Self {
+        Outcomes(Arc::new(Mutex::new(Vec::new())))
+    }
+}
+
+#[allow(dead_code)] // Only used in tests
+impl<T: Clone> Outcomes<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an outcome of the current execution
+    pub fn insert(&self, outcome: T) {
+        self.0.lock().unwrap().push(outcome);
+    }
+
+    /// The recorded outcomes, in the order in which the executions ran
+    pub fn all(&self) -> Vec<T> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// The distinct recorded outcomes
+    pub fn distinct(&self) -> BTreeSet<T>
+    where
+        T: Ord,
+    {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
@@ -0,0 +1,51 @@
+use traceforge::thread;
+use traceforge::Config;
+
+// `n` workers send their id to the main thread, which receives them in any of the n! orders.
+// With `ordered`, the main thread expects the last two workers not to overtake each other.
+fn workers(n: u32, ordered: bool) {
+    let me = thread::current().id();
+    for i in 0..n {
+        thread::spawn(move || traceforge::send_msg(me, i));
+    }
+    let received: Vec<u32> = (0..n).map(|_| traceforge::recv_msg_block()).collect();
+    if ordered && n >= 3 {
+        traceforge::assert(
+            received.iter().position(|&i| i == 1) < received.iter().position(|&i| i == 2),
+        );
+    }
+}
+
+#[test]
+fn report_is_grouped_by_input() {
+    let report =
+        traceforge::verify_for_each(Config::builder().build(), [1, 2, 3], |n| workers(*n, false));
+    let execs: Vec<(u32, usize)> = report
+        .inputs
+        .iter()
+        .map(|i| (i.input, i.stats.execs))
+        .collect();
+    assert_eq!(execs, [(1, 1), (2, 2), (3, 6)]);
+    assert_eq!(report.total().execs, 9);
+    assert_eq!(report.failures().count(), 0);
+    assert_eq!(
+        report.to_string(),
+        "1: 1 executions, 0 blocked\n2: 2 executions, 0 blocked\n3: 6 executions, 0 blocked\n"
+    );
+}
+
+#[test]
+fn failing_input_does_not_stop_the_sweep() {
+    let report =
+        traceforge::verify_for_each(Config::builder().build(), [3, 1, 2], |n| workers(*n, true));
+    let failed: Vec<(u32, bool)> = report
+        .inputs
+        .iter()
+        .map(|i| (i.input, i.failure.is_some()))
+        .collect();
+    assert_eq!(failed, [(3, true), (1, false), (2, false)]);
+    let failure = report.failures().next().unwrap().failure.as_ref().unwrap();
+    assert!(failure.starts_with("[assertion]"), "{}", failure);
+    assert_eq!(report.inputs[1].stats.execs, 1);
+    assert_eq!(report.inputs[2].stats.execs, 2);
+}
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use traceforge::coverage::ExecutionObserver;
+use traceforge::monitor_types::EndCondition;
+use traceforge::thread;
+use traceforge::{Config, Nondet};
+
+// Counts the executions it is notified of
+struct Counter(Arc<AtomicUsize>);
+
+impl ExecutionObserver for Counter {
+    fn after(
+        &mut self,
+        _eid: traceforge::ExecutionId,
+        _: &EndCondition,
+        _: traceforge::CoverageInfo,
+    ) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn verify_value_returns_the_summary_of_the_model() {
+    let (stats, sum) = traceforge::verify_value(Config::builder().build(), || {
+        let me = thread::current().id();
+        for i in 1..=3 {
+            thread::spawn(move || traceforge::send_msg(me, i));
+        }
+        // The messages are received in any order, but their sum is always the same
+        (0..3)
+            .map(|_| traceforge::recv_msg_block::<i32>())
+            .sum::<i32>()
+    });
+    assert_eq!(stats.execs, 6);
+    assert_eq!(sum, Some(6));
+}
+
+#[test]
+fn values_of_blocked_executions_are_discarded() {
+    let (stats, value) = traceforge::verify_value(Config::builder().build(), || {
+        let v = (0..3).nondet();
+        traceforge::assume!(v == 1);
+        v * 10
+    });
+    assert_eq!((stats.execs, stats.block), (1, 2));
+    assert_eq!(value, Some(10));
+
+    let (_, value) = traceforge::verify_value(Config::builder().build(), || {
+        traceforge::assume!(false);
+    });
+    assert_eq!(value, None);
+}
+
+#[test]
+fn observers_of_the_config_are_still_notified() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let config = Config::builder()
+        .with_callback(Box::new(Counter(count.clone())))
+        .build();
+    let (stats, _) = traceforge::verify_value(config, || (0..4).nondet());
+    assert_eq!(stats.execs, 4);
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+}
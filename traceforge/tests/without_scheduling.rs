@@ -0,0 +1,34 @@
+use traceforge::thread;
+use traceforge::Config;
+
+// Three workers register with the main thread, which builds a sorted roster out of the
+// registrations before the model proper starts: the order of the registrations is irrelevant
+fn model(unscheduled: bool) -> impl Fn() + Send + Sync + 'static {
+    move || {
+        let me = thread::current().id();
+        for i in 0..3 {
+            thread::spawn(move || traceforge::send_msg(me, i));
+        }
+        let setup = || {
+            let mut roster: Vec<i32> = (0..3).map(|_| traceforge::recv_msg_block()).collect();
+            roster.sort();
+            roster
+        };
+        let roster = if unscheduled {
+            traceforge::without_scheduling(setup)
+        } else {
+            setup()
+        };
+        traceforge::assert(roster == vec![0, 1, 2]);
+    }
+}
+
+#[test]
+fn setup_code_adds_no_schedules() {
+    let plain = traceforge::verify(Config::builder().build(), model(false));
+    // The 3! orders of the registrations
+    assert_eq!(plain.execs, 6);
+    let unscheduled = traceforge::verify(Config::builder().build(), model(true));
+    assert_eq!(unscheduled.execs, 1);
+    assert_eq!(unscheduled.block, 0);
+}